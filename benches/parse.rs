@@ -0,0 +1,146 @@
+//! Measures parsing of the objects and content streams that make up typical
+//! documents, which are mostly short arrays and small dictionaries.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use bdf::content::operators::parse_operators;
+use bdf::objects::{names, Dictionary, IndirectRef, Name, Object};
+use bdf::parsing::context::{ParseContext, ParseOptions};
+use bdf::parsing::pdf_file::PdfFile;
+use bdf::writing::document::DocumentBuilder;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::borrow::Cow;
+
+/// The body of a page of text, set line by line with a few font changes, as
+/// word processors write it.
+fn content_stream() -> Vec<u8> {
+    let mut content = b"q 1 0 0 1 72 72 cm 0 0 0 rg\nBT /F1 11 Tf 14 TL\n".to_vec();
+    for line in 0..50 {
+        if line % 10 == 0 {
+            content.extend_from_slice(b"/F2 11 Tf\n");
+        }
+        content.extend_from_slice(
+            format!(
+                "1 0 0 1 72 {} Tm [(Line) -250 ({}) -250 (of the page.)] TJ\n",
+                720 - line * 14,
+                line
+            )
+            .as_bytes(),
+        );
+    }
+    content.extend_from_slice(b"ET Q\n0.5 w 72 60 m 540 60 l S\n");
+    content
+}
+
+/// A document whose pages each have a media box, resources and some text.
+fn document(page_count: usize, content: &[u8]) -> Vec<u8> {
+    let name = |name: &'static str| Object::Name(Name::from(name));
+    let dict = |entries: Vec<(&'static str, Object<'static>)>| {
+        Object::Dictionary(
+            entries
+                .into_iter()
+                .map(|(key, value)| (Name::from(key), value))
+                .collect::<Dictionary>(),
+        )
+    };
+
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    let pages = builder.reserve();
+    let fonts = ["Helvetica", "Helvetica-Bold"].map(|font| {
+        Object::Indirect(builder.add(dict(vec![
+            (names::TYPE, name("Font")),
+            (names::SUBTYPE, name("Type1")),
+            ("BaseFont", name(font)),
+        ])))
+    });
+    let mut kids = Vec::new();
+    for _ in 0..page_count {
+        let stream = builder.add(Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::new())),
+            Cow::Owned(content.to_vec()),
+        ));
+        let page = builder.add(dict(vec![
+            (names::TYPE, name(names::PAGE)),
+            (names::PARENT, Object::Indirect(pages)),
+            (
+                names::MEDIA_BOX,
+                Object::Array([0, 0, 612, 792].map(Object::Integer).to_vec()),
+            ),
+            (
+                names::RESOURCES,
+                dict(vec![
+                    (
+                        "Font",
+                        dict(vec![("F1", fonts[0].clone()), ("F2", fonts[1].clone())]),
+                    ),
+                    ("ProcSet", Object::Array(vec![name("PDF"), name("Text")])),
+                ]),
+            ),
+            (names::CONTENTS, Object::Indirect(stream)),
+        ]));
+        kids.push(Object::Indirect(page));
+    }
+    builder.insert(
+        pages,
+        dict(vec![
+            (names::TYPE, name(names::PAGES)),
+            (names::COUNT, Object::Integer(page_count as i64)),
+            (names::KIDS, Object::Array(kids)),
+        ]),
+    );
+    builder.insert(
+        catalog,
+        dict(vec![
+            (names::TYPE, name(names::CATALOG)),
+            (names::PAGES, Object::Indirect(pages)),
+        ]),
+    );
+    builder.set_root(catalog);
+    builder.to_bytes().unwrap()
+}
+
+fn parse_objects(c: &mut Criterion) {
+    let raw = document(100, &content_stream());
+    let mut file = PdfFile::from_bytes(&raw);
+    file.load_xref_table().unwrap();
+    let references = file
+        .xref_entries()
+        .unwrap()
+        .into_iter()
+        .map(|(reference, _)| reference)
+        .filter(|reference: &IndirectRef| reference.number != 0)
+        .collect::<Vec<_>>();
+
+    c.bench_function("parse every object", |b| {
+        b.iter(|| {
+            for &reference in &references {
+                file.object(reference).unwrap();
+            }
+        })
+    });
+}
+
+fn parse_content(c: &mut Criterion) {
+    let content = content_stream();
+    let context = ParseContext::with_options(ParseOptions::strict());
+    c.bench_function("parse content stream", |b| {
+        b.iter(|| parse_operators(&content, &context).unwrap())
+    });
+}
+
+fn load_example(c: &mut Criterion) {
+    let raw = std::fs::read("examples/hello-world.pdf").unwrap();
+    c.bench_function("load hello world", |b| {
+        b.iter(|| {
+            let mut file = PdfFile::from_bytes(&raw);
+            file.load_xref_table().unwrap();
+            for page in file.pages().unwrap() {
+                page.content_bytes().unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, parse_objects, parse_content, load_example);
+criterion_main!(benches);
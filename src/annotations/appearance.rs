@@ -0,0 +1,162 @@
+//! Builds appearance streams, which are form XObjects that show an annotation
+//! or form field (Adobe, 2008, p. 390-393).
+
+use crate::content::operators::{write_operators, Operator};
+use crate::error::Result;
+use crate::geometry::{number_object, Rect};
+use crate::objects::{names, Dictionary, Name, Object};
+use std::borrow::Cow;
+
+/// An appearance stream that is built one operator at a time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Appearance<'a> {
+    /// The bounding box, which is mapped onto the annotation's `/Rect`.
+    pub bbox: Rect,
+    pub resources: Dictionary<'a>,
+    pub operators: Vec<Operator<'a>>,
+}
+
+impl<'a> Appearance<'a> {
+    /// Starts an empty appearance for an annotation of the given size.
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            bbox: Rect::new(0.0, 0.0, width, height),
+            ..Default::default()
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.bbox.width()
+    }
+
+    pub fn height(&self) -> f64 {
+        self.bbox.height()
+    }
+
+    pub fn push(&mut self, operator: &'a [u8], operands: Vec<Object<'a>>) {
+        self.operators.push(Operator::new(operator, operands));
+    }
+
+    /// Pushes an operator whose operands are all numbers.
+    pub fn push_numbers(&mut self, operator: &'a [u8], operands: &[f64]) {
+        let operands = operands.iter().map(|&n| number_object(n)).collect();
+        self.push(operator, operands);
+    }
+
+    /// Sets the fill or stroke colour from an array of components, which is
+    /// gray, RGB or CMYK depending on its length, as in the `/C` entry of an
+    /// annotation (Adobe, 2008, p. 384). Returns false for any other length,
+    /// such as the empty array that means transparent.
+    pub fn set_color(&mut self, color: &[f64], stroke: bool) -> bool {
+        let operator: &'static [u8] = match (color.len(), stroke) {
+            (1, false) => b"g",
+            (1, true) => b"G",
+            (3, false) => b"rg",
+            (3, true) => b"RG",
+            (4, false) => b"k",
+            (4, true) => b"K",
+            _ => return false,
+        };
+        self.push_numbers(operator, color);
+        true
+    }
+
+    /// Fills a rectangle with the current fill colour.
+    pub fn fill_rect(&mut self, rect: Rect) {
+        self.push_numbers(b"re", &[rect.x0, rect.y0, rect.width(), rect.height()]);
+        self.push(b"f", vec![]);
+    }
+
+    /// Strokes a border of the given width just inside a rectangle, with the
+    /// current stroke colour.
+    pub fn stroke_rect(&mut self, rect: Rect, width: f64) {
+        let inset = width / 2.0;
+        self.push_numbers(b"w", &[width]);
+        self.push_numbers(
+            b"re",
+            &[
+                rect.x0 + inset,
+                rect.y0 + inset,
+                rect.width() - width,
+                rect.height() - width,
+            ],
+        );
+        self.push(b"S", vec![]);
+    }
+
+    /// Adds an ellipse that fits in a rectangle to the current path, made of
+    /// four Bézier curves that each approximate a quarter of it.
+    pub fn ellipse(&mut self, rect: Rect) {
+        let (rx, ry) = (rect.width() / 2.0, rect.height() / 2.0);
+        let (x, y) = (rect.x0 + rx, rect.y0 + ry);
+        let (kx, ky) = (rx * 0.5523, ry * 0.5523);
+        self.push_numbers(b"m", &[x + rx, y]);
+        self.push_numbers(b"c", &[x + rx, y + ky, x + kx, y + ry, x, y + ry]);
+        self.push_numbers(b"c", &[x - kx, y + ry, x - rx, y + ky, x - rx, y]);
+        self.push_numbers(b"c", &[x - rx, y - ky, x - kx, y - ry, x, y - ry]);
+        self.push_numbers(b"c", &[x + kx, y - ry, x + rx, y - ky, x + rx, y]);
+    }
+
+    /// Adds a resource, such as a font, under a category like `names::FONT`.
+    pub fn add_resource(&mut self, category: &str, name: Name<'a>, resource: Object<'a>) {
+        let category = self
+            .resources
+            .entry(Name::from(category).into_owned())
+            .or_insert_with(|| Object::Dictionary(Dictionary::new()));
+        if let Object::Dictionary(entries) = category {
+            entries.insert(name, resource);
+        }
+    }
+
+    /// Writes the appearance as a form XObject.
+    pub fn to_stream(&self) -> Result<Object<'a>> {
+        let mut content = Vec::new();
+        write_operators(&mut content, &self.operators)?;
+
+        let dict = [
+            (names::TYPE, Object::Name(Name::from(names::X_OBJECT))),
+            (names::SUBTYPE, Object::Name(Name::from(names::FORM))),
+            (names::BBOX, self.bbox.to_object()),
+            (names::RESOURCES, Object::Dictionary(self.resources.clone())),
+        ]
+        .into_iter()
+        .map(|(key, value)| (Name::from(key), value))
+        .collect::<Dictionary>();
+        Ok(Object::Stream(
+            Box::new(Object::Dictionary(dict)),
+            Cow::Owned(content),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_appearance_streams() {
+        let mut appearance = Appearance::new(20.0, 10.0);
+        assert!(appearance.set_color(&[1.0, 0.5, 0.0], false));
+        assert!(!appearance.set_color(&[], true));
+        appearance.fill_rect(appearance.bbox);
+        appearance.set_color(&[0.0], true);
+        appearance.stroke_rect(appearance.bbox, 1.0);
+        appearance.add_resource(
+            names::FONT,
+            Name::from("Helv"),
+            Object::Name(Name::from("Font")),
+        );
+
+        let stream = appearance.to_stream().unwrap();
+        let (dict, content) = stream.as_stream().unwrap();
+        assert_eq!(
+            content.as_ref(),
+            b"1 0.5 0 rg\n0 0 20 10 re\nf\n0 G\n1 w\n0.5 0.5 19 9 re\nS\n"
+        );
+        assert_eq!(
+            Rect::from_object(&dict[names::BBOX.as_bytes()]),
+            Ok(Rect::new(0.0, 0.0, 20.0, 10.0))
+        );
+        assert!(dict[names::RESOURCES.as_bytes()].get(names::FONT).is_some());
+    }
+}
@@ -0,0 +1,582 @@
+//! Creates markup annotations, such as notes, free text, shapes and stamps
+//! (Adobe, 2008, p. 390-404).
+
+use crate::annotations::appearance::Appearance;
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::forms::appearance::{wrap, FieldFont};
+use crate::geometry::{number_object, points_to_object, Rect};
+use crate::objects::{names, Dictionary, IndirectRef, Name, Object};
+use crate::utils::text::encode_text_string;
+use crate::writing::app_data::pdf_date;
+use crate::writing::incremental::IncrementalUpdate;
+use std::borrow::Cow;
+use std::time::SystemTime;
+
+/// Annotation flags (Adobe, 2008, p. 385).
+const PRINT_FLAG: i64 = 4;
+
+/// The space left between the border of an annotation and its text.
+const PADDING: f64 = 2.0;
+
+/// The kind of annotation to create, along with what is particular to it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnnotationKind {
+    /// A note, which is shown as an icon and opens in a pop-up window
+    /// (Adobe, 2008, p. 396).
+    Text {
+        open: bool,
+    },
+    /// Text that is shown directly on the page (Adobe, 2008, p. 398).
+    FreeText {
+        text: String,
+        font_size: f64,
+    },
+    Square,
+    Circle,
+    /// A rubber stamp, with a standard name such as `Approved` or `Draft`
+    /// (Adobe, 2008, p. 404).
+    Stamp {
+        name: String,
+    },
+    /// Freehand drawing, as paths of points in default user space
+    /// (Adobe, 2008, p. 403).
+    Ink {
+        paths: Vec<Vec<(f64, f64)>>,
+    },
+    /// A closed shape, which is filled with the interior colour
+    /// (Adobe, 2008, p. 402).
+    Polygon {
+        vertices: Vec<(f64, f64)>,
+    },
+    /// Connected lines that are not closed.
+    PolyLine {
+        vertices: Vec<(f64, f64)>,
+    },
+}
+
+impl AnnotationKind {
+    fn subtype(&self) -> &'static str {
+        match self {
+            Self::Text { .. } => names::TEXT,
+            Self::FreeText { .. } => names::FREE_TEXT,
+            Self::Square => names::SQUARE,
+            Self::Circle => names::CIRCLE,
+            Self::Stamp { .. } => names::STAMP,
+            Self::Ink { .. } => names::INK,
+            Self::Polygon { .. } => names::POLYGON,
+            Self::PolyLine { .. } => names::POLY_LINE,
+        }
+    }
+
+    /// The rectangle that an ink, polygon or polyline annotation needs, which
+    /// contains its points and a border of the given width around them.
+    pub fn bounds(&self, border_width: f64) -> Option<Rect> {
+        let bounds = match self {
+            Self::Ink { paths } => Rect::bounding(&paths.concat()),
+            Self::Polygon { vertices } | Self::PolyLine { vertices } => Rect::bounding(vertices),
+            _ => None,
+        }?;
+        let margin = border_width / 2.0;
+        Some(Rect::new(
+            bounds.x0 - margin,
+            bounds.y0 - margin,
+            bounds.x1 + margin,
+            bounds.y1 + margin,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotationOptions {
+    /// The colour of the icon, border or text, as gray, RGB or CMYK
+    /// components, or empty for none.
+    pub color: Vec<f64>,
+    /// The colour that squares and circles are filled with, or empty for
+    /// none.
+    pub interior_color: Vec<f64>,
+    /// The opacity, from 0 for transparent to 1 for opaque.
+    pub opacity: f64,
+    pub border_width: f64,
+    /// The author, which viewers show as the title of the pop-up window.
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    /// The text of the pop-up window, which for free text defaults to the
+    /// text that is shown.
+    pub contents: Option<String>,
+    /// When the annotation was created and last modified, which both default
+    /// to the current time.
+    pub created: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+}
+
+impl Default for AnnotationOptions {
+    fn default() -> Self {
+        Self {
+            color: vec![1.0, 0.0, 0.0],
+            interior_color: Vec::new(),
+            opacity: 1.0,
+            border_width: 1.0,
+            author: None,
+            subject: None,
+            contents: None,
+            created: None,
+            modified: None,
+        }
+    }
+}
+
+impl<'a> IncrementalUpdate<'a> {
+    /// Adds an annotation to a page, along with an appearance stream so that
+    /// it is shown by viewers that do not generate their own. The rectangle
+    /// of an ink, polygon or polyline annotation is usually its `bounds`.
+    pub fn add_annotation(
+        &mut self,
+        page: &Page<'a>,
+        rect: Rect,
+        kind: &AnnotationKind,
+        options: &AnnotationOptions,
+    ) -> Result<IndirectRef> {
+        let page_reference = page
+            .reference()
+            .ok_or(Error::Type("Cannot annotate a direct page object".into()))?;
+        let now = SystemTime::now();
+        let date = |time: Option<SystemTime>| {
+            Object::String(Cow::Owned(pdf_date(time.unwrap_or(now)).into_bytes()))
+        };
+        let text = |text: &str| Object::String(Cow::Owned(encode_text_string(text)));
+        let colors =
+            |color: &[f64]| Object::Array(color.iter().map(|&c| number_object(c)).collect());
+
+        let mut dict = Dictionary::new();
+        let mut insert = |key: &str, value: Object<'a>| {
+            dict.insert(Name::from(key).into_owned(), value);
+        };
+        insert(names::TYPE, Object::Name(Name::from(names::ANNOT)));
+        insert(names::SUBTYPE, Object::Name(Name::from(kind.subtype())));
+        insert(names::RECT, rect.to_object());
+        insert(names::F, Object::Integer(PRINT_FLAG));
+        insert(names::P, Object::Indirect(page_reference));
+        insert(names::C, colors(&options.color));
+        insert(names::M, date(options.modified));
+        insert(names::CREATION_DATE, date(options.created));
+        if let Some(author) = &options.author {
+            insert(names::T, text(author));
+        }
+        if let Some(subject) = &options.subject {
+            insert(names::SUBJ, text(subject));
+        }
+        if options.opacity < 1.0 {
+            insert(names::STROKING_ALPHA, number_object(options.opacity));
+        }
+
+        let mut appearance = Appearance::new(rect.width(), rect.height());
+        if options.opacity < 1.0 {
+            let state = [names::STROKING_ALPHA, names::NON_STROKING_ALPHA]
+                .into_iter()
+                .map(|key| (Name::from(key), number_object(options.opacity)))
+                .collect();
+            appearance.add_resource(
+                names::EXT_G_STATE,
+                Name::from("GS0"),
+                Object::Dictionary(state),
+            );
+            appearance.push(b"gs", vec![Object::Name(Name::from("GS0"))]);
+        }
+
+        let mut contents = options.contents.clone();
+        match kind {
+            AnnotationKind::Text { open } => {
+                insert(names::OPEN, Object::Boolean(*open));
+                insert(names::NAME, Object::Name(Name::from("Note")));
+                draw_note(&mut appearance, options);
+            }
+            AnnotationKind::FreeText { text, font_size } => {
+                let font = FieldFont::load(self.file(), "Helv")?;
+                let default_appearance =
+                    format!("/Helv {} Tf {}", font_size, color_operator(&options.color));
+                insert(
+                    names::DA,
+                    Object::String(Cow::Owned(default_appearance.into_bytes())),
+                );
+                insert(names::BS, border_style(options.border_width));
+                draw_free_text(&mut appearance, &font, text, *font_size, options);
+                contents.get_or_insert_with(|| text.clone());
+            }
+            AnnotationKind::Square | AnnotationKind::Circle => {
+                if !options.interior_color.is_empty() {
+                    insert(names::IC, colors(&options.interior_color));
+                }
+                insert(names::BS, border_style(options.border_width));
+                draw_shape(&mut appearance, options, *kind == AnnotationKind::Circle);
+            }
+            AnnotationKind::Stamp { name } => {
+                let font = FieldFont::load(self.file(), "Helv")?;
+                insert(
+                    names::NAME,
+                    Object::Name(Name(Cow::Owned(name.as_bytes().to_vec()))),
+                );
+                draw_stamp(&mut appearance, &font, name, options);
+            }
+            AnnotationKind::Ink { paths } => {
+                let ink_list = paths.iter().map(|path| points_to_object(path)).collect();
+                insert(names::INK_LIST, Object::Array(ink_list));
+                insert(names::BS, border_style(options.border_width));
+                draw_paths(&mut appearance, rect, paths, false, options);
+            }
+            AnnotationKind::Polygon { vertices } | AnnotationKind::PolyLine { vertices } => {
+                insert(names::VERTICES, points_to_object(vertices));
+                insert(names::BS, border_style(options.border_width));
+                let closed = matches!(kind, AnnotationKind::Polygon { .. });
+                if closed && !options.interior_color.is_empty() {
+                    insert(names::IC, colors(&options.interior_color));
+                }
+                draw_paths(
+                    &mut appearance,
+                    rect,
+                    std::slice::from_ref(vertices),
+                    closed,
+                    options,
+                );
+            }
+        }
+        if let Some(contents) = &contents {
+            insert(names::CONTENTS, text(contents));
+        }
+
+        let normal = self.add(appearance.to_stream()?);
+        let appearances = [(Name::from(names::N), Object::Indirect(normal))];
+        insert(
+            names::AP,
+            Object::Dictionary(appearances.into_iter().collect()),
+        );
+
+        let annotation = self.add(Object::Dictionary(dict));
+        self.append_annotation(page_reference, annotation)?;
+        Ok(annotation)
+    }
+}
+
+fn border_style<'a>(width: f64) -> Object<'a> {
+    let entries = [(Name::from(names::W), number_object(width))];
+    Object::Dictionary(entries.into_iter().collect())
+}
+
+/// Writes the operator that sets the fill colour, for a default appearance
+/// string.
+fn color_operator(color: &[f64]) -> String {
+    let operator = match color.len() {
+        1 => "g",
+        3 => "rg",
+        4 => "k",
+        _ => return "0 g".into(),
+    };
+    let mut result = String::new();
+    for component in color {
+        result.push_str(&format!("{} ", component));
+    }
+    result + operator
+}
+
+/// Draws a note icon: a sheet of paper in the annotation's colour, with
+/// lines of writing.
+fn draw_note(appearance: &mut Appearance, options: &AnnotationOptions) {
+    let (width, height) = (appearance.width(), appearance.height());
+    let fill = appearance.set_color(&options.color, false);
+    appearance.set_color(&[0.0], true);
+    appearance.push_numbers(b"w", &[1.0]);
+    appearance.push_numbers(b"re", &[0.5, 0.5, width - 1.0, height - 1.0]);
+    appearance.push(if fill { b"B" } else { b"S" }, vec![]);
+    for i in 1..=3 {
+        let y = height * i as f64 / 4.0;
+        appearance.push_numbers(b"m", &[width * 0.2, y]);
+        appearance.push_numbers(b"l", &[width * 0.8, y]);
+    }
+    appearance.push(b"S", vec![]);
+}
+
+/// Draws a rectangle or ellipse just inside the annotation, filled with the
+/// interior colour and stroked with the border.
+fn draw_shape(appearance: &mut Appearance, options: &AnnotationOptions, ellipse: bool) {
+    let fill = appearance.set_color(&options.interior_color, false);
+    let stroke = options.border_width > 0.0 && appearance.set_color(&options.color, true);
+    let operator: &'static [u8] = match (fill, stroke) {
+        (true, true) => b"B",
+        (true, false) => b"f",
+        (false, true) => b"S",
+        (false, false) => return,
+    };
+
+    let inset = options.border_width / 2.0;
+    let bbox = appearance.bbox;
+    let rect = Rect::new(inset, inset, bbox.x1 - inset, bbox.y1 - inset);
+    appearance.push_numbers(b"w", &[options.border_width]);
+    if ellipse {
+        appearance.ellipse(rect);
+    } else {
+        appearance.push_numbers(b"re", &[rect.x0, rect.y0, rect.width(), rect.height()]);
+    }
+    appearance.push(operator, vec![]);
+}
+
+/// Draws paths whose points are in default user space, moving them into the
+/// annotation's rectangle. Closed paths are also filled with the interior
+/// colour.
+fn draw_paths(
+    appearance: &mut Appearance,
+    rect: Rect,
+    paths: &[Vec<(f64, f64)>],
+    closed: bool,
+    options: &AnnotationOptions,
+) {
+    let fill = closed && appearance.set_color(&options.interior_color, false);
+    let stroke = options.border_width > 0.0 && appearance.set_color(&options.color, true);
+    let operator: &'static [u8] = match (fill, stroke) {
+        (true, true) => b"B",
+        (true, false) => b"f",
+        (false, true) => b"S",
+        (false, false) => return,
+    };
+
+    // Round caps and joins, so that freehand strokes look smooth
+    appearance.push_numbers(b"w", &[options.border_width]);
+    appearance.push_numbers(b"J", &[1.0]);
+    appearance.push_numbers(b"j", &[1.0]);
+    for path in paths {
+        let Some(((x, y), rest)) = path.split_first() else {
+            continue;
+        };
+        appearance.push_numbers(b"m", &[x - rect.x0, y - rect.y0]);
+        for (x, y) in rest {
+            appearance.push_numbers(b"l", &[x - rect.x0, y - rect.y0]);
+        }
+        if closed {
+            appearance.push(b"h", vec![]);
+        }
+    }
+    appearance.push(operator, vec![]);
+}
+
+/// Draws text that is wrapped to the width of the annotation, starting from
+/// the top, inside an optional border.
+fn draw_free_text<'a>(
+    appearance: &mut Appearance<'a>,
+    font: &FieldFont<'a>,
+    text: &str,
+    size: f64,
+    options: &AnnotationOptions,
+) {
+    let bbox = appearance.bbox;
+    if options.border_width > 0.0 && appearance.set_color(&options.color, true) {
+        appearance.stroke_rect(bbox, options.border_width);
+    }
+
+    let inset = options.border_width + PADDING;
+    let lines = wrap(font, text, size, bbox.width() - 2.0 * inset);
+    let top = bbox.y1 - inset - font.line_height(size) + font.descent(size);
+    appearance.add_resource(names::FONT, font.name.clone(), font.resource.clone());
+    appearance.push(b"BT", vec![]);
+    appearance.push(
+        b"Tf",
+        vec![Object::Name(font.name.clone()), number_object(size)],
+    );
+    appearance.push_numbers(b"TL", &[font.line_height(size)]);
+    if !appearance.set_color(&options.color, false) {
+        appearance.set_color(&[0.0], false);
+    }
+    appearance.push_numbers(b"Td", &[inset, top]);
+    for line in lines {
+        let line = Object::String(Cow::Owned(font.encode(&line)));
+        appearance.push(b"Tj", vec![line]);
+        appearance.push(b"T*", vec![]);
+    }
+    appearance.push(b"ET", vec![]);
+}
+
+/// Draws a stamp: its name in capitals, as large as fits, inside a thick
+/// border.
+fn draw_stamp<'a>(
+    appearance: &mut Appearance<'a>,
+    font: &FieldFont<'a>,
+    name: &str,
+    options: &AnnotationOptions,
+) {
+    let bbox = appearance.bbox;
+    let border = (bbox.width().min(bbox.height()) / 10.0).max(1.0);
+    if appearance.set_color(&options.color, true) {
+        appearance.stroke_rect(bbox, border);
+    }
+
+    let label = name.to_uppercase();
+    let inside = bbox.width() - 2.0 * (border + PADDING);
+    let mut size = (bbox.height() - 2.0 * (border + PADDING)) / font.line_height(1.0);
+    let label_width = font.measure(&label, 1.0);
+    if label_width > 0.0 {
+        size = size.min(inside / label_width);
+    }
+    let x = (bbox.width() - font.measure(&label, size)) / 2.0;
+    let y = (bbox.height() - font.line_height(size)) / 2.0 + font.descent(size);
+
+    appearance.add_resource(names::FONT, font.name.clone(), font.resource.clone());
+    appearance.push(b"BT", vec![]);
+    appearance.push(
+        b"Tf",
+        vec![Object::Name(font.name.clone()), number_object(size)],
+    );
+    if !appearance.set_color(&options.color, false) {
+        appearance.set_color(&[0.0], false);
+    }
+    appearance.push_numbers(b"Td", &[x, y]);
+    appearance.push(b"Tj", vec![Object::String(Cow::Owned(font.encode(&label)))]);
+    appearance.push(b"ET", vec![]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::dicts::PageNode;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support::hello_world;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn should_create_annotations() {
+        let mut file = PdfFile::from_raw(hello_world(Default::default()));
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let options = AnnotationOptions {
+            interior_color: vec![0.0, 0.0, 1.0],
+            opacity: 0.5,
+            author: Some("Zoë".into()),
+            created: Some(UNIX_EPOCH + Duration::from_secs(86400)),
+            ..Default::default()
+        };
+        let kinds = [
+            AnnotationKind::Text { open: true },
+            AnnotationKind::FreeText {
+                text: "A long comment that wraps onto several lines".into(),
+                font_size: 10.0,
+            },
+            AnnotationKind::Square,
+            AnnotationKind::Circle,
+            AnnotationKind::Stamp {
+                name: "Approved".into(),
+            },
+        ];
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        for kind in &kinds {
+            let rect = Rect::new(100.0, 100.0, 200.0, 150.0);
+            update
+                .add_annotation(&pages[0], rect, kind, &options)
+                .unwrap();
+        }
+
+        let mut file = PdfFile::from_raw(update.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let annots = file
+            .resolve_owned(pages[0].dict().get(names::ANNOTS).unwrap())
+            .unwrap();
+        let annots = annots
+            .into_iter()
+            .map(|annot| file.resolve_owned(annot).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(annots.len(), 5);
+
+        let appearance = |annot: &Object| {
+            let appearances = annot.get(names::AP).unwrap();
+            let stream = file
+                .resolve_owned(appearances.get(names::N).unwrap())
+                .unwrap();
+            String::from_utf8_lossy(&stream.as_stream().unwrap().1).into_owned()
+        };
+        for (annot, kind) in annots.iter().zip(&kinds) {
+            assert!(annot
+                .get_name(names::SUBTYPE)
+                .unwrap()
+                .matches(kind.subtype()));
+            assert_eq!(annot.get_text(names::T).unwrap(), "Zoë");
+            assert_eq!(annot.get(names::STROKING_ALPHA), Some(&Object::Real(0.5)));
+            assert_eq!(
+                annot.get_text(names::CREATION_DATE).unwrap(),
+                "D:19700102000000Z"
+            );
+            assert!(appearance(annot).starts_with("/GS0 gs\n"));
+        }
+
+        assert_eq!(annots[0].get(names::OPEN), Some(&Object::Boolean(true)));
+        assert_eq!(
+            annots[1].get_text(names::DA).unwrap(),
+            "/Helv 10 Tf 1 0 0 rg"
+        );
+        assert!(annots[1]
+            .get_text(names::CONTENTS)
+            .unwrap()
+            .starts_with("A long"));
+        assert!(appearance(&annots[1]).matches("Tj").count() > 1);
+        assert!(annots[2].get(names::IC).is_some());
+        assert!(appearance(&annots[2]).ends_with("re\nB\n"));
+        assert!(appearance(&annots[3]).ends_with("c\nB\n"));
+        assert!(annots[4].get_name(names::NAME).unwrap().matches("Approved"));
+        assert!(appearance(&annots[4]).contains("(APPROVED) Tj"));
+    }
+
+    #[test]
+    fn should_create_ink_and_polygon_annotations() {
+        let mut file = PdfFile::from_raw(hello_world(Default::default()));
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        // A stroke drawn from the top left corner of the page as it is shown
+        let stroke = pages[0]
+            .points_from_display(&[(10.0, 782.0), (20.0, 772.0), (40.0, 782.0)])
+            .unwrap();
+        let ink = AnnotationKind::Ink {
+            paths: vec![stroke.clone()],
+        };
+        let polygon = AnnotationKind::Polygon {
+            vertices: vec![(100.0, 100.0), (200.0, 100.0), (150.0, 180.0)],
+        };
+        let options = AnnotationOptions {
+            interior_color: vec![0.5],
+            border_width: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(ink.bounds(2.0), Some(Rect::new(9.0, 771.0, 41.0, 783.0)));
+
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        for kind in [&ink, &polygon] {
+            let rect = kind.bounds(options.border_width).unwrap();
+            update
+                .add_annotation(&pages[0], rect, kind, &options)
+                .unwrap();
+        }
+
+        let mut file = PdfFile::from_raw(update.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let page = file.pages().unwrap()[0].reference().unwrap();
+        let annots = PageNode::new(&file, &Object::Indirect(page))
+            .unwrap()
+            .annotations()
+            .unwrap();
+        assert_eq!(annots[0].ink_list().unwrap(), Some(vec![stroke]));
+        assert_eq!(annots[0].vertices().unwrap(), None);
+        let vertices = annots[1].vertices().unwrap().unwrap();
+        assert_eq!(vertices[2], (150.0, 180.0));
+        assert!(annots[1].dict().get(names::IC.as_bytes()).is_some());
+
+        let appearance = file
+            .resolve_owned(
+                annots[1]
+                    .appearance()
+                    .unwrap()
+                    .unwrap()
+                    .get(names::N)
+                    .unwrap(),
+            )
+            .unwrap();
+        let content = appearance.as_stream().unwrap().1;
+        assert!(
+            content.starts_with(b"0.5 g\n1 0 0 RG\n2 w\n1 J\n1 j\n1 1 m\n101 1 l\n51 81 l\nh\nB\n")
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Creates highlight annotations (Adobe, 2008, p. 404-405).
+
+use crate::content::search::SearchMatch;
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::geometry::Quad;
+use crate::objects::{names, Dictionary, IndirectRef, Name, Object};
+use crate::parsing::pdf_file::PdfFile;
+use crate::writing::incremental::IncrementalUpdate;
+use std::borrow::Cow;
+
+/// Annotation flags (Adobe, 2008, p. 385).
+const PRINT_FLAG: i64 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HighlightOptions {
+    /// The colour, as RGB components from 0 to 1.
+    pub color: [f64; 3],
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        Self {
+            color: [1.0, 1.0, 0.0],
+        }
+    }
+}
+
+impl<'a> IncrementalUpdate<'a> {
+    /// Adds a highlight annotation covering the quadrilaterals to a page,
+    /// along with an appearance stream so that it is shown by viewers that do
+    /// not generate their own.
+    pub fn add_highlight(
+        &mut self,
+        page: &Page<'a>,
+        quads: &[Quad],
+        options: &HighlightOptions,
+    ) -> Result<IndirectRef> {
+        let page_reference = page
+            .reference()
+            .ok_or(Error::Type("Cannot annotate a direct page object".into()))?;
+        let rect = quads
+            .iter()
+            .map(Quad::bounds)
+            .reduce(|a, b| a.union(&b))
+            .ok_or(Error::Type("Highlight needs at least one quad".into()))?;
+        let number = Object::Real;
+        let color = options.color.map(number).to_vec();
+
+        // The highlight is multiplied with the page, so that text stays legible
+        let mut content = format!(
+            "/GS0 gs {} {} {} rg\n",
+            options.color[0], options.color[1], options.color[2]
+        );
+        for quad in quads {
+            let [top_left, top_right, bottom_left, bottom_right] = quad.points;
+            content.push_str(&format!(
+                "{} {} m {} {} l {} {} l {} {} l h f\n",
+                top_left.0,
+                top_left.1,
+                top_right.0,
+                top_right.1,
+                bottom_right.0,
+                bottom_right.1,
+                bottom_left.0,
+                bottom_left.1
+            ));
+        }
+        let appearance = Object::Stream(
+            Box::new(dict([
+                (names::TYPE, name(names::X_OBJECT)),
+                (names::SUBTYPE, name(names::FORM)),
+                (names::BBOX, rect_object(rect.x0, rect.y0, rect.x1, rect.y1)),
+                (
+                    names::RESOURCES,
+                    dict([(
+                        names::EXT_G_STATE,
+                        dict([(
+                            "GS0",
+                            dict([
+                                (names::BLEND_MODE, name(names::MULTIPLY)),
+                                (names::NON_STROKING_ALPHA, Object::Real(1.0)),
+                            ]),
+                        )]),
+                    )]),
+                ),
+            ])),
+            Cow::Owned(content.into_bytes()),
+        );
+        let appearance = self.add(appearance);
+
+        let quad_points = quads
+            .iter()
+            .flat_map(|quad| quad.points)
+            .flat_map(|(x, y)| [number(x), number(y)])
+            .collect();
+        let annotation = self.add(dict([
+            (names::TYPE, name(names::ANNOT)),
+            (names::SUBTYPE, name(names::HIGHLIGHT)),
+            (names::RECT, rect_object(rect.x0, rect.y0, rect.x1, rect.y1)),
+            (names::QUAD_POINTS, Object::Array(quad_points)),
+            (names::C, Object::Array(color)),
+            (names::F, Object::Integer(PRINT_FLAG)),
+            (names::P, Object::Indirect(page_reference)),
+            (names::AP, dict([(names::N, Object::Indirect(appearance))])),
+        ]));
+
+        self.append_annotation(page_reference, annotation)?;
+        Ok(annotation)
+    }
+
+    /// Adds an annotation to a page's `/Annots` array, which may be direct or
+    /// indirect.
+    pub(crate) fn append_annotation(&mut self, page: IndirectRef, annotation: IndirectRef) -> Result<()> {
+        let mut page_dict = self.object(page)?;
+        let Object::Dictionary(dict) = &mut page_dict else {
+            return Err(Error::Type(format!("Expected dict got {:?}", page_dict)));
+        };
+
+        match dict.get(names::ANNOTS.as_bytes()) {
+            Some(&Object::Indirect(annots)) => {
+                let mut array = self.object(annots)?;
+                let Object::Array(items) = &mut array else {
+                    return Err(Error::Type(format!("Expected array got {:?}", array)));
+                };
+                items.push(Object::Indirect(annotation));
+                self.replace(annots, array);
+            }
+            Some(Object::Array(items)) => {
+                let mut items = items.clone();
+                items.push(Object::Indirect(annotation));
+                dict.insert(Name::from(names::ANNOTS), Object::Array(items));
+                self.replace(page, page_dict);
+            }
+            _ => {
+                dict.insert(
+                    Name::from(names::ANNOTS),
+                    Object::Array(vec![Object::Indirect(annotation)]),
+                );
+                self.replace(page, page_dict);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// Highlights each search match, returning the updated file.
+    pub fn highlight_matches(
+        &self,
+        matches: &[SearchMatch],
+        options: &HighlightOptions,
+    ) -> Result<Vec<u8>> {
+        let pages = self.pages()?;
+        let mut update = IncrementalUpdate::new(self)?;
+        for search_match in matches {
+            let page = pages
+                .get(search_match.page_index)
+                .ok_or(Error::Type(format!(
+                    "Page {} is out of range",
+                    search_match.page_index
+                )))?;
+            update.add_highlight(page, &search_match.quads, options)?;
+        }
+        update.to_bytes()
+    }
+}
+
+fn dict<'a, const N: usize>(entries: [(&'a str, Object<'a>); N]) -> Object<'a> {
+    let dict = entries
+        .into_iter()
+        .map(|(key, value)| (Name::from(key), value))
+        .collect::<Dictionary>();
+    Object::Dictionary(dict)
+}
+
+fn name(name: &str) -> Object<'_> {
+    Object::Name(Name::from(name))
+}
+
+fn rect_object<'a>(x0: f64, y0: f64, x1: f64, y1: f64) -> Object<'a> {
+    Object::Array(vec![
+        Object::Real(x0),
+        Object::Real(y0),
+        Object::Real(x1),
+        Object::Real(y1),
+    ])
+}
+
+#[cfg(all(test, any(feature = "miniz_oxide", feature = "flate2")))]
+mod tests {
+    use super::*;
+    use crate::geometry::Rect;
+
+    #[test]
+    fn should_highlight_search_results() {
+        let mut file = PdfFile::read_file("./examples/hello-world.pdf").unwrap();
+        file.load_xref_table().unwrap();
+        let matches = file.search("world").unwrap();
+        let updated = file
+            .highlight_matches(&matches, &HighlightOptions::default())
+            .unwrap();
+
+        let mut file = PdfFile::from_raw(updated);
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let annots = file
+            .resolve_owned(pages[0].dict().get(names::ANNOTS).unwrap())
+            .unwrap();
+        let Object::Array(annots) = annots else {
+            panic!("Expected array");
+        };
+        assert_eq!(annots.len(), 1);
+
+        let annotation = file.resolve_owned(&annots[0]).unwrap();
+        assert!(annotation
+            .get_name(names::SUBTYPE)
+            .unwrap()
+            .matches(names::HIGHLIGHT));
+        assert_eq!(annotation.get_array(names::QUAD_POINTS).unwrap().len(), 8);
+        let rect = Rect::from_object(annotation.get(names::RECT).unwrap()).unwrap();
+        assert_eq!(rect, matches[0].quads[0].bounds());
+
+        let appearance = annotation.get_dict(names::AP).unwrap();
+        let appearance = file
+            .resolve_owned(appearance.get(names::N.as_bytes()).unwrap())
+            .unwrap();
+        let (_, content) = appearance.as_stream().unwrap();
+        assert!(content.starts_with(b"/GS0 gs 1 1 0 rg"));
+
+        // The original text is still there
+        assert_eq!(pages[0].extract_text().unwrap(), "Hello, world!");
+    }
+}
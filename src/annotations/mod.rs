@@ -0,0 +1,8 @@
+pub mod appearance;
+#[cfg(feature = "write")]
+pub mod create;
+#[cfg(feature = "write")]
+pub mod highlight;
+#[cfg(feature = "write")]
+pub mod replies;
+pub mod threads;
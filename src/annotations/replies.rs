@@ -0,0 +1,110 @@
+//! Adds replies, review states and pop-up windows to annotations, which are
+//! read back as threads by `Page::comment_threads` (Adobe, 2008, p. 390-392,
+//! 397, 406).
+
+use crate::annotations::create::{AnnotationKind, AnnotationOptions};
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::geometry::Rect;
+use crate::objects::{names, Dictionary, IndirectRef, Name, Object};
+use crate::writing::incremental::IncrementalUpdate;
+use std::borrow::Cow;
+
+/// Annotation flags (Adobe, 2008, p. 385).
+const HIDDEN_FLAG: i64 = 2;
+
+impl<'a> IncrementalUpdate<'a> {
+    /// Adds a note that replies to an annotation, at the same place on the
+    /// page.
+    pub fn add_reply(
+        &mut self,
+        page: &Page<'a>,
+        target: IndirectRef,
+        text: &str,
+        options: &AnnotationOptions,
+    ) -> Result<IndirectRef> {
+        let options = AnnotationOptions {
+            contents: Some(text.into()),
+            ..options.clone()
+        };
+        self.add_related(page, target, &options, &[])
+    }
+
+    /// Records a review state, such as `Accepted` or `Rejected` in the
+    /// `Review` model, as a hidden reply to an annotation.
+    pub fn set_review_state(
+        &mut self,
+        page: &Page<'a>,
+        target: IndirectRef,
+        model: &str,
+        state: &str,
+        options: &AnnotationOptions,
+    ) -> Result<IndirectRef> {
+        let text = |text: &str| Object::String(Cow::Owned(text.as_bytes().to_vec()));
+        let entries = [
+            (names::STATE, text(state)),
+            (names::STATE_MODEL, text(model)),
+            (names::F, Object::Integer(HIDDEN_FLAG)),
+        ];
+        self.add_related(page, target, options, &entries)
+    }
+
+    /// Adds a note that refers to another annotation with `/IRT`, with some
+    /// extra entries.
+    fn add_related(
+        &mut self,
+        page: &Page<'a>,
+        target: IndirectRef,
+        options: &AnnotationOptions,
+        entries: &[(&str, Object<'a>)],
+    ) -> Result<IndirectRef> {
+        let rect = Rect::from_object(self.object(target)?.get_required(names::RECT)?)?;
+        let kind = AnnotationKind::Text { open: false };
+        let reply = self.add_annotation(page, rect, &kind, options)?;
+
+        let mut annotation = self.object(reply)?;
+        let Object::Dictionary(dict) = &mut annotation else {
+            return Err(Error::Type(format!("Expected dict got {:?}", annotation)));
+        };
+        dict.insert(Name::from(names::IRT), Object::Indirect(target));
+        for (key, value) in entries {
+            dict.insert(Name::from(*key).into_owned(), value.clone());
+        }
+        self.replace(reply, annotation);
+        Ok(reply)
+    }
+
+    /// Adds a pop-up window that shows the text of an annotation.
+    pub fn add_popup(
+        &mut self,
+        page: &Page<'a>,
+        parent: IndirectRef,
+        rect: Rect,
+        open: bool,
+    ) -> Result<IndirectRef> {
+        let page_reference = page
+            .reference()
+            .ok_or(Error::Type("Cannot annotate a direct page object".into()))?;
+        let popup = [
+            (names::TYPE, Object::Name(Name::from(names::ANNOT))),
+            (names::SUBTYPE, Object::Name(Name::from(names::POPUP))),
+            (names::RECT, rect.to_object()),
+            (names::P, Object::Indirect(page_reference)),
+            (names::PARENT, Object::Indirect(parent)),
+            (names::OPEN, Object::Boolean(open)),
+        ]
+        .into_iter()
+        .map(|(key, value)| (Name::from(key), value))
+        .collect::<Dictionary>();
+        let popup = self.add(Object::Dictionary(popup));
+
+        let mut annotation = self.object(parent)?;
+        let Object::Dictionary(dict) = &mut annotation else {
+            return Err(Error::Type(format!("Expected dict got {:?}", annotation)));
+        };
+        dict.insert(Name::from(names::POPUP), Object::Indirect(popup));
+        self.replace(parent, annotation);
+        self.append_annotation(page_reference, popup)?;
+        Ok(popup)
+    }
+}
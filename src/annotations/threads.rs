@@ -0,0 +1,256 @@
+//! Models the conversations in marked-up documents, where annotations have
+//! pop-up windows, reply to each other and record review states
+//! (Adobe, 2008, p. 390-392, 397, 406).
+
+use crate::document::dicts::{AnnotationDict, PageNode};
+use crate::document::page::Page;
+use crate::error::Result;
+use crate::objects::{names, IndirectRef};
+use std::collections::{HashMap, HashSet};
+
+/// A change to the review state of an annotation, which is recorded as a
+/// reply with a `/State` entry (Adobe, 2008, p. 397).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReviewState {
+    pub author: Option<String>,
+    /// The state model, `Review` or `Marked`.
+    pub model: String,
+    /// The state, such as `Accepted`, `Rejected`, `Cancelled`, `Completed`,
+    /// `None`, `Marked` or `Unmarked`.
+    pub state: String,
+    pub modified: Option<String>,
+}
+
+/// An annotation along with the replies to it.
+#[derive(Clone, Debug)]
+pub struct Thread<'a> {
+    pub annotation: AnnotationDict<'a>,
+    pub popup: Option<AnnotationDict<'a>>,
+    /// The replies, in the order that they are on the page, each with its
+    /// own replies.
+    pub replies: Vec<Thread<'a>>,
+    /// The annotations that are grouped with this one, which viewers treat
+    /// as a single unit.
+    pub group: Vec<AnnotationDict<'a>>,
+    /// The review states that have been set, oldest first.
+    pub states: Vec<ReviewState>,
+}
+
+impl Thread<'_> {
+    /// The latest state in a state model, or `None` if it has not been set.
+    pub fn latest_state(&self, model: &str) -> Option<&str> {
+        self.states
+            .iter()
+            .rev()
+            .find(|state| state.model == model)
+            .map(|state| state.state.as_str())
+    }
+
+    /// The latest review state, such as `Accepted` or `Rejected`.
+    pub fn review_state(&self) -> Option<&str> {
+        self.latest_state("Review")
+    }
+
+    /// The number of replies, including replies to replies.
+    pub fn reply_count(&self) -> usize {
+        self.replies
+            .iter()
+            .map(|reply| 1 + reply.reply_count())
+            .sum()
+    }
+}
+
+/// How an annotation relates to the one that its `/IRT` refers to.
+enum Relation {
+    Reply,
+    Group,
+    State(ReviewState),
+}
+
+impl<'a> Page<'a> {
+    /// The annotations on the page as threads of replies. Annotations that
+    /// do not reply to another annotation on the page start a thread, and
+    /// pop-up windows and review states are attached to the annotations that
+    /// they belong to.
+    pub fn comment_threads(&self) -> Result<Vec<Thread<'a>>> {
+        let annotations = PageNode::new(self.file(), self.dict())?.annotations()?;
+        let references = annotations
+            .iter()
+            .filter_map(|annotation| annotation.reference())
+            .collect::<HashSet<_>>();
+
+        let mut popups = HashMap::new();
+        let mut targets = Vec::new();
+        for (i, annotation) in annotations.iter().enumerate() {
+            if annotation.subtype()? == names::POPUP {
+                if let Some(parent) = annotation.parent() {
+                    popups.insert(parent, i);
+                }
+                continue;
+            }
+            let target = annotation
+                .in_reply_to()
+                .filter(|target| references.contains(target))
+                .filter(|&target| Some(target) != annotation.reference());
+            let relation = match (annotation.state()?, annotation.reply_type()?.as_str()) {
+                (Some(state), _) => Relation::State(ReviewState {
+                    author: annotation.author()?,
+                    model: annotation
+                        .state_model()?
+                        .unwrap_or_else(|| default_model(&state).into()),
+                    state,
+                    modified: annotation.modified()?,
+                }),
+                (None, "Group") => Relation::Group,
+                (None, _) => Relation::Reply,
+            };
+            targets.push((i, target, relation));
+        }
+
+        let builder = ThreadBuilder {
+            annotations: &annotations,
+            popups,
+            targets,
+        };
+        let mut visited = HashSet::new();
+        let mut threads = Vec::new();
+        for &(i, target, _) in &builder.targets {
+            if target.is_none() {
+                threads.push(builder.build(i, &mut visited)?);
+            }
+        }
+        // Replies that form a cycle have no root, so each cycle is started
+        // from its first annotation on the page
+        for (i, _, relation) in &builder.targets {
+            if matches!(relation, Relation::Reply) && !visited.contains(i) {
+                threads.push(builder.build(*i, &mut visited)?);
+            }
+        }
+        Ok(threads)
+    }
+}
+
+/// The state model that a state belongs to when the annotation does not
+/// say (Adobe, 2008, p. 397).
+fn default_model(state: &str) -> &'static str {
+    match state {
+        "Marked" | "Unmarked" => "Marked",
+        _ => "Review",
+    }
+}
+
+struct ThreadBuilder<'b, 'a> {
+    annotations: &'b [AnnotationDict<'a>],
+    popups: HashMap<IndirectRef, usize>,
+    /// Each annotation other than a pop-up, along with the annotation on the
+    /// page that it refers to and how.
+    targets: Vec<(usize, Option<IndirectRef>, Relation)>,
+}
+
+impl<'a> ThreadBuilder<'_, 'a> {
+    fn build(&self, index: usize, visited: &mut HashSet<usize>) -> Result<Thread<'a>> {
+        visited.insert(index);
+        let annotation = &self.annotations[index];
+        let mut thread = Thread {
+            annotation: annotation.clone(),
+            popup: None,
+            replies: Vec::new(),
+            group: Vec::new(),
+            states: Vec::new(),
+        };
+        let Some(reference) = annotation.reference() else {
+            return Ok(thread);
+        };
+        thread.popup = self
+            .popups
+            .get(&reference)
+            .map(|&i| self.annotations[i].clone());
+
+        for (i, target, relation) in &self.targets {
+            if *target != Some(reference) || visited.contains(i) {
+                continue;
+            }
+            match relation {
+                Relation::Reply => thread.replies.push(self.build(*i, visited)?),
+                Relation::Group => {
+                    visited.insert(*i);
+                    thread.group.push(self.annotations[*i].clone());
+                }
+                Relation::State(state) => {
+                    visited.insert(*i);
+                    thread.states.push(state.clone());
+                }
+            }
+        }
+        Ok(thread)
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use crate::annotations::create::{AnnotationKind, AnnotationOptions};
+    use crate::geometry::Rect;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support::hello_world;
+    use crate::writing::incremental::IncrementalUpdate;
+
+    #[test]
+    fn should_thread_replies() {
+        let mut file = PdfFile::from_raw(hello_world(Default::default()));
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let page = &pages[0];
+        let author = |name: &str| AnnotationOptions {
+            author: Some(name.into()),
+            ..Default::default()
+        };
+
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        let rect = Rect::new(100.0, 100.0, 200.0, 150.0);
+        let square = update
+            .add_annotation(page, rect, &AnnotationKind::Square, &author("Ann"))
+            .unwrap();
+        let popup = update
+            .add_popup(page, square, Rect::new(200.0, 150.0, 300.0, 250.0), false)
+            .unwrap();
+        let reply = update
+            .add_reply(page, square, "Why?", &author("Bob"))
+            .unwrap();
+        update
+            .add_reply(page, reply, "Because", &author("Ann"))
+            .unwrap();
+        update
+            .set_review_state(page, square, "Review", "Rejected", &author("Bob"))
+            .unwrap();
+        update
+            .set_review_state(page, square, "Review", "Accepted", &author("Cat"))
+            .unwrap();
+        update
+            .add_annotation(page, rect, &AnnotationKind::Circle, &author("Cat"))
+            .unwrap();
+        let updated = update.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_raw(updated);
+        file.load_xref_table().unwrap();
+        let threads = file.pages().unwrap()[0].comment_threads().unwrap();
+        assert_eq!(threads.len(), 2);
+
+        let thread = &threads[0];
+        assert_eq!(thread.annotation.reference(), Some(square));
+        assert_eq!(
+            thread.popup.as_ref().and_then(|popup| popup.reference()),
+            Some(popup)
+        );
+        assert_eq!(thread.reply_count(), 2);
+        let reply = &thread.replies[0];
+        assert_eq!(reply.annotation.author().unwrap().as_deref(), Some("Bob"));
+        assert_eq!(
+            reply.replies[0].annotation.contents().unwrap().as_deref(),
+            Some("Because")
+        );
+        assert_eq!(thread.states.len(), 2);
+        assert_eq!(thread.review_state(), Some("Accepted"));
+        assert_eq!(thread.latest_state("Marked"), None);
+        assert_eq!(threads[1].reply_count(), 0);
+    }
+}
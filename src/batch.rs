@@ -0,0 +1,151 @@
+//! Helpers for processing many documents at once, such as when analysing a
+//! corpus.
+
+use crate::error::{Error, Result};
+use crate::parsing::context::ParseOptions;
+use crate::parsing::pdf_file::PdfFile;
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// The number of documents processed at once. Each worker only holds one
+    /// document in memory at a time, so this also bounds memory usage.
+    pub threads: NonZeroUsize,
+    /// The options used to parse each document.
+    pub parse: ParseOptions,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            parse: ParseOptions::default(),
+        }
+    }
+}
+
+/// The outcome of processing a single document.
+#[derive(Debug, PartialEq)]
+pub struct BatchResult<T> {
+    pub path: PathBuf,
+    pub result: Result<T>,
+}
+
+/// Opens each document and passes it to `f`, using a pool of worker threads.
+///
+/// Paths are only read from the iterator as workers become free, so it may be
+/// lazy. The results are returned in the same order as the paths, and an
+/// error reading or processing one document does not stop the others. Nor
+/// does a panic, which is reported as an `Error::Panic` for that document,
+/// though the panic hook still prints its message as usual.
+pub fn process<I, F, T>(paths: I, options: BatchOptions, f: F) -> Vec<BatchResult<T>>
+where
+    I: IntoIterator,
+    I::Item: AsRef<Path>,
+    I::IntoIter: Send,
+    F: Fn(&mut PdfFile<'static>) -> Result<T> + Sync,
+    T: Send,
+{
+    let paths = Mutex::new(paths.into_iter().enumerate());
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..options.threads.get() {
+            scope.spawn(|| loop {
+                // Only hold the lock while taking the next path
+                let next = paths.lock().unwrap_or_else(|err| err.into_inner()).next();
+                let Some((index, path)) = next else { break };
+
+                let path = path.as_ref().to_path_buf();
+                // The document is dropped if processing it panics, so only
+                // state captured by `f` could be seen half-updated
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    PdfFile::read_file(&path)
+                        .and_then(|file| f(&mut file.with_options(options.parse)))
+                }))
+                .unwrap_or_else(|payload| Err(Error::Panic(panic_message(payload))));
+
+                results
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .push((index, BatchResult { path, result }));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(|err| err.into_inner());
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// The message that a panic was started with, if it was a string.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "Unknown panic".into(), |message| message.to_string()),
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn should_process_documents_in_order() {
+        let encrypted = test_support::temp_file("batch", &test_support::encrypted());
+        let paths = [
+            encrypted.clone(),
+            PathBuf::from("./tests/corpus/missing.pdf"),
+            PathBuf::from("./tests/corpus/valid.pdf"),
+        ];
+        let options = BatchOptions {
+            threads: NonZeroUsize::new(2).unwrap(),
+            ..Default::default()
+        };
+
+        let results = process(&paths, options, |file| {
+            file.version().map(|v| v.into_owned())
+        });
+        std::fs::remove_file(encrypted).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].path, paths[0]);
+        assert_eq!(results[0].result, Ok("1.7".into()));
+        assert!(matches!(results[1].result, Err(Error::IO(_))));
+        assert_eq!(results[2].path, paths[2]);
+        assert!(results[2].result.is_ok());
+    }
+
+    #[test]
+    fn should_report_panics_as_errors() {
+        let encrypted = test_support::temp_file("batch-panic", &test_support::encrypted());
+        let paths = [encrypted.clone(), PathBuf::from("./tests/corpus/valid.pdf")];
+        let options = BatchOptions {
+            threads: NonZeroUsize::MIN,
+            ..Default::default()
+        };
+
+        let results = process(&paths, options, |file| {
+            file.load_xref_table()?;
+            if file.encryption_info()?.is_some() {
+                panic!("Cannot process encrypted files");
+            }
+            Ok(())
+        });
+        std::fs::remove_file(encrypted).unwrap();
+
+        assert_eq!(
+            results[0].result,
+            Err(Error::Panic("Cannot process encrypted files".into()))
+        );
+        assert_eq!(results[1].result, Ok(()));
+    }
+}
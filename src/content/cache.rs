@@ -0,0 +1,163 @@
+//! Caches decoded content streams and the operators parsed from them, so that
+//! running several analyses over the same page, such as text extraction and
+//! finding images, decodes and parses its content once.
+
+use crate::content::operators::{parse_operators, Operator};
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::objects::{IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The caches of a file, which are filled as streams are read.
+#[derive(Debug, Default)]
+pub(crate) struct ContentCache {
+    /// Decoded streams, by the reference of the stream.
+    streams: Mutex<HashMap<IndirectRef, Arc<[u8]>>>,
+    /// Parsed operators, by the reference of the page that they belong to.
+    operators: Mutex<HashMap<IndirectRef, Arc<[Operator<'static>]>>>,
+}
+
+/// An object that was resolved through the stream cache.
+pub(crate) enum Resolved<'a> {
+    Stream(Arc<[u8]>),
+    Other(Object<'a>),
+}
+
+impl<'a> PdfFile<'a> {
+    /// The decoded data of a stream, which is cached until `clear_caches` is
+    /// called.
+    pub fn decoded_stream(&self, reference: IndirectRef) -> Result<Arc<[u8]>> {
+        match self.resolve_cached(&Object::Indirect(reference))? {
+            Resolved::Stream(data) => Ok(data),
+            Resolved::Other(other) => Err(Error::Type(format!("Expected stream got {:?}", other))),
+        }
+    }
+
+    /// Empties the caches of decoded streams and parsed operators, such as
+    /// to free memory once a page has been processed.
+    pub fn clear_caches(&mut self) {
+        let cache = &mut self.content_cache;
+        cache
+            .streams
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+        cache
+            .operators
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+
+    /// Resolves an object, taking streams from the cache, and caching any
+    /// stream that is decoded.
+    pub(crate) fn resolve_cached<'b>(&'b self, object: &Object<'b>) -> Result<Resolved<'b>> {
+        let &Object::Indirect(reference) = object else {
+            return Ok(match object {
+                Object::Stream(_, data) => Resolved::Stream(data.as_ref().into()),
+                other => Resolved::Other(other.clone()),
+            });
+        };
+        let streams = &self.content_cache.streams;
+        if let Some(data) = lock(streams).get(&reference) {
+            return Ok(Resolved::Stream(data.clone()));
+        }
+        match self.object(reference)? {
+            Object::Stream(_, data) => {
+                let data = Arc::<[u8]>::from(data.as_ref());
+                lock(streams).insert(reference, data.clone());
+                Ok(Resolved::Stream(data))
+            }
+            other => Ok(Resolved::Other(other)),
+        }
+    }
+}
+
+impl<'a> Page<'a> {
+    /// The operators in the page's content streams, which are cached until
+    /// `clear_caches` is called. Operators that are not defined by the spec,
+    /// which may only appear between `BX` and `EX`, are left out along with
+    /// their operands, as their meaning is unknown (Adobe, 2008, p. 84).
+    pub fn operators(&self) -> Result<Arc<[Operator<'static>]>> {
+        let operators = &self.file().content_cache.operators;
+        if let Some(reference) = self.reference() {
+            if let Some(cached) = lock(operators).get(&reference) {
+                return Ok(cached.clone());
+            }
+        }
+
+        let content = self.content_bytes()?;
+        let parsed = parse_operators(&content, &Default::default())?
+            .iter()
+            .filter_map(Operator::to_static)
+            .collect::<Arc<[_]>>();
+        if let Some(reference) = self.reference() {
+            lock(operators).insert(reference, parsed.clone());
+        }
+        Ok(parsed)
+    }
+}
+
+/// Locks a cache, even if another thread panicked while holding it. Entries
+/// are only ever inserted whole, so a poisoned cache is still consistent.
+fn lock<T>(cache: &Mutex<T>) -> MutexGuard<'_, T> {
+    cache.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::hello_world;
+
+    #[test]
+    fn should_cache_content_until_cleared() {
+        let raw = hello_world(Default::default());
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+
+        let pages = file.pages().unwrap();
+        let operators = pages[0].operators().unwrap();
+        assert_eq!(operators.len(), 5);
+        assert_eq!(operators[3].operator, b"Tj");
+        assert!(Arc::ptr_eq(&operators, &pages[0].operators().unwrap()));
+
+        let content = file.decoded_stream(IndirectRef::new(4, 0)).unwrap();
+        assert_eq!(&content[..], crate::test_support::HELLO_WORLD_CONTENT);
+        assert!(Arc::ptr_eq(
+            &content,
+            &file.decoded_stream(IndirectRef::new(4, 0)).unwrap()
+        ));
+        assert!(file.decoded_stream(IndirectRef::new(5, 0)).is_err());
+        drop(pages);
+
+        file.clear_caches();
+        let pages = file.pages().unwrap();
+        assert!(!Arc::ptr_eq(&operators, &pages[0].operators().unwrap()));
+    }
+
+    #[test]
+    fn should_survive_a_panic_while_locked() {
+        let raw = hello_world(Default::default());
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+
+        let cache = &file.content_cache;
+        let _ = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let _streams = lock(&cache.streams);
+                    let _operators = lock(&cache.operators);
+                    panic!("poison the caches");
+                })
+                .join()
+        });
+        assert!(cache.streams.is_poisoned());
+
+        let pages = file.pages().unwrap();
+        assert_eq!(pages[0].operators().unwrap().len(), 5);
+        drop(pages);
+        file.clear_caches();
+    }
+}
@@ -0,0 +1,208 @@
+//! Editing content streams one operator at a time, for tasks such as removing
+//! images or text, or transforming everything on a page.
+
+use crate::content::operators::{parse_operators, write_operators, Operator, INLINE_IMAGE};
+use crate::document::page::Page;
+use crate::error::Result;
+use crate::geometry::Matrix;
+use crate::objects::{names, Name, Object};
+use crate::parsing::context::ParseContext;
+
+/// A parsed content stream that can be edited and written back.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ContentStream<'a> {
+    pub operators: Vec<Operator<'a>>,
+}
+
+impl<'a> ContentStream<'a> {
+    pub fn new(operators: Vec<Operator<'a>>) -> Self {
+        Self { operators }
+    }
+
+    pub fn parse(raw: &'a [u8], context: &ParseContext) -> Result<Self> {
+        Ok(Self::new(parse_operators(raw, context)?))
+    }
+
+    pub fn insert(&mut self, index: usize, operator: Operator<'a>) {
+        self.operators.insert(index, operator);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Operator<'a> {
+        self.operators.remove(index)
+    }
+
+    /// Replaces an operator with any number of operators.
+    pub fn replace<I>(&mut self, index: usize, operators: I)
+    where
+        I: IntoIterator<Item = Operator<'a>>,
+    {
+        self.operators.splice(index..=index, operators);
+    }
+
+    /// Keeps only the operators for which `f` returns true.
+    pub fn retain<F: FnMut(&Operator<'a>) -> bool>(&mut self, f: F) {
+        self.operators.retain(f);
+    }
+
+    /// Replaces every operator with the operators that `f` returns for it,
+    /// which may be none, the operator itself, or several operators.
+    pub fn flat_map<F, I>(&mut self, mut f: F)
+    where
+        F: FnMut(Operator<'a>) -> I,
+        I: IntoIterator<Item = Operator<'a>>,
+    {
+        let operators = std::mem::take(&mut self.operators);
+        self.operators = operators.into_iter().flat_map(&mut f).collect();
+    }
+
+    /// Removes every text object, from `BT` to `ET` (Adobe, 2008, p. 405).
+    /// Anything else inside a text object, such as a colour change, is
+    /// removed too.
+    pub fn remove_text(&mut self) {
+        let mut in_text = false;
+        self.retain(|operator| match operator.operator {
+            b"BT" => {
+                in_text = true;
+                false
+            }
+            b"ET" => {
+                in_text = false;
+                false
+            }
+            _ => !in_text,
+        });
+    }
+
+    /// Removes inline images, and the XObjects drawn with `Do` for which
+    /// `is_image` returns true. See `Page::image_names` for the names of a
+    /// page's image XObjects.
+    pub fn remove_images<F: Fn(&Name) -> bool>(&mut self, is_image: F) {
+        self.retain(
+            |operator| match (operator.operator, operator.operands.as_slice()) {
+                (INLINE_IMAGE, _) => false,
+                (b"Do", [Object::Name(name)]) => !is_image(name),
+                _ => true,
+            },
+        );
+    }
+
+    /// Transforms everything in the stream by a matrix, by wrapping it in
+    /// `q` and `Q` with a `cm` operator at the start (Adobe, 2008, p. 219).
+    pub fn transform(&mut self, matrix: Matrix) {
+        let cm = Operator::new(b"cm", matrix.to_objects());
+        self.operators
+            .splice(0..0, [Operator::new(b"q", vec![]), cm]);
+        self.operators.push(Operator::new(b"Q", vec![]));
+    }
+
+    /// Serializes the operators, one per line.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        write_operators(&mut out, &self.operators)?;
+        Ok(out)
+    }
+}
+
+impl<'a> Page<'a> {
+    /// The names of the image XObjects in the page's resources, for use with
+    /// `ContentStream::remove_images`.
+    pub fn image_names(&self) -> Result<Vec<Name<'a>>> {
+        Ok(self
+            .encoded_resources_of(names::X_OBJECT)?
+            .into_iter()
+            .filter(|(_, x_object)| {
+                x_object
+                    .get_name(names::SUBTYPE)
+                    .is_ok_and(|subtype| subtype.matches(names::IMAGE))
+            })
+            .map(|(name, _)| name)
+            .collect())
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    fn parse(raw: &[u8]) -> ContentStream<'_> {
+        ContentStream::parse(raw, &ParseContext::default()).unwrap()
+    }
+
+    #[test]
+    fn should_edit_and_write_operators() {
+        let raw = b"q 1 0 0 1 10 20 cm /Im0 Do Q BT /F1 12 Tf (Hi) Tj ET BI /W 1 /H 1 ID \xff\nEI";
+        let mut content = parse(raw);
+        assert_eq!(content.operators.len(), 9);
+
+        let written = content.to_bytes().unwrap();
+        assert_eq!(parse(&written), content);
+
+        content.replace(1, [Operator::new(b"w", vec![Object::Integer(2)])]);
+        assert_eq!(
+            content.operators[1],
+            Operator::new(b"w", vec![Object::Integer(2)])
+        );
+        content.remove_text();
+        content.remove_images(|name| name.matches("Im0"));
+        let operators = content
+            .operators
+            .iter()
+            .map(|operator| operator.operator)
+            .collect::<Vec<_>>();
+        assert_eq!(operators, [b"q".as_slice(), b"w", b"Q"]);
+
+        content.transform(Matrix::scale(0.5, 0.5));
+        assert_eq!(
+            content.to_bytes().unwrap(),
+            b"q\n0.5 0 0 0.5 0 0 cm\nq\n2 w\nQ\nQ\n"
+        );
+    }
+
+    #[test]
+    fn should_find_image_names() {
+        let mut builder = DocumentBuilder::new();
+        let image = builder.add(Object::Stream(
+            Box::new(dict(&[
+                (names::TYPE, name(names::X_OBJECT)),
+                (names::SUBTYPE, name(names::IMAGE)),
+            ])),
+            Cow::Borrowed(b"\0"),
+        ));
+        let form = builder.add(Object::Stream(
+            Box::new(dict(&[
+                (names::TYPE, name(names::X_OBJECT)),
+                (names::SUBTYPE, name(names::FORM)),
+            ])),
+            Cow::Borrowed(b""),
+        ));
+        let resources = dict(&[(
+            names::X_OBJECT,
+            dict(&[
+                ("Im0", Object::Indirect(image)),
+                ("Fm0", Object::Indirect(form)),
+            ]),
+        )]);
+        let raw = test_support::single_page(builder, resources, b"/Im0 Do /Fm0 Do");
+
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        let page = &file.pages().unwrap()[0];
+        let images = page.image_names().unwrap();
+        assert_eq!(images, vec![Name::from("Im0")]);
+
+        let bytes = page.content_bytes().unwrap();
+        let mut content = parse(&bytes);
+        content.remove_images(|name| images.contains(name));
+        assert_eq!(content.to_bytes().unwrap(), b"/Fm0 Do\n");
+
+        // Images whose filters cannot be decoded are found as well
+        let mut file = PdfFile::from_raw(test_support::jpeg_page());
+        file.load_xref_table().unwrap();
+        let images = file.pages().unwrap()[0].image_names().unwrap();
+        assert_eq!(images, vec![Name::from("Im1")]);
+    }
+}
@@ -0,0 +1,509 @@
+//! Groups positioned glyphs into words, lines and blocks of text, using the
+//! distances between them.
+
+use crate::content::text::{Glyph, TextFragment};
+use crate::document::page::Page;
+use crate::error::Result;
+use crate::geometry::Rect;
+#[cfg(feature = "parallel")]
+use crate::parsing::pdf_file::PdfFile;
+
+/// Thresholds used when grouping text, as multiples of the font size or the
+/// width of a space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayoutOptions {
+    /// The furthest that two baselines may be apart while still being on the
+    /// same line, as a multiple of the font size.
+    pub line_tolerance: f64,
+    /// The smallest gap between glyphs that starts a new word, as a multiple
+    /// of the width of a space.
+    pub word_gap: f64,
+    /// The largest distance between baselines within a block, as a multiple
+    /// of the font size.
+    pub block_spacing: f64,
+    /// Joins words that are hyphenated across lines within a block, and drops
+    /// soft hyphens (U+00AD), which only mark where a word may be broken.
+    pub join_hyphenated: bool,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            line_tolerance: 0.3,
+            word_gap: 0.5,
+            block_spacing: 1.5,
+            join_hyphenated: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Word {
+    pub text: String,
+    pub bbox: Rect,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    pub words: Vec<Word>,
+    pub bbox: Rect,
+    /// The y coordinate of the baseline.
+    pub baseline: f64,
+    pub font_size: f64,
+}
+
+impl Line {
+    pub fn text(&self) -> String {
+        let words = self.words.iter().map(|word| word.text.as_str());
+        words.collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// A paragraph or other block of consecutive lines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextBlock {
+    pub lines: Vec<Line>,
+    pub bbox: Rect,
+}
+
+impl TextBlock {
+    pub fn text(&self) -> String {
+        let lines = self.lines.iter().map(Line::text);
+        lines.collect::<Vec<_>>().join("\n")
+    }
+}
+
+impl<'a> Page<'a> {
+    /// Returns the text on the page grouped into blocks, from top to bottom.
+    pub fn text_blocks(&self, options: &LayoutOptions) -> Result<Vec<TextBlock>> {
+        Ok(segment(&self.text_fragments()?, options))
+    }
+
+    /// Returns the text on the page, with blocks separated by blank lines.
+    pub fn extract_text(&self) -> Result<String> {
+        self.extract_text_with(&LayoutOptions::default())
+    }
+
+    /// Like `extract_text`, but with the given thresholds and options.
+    pub fn extract_text_with(&self, options: &LayoutOptions) -> Result<String> {
+        let blocks = self.text_blocks(options)?;
+        let blocks = blocks.iter().map(TextBlock::text);
+        Ok(blocks.collect::<Vec<_>>().join("\n\n"))
+    }
+
+    /// Returns the text on the page in the order that it is drawn, which is
+    /// usually the order that it was written in, with a new line wherever the
+    /// baseline moves.
+    pub fn extract_raw_text(&self, options: &LayoutOptions) -> Result<String> {
+        Ok(raw_text(&self.text_fragments()?, options))
+    }
+
+    /// Returns the text on the page laid out in a grid of fixed-width
+    /// characters, keeping columns and gaps roughly where they are on the
+    /// page.
+    pub fn extract_layout_text(&self, options: &LayoutOptions) -> Result<String> {
+        Ok(layout_text(&self.text_fragments()?, options))
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<'a> PdfFile<'a> {
+    /// Extracts the text of every page on the rayon thread pool, returning it
+    /// in page order. The xref table must already be loaded.
+    ///
+    /// Pages only share the file, which can be read from many threads at
+    /// once, so this scales with the number of pages.
+    pub fn extract_text_parallel(&self) -> Result<Vec<String>> {
+        use rayon::prelude::*;
+
+        self.pages()?.par_iter().map(Page::extract_text).collect()
+    }
+}
+
+/// A glyph along with the properties of the fragment it came from.
+pub(crate) struct Entry<'g> {
+    pub glyph: &'g Glyph,
+    pub font_size: f64,
+    pub space_width: f64,
+}
+
+/// Groups glyphs into lines from top to bottom, with the glyphs in each line
+/// sorted from left to right. Text is assumed to be horizontal.
+pub(crate) fn group_lines<'g>(
+    fragments: &'g [TextFragment],
+    options: &LayoutOptions,
+) -> Vec<Vec<Entry<'g>>> {
+    let mut entries = fragments
+        .iter()
+        .flat_map(|fragment| {
+            fragment.glyphs.iter().map(|glyph| Entry {
+                glyph,
+                font_size: fragment.font_size,
+                space_width: fragment.space_width,
+            })
+        })
+        .collect::<Vec<_>>();
+    // From top to bottom
+    entries.sort_by(|a, b| b.glyph.origin.1.total_cmp(&a.glyph.origin.1));
+
+    let mut lines: Vec<Vec<Entry>> = Vec::new();
+    for entry in entries {
+        match lines.last_mut() {
+            Some(line)
+                if (line[0].glyph.origin.1 - entry.glyph.origin.1).abs()
+                    <= options.line_tolerance * line[0].font_size.max(entry.font_size) =>
+            {
+                line.push(entry)
+            }
+            _ => lines.push(vec![entry]),
+        }
+    }
+
+    for line in &mut lines {
+        line.sort_by(|a, b| a.glyph.origin.0.total_cmp(&b.glyph.origin.0));
+    }
+    lines
+}
+
+/// Returns true if the gap between two glyphs on a line is wide enough to
+/// separate words.
+pub(crate) fn is_word_gap(previous: &Entry, entry: &Entry, options: &LayoutOptions) -> bool {
+    let gap = entry.glyph.bbox.x0 - previous.glyph.bbox.x1;
+    gap >= options.word_gap * previous.space_width.max(entry.space_width)
+}
+
+/// Groups fragments into words, lines and blocks. Text is assumed to be
+/// horizontal and read from left to right.
+pub fn segment(fragments: &[TextFragment], options: &LayoutOptions) -> Vec<TextBlock> {
+    let mut blocks: Vec<TextBlock> = Vec::new();
+    for entries in group_lines(fragments, options) {
+        let Some(line) = build_line(&entries, options) else {
+            continue;
+        };
+
+        match blocks.last_mut() {
+            Some(block) if belongs_to_block(block, &line, options) => {
+                block.bbox = block.bbox.union(&line.bbox);
+                block.lines.push(line);
+            }
+            _ => blocks.push(TextBlock {
+                bbox: line.bbox,
+                lines: vec![line],
+            }),
+        }
+    }
+
+    if options.join_hyphenated {
+        for block in &mut blocks {
+            join_hyphenated(block);
+        }
+    }
+    blocks
+}
+
+const SOFT_HYPHEN: char = '\u{AD}';
+
+/// Moves the rest of each word that is hyphenated at the end of a line onto
+/// that line, then removes any soft hyphens that are left. A hard hyphen is
+/// only removed if the next line carries on with a lowercase letter, so
+/// compounds such as "Jean-\nPaul" keep theirs.
+fn join_hyphenated(block: &mut TextBlock) {
+    let mut i = 0;
+    while i + 1 < block.lines.len() {
+        let (before, after) = block.lines.split_at_mut(i + 1);
+        let (line, next) = (&mut before[i], &mut after[0]);
+        if let (Some(word), Some(rest)) = (line.words.last_mut(), next.words.first()) {
+            let soft = word.text.ends_with(SOFT_HYPHEN);
+            let hard = word.text.ends_with('-')
+                && word
+                    .text
+                    .chars()
+                    .rev()
+                    .nth(1)
+                    .is_some_and(char::is_alphabetic)
+                && rest.text.starts_with(char::is_lowercase);
+            if soft || hard {
+                word.text.pop();
+                word.text.push_str(&rest.text);
+                word.bbox = word.bbox.union(&rest.bbox);
+                line.bbox = line.bbox.union(&rest.bbox);
+                next.words.remove(0);
+            }
+        }
+
+        match next
+            .words
+            .iter()
+            .map(|word| word.bbox)
+            .reduce(|a, b| a.union(&b))
+        {
+            Some(bbox) => {
+                next.bbox = bbox;
+                i += 1;
+            }
+            None => {
+                block.lines.remove(i + 1);
+            }
+        }
+    }
+
+    for line in &mut block.lines {
+        for word in &mut line.words {
+            word.text.retain(|c| c != SOFT_HYPHEN);
+        }
+    }
+}
+
+/// Splits a line of glyphs, sorted from left to right, into words.
+fn build_line(entries: &[Entry], options: &LayoutOptions) -> Option<Line> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut in_word = false;
+    let mut previous: Option<&Entry> = None;
+
+    for entry in entries {
+        let glyph = entry.glyph;
+        if glyph.text.trim().is_empty() {
+            in_word = false;
+            previous = Some(entry);
+            continue;
+        }
+
+        let is_gap = previous.is_some_and(|previous| is_word_gap(previous, entry, options));
+        match words.last_mut() {
+            Some(word) if in_word && !is_gap => {
+                word.text.push_str(&glyph.text);
+                word.bbox = word.bbox.union(&glyph.bbox);
+            }
+            _ => words.push(Word {
+                text: glyph.text.clone(),
+                bbox: glyph.bbox,
+            }),
+        }
+        in_word = true;
+        previous = Some(entry);
+    }
+
+    let bbox = words
+        .iter()
+        .map(|word| word.bbox)
+        .reduce(|a, b| a.union(&b))?;
+    let first = &entries[0];
+    Some(Line {
+        words,
+        bbox,
+        baseline: first.glyph.origin.1,
+        font_size: entries.iter().map(|e| e.font_size).fold(0.0, f64::max),
+    })
+}
+
+/// Joins fragments in the order they are given, starting a new line when the
+/// baseline moves and adding a space at gaps between words.
+pub fn raw_text(fragments: &[TextFragment], options: &LayoutOptions) -> String {
+    let mut text = String::new();
+    let mut previous: Option<(&Glyph, &TextFragment)> = None;
+    for fragment in fragments {
+        let Some(first) = fragment.glyphs.first() else {
+            continue;
+        };
+        if let Some((last, last_fragment)) = previous {
+            let font_size = fragment.font_size.max(last_fragment.font_size);
+            let space_width = fragment.space_width.max(last_fragment.space_width);
+            if (first.origin.1 - last.origin.1).abs() > options.line_tolerance * font_size {
+                text.push('\n');
+            } else if first.bbox.x0 - last.bbox.x1 >= options.word_gap * space_width
+                && !text.ends_with(' ')
+                && !first.text.starts_with(' ')
+            {
+                text.push(' ');
+            }
+        }
+        text.push_str(&fragment.text());
+        previous = fragment.glyphs.last().map(|last| (last, fragment));
+    }
+    text
+}
+
+/// Lays out lines of text in a grid, with each word in the column nearest to
+/// where it starts on the page, and blank lines for large gaps between lines.
+pub fn layout_text(fragments: &[TextFragment], options: &LayoutOptions) -> String {
+    let lines = group_lines(fragments, options)
+        .iter()
+        .filter_map(|entries| build_line(entries, options))
+        .collect::<Vec<_>>();
+    let words = || lines.iter().flat_map(|line| &line.words);
+    let Some(left) = words().map(|word| word.bbox.x0).reduce(f64::min) else {
+        return String::new();
+    };
+    // The average width of a character is used as the width of a column
+    let (width, chars) = words().fold((0.0, 0), |(width, chars), word| {
+        (width + word.bbox.width(), chars + word.text.chars().count())
+    });
+    let column_width = width / chars.max(1) as f64;
+
+    let mut text = String::new();
+    let mut previous: Option<&Line> = None;
+    for line in &lines {
+        if let Some(previous) = previous {
+            let line_height = LINE_HEIGHT * previous.font_size.max(line.font_size);
+            let gap = ((previous.baseline - line.baseline) / line_height).round();
+            for _ in 0..(gap as usize).max(1) {
+                text.push('\n');
+            }
+        }
+
+        let mut column = 0;
+        for word in &line.words {
+            let mut start = ((word.bbox.x0 - left) / column_width).round() as usize;
+            if column > 0 {
+                start = start.max(column + 1);
+            }
+            text.extend(std::iter::repeat_n(' ', start.saturating_sub(column)));
+            text.push_str(&word.text);
+            column = start.max(column) + word.text.chars().count();
+        }
+        previous = Some(line);
+    }
+    text
+}
+
+/// The usual distance between baselines, as a multiple of the font size.
+const LINE_HEIGHT: f64 = 1.2;
+
+fn belongs_to_block(block: &TextBlock, line: &Line, options: &LayoutOptions) -> bool {
+    let previous = &block.lines[block.lines.len() - 1];
+    let spacing = previous.baseline - line.baseline;
+    let font_size = previous.font_size.max(line.font_size);
+
+    spacing <= options.block_spacing * font_size && block.bbox.overlaps_horizontally(&line.bbox)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(all(feature = "write", any(feature = "miniz_oxide", feature = "flate2")))]
+    use crate::parsing::pdf_file::PdfFile;
+
+    /// Builds a fragment of monospaced glyphs, 10 units wide.
+    fn fragment(text: &str, x: f64, y: f64) -> TextFragment {
+        let glyphs = text
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let x = x + 10.0 * i as f64;
+                Glyph {
+                    text: c.into(),
+                    origin: (x, y),
+                    bbox: Rect::new(x, y - 4.0, x + 10.0, y + 16.0),
+                }
+            })
+            .collect();
+        TextFragment {
+            glyphs,
+            font_size: 20.0,
+            space_width: 10.0,
+        }
+    }
+
+    #[test]
+    fn should_split_words_at_spaces_and_gaps() {
+        let fragments = [
+            fragment("Hello, wo", 0.0, 100.0),
+            fragment("rld!", 90.0, 100.0),
+            fragment("Bye", 200.0, 100.0),
+        ];
+        let blocks = segment(&fragments, &LayoutOptions::default());
+
+        assert_eq!(blocks.len(), 1);
+        let words = &blocks[0].lines[0].words;
+        assert_eq!(
+            words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>(),
+            vec!["Hello,", "world!", "Bye"]
+        );
+        assert_eq!(words[1].bbox, Rect::new(70.0, 96.0, 130.0, 116.0));
+    }
+
+    #[test]
+    fn should_group_lines_into_blocks() {
+        let fragments = [
+            fragment("second", 0.0, 76.0),
+            fragment("first", 0.0, 100.0),
+            fragment("line", 70.0, 101.0),
+            fragment("new block", 0.0, 10.0),
+        ];
+        let blocks = segment(&fragments, &LayoutOptions::default());
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].text(), "first line\nsecond");
+        assert_eq!(blocks[1].text(), "new block");
+        assert_eq!(blocks[0].bbox, Rect::new(0.0, 72.0, 110.0, 117.0));
+    }
+
+    #[test]
+    fn should_join_hyphenated_words() {
+        let fragments = [
+            fragment("a hyphen-", 0.0, 100.0),
+            fragment("ated word", 0.0, 80.0),
+            fragment("soft\u{AD}", 0.0, 60.0),
+            fragment("ly and Jean-", 0.0, 40.0),
+            fragment("Paul dis\u{AD}cretion", 0.0, 20.0),
+        ];
+        let options = LayoutOptions {
+            join_hyphenated: true,
+            ..Default::default()
+        };
+
+        let blocks = segment(&fragments, &LayoutOptions::default());
+        assert_eq!(blocks[0].lines.len(), 5);
+        let blocks = segment(&fragments, &options);
+        assert_eq!(
+            blocks[0].text(),
+            "a hyphenated\nword\nsoftly\nand Jean-\nPaul discretion"
+        );
+        let words = &blocks[0].lines[0].words;
+        assert_eq!(words[1].bbox, Rect::new(0.0, 76.0, 90.0, 116.0));
+    }
+
+    #[test]
+    fn should_extract_raw_and_layout_text() {
+        let fragments = [
+            fragment("Name", 0.0, 100.0),
+            fragment("Age", 100.0, 100.0),
+            fragment("Bob", 0.0, 76.0),
+            fragment("42", 100.0, 76.0),
+            fragment("Total", 0.0, 28.0),
+            fragment("one", 0.0, 4.0),
+        ];
+        let options = LayoutOptions::default();
+
+        assert_eq!(raw_text(&fragments[..4], &options), "Name Age\nBob 42");
+        assert_eq!(
+            layout_text(&fragments, &options),
+            "Name      Age\nBob       42\n\nTotal\none"
+        );
+    }
+
+    #[cfg(all(feature = "write", any(feature = "miniz_oxide", feature = "flate2")))]
+    #[test]
+    fn should_extract_text() {
+        let mut file = PdfFile::from_raw(crate::test_support::compressed_xref());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        assert_eq!(pages[0].extract_text().unwrap(), "Hello, world!");
+        let blocks = pages[0].text_blocks(&LayoutOptions::default()).unwrap();
+        assert_eq!(blocks[0].lines[0].words.len(), 2);
+    }
+
+    #[cfg(all(feature = "parallel", feature = "write"))]
+    #[test]
+    fn should_extract_text_in_parallel() {
+        let mut file =
+            crate::parsing::pdf_file::PdfFile::from_raw(crate::test_support::multi_page(20));
+        file.load_xref_table().unwrap();
+
+        let text = file.extract_text_parallel().unwrap();
+        assert_eq!(text.len(), 20);
+        assert_eq!(text[0], "Page 1");
+        assert_eq!(text[19], "Page 20");
+    }
+}
@@ -0,0 +1,9 @@
+pub mod cache;
+pub mod edit;
+pub mod layout;
+pub mod operators;
+pub mod resources;
+pub mod search;
+#[cfg(feature = "tables")]
+pub mod tables;
+pub mod text;
@@ -0,0 +1,326 @@
+use crate::error::{Error, Result};
+use crate::objects::{sorted_entries, Object};
+use crate::parsing::context::ParseContext;
+use crate::parsing::objects::{process_array, process_dictionary, ParseStack, ParseStackEntry::*};
+use crate::parsing::tokens::{self, parse_token, Token};
+use crate::utils::chars::{is_name_char, is_numeric_char, is_whitespace_char};
+use crate::writing::objects::{write_object, WriteOptions};
+use memchr::memmem;
+use std::borrow::Cow;
+
+/// An operator in a content stream, along with its operands
+/// (Adobe, 2008, p. 81).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Operator<'a> {
+    pub operator: &'a [u8],
+    pub operands: Vec<Object<'a>>,
+}
+
+impl<'a> Operator<'a> {
+    pub fn new(operator: &'a [u8], operands: Vec<Object<'a>>) -> Self {
+        Self { operator, operands }
+    }
+
+    /// Copies the operator and its operands so that they can outlive the
+    /// content stream, or returns `None` if the operator is not defined by
+    /// the spec.
+    pub fn to_static(&self) -> Option<Operator<'static>> {
+        let operator = OPERATORS.iter().find(|&&op| op == self.operator)?;
+        Some(Operator::new(
+            operator,
+            self.operands.iter().map(Object::to_static).collect(),
+        ))
+    }
+}
+
+/// Every operator that may appear in a content stream (Adobe, 2008,
+/// p. 643-646).
+const OPERATORS: &[&[u8]] = &[
+    b"b", b"B", b"b*", b"B*", b"BDC", b"BI", b"BMC", b"BT", b"BX", b"c", b"cm", b"CS", b"cs", b"d",
+    b"d0", b"d1", b"Do", b"DP", b"EI", b"EMC", b"ET", b"EX", b"f", b"F", b"f*", b"G", b"g", b"gs",
+    b"h", b"i", b"ID", b"j", b"J", b"K", b"k", b"l", b"m", b"M", b"MP", b"n", b"q", b"Q", b"re",
+    b"RG", b"rg", b"ri", b"s", b"S", b"SC", b"sc", b"SCN", b"scn", b"sh", b"T*", b"Tc", b"Td",
+    b"TD", b"Tf", b"Tj", b"TJ", b"TL", b"Tm", b"Tr", b"Ts", b"Tw", b"Tz", b"v", b"w", b"W", b"W*",
+    b"y", b"'", b"\"",
+];
+
+/// The operator used for inline images. Its only operand is a stream holding
+/// the image dictionary and data (Adobe, 2008, p. 214).
+pub const INLINE_IMAGE: &[u8] = b"BI";
+
+/// Parses the operators in a content stream.
+pub fn parse_operators<'a>(raw: &'a [u8], context: &ParseContext) -> Result<Vec<Operator<'a>>> {
+    let mut stack = ParseStack::new();
+    let mut operators = Vec::new();
+    let raw = parse_into(raw, context, &mut stack, &mut operators)?;
+
+    if !stack.take_objects()?.is_empty() {
+        context.recover(
+            raw,
+            Error::Syntax("Operands without an operator", "".into()),
+            "Ignored the operands",
+        )?;
+    }
+
+    Ok(operators)
+}
+
+/// Parses objects and operators until the end of the data, leaving any
+/// trailing operands on the stack.
+fn parse_into<'a>(
+    mut raw: &'a [u8],
+    context: &ParseContext,
+    stack: &mut ParseStack<'a>,
+    operators: &mut Vec<Operator<'a>>,
+) -> Result<&'a [u8]> {
+    loop {
+        raw = match tokens::parse_whitespace(raw) {
+            Ok(((), raw)) => raw,
+            Err(Error::EOF) => return Ok(raw),
+            Err(err) => return Err(err),
+        };
+
+        // Operators are any run of regular characters that is not a number,
+        // which includes ones such as `T*` and `'` that are not keywords
+        let first_char = raw[0];
+        if is_name_char(first_char) && !is_numeric_char(first_char) {
+            let length = raw
+                .iter()
+                .position(|&c| !is_name_char(c))
+                .unwrap_or(raw.len());
+            let (operator, rest) = raw.split_at(length);
+            raw = rest;
+
+            match operator {
+                b"true" => stack.push(Obj(Object::Boolean(true))),
+                b"false" => stack.push(Obj(Object::Boolean(false))),
+                b"null" => stack.push(Obj(Object::Null)),
+                INLINE_IMAGE => {
+                    let (image, rest) = parse_inline_image(raw, context)?;
+                    raw = rest;
+                    operators.push(Operator::new(INLINE_IMAGE, vec![image]));
+                }
+                _ => operators.push(Operator::new(operator, stack.take_objects()?)),
+            }
+            continue;
+        }
+
+        let (token, rest) = parse_token(raw, context)?;
+        raw = rest;
+        match token {
+            Token::Integer(i) => stack.push(Obj(Object::Integer(i))),
+            Token::Real(x) => stack.push(Obj(Object::Real(x))),
+            Token::LiteralString(s) => stack.push(Obj(Object::String(context.own(s, "String")?))),
+            Token::HexadecimalString(s) => {
+                stack.push(Obj(Object::String(context.own(s, "Hex string")?)))
+            }
+            Token::Name(n) => stack.push(Obj(Object::Name(context.own(n, "Name")?.into()))),
+            Token::BeginArray => stack.begin(BeginArray)?,
+            Token::EndArray => process_array(stack)?,
+            Token::BeginDictionary => stack.begin(BeginDictionary)?,
+            Token::EndDictionary => process_dictionary(stack)?,
+            Token::Keyword(_) | Token::Stream(_) => {
+                return Err(Error::Syntax(
+                    "Unexpected token in content stream",
+                    tokens::snippet(raw),
+                ))
+            }
+        }
+    }
+}
+
+/// Parses an inline image, from after the `BI` operator to after the `EI`
+/// operator.
+fn parse_inline_image<'a>(raw: &'a [u8], context: &ParseContext) -> Result<(Object<'a>, &'a [u8])> {
+    let id_index = find_operator(raw, b"ID")
+        .ok_or_else(|| Error::Syntax("Could not find ID operator", tokens::snippet(raw)))?;
+
+    // The dictionary is written without `<<` and `>>`
+    let mut stack = ParseStack::new();
+    let mut operators = Vec::new();
+    stack.begin(BeginDictionary)?;
+    parse_into(&raw[..id_index], context, &mut stack, &mut operators)?;
+    if let Some(operator) = operators.first() {
+        return Err(Error::Syntax(
+            "Unexpected operator in inline image",
+            String::from_utf8_lossy(operator.operator).into(),
+        ));
+    }
+    process_dictionary(&mut stack)?;
+    let dict = stack.pop_obj()?;
+
+    // A single whitespace character separates `ID` from the data
+    let data = &raw[(id_index + 3).min(raw.len())..];
+    let end = find_operator(data, b"EI")
+        .ok_or_else(|| Error::Syntax("Could not find EI operator", tokens::snippet(raw)))?;
+    let image_data = &data[..end];
+    let image_data = image_data
+        .strip_suffix(b"\n")
+        .or_else(|| image_data.strip_suffix(b" "))
+        .unwrap_or(image_data);
+    let image = Object::Stream(Box::new(dict), Cow::Borrowed(image_data));
+
+    Ok((image, &data[end + 2..]))
+}
+
+/// Finds an operator that is surrounded by whitespace.
+fn find_operator(raw: &[u8], operator: &[u8]) -> Option<usize> {
+    let is_boundary = |c: Option<&u8>| c.is_none_or(|&c| is_whitespace_char(c));
+    memmem::find_iter(raw, operator).find(|&i| {
+        (i == 0 || is_whitespace_char(raw[i - 1])) && is_boundary(raw.get(i + operator.len()))
+    })
+}
+
+/// Writes operators as a content stream, one per line, so that it parses back
+/// into the same operators.
+pub fn write_operators(out: &mut Vec<u8>, operators: &[Operator]) -> Result<()> {
+    for operator in operators {
+        write_operator(out, operator)?;
+        out.push(b'\n');
+    }
+    Ok(())
+}
+
+fn write_operator(out: &mut Vec<u8>, operator: &Operator) -> Result<()> {
+    if operator.operator == INLINE_IMAGE {
+        return match operator.operands.as_slice() {
+            [Object::Stream(dict, data)] => write_inline_image(out, dict, data),
+            operands => Err(Error::Type(format!(
+                "Expected an inline image stream got {:?}",
+                operands
+            ))),
+        };
+    }
+
+    for operand in &operator.operands {
+        check_operand(operand)?;
+        write_object(out, operand, &WriteOptions::default())?;
+        out.push(b' ');
+    }
+    out.extend_from_slice(operator.operator);
+    Ok(())
+}
+
+/// Operands must be direct objects other than streams (Adobe, 2008, p. 81).
+fn check_operand(operand: &Object) -> Result<()> {
+    match operand {
+        Object::Indirect(_) | Object::Stream(_, _) => Err(Error::Type(format!(
+            "Content stream operands must be direct objects, got {:?}",
+            operand
+        ))),
+        Object::Array(array) => array.iter().try_for_each(check_operand),
+        Object::Dictionary(dict) => dict.values().try_for_each(check_operand),
+        _ => Ok(()),
+    }
+}
+
+/// Writes an inline image, whose dictionary is written without `<<` and `>>`
+/// between the `BI` and `ID` operators (Adobe, 2008, p. 214).
+fn write_inline_image(out: &mut Vec<u8>, dict: &Object, data: &[u8]) -> Result<()> {
+    let options = WriteOptions::default();
+    out.extend_from_slice(INLINE_IMAGE);
+    for (key, value) in sorted_entries(dict.as_dict()?) {
+        check_operand(value)?;
+        out.push(b' ');
+        write_object(out, &Object::Name(key.clone()), &options)?;
+        out.push(b' ');
+        write_object(out, value, &options)?;
+    }
+    out.extend_from_slice(b" ID ");
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\nEI");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{IndirectRef, Name};
+
+    fn parse(raw: &[u8]) -> Vec<Operator<'_>> {
+        parse_operators(raw, &ParseContext::default()).unwrap()
+    }
+
+    #[test]
+    fn should_parse_operators() {
+        let operators =
+            parse(b"q 1 0 0 1 72.5 720 cm\nBT /F1 12 Tf (Hi) Tj T* [(A) -20 <42>] TJ ET Q");
+        assert_eq!(operators.len(), 9);
+        assert_eq!(operators[1].operator, b"cm");
+        assert_eq!(operators[1].operands[4], Object::Real(72.5));
+        assert_eq!(
+            operators[3],
+            Operator::new(
+                b"Tf",
+                vec![Object::Name(Name::borrowed(b"F1")), Object::Integer(12)]
+            )
+        );
+        assert_eq!(operators[5], Operator::new(b"T*", vec![]));
+        assert_eq!(
+            operators[6].operands,
+            vec![Object::Array(vec![
+                Object::String(Cow::Borrowed(b"A")),
+                Object::Integer(-20),
+                Object::String(Cow::Borrowed(b"B")),
+            ])]
+        );
+        assert_eq!(operators[8].operator, b"Q");
+    }
+
+    #[test]
+    fn should_parse_quote_operators() {
+        let operators = parse(b"(a) ' 1 2 (b) \"");
+        assert_eq!(operators[0].operator, b"'");
+        assert_eq!(operators[1].operator, b"\"");
+        assert_eq!(operators[1].operands.len(), 3);
+    }
+
+    #[test]
+    fn should_parse_marked_content() {
+        let operators = parse(b"/Span <</MCID 0 /Alt (x)>> BDC EMC");
+        assert_eq!(operators[0].operator, b"BDC");
+        assert_eq!(operators[0].operands[1].get_int("MCID"), Ok(0));
+    }
+
+    #[test]
+    fn should_parse_inline_images() {
+        let operators = parse(b"q BI /W 2 /H 1 /BPC 8 /CS /G ID \x00EI\xFF\nEI Q");
+        assert_eq!(operators.len(), 3);
+        assert_eq!(operators[1].operator, INLINE_IMAGE);
+        let (dict, data) = operators[1].operands[0].as_stream().unwrap();
+        assert_eq!(dict.get(b"W".as_slice()), Some(&Object::Integer(2)));
+        assert_eq!(data, Cow::Borrowed(b"\x00EI\xFF"));
+        assert_eq!(operators[2].operator, b"Q");
+    }
+
+    #[test]
+    fn should_write_operators() {
+        let raw = b"q 1 0 0 1 72.5 720 cm BT /F#201 12 Tf [(A\\)) -20 <FF>] TJ ET \
+            /Span <</MCID 0>> BDC BI /W 1 /H 1 ID \xff\nEI EMC Q";
+        let operators = parse(raw);
+        let mut written = Vec::new();
+        write_operators(&mut written, &operators).unwrap();
+        assert_eq!(parse(&written), operators);
+        assert!(written.starts_with(b"q\n1 0 0 1 72.5 720 cm\nBT\n/F#201 12 Tf\n"));
+
+        let indirect = Operator::new(b"Do", vec![Object::Indirect(IndirectRef::new(1, 0))]);
+        assert!(matches!(
+            write_operators(&mut Vec::new(), &[indirect]),
+            Err(Error::Type(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_trailing_operands_in_strict_mode() {
+        assert!(parse_operators(b"1 2", &ParseContext::default()).is_err());
+    }
+
+    #[test]
+    fn should_reject_deeply_nested_operands() {
+        let raw = b"[".repeat(1_000_000);
+        assert!(matches!(
+            parse_operators(&raw, &ParseContext::default()),
+            Err(Error::Syntax("Objects are nested too deeply", _))
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+//! Finds which of a page's resources its content streams actually use, so
+//! that copying a page into another document does not copy every font and
+//! image in a resource dictionary that it shares with other pages.
+
+use crate::content::operators::{parse_operators, Operator, INLINE_IMAGE};
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::objects::{names, Dictionary, Name, Object};
+use std::collections::HashSet;
+
+/// The categories of resources that operators refer to by name (Adobe, 2008,
+/// p. 83). Other categories, such as `/ProcSet`, are kept whole.
+const NAMED_CATEGORIES: [&str; 7] = [
+    names::EXT_G_STATE,
+    names::COLOR_SPACE,
+    names::PATTERN,
+    names::SHADING,
+    names::X_OBJECT,
+    names::FONT,
+    names::PROPERTIES,
+];
+
+impl<'a> Page<'a> {
+    /// The page's resources, with only the entries that its content streams
+    /// name. Form XObjects without resources of their own use the page's
+    /// (Adobe, 2008, p. 219), so the names in their content streams are kept
+    /// as well. Forms, patterns and Type 3 fonts that have their own
+    /// resources are left as they are.
+    pub fn used_resources(&self) -> Result<Object<'a>> {
+        let file = self.file();
+        let resources = match self.resources()? {
+            Object::Dictionary(resources) => resources,
+            other => return Err(Error::Type(format!("Expected dict got {:?}", other))),
+        };
+
+        let mut used = HashSet::new();
+        let mut forms = Vec::new();
+        add_used_names(&self.operators()?, &mut used, &mut forms);
+        let x_objects = match resources.get(names::X_OBJECT.as_bytes()) {
+            Some(x_objects) => file.resolve_owned(x_objects)?,
+            None => Object::Null,
+        };
+        let mut visited = HashSet::new();
+        while let Some(name) = forms.pop() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            let Some(form) = x_objects.get(&name) else {
+                continue;
+            };
+            // Only forms are decoded, since images often use filters that
+            // cannot be
+            let encoded = file.resolve_encoded(form)?;
+            let is_form = matches!(
+                encoded.get(names::SUBTYPE),
+                Some(Object::Name(subtype)) if subtype.matches(names::FORM)
+            );
+            if is_form && encoded.get(names::RESOURCES).is_none() {
+                let form = file.resolve_owned(form)?;
+                let (_, data) = form.as_stream()?;
+                let operators = parse_operators(&data, &Default::default())?;
+                add_used_names(&operators, &mut used, &mut forms);
+            }
+        }
+
+        let mut pruned = Dictionary::new();
+        for (category, entries) in resources {
+            let Some(&known) = NAMED_CATEGORIES.iter().find(|&&c| category.matches(c)) else {
+                pruned.insert(category, entries);
+                continue;
+            };
+            let Object::Dictionary(entries) = file.resolve_owned(&entries)? else {
+                continue;
+            };
+            let entries = entries
+                .into_iter()
+                .filter(|(name, _)| used.contains(&(known, name.to_vec())))
+                .collect::<Dictionary>();
+            if !entries.is_empty() {
+                pruned.insert(category, Object::Dictionary(entries));
+            }
+        }
+        Ok(Object::Dictionary(pruned))
+    }
+}
+
+/// Records the resources that operators name, by category, and the names of
+/// the XObjects that they draw.
+fn add_used_names(
+    operators: &[Operator],
+    used: &mut HashSet<(&'static str, Vec<u8>)>,
+    x_objects: &mut Vec<Vec<u8>>,
+) {
+    for operator in operators {
+        let operands = &operator.operands;
+        let (category, operand) = match operator.operator {
+            b"Tf" => (names::FONT, operands.first()),
+            b"Do" => (names::X_OBJECT, operands.first()),
+            b"gs" => (names::EXT_G_STATE, operands.first()),
+            b"cs" | b"CS" => (names::COLOR_SPACE, operands.first()),
+            // Patterns are named by the last operand, after any colour
+            // components for uncoloured patterns
+            b"scn" | b"SCN" => (names::PATTERN, operands.last()),
+            b"sh" => (names::SHADING, operands.first()),
+            b"BDC" | b"DP" => (names::PROPERTIES, operands.get(1)),
+            INLINE_IMAGE => match operands.first() {
+                Some(image) => (
+                    names::COLOR_SPACE,
+                    image
+                        .get(names::CS)
+                        .or_else(|| image.get(names::COLOR_SPACE)),
+                ),
+                None => continue,
+            },
+            _ => continue,
+        };
+        let Some(Object::Name(Name(name))) = operand else {
+            continue;
+        };
+        if category == names::X_OBJECT {
+            x_objects.push(name.to_vec());
+        }
+        used.insert((category, name.to_vec()));
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support::{dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    #[test]
+    fn should_keep_only_used_resources() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = builder.reserve();
+        let pages = builder.reserve();
+        let form = builder.add(Object::Stream(
+            Box::new(dict(&[(names::SUBTYPE, name(names::FORM))])),
+            Cow::Borrowed(b"/Sh1 sh"),
+        ));
+        let content = builder.add(Object::Stream(
+            Box::new(dict(&[])),
+            Cow::Borrowed(b"/GS1 gs /Fm1 Do BT /F1 12 Tf (Hi) Tj ET /P0 cs /Pat scn"),
+        ));
+        let entry = |n: i64| dict(&[("N", Object::Integer(n))]);
+        let resources = dict(&[
+            (names::FONT, dict(&[("F1", entry(1)), ("F2", entry(2))])),
+            (names::X_OBJECT, dict(&[("Fm1", Object::Indirect(form))])),
+            (
+                names::EXT_G_STATE,
+                dict(&[("GS1", entry(3)), ("GS2", entry(4))]),
+            ),
+            (
+                names::SHADING,
+                dict(&[("Sh1", entry(5)), ("Sh2", entry(6))]),
+            ),
+            (names::COLOR_SPACE, dict(&[("P0", entry(7))])),
+            (names::PATTERN, dict(&[("Pat", entry(8))])),
+            (names::PROPERTIES, dict(&[("MC0", entry(9))])),
+            (names::PROC_SET, Object::Array(vec![name("PDF")])),
+        ]);
+        let page = builder.add(dict(&[
+            (names::TYPE, name(names::PAGE)),
+            (names::PARENT, Object::Indirect(pages)),
+            (names::RESOURCES, resources),
+            (names::CONTENTS, Object::Indirect(content)),
+        ]));
+        builder.insert(
+            pages,
+            dict(&[
+                (names::TYPE, name(names::PAGES)),
+                (names::COUNT, Object::Integer(1)),
+                (names::KIDS, Object::Array(vec![Object::Indirect(page)])),
+            ]),
+        );
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(pages)),
+            ]),
+        );
+        builder.set_root(catalog);
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let used = pages[0].used_resources().unwrap();
+        let keys = |category: &str| {
+            let mut keys = match used.get(category) {
+                Some(Object::Dictionary(entries)) => {
+                    entries.keys().map(|key| key.to_vec()).collect()
+                }
+                _ => Vec::new(),
+            };
+            keys.sort();
+            keys
+        };
+        assert_eq!(keys(names::FONT), [b"F1"]);
+        assert_eq!(keys(names::EXT_G_STATE), [b"GS1"]);
+        assert_eq!(keys(names::X_OBJECT), [b"Fm1"]);
+        // The form has no resources, so draws the shading from the page's
+        assert_eq!(keys(names::SHADING), [b"Sh1"]);
+        assert_eq!(keys(names::COLOR_SPACE), [b"P0"]);
+        assert_eq!(keys(names::PATTERN), [b"Pat"]);
+        assert!(used.get(names::PROPERTIES).is_none());
+        assert!(used.get(names::PROC_SET).is_some());
+    }
+
+    #[test]
+    fn should_keep_images_that_cannot_be_decoded() {
+        let mut file = PdfFile::from_raw(crate::test_support::jpeg_page());
+        file.load_xref_table().unwrap();
+        let used = file.pages().unwrap()[0].used_resources().unwrap();
+        assert!(used.get(names::X_OBJECT).unwrap().get("Im1").is_some());
+        assert!(used.get(names::FONT).unwrap().get("F1").is_some());
+    }
+}
@@ -0,0 +1,153 @@
+//! Finds text on the pages of a document, along with where it is drawn.
+
+use crate::content::layout::{group_lines, is_word_gap, LayoutOptions};
+use crate::content::text::TextFragment;
+use crate::error::Result;
+use crate::geometry::{Quad, Rect};
+use crate::parsing::pdf_file::PdfFile;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    /// The index of the page, starting from zero.
+    pub page_index: usize,
+    /// One quadrilateral for each line that the match covers.
+    pub quads: Vec<Quad>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Finds each occurrence of some text. Any run of whitespace in the
+    /// pattern matches any break between words, including line breaks.
+    pub fn search(&self, pattern: &str) -> Result<Vec<SearchMatch>> {
+        let pattern = pattern.split_whitespace().collect::<Vec<_>>().join(" ");
+        if pattern.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut matches = Vec::new();
+        for (page_index, page) in self.pages()?.iter().enumerate() {
+            let fragments = page.text_fragments()?;
+            for quads in find(&fragments, &pattern, &LayoutOptions::default()) {
+                matches.push(SearchMatch { page_index, quads });
+            }
+        }
+
+        Ok(matches)
+    }
+}
+
+/// Finds the pattern in the text of a page, returning the quadrilaterals for
+/// each match.
+fn find(fragments: &[TextFragment], pattern: &str, options: &LayoutOptions) -> Vec<Vec<Quad>> {
+    // The text of the page with whitespace normalized, along with the line
+    // and box of the glyph that each byte came from
+    let mut text = String::new();
+    let mut sources: Vec<Option<(usize, Rect)>> = Vec::new();
+    let mut push = |text: &mut String, s: &str, source| {
+        for _ in 0..s.len() {
+            sources.push(source);
+        }
+        text.push_str(s);
+    };
+
+    for (line_index, line) in group_lines(fragments, options).iter().enumerate() {
+        for (i, entry) in line.iter().enumerate() {
+            let glyph_text = entry.glyph.text.as_str();
+            let is_space = glyph_text.trim().is_empty();
+            let is_break = i > 0 && is_word_gap(&line[i - 1], entry, options);
+            if (is_space || is_break) && !text.is_empty() && !text.ends_with(' ') {
+                push(&mut text, " ", None);
+            }
+            if !is_space {
+                push(&mut text, glyph_text, Some((line_index, entry.glyph.bbox)));
+            }
+        }
+        if !text.is_empty() && !text.ends_with(' ') {
+            push(&mut text, " ", None);
+        }
+    }
+
+    text.match_indices(pattern)
+        .map(|(start, matched)| {
+            let mut quads: Vec<(usize, Rect)> = Vec::new();
+            for &(line_index, bbox) in sources[start..start + matched.len()].iter().flatten() {
+                match quads.last_mut() {
+                    Some((line, rect)) if *line == line_index => *rect = rect.union(&bbox),
+                    _ => quads.push((line_index, bbox)),
+                }
+            }
+            quads
+                .iter()
+                .map(|(_, rect)| Quad::from_rect(rect))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::text::Glyph;
+    #[cfg(feature = "write")]
+    use crate::test_support;
+
+    #[cfg(all(feature = "write", any(feature = "miniz_oxide", feature = "flate2")))]
+    #[test]
+    fn should_find_text() {
+        let mut file = PdfFile::from_raw(test_support::compressed_xref());
+        file.load_xref_table().unwrap();
+
+        let matches = file.search("world").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].page_index, 0);
+        assert_eq!(matches[0].quads.len(), 1);
+
+        let bounds = matches[0].quads[0].bounds();
+        let everything = file.search("Hello, world!").unwrap()[0].quads[0].bounds();
+        assert!(bounds.x0 > everything.x0);
+        assert!(bounds.x1 < everything.x1);
+        assert!(file.search("World").unwrap().is_empty());
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_find_text_on_each_page() {
+        let mut file = PdfFile::from_raw(test_support::multi_page(3));
+        file.load_xref_table().unwrap();
+
+        let matches = file.search("Page").unwrap();
+        let pages = matches.iter().map(|m| m.page_index).collect::<Vec<_>>();
+        assert_eq!(pages, vec![0, 1, 2]);
+        assert_eq!(file.search("Page  3").unwrap()[0].page_index, 2);
+    }
+
+    #[test]
+    fn should_match_across_lines() {
+        let fragment = |text: &str, y: f64| TextFragment {
+            glyphs: text
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    let x = 10.0 * i as f64;
+                    Glyph {
+                        text: c.into(),
+                        origin: (x, y),
+                        bbox: Rect::new(x, y, x + 10.0, y + 10.0),
+                    }
+                })
+                .collect(),
+            font_size: 10.0,
+            space_width: 5.0,
+        };
+        let fragments = [fragment("the quick", 100.0), fragment("brown fox", 80.0)];
+
+        let quads = find(&fragments, "quick brown", &LayoutOptions::default());
+        assert_eq!(quads.len(), 1);
+        assert_eq!(
+            quads[0],
+            vec![
+                Quad::from_rect(&Rect::new(40.0, 100.0, 90.0, 110.0)),
+                Quad::from_rect(&Rect::new(0.0, 80.0, 50.0, 90.0)),
+            ]
+        );
+    }
+}
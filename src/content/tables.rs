@@ -0,0 +1,393 @@
+//! Detects simple tables, either drawn with ruling lines or laid out by
+//! aligning text in columns.
+
+use crate::content::layout::{segment, LayoutOptions, Line, Word};
+use crate::document::page::Page;
+use crate::error::Result;
+use crate::geometry::{number, ContentMatrix, Rect};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TableOptions {
+    pub layout: LayoutOptions,
+    /// How close lines must be to be treated as touching, in user space units.
+    pub rule_tolerance: f64,
+    /// The smallest gap between words that separates columns, as a multiple
+    /// of the font size.
+    pub column_gap: f64,
+    /// The fewest rows that text must be aligned over to count as a table.
+    pub min_rows: usize,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            layout: LayoutOptions::default(),
+            rule_tolerance: 2.0,
+            column_gap: 1.0,
+            min_rows: 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub bbox: Rect,
+    /// The text of each cell, from top to bottom and then left to right.
+    pub rows: Vec<Vec<String>>,
+    /// Whether the table was found from ruling lines, rather than from the
+    /// alignment of its text.
+    pub ruled: bool,
+}
+
+impl<'a> Page<'a> {
+    /// Finds tables on the page. Ruled tables are found first, and then the
+    /// remaining text is checked for columns.
+    pub fn tables(&self, options: &TableOptions) -> Result<Vec<Table>> {
+        let blocks = segment(&self.text_fragments()?, &options.layout);
+        let lines = blocks
+            .into_iter()
+            .flat_map(|block| block.lines)
+            .collect::<Vec<_>>();
+        let words = lines
+            .iter()
+            .flat_map(|line| &line.words)
+            .collect::<Vec<_>>();
+
+        let rules = self.rules()?;
+        let mut tables = ruled_tables(&rules, &words, options);
+
+        let is_free = |line: &&Line| {
+            !tables
+                .iter()
+                .any(|table: &Table| contains(&table.bbox, &line.bbox))
+        };
+        let free_lines = lines.iter().filter(is_free).collect::<Vec<_>>();
+        tables.extend(aligned_tables(&free_lines, options));
+
+        Ok(tables)
+    }
+
+    /// Finds the horizontal and vertical lines that are painted on the page.
+    fn rules(&self) -> Result<Vec<Rule>> {
+        let operators = self.operators()?;
+        let mut ctm = ContentMatrix::IDENTITY;
+        let mut stack = Vec::new();
+        let mut path = Vec::new();
+        let mut current = (0.0, 0.0);
+        let mut rules = Vec::new();
+
+        for operator in operators.iter() {
+            let operands = &operator.operands;
+            let point = |i: usize| -> Result<(f64, f64)> {
+                Ok(ctm.transform(number(&operands[i])?, number(&operands[i + 1])?))
+            };
+
+            match operator.operator {
+                b"q" => stack.push(ctm),
+                b"Q" => ctm = stack.pop().unwrap_or(ctm),
+                b"cm" => ctm = ContentMatrix::from_objects(operands)? * ctm,
+                b"m" if operands.len() == 2 => current = point(0)?,
+                b"l" if operands.len() == 2 => {
+                    let next = point(0)?;
+                    path.push(Rect::new(current.0, current.1, next.0, next.1));
+                    current = next;
+                }
+                b"re" if operands.len() == 4 => {
+                    let (x, y) = (number(&operands[0])?, number(&operands[1])?);
+                    let (w, h) = (number(&operands[2])?, number(&operands[3])?);
+                    path.push(ctm.transform_rect(&Rect::new(x, y, x + w, y + h)));
+                }
+                b"S" | b"s" | b"f" | b"F" | b"f*" | b"B" | b"B*" | b"b" | b"b*" => {
+                    for rect in path.drain(..) {
+                        rules.extend(Rule::from_rect(&rect));
+                    }
+                }
+                b"n" => path.clear(),
+                _ => {}
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+/// A horizontal or vertical line segment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rule {
+    horizontal: bool,
+    /// The y coordinate of a horizontal rule, or the x coordinate of a
+    /// vertical one.
+    position: f64,
+    start: f64,
+    end: f64,
+}
+
+impl Rule {
+    /// Converts a painted segment or rectangle into rules. Thin rectangles
+    /// become a single rule, and others become their four edges.
+    fn from_rect(rect: &Rect) -> Vec<Rule> {
+        const THIN: f64 = 2.0;
+        let horizontal = |y| Rule {
+            horizontal: true,
+            position: y,
+            start: rect.x0,
+            end: rect.x1,
+        };
+        let vertical = |x| Rule {
+            horizontal: false,
+            position: x,
+            start: rect.y0,
+            end: rect.y1,
+        };
+
+        match (rect.width() <= THIN, rect.height() <= THIN) {
+            (true, true) => vec![],
+            (false, true) => vec![horizontal((rect.y0 + rect.y1) / 2.0)],
+            (true, false) => vec![vertical((rect.x0 + rect.x1) / 2.0)],
+            (false, false) => vec![
+                horizontal(rect.y0),
+                horizontal(rect.y1),
+                vertical(rect.x0),
+                vertical(rect.x1),
+            ],
+        }
+    }
+
+    fn touches(&self, other: &Rule, tolerance: f64) -> bool {
+        let within = |value: f64, start: f64, end: f64| {
+            start - tolerance <= value && value <= end + tolerance
+        };
+        if self.horizontal == other.horizontal {
+            (self.position - other.position).abs() <= tolerance
+                && self.start <= other.end + tolerance
+                && other.start <= self.end + tolerance
+        } else {
+            within(self.position, other.start, other.end)
+                && within(other.position, self.start, self.end)
+        }
+    }
+}
+
+fn ruled_tables(rules: &[Rule], words: &[&Word], options: &TableOptions) -> Vec<Table> {
+    let tolerance = options.rule_tolerance;
+
+    // Group rules that touch, as each group may be a separate table
+    let mut groups = (0..rules.len()).collect::<Vec<_>>();
+    fn find(groups: &mut [usize], i: usize) -> usize {
+        if groups[i] != i {
+            groups[i] = find(groups, groups[i]);
+        }
+        groups[i]
+    }
+    for i in 0..rules.len() {
+        for j in i + 1..rules.len() {
+            if rules[i].touches(&rules[j], tolerance) {
+                let (a, b) = (find(&mut groups, i), find(&mut groups, j));
+                groups[a] = b;
+            }
+        }
+    }
+
+    let mut roots = (0..rules.len())
+        .map(|i| find(&mut groups, i))
+        .collect::<Vec<_>>();
+    roots.sort_unstable();
+    roots.dedup();
+
+    let mut tables = Vec::new();
+    for root in roots {
+        let group = (0..rules.len())
+            .filter(|&i| find(&mut groups, i) == root)
+            .map(|i| rules[i]);
+        let (horizontal, vertical): (Vec<_>, Vec<_>) = group.partition(|rule| rule.horizontal);
+
+        // From top to bottom, and from left to right
+        let mut ys = cluster(horizontal.iter().map(|rule| rule.position), tolerance);
+        ys.reverse();
+        let xs = cluster(vertical.iter().map(|rule| rule.position), tolerance);
+        if ys.len() < 2 || xs.len() < 2 {
+            continue;
+        }
+
+        let mut rows = vec![vec![String::new(); xs.len() - 1]; ys.len() - 1];
+        for word in words {
+            let x = (word.bbox.x0 + word.bbox.x1) / 2.0;
+            let y = (word.bbox.y0 + word.bbox.y1) / 2.0;
+            let row = ys.windows(2).position(|pair| pair[1] <= y && y <= pair[0]);
+            let column = xs.windows(2).position(|pair| pair[0] <= x && x <= pair[1]);
+            if let (Some(row), Some(column)) = (row, column) {
+                let cell = &mut rows[row][column];
+                if !cell.is_empty() {
+                    cell.push(' ');
+                }
+                cell.push_str(&word.text);
+            }
+        }
+
+        tables.push(Table {
+            bbox: Rect::new(xs[0], ys[ys.len() - 1], xs[xs.len() - 1], ys[0]),
+            rows,
+            ruled: true,
+        });
+    }
+
+    tables
+}
+
+/// Sorts positions and merges those that are within the tolerance.
+fn cluster(positions: impl Iterator<Item = f64>, tolerance: f64) -> Vec<f64> {
+    let mut positions = positions.collect::<Vec<_>>();
+    positions.sort_by(f64::total_cmp);
+    positions.dedup_by(|b, a| *b - *a <= tolerance);
+    positions
+}
+
+fn contains(outer: &Rect, inner: &Rect) -> bool {
+    let center = ((inner.x0 + inner.x1) / 2.0, (inner.y0 + inner.y1) / 2.0);
+    outer.x0 <= center.0 && center.0 <= outer.x1 && outer.y0 <= center.1 && center.1 <= outer.y1
+}
+
+/// A run of words in a line that is separated from its neighbours by a gap
+/// wide enough to be a column break.
+struct Cell {
+    text: String,
+    bbox: Rect,
+}
+
+fn cells(line: &Line, options: &TableOptions) -> Vec<Cell> {
+    let mut cells: Vec<Cell> = Vec::new();
+    for word in &line.words {
+        match cells.last_mut() {
+            Some(cell) if word.bbox.x0 - cell.bbox.x1 < options.column_gap * line.font_size => {
+                cell.text.push(' ');
+                cell.text.push_str(&word.text);
+                cell.bbox = cell.bbox.union(&word.bbox);
+            }
+            _ => cells.push(Cell {
+                text: word.text.clone(),
+                bbox: word.bbox,
+            }),
+        }
+    }
+    cells
+}
+
+/// Finds runs of consecutive lines whose cells line up in the same columns.
+fn aligned_tables(lines: &[&Line], options: &TableOptions) -> Vec<Table> {
+    struct Candidate {
+        columns: Vec<(f64, f64)>,
+        rows: Vec<Vec<String>>,
+        bbox: Rect,
+    }
+
+    let mut tables = Vec::new();
+    let mut candidate: Option<Candidate> = None;
+    let mut finish = |candidate: Option<Candidate>| {
+        if let Some(candidate) = candidate.filter(|c| c.rows.len() >= options.min_rows) {
+            tables.push(Table {
+                bbox: candidate.bbox,
+                rows: candidate.rows,
+                ruled: false,
+            });
+        }
+    };
+
+    for line in lines {
+        let cells = cells(line, options);
+        if cells.len() < 2 {
+            finish(candidate.take());
+            continue;
+        }
+
+        let aligned = candidate.as_ref().is_some_and(|candidate| {
+            candidate.columns.len() == cells.len()
+                && candidate
+                    .columns
+                    .iter()
+                    .zip(&cells)
+                    .all(|(&(x0, x1), cell)| cell.bbox.x0 <= x1 && x0 <= cell.bbox.x1)
+        });
+        if !aligned {
+            finish(candidate.take());
+            candidate = Some(Candidate {
+                columns: Vec::new(),
+                rows: Vec::new(),
+                bbox: line.bbox,
+            });
+        }
+
+        let candidate = candidate.as_mut().unwrap();
+        if candidate.columns.is_empty() {
+            candidate.columns = cells.iter().map(|c| (c.bbox.x0, c.bbox.x1)).collect();
+        }
+        for (column, cell) in candidate.columns.iter_mut().zip(&cells) {
+            *column = (column.0.min(cell.bbox.x0), column.1.max(cell.bbox.x1));
+        }
+        candidate.bbox = candidate.bbox.union(&line.bbox);
+        candidate
+            .rows
+            .push(cells.into_iter().map(|cell| cell.text).collect());
+    }
+    finish(candidate);
+
+    tables
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support;
+    use crate::writing::document::DocumentBuilder;
+
+    fn first_page_tables(content: &[u8]) -> Vec<Table> {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[content]);
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        pages[0].tables(&TableOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn should_detect_aligned_tables() {
+        let tables = first_page_tables(
+            b"BT /F1 10 Tf 72 700 Td (Introduction) Tj \
+              0 -40 Td (Name) Tj 100 0 Td (Qty) Tj \
+              -100 -14 Td (Apples) Tj 100 0 Td (3) Tj \
+              -100 -14 Td (Pears) Tj 100 0 Td (12) Tj ET",
+        );
+
+        assert_eq!(tables.len(), 1);
+        assert!(!tables[0].ruled);
+        assert_eq!(
+            tables[0].rows,
+            vec![
+                vec!["Name", "Qty"],
+                vec!["Apples", "3"],
+                vec!["Pears", "12"]
+            ]
+        );
+    }
+
+    #[test]
+    fn should_detect_ruled_tables() {
+        let tables = first_page_tables(
+            b"0.5 w 72 600 200 40 re S 172 600 m 172 640 l S 72 620 m 272 620 l S \
+              BT /F1 10 Tf 80 626 Td (A) Tj 100 0 Td (B) Tj -100 -20 Td (C) Tj ET",
+        );
+
+        assert_eq!(tables.len(), 1);
+        assert!(tables[0].ruled);
+        assert_eq!(tables[0].bbox, Rect::new(72.0, 600.0, 272.0, 640.0));
+        assert_eq!(tables[0].rows, vec![vec!["A", "B"], vec!["C", ""]]);
+    }
+
+    #[test]
+    fn should_not_detect_tables_in_prose() {
+        let tables = first_page_tables(
+            b"BT /F1 10 Tf 72 700 Td (Some text) Tj 0 -14 Td (over two lines) Tj ET",
+        );
+        assert_eq!(tables, vec![]);
+    }
+}
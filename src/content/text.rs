@@ -0,0 +1,636 @@
+//! Positions the text shown by a content stream (Adobe, 2008, p. 243-252).
+
+use crate::content::operators::{parse_operators, Operator};
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::fonts::Font;
+use crate::geometry::{number, to_matrix, ContentMatrix, Matrix, Rect};
+use crate::objects::{names, IndirectRef, Object};
+use crate::utils::trace::trace_span;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// How deeply Form XObjects may be nested, to stop runaway recursion in
+/// malformed files.
+const MAX_FORM_DEPTH: usize = 32;
+
+/// A single glyph, positioned in user space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyph {
+    /// The text for the glyph, which may be empty or have several characters.
+    pub text: String,
+    /// The glyph origin, on the baseline.
+    pub origin: (f64, f64),
+    /// The glyph's box, from the font's descent to its ascent.
+    pub bbox: Rect,
+}
+
+/// The glyphs shown by a single text-showing operator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextFragment {
+    pub glyphs: Vec<Glyph>,
+    /// The font size in user space.
+    pub font_size: f64,
+    /// The width of a space in user space, used to find breaks between words.
+    pub space_width: f64,
+}
+
+impl TextFragment {
+    pub fn text(&self) -> String {
+        self.glyphs
+            .iter()
+            .map(|glyph| glyph.text.as_str())
+            .collect()
+    }
+
+    pub fn bbox(&self) -> Option<Rect> {
+        self.glyphs
+            .iter()
+            .map(|glyph| glyph.bbox)
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+/// The parts of the graphics state that affect text (Adobe, 2008, p. 243).
+#[derive(Clone, Debug)]
+struct TextState {
+    ctm: ContentMatrix,
+    char_spacing: f64,
+    word_spacing: f64,
+    horizontal_scaling: f64,
+    leading: f64,
+    font: Option<Rc<Font>>,
+    font_size: f64,
+    rise: f64,
+}
+
+impl Default for TextState {
+    fn default() -> Self {
+        Self {
+            ctm: ContentMatrix::IDENTITY,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scaling: 1.0,
+            leading: 0.0,
+            font: None,
+            font_size: 0.0,
+            rise: 0.0,
+        }
+    }
+}
+
+/// A resource dictionary, along with the fonts that have been loaded from it.
+struct Resources<'a> {
+    dict: Object<'a>,
+    fonts: HashMap<Vec<u8>, Rc<Font>>,
+}
+
+impl<'a> Resources<'a> {
+    fn new(dict: Object<'a>) -> Self {
+        Self {
+            dict,
+            fonts: HashMap::new(),
+        }
+    }
+}
+
+struct TextInterpreter<'p, 'a> {
+    page: &'p Page<'a>,
+    /// The page's resources, followed by those of each Form XObject that is
+    /// being drawn. Names are looked up from the innermost form outwards, so
+    /// that forms without their own resources can use the page's (Adobe,
+    /// 2008, p. 219).
+    resources: Vec<Resources<'a>>,
+    /// The forms that are being drawn, so that forms which draw themselves
+    /// are skipped.
+    forms: Vec<IndirectRef>,
+    state: TextState,
+    stack: Vec<TextState>,
+    text_matrix: ContentMatrix,
+    line_matrix: ContentMatrix,
+    fragments: Vec<TextFragment>,
+}
+
+impl<'a> Page<'a> {
+    /// Returns the text shown on the page, in the order it was drawn.
+    pub fn text_fragments(&self) -> Result<Vec<TextFragment>> {
+        trace_span!("interpret", page = ?self.reference());
+        let operators = self.operators()?;
+
+        let mut interpreter = TextInterpreter {
+            page: self,
+            resources: vec![Resources::new(self.resources()?)],
+            forms: Vec::new(),
+            state: TextState::default(),
+            stack: Vec::new(),
+            text_matrix: ContentMatrix::IDENTITY,
+            line_matrix: ContentMatrix::IDENTITY,
+            fragments: Vec::new(),
+        };
+        for operator in operators.iter() {
+            interpreter.process(operator)?;
+        }
+
+        Ok(interpreter.fragments)
+    }
+}
+
+impl<'p, 'a> TextInterpreter<'p, 'a> {
+    fn process(&mut self, operator: &Operator) -> Result<()> {
+        let operands = &operator.operands;
+        let operand = |i: usize| -> Result<f64> {
+            operands
+                .get(i)
+                .map(number)
+                .unwrap_or_else(|| Err(Error::Type("Missing operand".into())))
+        };
+
+        match operator.operator {
+            b"q" => self.stack.push(self.state.clone()),
+            b"Q" => {
+                if let Some(state) = self.stack.pop() {
+                    self.state = state;
+                }
+            }
+            b"cm" => self.state.ctm = ContentMatrix::from_objects(operands)? * self.state.ctm,
+
+            b"BT" => {
+                self.text_matrix = ContentMatrix::IDENTITY;
+                self.line_matrix = ContentMatrix::IDENTITY;
+            }
+            b"Tc" => self.state.char_spacing = operand(0)?,
+            b"Tw" => self.state.word_spacing = operand(0)?,
+            b"Tz" => self.state.horizontal_scaling = operand(0)? / 100.0,
+            b"TL" => self.state.leading = operand(0)?,
+            b"Ts" => self.state.rise = operand(0)?,
+            b"Tf" => {
+                let name = operands.first().map(|name| name.as_name()).transpose()?;
+                self.state.font = match name {
+                    Some(name) => Some(self.font(name)?),
+                    None => None,
+                };
+                self.state.font_size = operand(1)?;
+            }
+
+            b"Td" => self.move_line(operand(0)?, operand(1)?),
+            b"TD" => {
+                self.state.leading = -operand(1)?;
+                self.move_line(operand(0)?, operand(1)?);
+            }
+            b"Tm" => {
+                self.text_matrix = ContentMatrix::from_objects(operands)?;
+                self.line_matrix = self.text_matrix;
+            }
+            b"T*" => self.move_line(0.0, -self.state.leading),
+
+            b"Tj" => {
+                let mut fragment = self.begin_fragment();
+                self.show(&mut fragment, operands.first())?;
+                self.fragments.push(fragment);
+            }
+            b"'" => {
+                self.move_line(0.0, -self.state.leading);
+                let mut fragment = self.begin_fragment();
+                self.show(&mut fragment, operands.first())?;
+                self.fragments.push(fragment);
+            }
+            b"\"" => {
+                self.state.word_spacing = operand(0)?;
+                self.state.char_spacing = operand(1)?;
+                self.move_line(0.0, -self.state.leading);
+                let mut fragment = self.begin_fragment();
+                self.show(&mut fragment, operands.get(2))?;
+                self.fragments.push(fragment);
+            }
+            b"Do" => {
+                if let Some(Object::Name(name)) = operands.first() {
+                    self.draw_x_object(name)?;
+                }
+            }
+
+            b"TJ" => {
+                let mut fragment = self.begin_fragment();
+                if let Some(Object::Array(items)) = operands.first() {
+                    for item in items {
+                        if let Object::String(_) = item {
+                            self.show(&mut fragment, Some(item))?;
+                        } else {
+                            // Adjustments are in thousandths of text space
+                            let adjustment = -number(item)? / 1000.0 * self.state.font_size;
+                            let vertical =
+                                self.state.font.as_ref().is_some_and(|f| f.is_vertical());
+                            self.text_matrix = if vertical {
+                                ContentMatrix::translate(0.0, adjustment) * self.text_matrix
+                            } else {
+                                let tx = adjustment * self.state.horizontal_scaling;
+                                ContentMatrix::translate(tx, 0.0) * self.text_matrix
+                            };
+                        }
+                    }
+                }
+                self.fragments.push(fragment);
+            }
+
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Looks up a named resource, starting with the innermost resource
+    /// dictionary. Returns the index of the dictionary that it was found in,
+    /// along with the entry, which may be a reference.
+    fn resource(&self, category: &str, name: &[u8]) -> Result<Option<(usize, Object<'a>)>> {
+        let file = self.page.file();
+        for (i, resources) in self.resources.iter().enumerate().rev() {
+            let Some(category) = resources.dict.get(category) else {
+                continue;
+            };
+            if let Some(resource) = file.resolve_owned(category)?.get(name) {
+                return Ok(Some((i, resource.clone())));
+            }
+        }
+        Ok(None)
+    }
+
+    fn font(&mut self, name: &[u8]) -> Result<Rc<Font>> {
+        let cached = self
+            .resources
+            .iter()
+            .rev()
+            .find_map(|resources| resources.fonts.get(name));
+        if let Some(font) = cached {
+            return Ok(font.clone());
+        }
+
+        let (i, font) = match self.resource(names::FONT, name)? {
+            Some((i, dict)) => {
+                let dict = self.page.file().resolve_owned(&dict)?;
+                (i, Font::load(self.page.file(), &dict)?)
+            }
+            None => (self.resources.len() - 1, Font::default()),
+        };
+        let font = Rc::new(font);
+        self.resources[i].fonts.insert(name.to_vec(), font.clone());
+        Ok(font)
+    }
+
+    /// Draws a Form XObject, using its own resources and coordinate space
+    /// (Adobe, 2008, p. 217-219). Other kinds of XObject contain no text.
+    fn draw_x_object(&mut self, name: &[u8]) -> Result<()> {
+        let Some((_, entry)) = self.resource(names::X_OBJECT, name)? else {
+            return Ok(());
+        };
+        let file = self.page.file();
+        // Images are often encoded with filters that cannot be decoded, such
+        // as `DCTDecode`, so the subtype is checked before anything is decoded
+        let is_form = matches!(
+            file.resolve_encoded(&entry)?.get(names::SUBTYPE),
+            Some(Object::Name(subtype)) if subtype.matches(names::FORM)
+        );
+        if !is_form || self.resources.len() > MAX_FORM_DEPTH {
+            return Ok(());
+        }
+        let reference = entry.as_indirect().ok();
+        if reference.is_some_and(|reference| self.forms.contains(&reference)) {
+            return Ok(());
+        }
+
+        let form = file.resolve_owned(&entry)?;
+        let Object::Stream(dict, data) = &form else {
+            return Ok(());
+        };
+        let operators = parse_operators(data, &Default::default())?;
+        let resources = match dict.get(names::RESOURCES) {
+            Some(resources) => file.resolve_owned(resources)?,
+            None => Object::Null,
+        };
+        let matrix = match dict.get(names::MATRIX) {
+            Some(Object::Array(matrix)) => ContentMatrix::from_objects(matrix)?,
+            _ => ContentMatrix::IDENTITY,
+        };
+
+        let state = self.state.clone();
+        let stack_len = self.stack.len();
+        let (text_matrix, line_matrix) = (self.text_matrix, self.line_matrix);
+        self.state.ctm = matrix * self.state.ctm;
+        self.resources.push(Resources::new(resources));
+        self.forms.extend(reference);
+
+        let result = operators
+            .iter()
+            .try_for_each(|operator| self.process(operator));
+
+        if reference.is_some() {
+            self.forms.pop();
+        }
+        self.resources.pop();
+        self.state = state;
+        self.stack.truncate(stack_len);
+        (self.text_matrix, self.line_matrix) = (text_matrix, line_matrix);
+        result
+    }
+
+    fn move_line(&mut self, tx: f64, ty: f64) {
+        self.line_matrix = ContentMatrix::translate(tx, ty) * self.line_matrix;
+        self.text_matrix = self.line_matrix;
+    }
+
+    fn begin_fragment(&self) -> TextFragment {
+        let matrix = to_matrix(self.text_matrix * self.state.ctm);
+        let scale = (matrix.c * matrix.c + matrix.d * matrix.d).sqrt();
+        let font_size = self.state.font_size * scale;
+
+        let space_width = match &self.state.font {
+            Some(font) if !font.is_composite() && font.advance(32) > 0.0 => font.advance(32),
+            _ => 0.25,
+        };
+
+        TextFragment {
+            glyphs: Vec::new(),
+            font_size,
+            space_width: space_width * font_size * self.state.horizontal_scaling,
+        }
+    }
+
+    /// Shows a string, adding its glyphs to the fragment and advancing the
+    /// text matrix (Adobe, 2008, p. 409-410).
+    fn show(&mut self, fragment: &mut TextFragment, string: Option<&Object>) -> Result<()> {
+        let Some(font) = self.state.font.clone() else {
+            return Err(Error::Syntax("Text shown without a font", "".into()));
+        };
+        let string = match string {
+            Some(Object::String(string)) => string,
+            other => return Err(Error::Type(format!("Expected string got {:?}", other))),
+        };
+
+        let state = &self.state;
+        let font_matrix = Matrix::new(
+            state.font_size * state.horizontal_scaling,
+            0.0,
+            0.0,
+            state.font_size,
+            0.0,
+            state.rise,
+        );
+        for (code, length) in font.codes(string) {
+            let matrix = font_matrix * to_matrix(self.text_matrix * state.ctm);
+
+            // In vertical writing, the glyph is drawn so that its position
+            // vector lands on the current point (Adobe, 2008, p. 272)
+            let metrics = font.vertical_metrics(code);
+            let glyph_matrix = if font.is_vertical() {
+                Matrix::translate(-metrics.v.0, -metrics.v.1) * font.font_matrix * matrix
+            } else {
+                font.font_matrix * matrix
+            };
+            fragment.glyphs.push(Glyph {
+                text: font.to_unicode(code),
+                origin: matrix.transform(0.0, 0.0),
+                bbox: glyph_matrix.transform_rect(&font.glyph_bbox(code)),
+            });
+
+            // Word spacing only applies to the single-byte code 32
+            let word_spacing = if code == 32 && length == 1 {
+                state.word_spacing
+            } else {
+                0.0
+            };
+            let spacing = state.char_spacing + word_spacing;
+            self.text_matrix = if font.is_vertical() {
+                let ty = metrics.w1y * font.font_matrix.d * state.font_size + spacing;
+                ContentMatrix::translate(0.0, ty) * self.text_matrix
+            } else {
+                let tx =
+                    (font.advance(code) * state.font_size + spacing) * state.horizontal_scaling;
+                ContentMatrix::translate(tx, 0.0) * self.text_matrix
+            };
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "write")]
+    use crate::objects::{names, Dictionary, Object};
+    #[cfg(feature = "write")]
+    use crate::parsing::pdf_file::PdfFile;
+    #[cfg(feature = "write")]
+    use crate::test_support::{self, dict, name};
+    #[cfg(feature = "write")]
+    use crate::writing::document::DocumentBuilder;
+
+    /// A page that draws a Form XObject named `Fm0`, which has the given
+    /// resources and content.
+    #[cfg(feature = "write")]
+    fn form_page(form_resources: Option<Object<'static>>, form_content: &'static [u8]) -> Vec<u8> {
+        let mut builder = DocumentBuilder::new();
+        let font = builder.add(dict(&[
+            (names::TYPE, name(names::FONT)),
+            (names::SUBTYPE, name("Type1")),
+            (names::BASE_FONT, name("Helvetica")),
+        ]));
+
+        let mut form = Dictionary::new();
+        form.insert(names::TYPE.into(), name(names::X_OBJECT));
+        form.insert(names::SUBTYPE.into(), name(names::FORM));
+        form.insert(
+            names::MATRIX.into(),
+            Object::Array([1, 0, 0, 1, 10, 0].map(Object::Integer).to_vec()),
+        );
+        if let Some(resources) = form_resources {
+            form.insert(names::RESOURCES.into(), resources);
+        }
+        let form = builder.add(Object::Stream(
+            Box::new(Object::Dictionary(form)),
+            form_content.into(),
+        ));
+
+        let resources = dict(&[
+            (names::FONT, dict(&[("F1", Object::Indirect(font))])),
+            (names::X_OBJECT, dict(&[("Fm0", Object::Indirect(form))])),
+        ]);
+        test_support::single_page(
+            builder,
+            resources,
+            b"q 1 0 0 1 100 100 cm /Fm0 Do Q BT /F1 10 Tf 5 5 Td (After) Tj ET",
+        )
+    }
+
+    #[cfg(all(feature = "write", any(feature = "miniz_oxide", feature = "flate2")))]
+    #[test]
+    fn should_position_text() {
+        let mut file = PdfFile::from_raw(test_support::compressed_xref());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let fragments = pages[0].text_fragments().unwrap();
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].text(), "Hello, world!");
+        assert_eq!(fragments[0].font_size, 24.0);
+        assert_eq!(fragments[0].glyphs[0].origin, (72.0, 720.0));
+
+        // Each glyph should start after the one before it
+        for pair in fragments[0].glyphs.windows(2) {
+            assert!(pair[1].origin.0 > pair[0].origin.0);
+        }
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_skip_images_that_cannot_be_decoded() {
+        let mut file = PdfFile::from_raw(test_support::jpeg_page());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let fragments = pages[0].text_fragments().unwrap();
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].text(), "Hello, world!");
+        assert_eq!(pages[0].extract_text().unwrap(), "Hello, world!");
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_follow_text_matrices() {
+        let mut file = PdfFile::from_raw(test_support::multi_page(2));
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let fragments = pages[1].text_fragments().unwrap();
+        assert_eq!(fragments[0].text(), "Page 2");
+        assert_eq!(fragments[0].glyphs[0].origin, (72.0, 720.0));
+        assert_eq!(fragments[0].font_size, 24.0);
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_draw_forms_with_their_own_resources() {
+        let resources = dict(&[(
+            names::FONT,
+            dict(&[(
+                "F2",
+                dict(&[
+                    (names::TYPE, name(names::FONT)),
+                    (names::SUBTYPE, name("Type1")),
+                    (names::BASE_FONT, name("Courier")),
+                ]),
+            )]),
+        )]);
+        // The form also draws itself, which should be skipped
+        let raw = form_page(Some(resources), b"BT /F2 12 Tf (Inside) Tj ET /Fm0 Do");
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let fragments = pages[0].text_fragments().unwrap();
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0].text(), "Inside");
+        assert_eq!(fragments[0].glyphs[0].origin, (110.0, 100.0));
+        assert_eq!(fragments[1].text(), "After");
+        assert_eq!(fragments[1].glyphs[0].origin, (5.0, 5.0));
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_fall_back_to_page_resources() {
+        let raw = form_page(None, b"BT /F1 12 Tf (Fallback) Tj ET");
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let fragments = pages[0].text_fragments().unwrap();
+        assert_eq!(fragments[0].text(), "Fallback");
+        assert_eq!(fragments[0].font_size, 12.0);
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_use_type_3_font_matrices() {
+        let mut builder = DocumentBuilder::new();
+        let char_proc = builder.add(Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::new())),
+            b"50 0 0 0 40 70 d1 0 0 40 70 re f".into(),
+        ));
+        let font = dict(&[
+            (names::TYPE, name(names::FONT)),
+            (names::SUBTYPE, name(names::TYPE_3)),
+            (
+                names::FONT_MATRIX,
+                Object::Array(vec![
+                    Object::Real(0.01),
+                    Object::Integer(0),
+                    Object::Integer(0),
+                    Object::Real(0.01),
+                    Object::Integer(0),
+                    Object::Integer(0),
+                ]),
+            ),
+            (
+                names::FONT_BBOX,
+                Object::Array([0, 0, 40, 70].map(Object::Integer).to_vec()),
+            ),
+            (names::FIRST_CHAR, Object::Integer(65)),
+            (names::WIDTHS, Object::Array(vec![Object::Integer(50)])),
+            (
+                names::ENCODING,
+                dict(&[(
+                    names::DIFFERENCES,
+                    Object::Array(vec![Object::Integer(65), name("A")]),
+                )]),
+            ),
+            (
+                names::CHAR_PROCS,
+                dict(&[("A", Object::Indirect(char_proc))]),
+            ),
+        ]);
+        let resources = dict(&[(names::FONT, dict(&[("T3", font)]))]);
+        let raw = test_support::single_page(builder, resources, b"BT /T3 10 Tf (AA) Tj ET");
+
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let fragments = pages[0].text_fragments().unwrap();
+        let glyphs = &fragments[0].glyphs;
+        assert_eq!(fragments[0].text(), "AA");
+        assert_eq!(glyphs[1].origin, (5.0, 0.0));
+        assert_eq!(glyphs[0].bbox.x1, 4.0);
+        assert_eq!(glyphs[0].bbox.y1, 7.0);
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_write_vertically() {
+        let descendant = dict(&[
+            (names::TYPE, name(names::FONT)),
+            (names::SUBTYPE, name(names::CID_FONT_TYPE_2)),
+            (names::BASE_FONT, name("MSMincho")),
+            (names::DW, Object::Integer(1000)),
+        ]);
+        let font = dict(&[
+            (names::TYPE, name(names::FONT)),
+            (names::SUBTYPE, name(names::TYPE_0)),
+            (names::BASE_FONT, name("MSMincho")),
+            (names::ENCODING, name("Identity-V")),
+            (names::DESCENDANT_FONTS, Object::Array(vec![descendant])),
+        ]);
+        let resources = dict(&[(names::FONT, dict(&[("F1", font)]))]);
+        let raw = test_support::single_page(
+            DocumentBuilder::new(),
+            resources,
+            b"BT /F1 10 Tf 100 700 Td <00010002> Tj ET",
+        );
+
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let glyphs = &pages[0].text_fragments().unwrap()[0].glyphs;
+        assert_eq!(glyphs[0].origin, (100.0, 700.0));
+        assert_eq!(glyphs[1].origin, (100.0, 690.0));
+        // The default position vector centres the glyph below the origin
+        assert_eq!(glyphs[0].bbox.x0, 95.0);
+        assert_eq!(glyphs[0].bbox.x1, 105.0);
+        assert!(glyphs[0].bbox.y1 < 700.0);
+    }
+}
@@ -0,0 +1,45 @@
+//! Reads data that applications have stored in the document, using the
+//! page-piece dictionary of the catalog (Adobe, 2008, p. 726).
+
+use crate::error::{Error, Result};
+use crate::objects::{names, Object};
+use crate::parsing::pdf_file::PdfFile;
+
+impl<'a> PdfFile<'a> {
+    /// Returns the data stored under an application's key, such as
+    /// `com.example.editor`, or `None` if there is none.
+    pub fn app_data(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let catalog = self.catalog()?;
+        let Some(piece_info) = catalog.get(names::PIECE_INFO) else {
+            return Ok(None);
+        };
+        let piece_info = self.resolve_owned(piece_info)?;
+        let Some(data) = piece_info.get(key) else {
+            return Ok(None);
+        };
+        let data = self.resolve_owned(data)?;
+        let Some(private) = data.get(names::PRIVATE) else {
+            return Ok(None);
+        };
+
+        match self.resolve_owned(private)? {
+            Object::Stream(_, data) => Ok(Some(data.into_owned())),
+            Object::String(data) => Ok(Some(data.into_owned())),
+            other => Err(Error::Type(format!(
+                "Expected stream or string got {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Deserializes the data stored under an application's key from JSON.
+    #[cfg(feature = "serde")]
+    pub fn app_value<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        match self.app_data(key)? {
+            Some(data) => serde_json::from_slice(&data)
+                .map(Some)
+                .map_err(|err| Error::Syntax("Invalid application data", err.to_string())),
+            None => Ok(None),
+        }
+    }
+}
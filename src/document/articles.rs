@@ -0,0 +1,231 @@
+//! Reads article threads, which lead a reader through the parts of an
+//! article that are spread across columns and pages (Adobe, 2008,
+//! p. 378-379).
+
+use crate::document::info::text_entries;
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::geometry::Rect;
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::{BTreeMap, HashSet};
+
+/// One part of an article, which is a rectangle on a page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bead {
+    pub reference: IndirectRef,
+    /// The thread that the bead belongs to.
+    pub thread: Option<IndirectRef>,
+    pub page: Option<IndirectRef>,
+    pub rect: Rect,
+}
+
+/// An article, which is a thread of beads in reading order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Article {
+    pub reference: Option<IndirectRef>,
+    /// The text entries of the thread information dictionary, such as
+    /// `Title` and `Author`, which are the same as those of the document
+    /// information dictionary.
+    pub info: BTreeMap<String, String>,
+    pub beads: Vec<Bead>,
+}
+
+impl Article {
+    pub fn title(&self) -> Option<&str> {
+        self.info.get(names::TITLE).map(String::as_str)
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// The article threads in the catalog's `/Threads` array.
+    pub fn articles(&self) -> Result<Vec<Article>> {
+        let catalog = self.catalog()?;
+        let Some(threads) = catalog.get(names::THREADS) else {
+            return Ok(Vec::new());
+        };
+
+        let mut articles = Vec::new();
+        for thread in &self.resolve_owned(threads)? {
+            let reference = match thread {
+                &Object::Indirect(reference) => Some(reference),
+                _ => None,
+            };
+            let dict = self.resolve_owned(thread)?;
+            let info = match dict.get(names::I) {
+                Some(info) => match self.resolve_owned(info)? {
+                    Object::Dictionary(info) => text_entries(self, &info)?,
+                    _ => BTreeMap::new(),
+                },
+                None => BTreeMap::new(),
+            };
+            let beads = match dict.get(names::F) {
+                Some(&Object::Indirect(first)) => self.beads_from(first)?,
+                Some(other) => {
+                    return Err(Error::Type(format!(
+                        "Expected bead reference got {:?}",
+                        other
+                    )))
+                }
+                None => Vec::new(),
+            };
+            articles.push(Article {
+                reference,
+                info,
+                beads,
+            });
+        }
+        Ok(articles)
+    }
+
+    /// Follows the `/N` links of a thread's beads, which form a loop back to
+    /// the first bead.
+    fn beads_from(&self, first: IndirectRef) -> Result<Vec<Bead>> {
+        let mut beads = Vec::new();
+        let mut visited = HashSet::new();
+        let mut next = first;
+        loop {
+            if !visited.insert(next) {
+                return Err(Error::Syntax(
+                    "Article beads do not loop back to the first bead",
+                    format!("{:?}", next),
+                ));
+            }
+            let bead = read_bead(self, next)?;
+            beads.push(bead);
+            match self.object(next)?.get(names::N) {
+                Some(&Object::Indirect(reference)) if reference == first => break,
+                Some(&Object::Indirect(reference)) => next = reference,
+                // A thread with one bead may leave out the links to itself
+                _ => break,
+            }
+        }
+        Ok(beads)
+    }
+}
+
+fn read_bead(file: &PdfFile, reference: IndirectRef) -> Result<Bead> {
+    let dict = file.object(reference)?;
+    let link = |key: &str| match dict.get(key) {
+        Some(&Object::Indirect(reference)) => Some(reference),
+        _ => None,
+    };
+    Ok(Bead {
+        reference,
+        thread: link(names::T),
+        page: link(names::P),
+        rect: Rect::from_object(&file.resolve_owned(dict.get_required(names::R)?)?)?,
+    })
+}
+
+impl<'a> Page<'a> {
+    /// The beads of articles that are on the page, from its `/B` array.
+    pub fn beads(&self) -> Result<Vec<Bead>> {
+        let Some(beads) = self.own_entry(names::B)? else {
+            return Ok(Vec::new());
+        };
+        let mut result = Vec::new();
+        for bead in &beads {
+            match bead {
+                &Object::Indirect(reference) => result.push(read_bead(self.file(), reference)?),
+                other => {
+                    return Err(Error::Type(format!(
+                        "Expected bead reference got {:?}",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::IndirectRef;
+    use crate::test_support::{build_pages, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    #[test]
+    fn should_read_article_threads() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = build_pages(&mut builder, &[b"", b""]);
+        let pages = [IndirectRef::new(5, 0), IndirectRef::new(7, 0)];
+        let thread = builder.reserve();
+        let beads = [builder.reserve(), builder.reserve(), builder.reserve()];
+        let rect = |x: i64| {
+            Object::Array(vec![
+                Object::Integer(x),
+                Object::Integer(0),
+                Object::Integer(x + 100),
+                Object::Integer(500),
+            ])
+        };
+        for (i, &bead) in beads.iter().enumerate() {
+            builder.insert(
+                bead,
+                dict(&[
+                    (names::TYPE, name("Bead")),
+                    (names::T, Object::Indirect(thread)),
+                    (names::N, Object::Indirect(beads[(i + 1) % 3])),
+                    (names::V, Object::Indirect(beads[(i + 2) % 3])),
+                    (names::P, Object::Indirect(pages[i / 2])),
+                    (names::R, rect(i as i64 * 200)),
+                ]),
+            );
+        }
+        builder.insert(
+            thread,
+            dict(&[
+                (names::TYPE, name("Thread")),
+                (names::F, Object::Indirect(beads[0])),
+                (
+                    names::I,
+                    dict(&[(names::TITLE, Object::String(Cow::Borrowed(b"News")))]),
+                ),
+            ]),
+        );
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(IndirectRef::new(2, 0))),
+                (
+                    names::THREADS,
+                    Object::Array(vec![Object::Indirect(thread)]),
+                ),
+            ]),
+        );
+        builder.insert(
+            pages[1],
+            dict(&[
+                (names::TYPE, name(names::PAGE)),
+                (names::PARENT, Object::Indirect(IndirectRef::new(2, 0))),
+                (names::B, Object::Array(vec![Object::Indirect(beads[2])])),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let articles = file.articles().unwrap();
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].title(), Some("News"));
+        let order = articles[0]
+            .beads
+            .iter()
+            .map(|bead| bead.reference)
+            .collect::<Vec<_>>();
+        assert_eq!(order, beads);
+        assert_eq!(
+            articles[0].beads[1].rect,
+            Rect::new(200.0, 0.0, 300.0, 500.0)
+        );
+        assert_eq!(articles[0].beads[0].thread, Some(thread));
+
+        let on_page = file.pages().unwrap()[1].beads().unwrap();
+        assert_eq!(on_page, [articles[0].beads[2].clone()]);
+        assert_eq!(file.pages().unwrap()[0].beads().unwrap(), []);
+    }
+}
@@ -0,0 +1,537 @@
+//! Typed views of common dictionaries, which resolve their entries in the file
+//! and check them against the types given in the spec, so that callers do not
+//! need to index dictionaries by name.
+
+use crate::error::{Error, Result};
+use crate::geometry::{points_from_object, Rect};
+use crate::objects::{names, Dictionary, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::fmt;
+
+/// The parts shared by every view: the dictionary, the object it came from,
+/// and the file that its entries are resolved in.
+#[derive(Clone)]
+struct View<'a> {
+    file: &'a PdfFile<'a>,
+    reference: Option<IndirectRef>,
+    dict: Dictionary<'a>,
+}
+
+impl fmt::Debug for View<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("View")
+            .field("reference", &self.reference)
+            .field("dict", &self.dict)
+            .finish()
+    }
+}
+
+impl<'a> View<'a> {
+    /// Resolves a dictionary, checking its `/Type` if it has one.
+    fn new(file: &'a PdfFile<'a>, object: &Object<'a>, types: &[&str]) -> Result<Self> {
+        let reference = match object {
+            &Object::Indirect(reference) => Some(reference),
+            _ => None,
+        };
+        let dict = match file.resolve_owned(object)? {
+            Object::Dictionary(dict) => dict,
+            other => return Err(Error::Type(format!("Expected dict got {:?}", other))),
+        };
+
+        if let Some(Object::Name(t)) = dict.get(names::TYPE.as_bytes()) {
+            if !types.is_empty() && !types.iter().any(|&expected| t.matches(expected)) {
+                return Err(Error::Type(format!(
+                    "Expected {} dictionary got {:?}",
+                    types.join(" or "),
+                    t
+                )));
+            }
+        }
+
+        Ok(Self {
+            file,
+            reference,
+            dict,
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Object<'a>>> {
+        match self.dict.get(key.as_bytes()) {
+            Some(value) => match self.file.resolve_owned(value)? {
+                Object::Null => Ok(None),
+                value => Ok(Some(value)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Reads an entry that refers to another object, without resolving it.
+    fn indirect(&self, key: &str) -> Option<IndirectRef> {
+        match self.dict.get(key.as_bytes()) {
+            Some(&Object::Indirect(reference)) => Some(reference),
+            _ => None,
+        }
+    }
+
+    fn required(&self, key: &str) -> Result<Object<'a>> {
+        self.get(key)?.ok_or_else(|| Error::MissingKey(key.into()))
+    }
+
+    fn name(&self, key: &str) -> Result<Option<String>> {
+        match self.get(key)? {
+            Some(Object::Name(name)) => Ok(Some(String::from_utf8_lossy(&name).into_owned())),
+            Some(other) => Err(Error::Type(format!("Expected name got {:?}", other))),
+            None => Ok(None),
+        }
+    }
+
+    fn int(&self, key: &str) -> Result<Option<i64>> {
+        self.get(key)?.map(|value| value.as_int()).transpose()
+    }
+
+    fn text(&self, key: &str) -> Result<Option<String>> {
+        self.get(key)?.map(|value| value.as_text()).transpose()
+    }
+
+    fn rect(&self, key: &str) -> Result<Option<Rect>> {
+        self.get(key)?
+            .map(|value| Rect::from_object(&value))
+            .transpose()
+    }
+}
+
+/// The document catalog, which is the root of the document (Adobe, 2008,
+/// p. 73-75).
+#[derive(Clone, Debug)]
+pub struct Catalog<'a>(View<'a>);
+
+impl<'a> Catalog<'a> {
+    pub fn new(file: &'a PdfFile<'a>, object: &Object<'a>) -> Result<Self> {
+        View::new(file, object, &[names::CATALOG]).map(Self)
+    }
+
+    /// Reads the catalog that the trailer refers to.
+    pub fn from_file(file: &'a PdfFile<'a>) -> Result<Self> {
+        let root = file.trailer()?.get_indirect(names::ROOT)?;
+        Self::new(file, &Object::Indirect(root))
+    }
+
+    pub fn reference(&self) -> Option<IndirectRef> {
+        self.0.reference
+    }
+
+    pub fn dict(&self) -> &Dictionary<'a> {
+        &self.0.dict
+    }
+
+    /// The version of the spec that the document conforms to, if it is later
+    /// than the version in the header.
+    pub fn version(&self) -> Result<Option<String>> {
+        self.0.name(names::VERSION)
+    }
+
+    /// The root of the page tree.
+    pub fn pages(&self) -> Result<PageNode<'a>> {
+        let pages = self
+            .0
+            .dict
+            .get(names::PAGES.as_bytes())
+            .ok_or_else(|| Error::MissingKey(names::PAGES.into()))?;
+        PageNode::new(self.0.file, pages)
+    }
+
+    pub fn page_layout(&self) -> Result<Option<String>> {
+        self.0.name(names::PAGE_LAYOUT)
+    }
+
+    pub fn page_mode(&self) -> Result<Option<String>> {
+        self.0.name(names::PAGE_MODE)
+    }
+
+    /// The natural language of the document, such as `en-US`.
+    pub fn lang(&self) -> Result<Option<String>> {
+        self.0.text(names::LANG)
+    }
+
+    pub fn outlines(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::OUTLINES)
+    }
+
+    pub fn names(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::NAMES)
+    }
+
+    pub fn acro_form(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::ACRO_FORM)
+    }
+}
+
+/// Whether a node of the page tree is an intermediate node or a page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageNodeKind {
+    Pages,
+    Page,
+}
+
+/// A node of the page tree (Adobe, 2008, p. 76-79). Unlike `Page`, this only
+/// has the node's own attributes, and not those that it inherits.
+#[derive(Clone, Debug)]
+pub struct PageNode<'a>(View<'a>);
+
+impl<'a> PageNode<'a> {
+    pub fn new(file: &'a PdfFile<'a>, object: &Object<'a>) -> Result<Self> {
+        View::new(file, object, &[names::PAGES, names::PAGE]).map(Self)
+    }
+
+    pub fn reference(&self) -> Option<IndirectRef> {
+        self.0.reference
+    }
+
+    pub fn dict(&self) -> &Dictionary<'a> {
+        &self.0.dict
+    }
+
+    /// Nodes without a type are treated as pages unless they have kids.
+    pub fn kind(&self) -> PageNodeKind {
+        let is_pages = match self.0.dict.get(names::TYPE.as_bytes()) {
+            Some(Object::Name(t)) => t.matches(names::PAGES),
+            _ => self.0.dict.contains_key(names::KIDS.as_bytes()),
+        };
+        if is_pages {
+            PageNodeKind::Pages
+        } else {
+            PageNodeKind::Page
+        }
+    }
+
+    /// The parent of the node, which only the root does not have.
+    pub fn parent(&self) -> Result<Option<PageNode<'a>>> {
+        match self.0.dict.get(names::PARENT.as_bytes()) {
+            Some(parent) => Ok(Some(Self::new(self.0.file, parent)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The children of an intermediate node, which pages do not have.
+    pub fn kids(&self) -> Result<Vec<PageNode<'a>>> {
+        match self.0.get(names::KIDS)? {
+            Some(Object::Array(kids)) => {
+                kids.iter().map(|kid| Self::new(self.0.file, kid)).collect()
+            }
+            Some(other) => Err(Error::Type(format!("Expected array got {:?}", other))),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The number of pages below an intermediate node.
+    pub fn count(&self) -> Result<Option<i64>> {
+        self.0.int(names::COUNT)
+    }
+
+    pub fn media_box(&self) -> Result<Option<Rect>> {
+        self.0.rect(names::MEDIA_BOX)
+    }
+
+    pub fn crop_box(&self) -> Result<Option<Rect>> {
+        self.0.rect(names::CROP_BOX)
+    }
+
+    pub fn rotate(&self) -> Result<Option<i64>> {
+        self.0.int(names::ROTATE)
+    }
+
+    pub fn resources(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::RESOURCES)
+    }
+
+    /// The fonts in the node's own resources, sorted by name.
+    pub fn fonts(&self) -> Result<Vec<(String, FontDict<'a>)>> {
+        let Some(resources) = self.resources()? else {
+            return Ok(Vec::new());
+        };
+        let fonts = match resources.get(names::FONT) {
+            Some(fonts) => self.0.file.resolve_owned(fonts)?,
+            None => return Ok(Vec::new()),
+        };
+        let Object::Dictionary(fonts) = fonts else {
+            return Err(Error::Type(format!("Expected dict got {:?}", fonts)));
+        };
+
+        let mut result = fonts
+            .iter()
+            .map(|(name, font)| {
+                let name = String::from_utf8_lossy(name).into_owned();
+                Ok((name, FontDict::new(self.0.file, font)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        result.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(result)
+    }
+
+    /// The annotations on a page.
+    pub fn annotations(&self) -> Result<Vec<AnnotationDict<'a>>> {
+        match self.0.get(names::ANNOTS)? {
+            Some(Object::Array(annots)) => annots
+                .iter()
+                .map(|annot| AnnotationDict::new(self.0.file, annot))
+                .collect(),
+            Some(other) => Err(Error::Type(format!("Expected array got {:?}", other))),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// A font dictionary (Adobe, 2008, p. 254-255).
+#[derive(Clone, Debug)]
+pub struct FontDict<'a>(View<'a>);
+
+impl<'a> FontDict<'a> {
+    pub fn new(file: &'a PdfFile<'a>, object: &Object<'a>) -> Result<Self> {
+        View::new(file, object, &[names::FONT]).map(Self)
+    }
+
+    pub fn reference(&self) -> Option<IndirectRef> {
+        self.0.reference
+    }
+
+    pub fn dict(&self) -> &Dictionary<'a> {
+        &self.0.dict
+    }
+
+    /// The type of font, such as `Type1` or `TrueType`.
+    pub fn subtype(&self) -> Result<String> {
+        self.0
+            .name(names::SUBTYPE)?
+            .ok_or_else(|| Error::MissingKey(names::SUBTYPE.into()))
+    }
+
+    /// The PostScript name of the font, which Type 3 fonts do not have.
+    pub fn base_font(&self) -> Result<Option<String>> {
+        self.0.name(names::BASE_FONT)
+    }
+
+    pub fn first_char(&self) -> Result<Option<i64>> {
+        self.0.int(names::FIRST_CHAR)
+    }
+
+    pub fn last_char(&self) -> Result<Option<i64>> {
+        self.0.int(names::LAST_CHAR)
+    }
+
+    /// The widths of the characters from `first_char` to `last_char`, in
+    /// thousandths of a unit of text space.
+    pub fn widths(&self) -> Result<Option<Vec<f64>>> {
+        match self.0.get(names::WIDTHS)? {
+            Some(Object::Array(widths)) => widths
+                .iter()
+                .map(|width| match self.0.file.resolve_owned(width)? {
+                    Object::Integer(width) => Ok(width as f64),
+                    width => width.as_real(),
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(Some),
+            Some(other) => Err(Error::Type(format!("Expected array got {:?}", other))),
+            None => Ok(None),
+        }
+    }
+
+    /// Either the name of a standard encoding or an encoding dictionary.
+    pub fn encoding(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::ENCODING)
+    }
+
+    pub fn font_descriptor(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::FONT_DESCRIPTOR)
+    }
+
+    pub fn to_unicode(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::TO_UNICODE)
+    }
+}
+
+/// The paths of an ink annotation, each a list of points.
+pub type InkList = Vec<Vec<(f64, f64)>>;
+
+/// An annotation dictionary (Adobe, 2008, p. 381-384).
+#[derive(Clone, Debug)]
+pub struct AnnotationDict<'a>(View<'a>);
+
+impl<'a> AnnotationDict<'a> {
+    pub fn new(file: &'a PdfFile<'a>, object: &Object<'a>) -> Result<Self> {
+        View::new(file, object, &[names::ANNOT]).map(Self)
+    }
+
+    pub fn reference(&self) -> Option<IndirectRef> {
+        self.0.reference
+    }
+
+    pub fn dict(&self) -> &Dictionary<'a> {
+        &self.0.dict
+    }
+
+    /// The type of annotation, such as `Link` or `Highlight`.
+    pub fn subtype(&self) -> Result<String> {
+        self.0
+            .name(names::SUBTYPE)?
+            .ok_or_else(|| Error::MissingKey(names::SUBTYPE.into()))
+    }
+
+    /// Where the annotation is on the page, in default user space.
+    pub fn rect(&self) -> Result<Rect> {
+        Rect::from_object(&self.0.required(names::RECT)?)
+    }
+
+    /// The text shown for the annotation, or a description of it.
+    pub fn contents(&self) -> Result<Option<String>> {
+        self.0.text(names::CONTENTS)
+    }
+
+    /// The name that identifies the annotation on its page.
+    pub fn name(&self) -> Result<Option<String>> {
+        self.0.text(names::NM)
+    }
+
+    /// The annotation flags, which default to none being set (Adobe, 2008,
+    /// p. 385).
+    pub fn flags(&self) -> Result<i64> {
+        Ok(self.0.int(names::F)?.unwrap_or(0))
+    }
+
+    /// The page that the annotation is on, if it says.
+    pub fn page(&self) -> Option<IndirectRef> {
+        self.0.indirect(names::P)
+    }
+
+    /// The author of a markup annotation (Adobe, 2008, p. 390).
+    pub fn author(&self) -> Result<Option<String>> {
+        self.0.text(names::T)
+    }
+
+    /// When the annotation was last modified, as a date string.
+    pub fn modified(&self) -> Result<Option<String>> {
+        self.0.text(names::M)
+    }
+
+    /// The pop-up window that shows the text of a markup annotation.
+    pub fn popup(&self) -> Option<IndirectRef> {
+        self.0.indirect(names::POPUP)
+    }
+
+    /// The annotation that a pop-up window belongs to.
+    pub fn parent(&self) -> Option<IndirectRef> {
+        self.0.indirect(names::PARENT)
+    }
+
+    /// The annotation that this one replies to, or is grouped with
+    /// (Adobe, 2008, p. 391).
+    pub fn in_reply_to(&self) -> Option<IndirectRef> {
+        self.0.indirect(names::IRT)
+    }
+
+    /// Whether the annotation is a reply, `R`, or grouped with the one it
+    /// refers to, `Group`. Defaults to a reply.
+    pub fn reply_type(&self) -> Result<String> {
+        Ok(self.0.name(names::RT)?.unwrap_or_else(|| "R".into()))
+    }
+
+    /// The review state that the annotation sets on the one it replies to,
+    /// such as `Accepted` or `Rejected` (Adobe, 2008, p. 397).
+    pub fn state(&self) -> Result<Option<String>> {
+        self.0.text(names::STATE)
+    }
+
+    /// The state model that `state` belongs to, `Review` or `Marked`.
+    pub fn state_model(&self) -> Result<Option<String>> {
+        self.0.text(names::STATE_MODEL)
+    }
+
+    pub fn appearance(&self) -> Result<Option<Object<'a>>> {
+        self.0.get(names::AP)
+    }
+
+    /// The paths of an ink annotation, each a list of points in default user
+    /// space (Adobe, 2008, p. 403).
+    pub fn ink_list(&self) -> Result<Option<InkList>> {
+        match self.0.get(names::INK_LIST)? {
+            Some(Object::Array(paths)) => paths
+                .iter()
+                .map(|path| points_from_object(&self.0.file.resolve_owned(path)?))
+                .collect::<Result<Vec<_>>>()
+                .map(Some),
+            Some(other) => Err(Error::Type(format!("Expected array got {:?}", other))),
+            None => Ok(None),
+        }
+    }
+
+    /// The vertices of a polygon or polyline annotation, in default user
+    /// space (Adobe, 2008, p. 402).
+    pub fn vertices(&self) -> Result<Option<Vec<(f64, f64)>>> {
+        self.0
+            .get(names::VERTICES)?
+            .map(|vertices| points_from_object(&vertices))
+            .transpose()
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    #[test]
+    fn should_read_typed_dictionaries() {
+        let mut file = PdfFile::from_raw(test_support::hello_world(Default::default()));
+        file.load_xref_table().unwrap();
+
+        let catalog = Catalog::from_file(&file).unwrap();
+        assert_eq!(catalog.reference(), Some(IndirectRef::new(1, 0)));
+        assert_eq!(catalog.lang(), Ok(None));
+        let root = catalog.pages().unwrap();
+        assert_eq!(root.kind(), PageNodeKind::Pages);
+        assert_eq!(root.count(), Ok(Some(1)));
+        assert!(root.parent().unwrap().is_none());
+
+        let page = &root.kids().unwrap()[0];
+        assert_eq!(page.kind(), PageNodeKind::Page);
+        assert_eq!(
+            page.parent().unwrap().unwrap().reference(),
+            root.reference()
+        );
+        assert_eq!(page.annotations().unwrap().len(), 0);
+        let fonts = page.fonts().unwrap();
+        assert_eq!(fonts.len(), 1);
+        assert_eq!(fonts[0].1.subtype(), Ok("Type1".into()));
+        assert_eq!(fonts[0].1.base_font(), Ok(Some("Helvetica".into())));
+        assert_eq!(fonts[0].1.widths(), Ok(None));
+
+        // The type is checked where it is given
+        assert!(PageNode::new(&file, &Object::Indirect(IndirectRef::new(1, 0))).is_err());
+    }
+
+    #[test]
+    fn should_read_annotations() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let annot = builder.add(dict(&[
+            (names::TYPE, name(names::ANNOT)),
+            (names::SUBTYPE, name("Text")),
+            (
+                names::RECT,
+                Object::Array([0, 0, 20, 10].map(Object::Integer).to_vec()),
+            ),
+            (names::CONTENTS, Object::String(Cow::Borrowed(b"Note"))),
+        ]));
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+
+        let annot = AnnotationDict::new(&file, &Object::Indirect(annot)).unwrap();
+        assert_eq!(annot.subtype(), Ok("Text".into()));
+        assert_eq!(annot.rect(), Ok(Rect::new(0.0, 0.0, 20.0, 10.0)));
+        assert_eq!(annot.contents(), Ok(Some("Note".into())));
+        assert_eq!(annot.flags(), Ok(0));
+        assert_eq!(annot.page(), None);
+        assert!(FontDict::new(&file, &Object::Indirect(annot.reference().unwrap())).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::error::Result;
+use crate::objects::{names, sorted_entries, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use crate::writing::object_map::ObjectMap;
+use crate::writing::objects::{write_object, WriteOptions};
+use std::collections::VecDeque;
+
+impl<'a> PdfFile<'a> {
+    /// Hashes the objects that can be reached from the catalog, so that files
+    /// with the same content have the same digest. The xref table must
+    /// already be loaded.
+    ///
+    /// Objects are numbered in the order that they are first referred to, and
+    /// written with sorted keys and decoded streams, so the digest does not
+    /// depend on how the file was numbered, ordered or compressed. The
+    /// document information dictionary and file identifier are left out.
+    ///
+    /// The digest is an MD5 hash, which is fine for finding duplicates but
+    /// should not be relied on to detect tampering.
+    pub fn content_digest(&self) -> Result<[u8; 16]> {
+        let root = Object::Indirect(self.trailer()?.get_indirect(names::ROOT)?);
+
+        let mut canonical = ObjectMap::new();
+        let mut queue = VecDeque::new();
+        number_references(&root, &mut canonical, &mut queue);
+
+        let mut context = md5::Context::new();
+        let mut bytes = Vec::new();
+        while let Some(reference) = queue.pop_front() {
+            // References to missing objects are treated as null (Adobe, 2008,
+            // p. 21)
+            let object = self.object(reference).unwrap_or(Object::Null);
+            number_references(&object, &mut canonical, &mut queue);
+
+            bytes.clear();
+            write_object(
+                &mut bytes,
+                &canonical.remap(&object),
+                &WriteOptions::default(),
+            )?;
+            bytes.push(b'\n');
+            context.consume(&bytes);
+        }
+
+        Ok(context.compute().0)
+    }
+}
+
+/// Gives each reference that has not been seen before the next number, in the
+/// order that the writer writes them, and queues the object to be hashed.
+fn number_references(
+    object: &Object,
+    canonical: &mut ObjectMap,
+    queue: &mut VecDeque<IndirectRef>,
+) {
+    match object {
+        &Object::Indirect(reference) if canonical.get(reference).is_none() => {
+            let number = canonical.len() as u32 + 1;
+            canonical.insert(
+                reference,
+                IndirectRef {
+                    number,
+                    generation: 0,
+                },
+            );
+            queue.push_back(reference);
+        }
+        Object::Array(array) => {
+            for item in array {
+                number_references(item, canonical, queue);
+            }
+        }
+        Object::Dictionary(dict) => {
+            for (_, value) in sorted_entries(dict) {
+                number_references(value, canonical, queue);
+            }
+        }
+        Object::Stream(dict, _) => number_references(dict, canonical, queue),
+        _ => {}
+    }
+}
+
+#[cfg(all(test, any(feature = "miniz_oxide", feature = "flate2")))]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::writing::merge::{merge, MergeOptions};
+
+    fn open(raw: Vec<u8>) -> PdfFile<'static> {
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    #[test]
+    fn should_ignore_numbering_and_compression() {
+        let plain = open(test_support::hello_world(WriteOptions::default()));
+        let compressed = open(test_support::hello_world(WriteOptions {
+            compress_streams: true,
+            ..Default::default()
+        }));
+        // Merging a single file renumbers its objects
+        let renumbered = open(
+            merge(&[&plain], MergeOptions::default())
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+        );
+        assert_ne!(
+            plain.pages().unwrap()[0].reference(),
+            renumbered.pages().unwrap()[0].reference()
+        );
+
+        let digest = plain.content_digest().unwrap();
+        assert_eq!(compressed.content_digest(), Ok(digest));
+        assert_eq!(renumbered.content_digest(), Ok(digest));
+
+        let other = open(test_support::multi_page(1));
+        assert_ne!(other.content_digest(), Ok(digest));
+    }
+}
@@ -0,0 +1,250 @@
+//! Reads the encryption dictionary, without decrypting the file
+//! (Adobe, 2008, p. 55-64).
+
+use crate::error::Result;
+use crate::objects::{names, Object};
+use crate::parsing::pdf_file::PdfFile;
+
+/// The user access permissions, from the `/P` entry (Adobe, 2008, p. 61).
+/// Each method checks the bit for one permission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Permissions(pub u32);
+
+impl Permissions {
+    fn bit(&self, position: u32) -> bool {
+        self.0 & (1 << (position - 1)) != 0
+    }
+
+    pub fn can_print(&self) -> bool {
+        self.bit(3)
+    }
+
+    pub fn can_modify(&self) -> bool {
+        self.bit(4)
+    }
+
+    pub fn can_copy(&self) -> bool {
+        self.bit(5)
+    }
+
+    pub fn can_annotate(&self) -> bool {
+        self.bit(6)
+    }
+
+    pub fn can_fill_forms(&self) -> bool {
+        self.bit(9)
+    }
+
+    pub fn can_extract_for_accessibility(&self) -> bool {
+        self.bit(10)
+    }
+
+    pub fn can_assemble(&self) -> bool {
+        self.bit(11)
+    }
+
+    /// Whether the document may be printed faithfully, rather than only as
+    /// a low-resolution image. Only meaningful for revision 3 and later.
+    pub fn can_print_high_quality(&self) -> bool {
+        self.bit(12)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncryptionInfo {
+    /// The security handler, which is `Standard` for password security.
+    pub filter: String,
+    pub sub_filter: Option<String>,
+    /// The algorithm, from the `/V` entry.
+    pub version: i64,
+    /// The revision of the standard security handler, from the `/R` entry.
+    pub revision: Option<i64>,
+    /// The length of the key in bits.
+    pub key_length: u32,
+    /// The method used for streams when `version` is 4 or 5, such as `AESV2`.
+    pub crypt_method: Option<String>,
+    pub permissions: Permissions,
+    pub encrypt_metadata: bool,
+}
+
+impl EncryptionInfo {
+    pub fn is_aes(&self) -> bool {
+        self.crypt_method
+            .as_deref()
+            .is_some_and(|method| method.starts_with("AES"))
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// Describes how the file is encrypted, or returns `None` if it is not.
+    pub fn encryption_info(&self) -> Result<Option<EncryptionInfo>> {
+        let trailer = self.trailer()?;
+        let Some(encrypt) = trailer.get(names::ENCRYPT) else {
+            return Ok(None);
+        };
+        let encrypt = self.resolve_owned(encrypt)?;
+
+        let name_entry = |object: &Object, key| match object.get(key) {
+            Some(Object::Name(name)) => Some(String::from_utf8_lossy(name).into_owned()),
+            _ => None,
+        };
+        let version = match encrypt.get(names::V) {
+            Some(version) => version.as_int()?,
+            None => 0,
+        };
+
+        // Versions 4 and 5 describe their algorithms in crypt filters
+        let crypt_filter = match (encrypt.get(names::CF), name_entry(&encrypt, names::STM_F)) {
+            (Some(filters), Some(name)) if version >= 4 => self
+                .resolve_owned(filters)?
+                .get(&name)
+                .map(|filter| self.resolve_owned(filter))
+                .transpose()?,
+            _ => None,
+        };
+        let crypt_method = crypt_filter
+            .as_ref()
+            .and_then(|filter| name_entry(filter, names::CFM));
+
+        let length = crypt_filter
+            .as_ref()
+            .and_then(|filter| filter.get(names::LENGTH))
+            .or_else(|| encrypt.get(names::LENGTH))
+            .map(|length| self.resolve_owned(length)?.as_int())
+            .transpose()?;
+        let key_length = match (version, crypt_method.as_deref(), length) {
+            (5, _, _) | (_, Some("AESV3"), _) => 256,
+            (_, Some("AESV2"), _) => 128,
+            // Crypt filters give their length in bytes, though some writers
+            // use bits
+            (4, _, Some(length)) if length <= 32 => length as u32 * 8,
+            (2.., _, Some(length)) => length as u32,
+            _ => 40,
+        };
+
+        // The permissions are a signed 32-bit integer
+        let permissions = match encrypt.get(names::P) {
+            Some(permissions) => permissions.as_int()? as u32,
+            None => 0,
+        };
+
+        Ok(Some(EncryptionInfo {
+            filter: name_entry(&encrypt, names::FILTER).unwrap_or_default(),
+            sub_filter: name_entry(&encrypt, names::SUB_FILTER),
+            version,
+            revision: encrypt.get(names::R).map(Object::as_int).transpose()?,
+            key_length,
+            crypt_method,
+            permissions: Permissions(permissions),
+            encrypt_metadata: match encrypt.get(names::ENCRYPT_METADATA) {
+                Some(value) => value.as_bool()?,
+                None => true,
+            },
+        }))
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+
+    fn encrypted(encrypt: Object<'static>) -> PdfFile<'static> {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let encrypt = builder.add(encrypt);
+        builder.set_trailer_entry(names::ENCRYPT, Object::Indirect(encrypt));
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    #[test]
+    fn should_report_unencrypted_files() {
+        let mut file = PdfFile::from_raw(test_support::object_streams());
+        file.load_xref_table().unwrap();
+        assert_eq!(file.encryption_info(), Ok(None));
+    }
+
+    #[test]
+    fn should_read_encrypted_files() {
+        let mut file = PdfFile::from_raw(test_support::encrypted());
+        file.load_xref_table().unwrap();
+
+        let info = file.encryption_info().unwrap().unwrap();
+        assert_eq!(info.filter, "Standard");
+        assert_eq!(info.version, 1);
+        assert_eq!(info.revision, Some(2));
+        assert_eq!(info.key_length, 40);
+        assert_eq!(
+            info.permissions,
+            Permissions(test_support::ENCRYPTED_PERMISSIONS as u32)
+        );
+        assert!(info.permissions.can_print());
+        assert!(!info.permissions.can_copy());
+
+        // Only strings and streams are encrypted, so the page tree can still
+        // be read, but not the content
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_ne!(
+            pages[0].content_bytes().unwrap(),
+            test_support::HELLO_WORLD_CONTENT
+        );
+    }
+
+    #[test]
+    fn should_read_rc4_encryption() {
+        let file = encrypted(dict(&[
+            (names::FILTER, name("Standard")),
+            (names::V, Object::Integer(2)),
+            (names::R, Object::Integer(3)),
+            (names::LENGTH, Object::Integer(128)),
+            // Everything except printing and copying
+            (names::P, Object::Integer(!(4 | 16))),
+        ]));
+
+        let info = file.encryption_info().unwrap().unwrap();
+        assert_eq!(info.filter, "Standard");
+        assert_eq!(info.version, 2);
+        assert_eq!(info.revision, Some(3));
+        assert_eq!(info.key_length, 128);
+        assert!(!info.is_aes());
+        assert!(!info.permissions.can_print());
+        assert!(!info.permissions.can_copy());
+        assert!(info.permissions.can_modify());
+        assert!(info.permissions.can_print_high_quality());
+        assert!(info.encrypt_metadata);
+    }
+
+    #[test]
+    fn should_read_crypt_filters() {
+        let file = encrypted(dict(&[
+            (names::FILTER, name("Standard")),
+            (names::V, Object::Integer(4)),
+            (names::R, Object::Integer(4)),
+            (
+                names::CF,
+                dict(&[(
+                    "StdCF",
+                    dict(&[
+                        (names::CFM, name("AESV2")),
+                        (names::LENGTH, Object::Integer(16)),
+                    ]),
+                )]),
+            ),
+            (names::STM_F, name("StdCF")),
+            (names::P, Object::Integer(-4)),
+            (names::ENCRYPT_METADATA, Object::Boolean(false)),
+        ]));
+
+        let info = file.encryption_info().unwrap().unwrap();
+        assert_eq!(info.crypt_method.as_deref(), Some("AESV2"));
+        assert_eq!(info.key_length, 128);
+        assert!(info.is_aes());
+        assert!(info.permissions.can_print());
+        assert!(!info.encrypt_metadata);
+    }
+}
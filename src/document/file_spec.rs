@@ -0,0 +1,210 @@
+//! Reads file specifications, which refer to files outside the document or
+//! embedded in it (Adobe, 2008, p. 179-184).
+
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+
+/// A file specification, from either a string or a dictionary.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileSpec {
+    /// The file system that names are interpreted in, such as `URL`.
+    pub file_system: Option<String>,
+    /// The file specification string from `/F`, or the string itself if the
+    /// specification is not a dictionary.
+    pub file: Option<String>,
+    /// The Unicode file name from `/UF`.
+    pub unicode_file: Option<String>,
+    /// The platform-specific names, which are deprecated.
+    pub dos: Option<String>,
+    pub mac: Option<String>,
+    pub unix: Option<String>,
+    pub description: Option<String>,
+    /// Whether the file changes often, so should not be cached.
+    pub volatile: bool,
+    /// The embedded file stream, from `/EF`, if the file is in the document.
+    pub embedded: Option<IndirectRef>,
+}
+
+impl FileSpec {
+    /// The best name for the file, preferring the Unicode name, then the
+    /// file specification string, then any platform-specific name.
+    pub fn name(&self) -> Option<&str> {
+        [
+            &self.unicode_file,
+            &self.file,
+            &self.unix,
+            &self.mac,
+            &self.dos,
+        ]
+        .into_iter()
+        .find_map(|name| name.as_deref())
+    }
+
+    /// The last component of the name, without any directories.
+    pub fn file_name(&self) -> Option<&str> {
+        let name = self.name()?;
+        name.rsplit(['/', '\\', ':']).next()
+    }
+
+    /// Whether the name is a URL rather than a path.
+    pub fn is_url(&self) -> bool {
+        self.file_system.as_deref() == Some("URL")
+    }
+}
+
+/// A go-to action that goes to a destination in another document
+/// (Adobe, 2008, p. 418).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemoteGoTo {
+    pub file: FileSpec,
+    /// The destination in the other document, which uses page numbers rather
+    /// than page objects, or a named destination.
+    pub destination: Object<'static>,
+    /// Whether to open the document in a new window, if the action says.
+    pub new_window: Option<bool>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Reads a file specification, which may be a string or a dictionary.
+    pub fn file_spec(&self, object: &Object) -> Result<FileSpec> {
+        let spec = self.resolve_owned(object)?;
+        let dict = match &spec {
+            Object::String(_) => {
+                return Ok(FileSpec {
+                    file: Some(spec.as_text()?),
+                    ..Default::default()
+                })
+            }
+            Object::Dictionary(dict) => dict,
+            other => {
+                return Err(Error::Type(format!(
+                    "Expected file specification got {:?}",
+                    other
+                )))
+            }
+        };
+
+        let text = |key: &str| match dict.get(key.as_bytes()) {
+            Some(value) => self.resolve_owned(value)?.as_text().map(Some),
+            None => Ok(None),
+        };
+        let embedded = match dict.get(names::EF.as_bytes()) {
+            Some(ef) => {
+                let ef = self.resolve_owned(ef)?;
+                match ef.get(names::UF).or_else(|| ef.get(names::F)) {
+                    Some(&Object::Indirect(stream)) => Some(stream),
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+        Ok(FileSpec {
+            file_system: match dict.get(names::FS.as_bytes()) {
+                Some(Object::Name(fs)) => Some(String::from_utf8_lossy(fs).into_owned()),
+                _ => None,
+            },
+            file: text(names::F)?,
+            unicode_file: text(names::UF)?,
+            dos: text(names::DOS)?,
+            mac: text(names::MAC)?,
+            unix: text(names::UNIX)?,
+            description: text(names::DESC)?,
+            volatile: match dict.get(names::V.as_bytes()) {
+                Some(volatile) => self.resolve_owned(volatile)?.as_bool()?,
+                None => false,
+            },
+            embedded,
+        })
+    }
+
+    /// Reads a remote go-to action, or returns `None` if the action is of
+    /// another type.
+    pub fn remote_go_to(&self, action: &Object) -> Result<Option<RemoteGoTo>> {
+        let action = self.resolve_owned(action)?;
+        if !action.get_name(names::S)?.matches(names::GO_TO_R) {
+            return Ok(None);
+        }
+        Ok(Some(RemoteGoTo {
+            file: self.file_spec(action.get_required(names::F)?)?,
+            destination: self
+                .resolve_owned(action.get_required(names::D)?)?
+                .to_static(),
+            new_window: match action.get("NewWindow") {
+                Some(new_window) => Some(self.resolve_owned(new_window)?.as_bool()?),
+                None => None,
+            },
+        }))
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    fn string(text: &str) -> Object<'_> {
+        Object::String(Cow::Borrowed(text.as_bytes()))
+    }
+
+    #[test]
+    fn should_read_file_specifications() {
+        let mut builder = DocumentBuilder::new();
+        let embedded = builder.add(Object::Stream(
+            Box::new(dict(&[(names::TYPE, name(names::EMBEDDED_FILE))])),
+            Cow::Borrowed(b"data"),
+        ));
+        let root = builder.add(dict(&[(names::TYPE, name(names::CATALOG))]));
+        builder.set_root(root);
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+
+        let spec = file.file_spec(&string("/C/docs/report.pdf")).unwrap();
+        assert_eq!(spec.name(), Some("/C/docs/report.pdf"));
+        assert_eq!(spec.file_name(), Some("report.pdf"));
+
+        let spec = file
+            .file_spec(&dict(&[
+                (names::TYPE, name(names::FILESPEC)),
+                (names::DOS, string("DOCS\\REPORT.PDF")),
+                (names::MAC, string("Docs:Report")),
+                (names::EF, dict(&[(names::F, Object::Indirect(embedded))])),
+            ]))
+            .unwrap();
+        assert_eq!(spec.mac.as_deref(), Some("Docs:Report"));
+        assert_eq!(spec.file_name(), Some("Report"));
+        assert_eq!(spec.embedded, Some(embedded));
+
+        // The Unicode name is decoded from UTF-16
+        let spec = file
+            .file_spec(&dict(&[
+                (names::F, string("r.pdf")),
+                (
+                    names::UF,
+                    Object::String(Cow::Borrowed(b"\xfe\xff\x00r\x00\xe9\x00.\x00p\x00d\x00f")),
+                ),
+                (names::FS, name("URL")),
+            ]))
+            .unwrap();
+        assert_eq!(spec.name(), Some("ré.pdf"));
+        assert!(spec.is_url());
+
+        let action = dict(&[
+            (names::S, name(names::GO_TO_R)),
+            (names::F, string("other.pdf")),
+            (
+                names::D,
+                Object::Array(vec![Object::Integer(2), name("Fit")]),
+            ),
+        ]);
+        let remote = file.remote_go_to(&action).unwrap().unwrap();
+        assert_eq!(remote.file.name(), Some("other.pdf"));
+        assert_eq!(
+            remote.destination,
+            Object::Array(vec![Object::Integer(2), name("Fit")])
+        );
+        assert_eq!(remote.new_window, None);
+    }
+}
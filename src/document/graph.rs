@@ -0,0 +1,214 @@
+//! The graph of indirect references between objects, for visualising the
+//! structure of a document.
+
+use crate::error::Result;
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Which objects refer to which. Nodes and edges are sorted by object number.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReferenceGraph {
+    /// Every object in the xref table, with its `/Type` if it has one.
+    pub nodes: Vec<(IndirectRef, Option<String>)>,
+    /// The objects that the trailer refers to, such as the catalog.
+    pub roots: Vec<IndirectRef>,
+    /// References from one object to another. An object that refers to the
+    /// same object more than once has a single edge.
+    pub edges: Vec<(IndirectRef, IndirectRef)>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Builds the graph of references between the objects in the file. The
+    /// xref table must already be loaded.
+    pub fn reference_graph(&self) -> Result<ReferenceGraph> {
+        let mut graph = ReferenceGraph::default();
+        for (reference, entry) in self.xref_entries()? {
+            if !entry.is_in_use() {
+                continue;
+            }
+
+            let object = self.object(reference)?;
+            let type_name = match object.get(names::TYPE) {
+                Some(Object::Name(name)) => Some(String::from_utf8_lossy(name).into_owned()),
+                _ => None,
+            };
+            graph.nodes.push((reference, type_name));
+            graph
+                .edges
+                .extend(sorted(object.references()).map(|to| (reference, to)));
+        }
+        graph.roots = sorted(self.trailer()?.references()).collect();
+
+        Ok(graph)
+    }
+}
+
+impl ReferenceGraph {
+    /// Objects that can't be reached from the trailer, which a writer would
+    /// usually drop.
+    pub fn orphans(&self) -> Vec<IndirectRef> {
+        let edges = self.edges_by_source();
+        let mut reachable = HashSet::new();
+        let mut pending = self.roots.clone();
+        while let Some(reference) = pending.pop() {
+            if reachable.insert(reference) {
+                pending.extend(edges.get(&reference).into_iter().flatten());
+            }
+        }
+
+        self.nodes
+            .iter()
+            .map(|&(reference, _)| reference)
+            .filter(|reference| !reachable.contains(reference))
+            .collect()
+    }
+
+    /// References to objects that are not in the file.
+    pub fn dangling(&self) -> Vec<(IndirectRef, IndirectRef)> {
+        let nodes = self.node_set();
+        self.edges
+            .iter()
+            .copied()
+            .filter(|(_, to)| !nodes.contains(to))
+            .collect()
+    }
+
+    /// Writes the graph in the DOT language, for GraphViz. Nodes are labelled
+    /// with their `/Type`, and references to missing objects are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph pdf {\n  trailer [shape=box];\n");
+        for (reference, type_name) in &self.nodes {
+            let id = node_id(*reference);
+            match type_name {
+                Some(type_name) => {
+                    writeln!(
+                        out,
+                        "  {} [label=\"{}\\n/{}\"];",
+                        id,
+                        label(*reference),
+                        type_name
+                    )
+                }
+                None => writeln!(out, "  {} [label=\"{}\"];", id, label(*reference)),
+            }
+            .unwrap();
+        }
+        for (_, to) in self.dangling() {
+            writeln!(
+                out,
+                "  {} [label=\"{}\", style=dashed];",
+                node_id(to),
+                label(to)
+            )
+            .unwrap();
+        }
+        for &root in &self.roots {
+            writeln!(out, "  trailer -> {};", node_id(root)).unwrap();
+        }
+        for &(from, to) in &self.edges {
+            writeln!(out, "  {} -> {};", node_id(from), node_id(to)).unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Writes one edge per line, as `1 0 R -> 2 0 R`, with `trailer` as the
+    /// source of the roots.
+    pub fn to_edge_list(&self) -> String {
+        let mut out = String::new();
+        for &root in &self.roots {
+            writeln!(out, "trailer -> {}", label(root)).unwrap();
+        }
+        for &(from, to) in &self.edges {
+            writeln!(out, "{} -> {}", label(from), label(to)).unwrap();
+        }
+        out
+    }
+
+    fn node_set(&self) -> HashSet<IndirectRef> {
+        self.nodes.iter().map(|&(reference, _)| reference).collect()
+    }
+
+    fn edges_by_source(&self) -> HashMap<IndirectRef, Vec<IndirectRef>> {
+        let mut edges = HashMap::<_, Vec<_>>::new();
+        for &(from, to) in &self.edges {
+            edges.entry(from).or_default().push(to);
+        }
+        edges
+    }
+}
+
+fn sorted(mut references: Vec<IndirectRef>) -> impl Iterator<Item = IndirectRef> {
+    references.sort();
+    references.dedup();
+    references.into_iter()
+}
+
+fn node_id(reference: IndirectRef) -> String {
+    format!("obj{}_{}", reference.number, reference.generation)
+}
+
+fn label(reference: IndirectRef) -> String {
+    format!("{} {} R", reference.number, reference.generation)
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::Name;
+    use crate::test_support;
+    use crate::writing::document::DocumentBuilder;
+
+    fn reference(number: u32) -> IndirectRef {
+        IndirectRef {
+            number,
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn should_build_reference_graph() {
+        let mut file = PdfFile::from_raw(test_support::hello_world(Default::default()));
+        file.load_xref_table().unwrap();
+        let graph = file.reference_graph().unwrap();
+
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.nodes[0], (reference(1), Some("Catalog".into())));
+        assert_eq!(graph.roots, vec![reference(1)]);
+        assert!(graph.edges.contains(&(reference(1), reference(2))));
+        assert_eq!(graph.orphans(), vec![]);
+        assert_eq!(graph.dangling(), vec![]);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph pdf {\n"));
+        assert!(dot.contains("  obj1_0 [label=\"1 0 R\\n/Catalog\"];\n"));
+        assert!(dot.contains("  trailer -> obj1_0;\n"));
+        assert!(dot.contains("  obj1_0 -> obj2_0;\n"));
+        assert!(graph
+            .to_edge_list()
+            .starts_with("trailer -> 1 0 R\n1 0 R -> 2 0 R\n"));
+    }
+
+    #[test]
+    fn should_find_orphaned_and_dangling_references() {
+        let mut builder = DocumentBuilder::new();
+        let missing = builder.reserve();
+        let root = builder.add(test_support::dict(&[
+            ("Type", Object::Name(Name::from(names::CATALOG))),
+            ("Missing", Object::Indirect(missing)),
+        ]));
+        builder.set_root(root);
+        let orphan = builder.add(Object::Integer(1));
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let graph = file.reference_graph().unwrap();
+        assert_eq!(graph.orphans(), vec![orphan]);
+        assert_eq!(graph.dangling(), vec![(root, missing)]);
+        assert!(graph
+            .to_dot()
+            .contains("obj1_0 [label=\"1 0 R\", style=dashed];"));
+    }
+}
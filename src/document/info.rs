@@ -0,0 +1,67 @@
+//! Reads the document information dictionary (Adobe, 2008, p. 550).
+
+use crate::error::Result;
+use crate::objects::{names, Dictionary, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::BTreeMap;
+
+impl<'a> PdfFile<'a> {
+    /// Returns the text entries of the document information dictionary, such
+    /// as `Title` and `Author`, decoded from whichever encoding the producer
+    /// used. Dates are returned as written. Files without a dictionary have
+    /// no entries.
+    pub fn info(&self) -> Result<BTreeMap<String, String>> {
+        let trailer = self.trailer()?;
+        let Some(info) = trailer.get(names::INFO) else {
+            return Ok(BTreeMap::new());
+        };
+        let Object::Dictionary(info) = self.resolve_owned(info)? else {
+            return Ok(BTreeMap::new());
+        };
+        text_entries(self, &info)
+    }
+}
+
+/// Decodes the entries of a dictionary that are text strings, such as those
+/// of an information dictionary, skipping any others.
+pub(crate) fn text_entries(file: &PdfFile, dict: &Dictionary) -> Result<BTreeMap<String, String>> {
+    let mut entries = BTreeMap::new();
+    for (key, value) in dict {
+        let value = file.resolve_owned(value)?;
+        if let Object::String(_) = value {
+            let key = String::from_utf8_lossy(key).into_owned();
+            entries.insert(key, value.as_text()?);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use crate::objects::{Name, Object};
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support;
+    use crate::writing::document::DocumentBuilder;
+
+    #[test]
+    fn should_decode_info_strings() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let info = builder.add(test_support::dict(&[
+            (
+                "Title",
+                Object::String(b"\xfe\xff\x00H\x00\xe9".as_slice().into()),
+            ),
+            ("Author", Object::String(b"Ann \x84 Bob".as_slice().into())),
+            ("Trapped", Object::Name(Name::borrowed(b"False"))),
+        ]));
+        builder.set_info(info);
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let info = file.info().unwrap();
+        assert_eq!(info["Title"], "Hé");
+        assert_eq!(info["Author"], "Ann — Bob");
+        assert!(!info.contains_key("Trapped"));
+    }
+}
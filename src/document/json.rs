@@ -0,0 +1,164 @@
+//! Exports the objects of a document as a tree of JSON, for inspecting the
+//! structure of a document with tools such as `jq`.
+//!
+//! Objects become JSON as follows:
+//!
+//! - Names become strings starting with a slash, such as `"/Page"`.
+//! - Strings become `{"string": "..."}` if they are text strings that decode
+//!   and encode back to the same bytes, and `{"hex": "..."}` otherwise.
+//! - Dictionaries become objects, with their keys written without a slash.
+//! - Streams become `{"stream": {...}, "length": n, "filters": [...]}`, with
+//!   the stream dictionary, the length of the decoded data and the names of
+//!   the filters. The data itself is left out.
+//! - References are replaced by the object that they refer to, except for
+//!   references back to an object that contains them, such as `/Parent`, and
+//!   those beyond the depth limit, which become `{"ref": "12 0 R"}`.
+//! - Objects that cannot be read become `{"ref": "12 0 R", "error": "..."}`.
+
+use crate::error::Result;
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use crate::utils::text::{decode_text_string, encode_text_string};
+use serde_json::{json, Map, Value};
+use std::fmt::Write;
+
+impl<'a> PdfFile<'a> {
+    /// Writes the objects that can be reached from the catalog as a JSON
+    /// tree. Objects more than `depth_limit` references away from the
+    /// catalog are left as references, so a depth limit of 0 gives just the
+    /// catalog itself.
+    pub fn to_json_tree(&self, depth_limit: usize) -> Result<Value> {
+        let trailer = self.trailer()?;
+        let mut tree = JsonTree {
+            file: self,
+            depth_limit,
+            path: Vec::new(),
+        };
+        Ok(tree.reference(trailer.get_required(names::ROOT)?, 0))
+    }
+}
+
+struct JsonTree<'f, 'a> {
+    file: &'f PdfFile<'a>,
+    depth_limit: usize,
+    /// The references that are being expanded, from the catalog down.
+    path: Vec<IndirectRef>,
+}
+
+impl<'f, 'a> JsonTree<'f, 'a> {
+    /// Expands an object that is reached through a reference, or the catalog
+    /// itself.
+    fn reference(&mut self, object: &Object, depth: usize) -> Value {
+        let &Object::Indirect(reference) = object else {
+            return self.object(object, depth);
+        };
+        if depth > self.depth_limit || self.path.contains(&reference) {
+            return json!({ "ref": reference.to_string() });
+        }
+
+        match self.file.object(reference) {
+            Ok(resolved) => {
+                self.path.push(reference);
+                let value = self.object(&resolved, depth);
+                self.path.pop();
+                value
+            }
+            Err(err) => json!({
+                "ref": reference.to_string(),
+                "error": format!("{:?}", err),
+            }),
+        }
+    }
+
+    fn object(&mut self, object: &Object, depth: usize) -> Value {
+        match object {
+            Object::Null => Value::Null,
+            Object::Boolean(boolean) => Value::Bool(*boolean),
+            Object::Integer(integer) => Value::from(*integer),
+            // JSON has no NaN or infinities, which become null
+            Object::Real(real) => Value::from(*real),
+            Object::Name(name) => Value::String(format!("/{}", String::from_utf8_lossy(name))),
+            Object::String(string) => string_to_json(string),
+            Object::Array(array) => array.iter().map(|item| self.object(item, depth)).collect(),
+            Object::Dictionary(dict) => Value::Object(
+                dict.iter()
+                    .map(|(key, value)| {
+                        let key = String::from_utf8_lossy(key).into_owned();
+                        (key, self.object(value, depth))
+                    })
+                    .collect::<Map<_, _>>(),
+            ),
+            Object::Stream(dict, data) => {
+                let filters = (&dict[names::FILTER.as_bytes()])
+                    .into_iter()
+                    .map(|filter| self.object(filter, depth))
+                    .collect::<Vec<_>>();
+                json!({
+                    "stream": self.object(dict, depth),
+                    "length": data.len(),
+                    "filters": filters,
+                })
+            }
+            Object::Indirect(_) => self.reference(object, depth + 1),
+        }
+    }
+}
+
+fn string_to_json(bytes: &[u8]) -> Value {
+    let text = decode_text_string(bytes);
+    if encode_text_string(&text) == bytes {
+        return json!({ "string": text });
+    }
+
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(hex, "{:02x}", byte).unwrap();
+    }
+    json!({ "hex": hex })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "write")]
+    use crate::test_support::hello_world;
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_export_the_object_tree() {
+        let raw = hello_world(Default::default());
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+
+        let tree = file.to_json_tree(10).unwrap();
+        assert_eq!(tree["Type"], "/Catalog");
+        let page = &tree["Pages"]["Kids"][0];
+        assert_eq!(page["Type"], "/Page");
+        assert_eq!(page["MediaBox"], json!([0, 0, 612, 792]));
+        // The page's parent contains the page, so is not expanded again
+        assert_eq!(page["Parent"], json!({ "ref": "2 0 R" }));
+        assert_eq!(page["Resources"]["Font"]["F1"]["BaseFont"], "/Helvetica");
+
+        let contents = &page["Contents"];
+        assert_eq!(
+            contents["length"],
+            crate::test_support::HELLO_WORLD_CONTENT.len()
+        );
+        assert!(contents["filters"].is_array());
+        assert!(contents["stream"].is_object());
+
+        let shallow = file.to_json_tree(1).unwrap();
+        assert_eq!(shallow["Pages"]["Type"], "/Pages");
+        assert_eq!(shallow["Pages"]["Kids"][0], json!({ "ref": "5 0 R" }));
+    }
+
+    #[test]
+    fn should_write_strings_as_text_or_hex() {
+        assert_eq!(string_to_json(b"Hello"), json!({ "string": "Hello" }));
+        assert_eq!(
+            string_to_json(b"\xfe\xff\x04\x1f"),
+            json!({ "string": "\u{41f}" })
+        );
+        assert_eq!(string_to_json(b"\x00\xff"), json!({ "hex": "00ff" }));
+    }
+}
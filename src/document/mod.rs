@@ -0,0 +1,25 @@
+pub mod app_data;
+pub mod articles;
+pub mod dicts;
+#[cfg(feature = "write")]
+pub mod digest;
+pub mod encryption;
+pub mod file_spec;
+pub mod graph;
+pub mod info;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod name_tree;
+pub mod output_intents;
+pub mod outline;
+pub mod page;
+pub mod portfolio;
+pub mod report;
+pub mod revisions;
+pub mod security;
+pub mod signatures;
+pub mod statistics;
+pub mod transitions;
+pub mod units;
+pub mod web_capture;
+pub mod xfa;
@@ -0,0 +1,116 @@
+//! Reads name trees, which map strings to objects (Adobe, 2008, p. 88).
+
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::HashSet;
+
+impl<'a> PdfFile<'a> {
+    /// Returns every entry in a name tree, in the order they appear. The
+    /// values are resolved if they are indirect references.
+    pub fn name_tree<'b>(&'b self, root: &Object<'b>) -> Result<Vec<(Vec<u8>, Object<'b>)>> {
+        let mut entries = Vec::new();
+        let mut visited = HashSet::new();
+        self.collect_name_tree(root, &mut visited, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn collect_name_tree<'b>(
+        &'b self,
+        node: &Object<'b>,
+        visited: &mut HashSet<IndirectRef>,
+        entries: &mut Vec<(Vec<u8>, Object<'b>)>,
+    ) -> Result<()> {
+        if let Ok(reference) = node.as_indirect() {
+            if !visited.insert(reference) {
+                return Err(Error::Syntax(
+                    "Name tree contains a cycle",
+                    format!("{:?}", reference),
+                ));
+            }
+        }
+        let node = self.resolve_owned(node)?;
+
+        if let Some(kids) = node.get(names::KIDS) {
+            for kid in &self.resolve_array(kids)? {
+                self.collect_name_tree(kid, visited, entries)?;
+            }
+        }
+        if let Some(leaves) = node.get(names::NAMES) {
+            // Alternating keys and values
+            for pair in self.resolve_array(leaves)?.chunks(2) {
+                let [key, value] = pair else {
+                    return Err(Error::Syntax(
+                        "Name tree has a key without a value",
+                        format!("{:?}", pair),
+                    ));
+                };
+                let key = self.resolve_owned(key)?.as_string()?.into_owned();
+                entries.push((key, self.resolve_owned(value)?));
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_array<'b>(&'b self, object: &Object<'b>) -> Result<Vec<Object<'b>>> {
+        match self.resolve_owned(object)? {
+            Object::Array(array) => Ok(array),
+            other => Err(Error::Type(format!("Expected array got {:?}", other))),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, dict};
+    use crate::writing::document::DocumentBuilder;
+
+    fn string(text: &str) -> Object<'_> {
+        Object::String(text.as_bytes().into())
+    }
+
+    #[test]
+    fn should_read_name_trees() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let two = builder.add(Object::Integer(2));
+        let first = builder.add(dict(&[(
+            names::NAMES,
+            Object::Array(vec![
+                string("a"),
+                Object::Integer(1),
+                string("b"),
+                Object::Indirect(two),
+            ]),
+        )]));
+        let second = builder.add(dict(&[(
+            names::NAMES,
+            Object::Array(vec![string("c"), Object::Integer(3)]),
+        )]));
+        let root = builder.add(dict(&[(
+            names::KIDS,
+            Object::Array(vec![Object::Indirect(first), Object::Indirect(second)]),
+        )]));
+        let cycle = builder.reserve();
+        builder.insert(
+            cycle,
+            dict(&[(names::KIDS, Object::Array(vec![Object::Indirect(cycle)]))]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        assert_eq!(
+            file.name_tree(&Object::Indirect(root)).unwrap(),
+            vec![
+                (b"a".to_vec(), Object::Integer(1)),
+                (b"b".to_vec(), Object::Integer(2)),
+                (b"c".to_vec(), Object::Integer(3)),
+            ]
+        );
+        assert!(matches!(
+            file.name_tree(&Object::Indirect(cycle)),
+            Err(Error::Syntax(_, _))
+        ));
+    }
+}
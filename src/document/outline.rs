@@ -0,0 +1,229 @@
+//! Reads the document outline, also known as bookmarks (Adobe, 2008, p. 367).
+
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineItem {
+    pub title: String,
+    /// The index of the page that the item goes to, or `None` if it has no
+    /// destination in this document, such as a link to a website.
+    pub page: Option<usize>,
+    pub children: Vec<OutlineItem>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Reads the outline, returning the top-level items. The outline is empty
+    /// if the document does not have one.
+    pub fn outline(&self) -> Result<Vec<OutlineItem>> {
+        let catalog = self.catalog()?;
+        let Some(outlines) = catalog.get(names::OUTLINES) else {
+            return Ok(Vec::new());
+        };
+        let outlines = self.resolve_owned(outlines)?;
+
+        let pages = self
+            .pages()?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, page)| Some((page.reference()?, i)))
+            .collect();
+        let mut named = HashMap::new();
+        let names_dict = match catalog.get(names::NAMES) {
+            Some(names_dict) => self.resolve_owned(names_dict)?,
+            None => Object::Null,
+        };
+        if let Some(tree) = names_dict.get(names::DESTS) {
+            named.extend(self.name_tree(tree)?);
+        }
+        let destinations = Destinations {
+            file: self,
+            pages,
+            dests: match catalog.get(names::DESTS) {
+                Some(dests) => self.resolve_owned(dests)?,
+                None => Object::Null,
+            },
+            named,
+        };
+
+        let mut visited = HashSet::new();
+        outline_items(&destinations, outlines.get(names::FIRST), &mut visited)
+    }
+}
+
+/// Reads a list of siblings, starting from the first and following `/Next`.
+fn outline_items(
+    destinations: &Destinations,
+    first: Option<&Object>,
+    visited: &mut HashSet<IndirectRef>,
+) -> Result<Vec<OutlineItem>> {
+    let file = destinations.file;
+    let mut items = Vec::new();
+    let mut next = first.cloned();
+    while let Some(node) = next {
+        if let Ok(reference) = node.as_indirect() {
+            if !visited.insert(reference) {
+                return Err(Error::Syntax(
+                    "Outline contains a cycle",
+                    format!("{:?}", reference),
+                ));
+            }
+        }
+        let node = file.resolve_owned(&node)?;
+
+        let title = match node.get(names::TITLE) {
+            Some(title) => file.resolve_owned(title)?.as_text()?,
+            None => String::new(),
+        };
+        items.push(OutlineItem {
+            title,
+            page: destinations.item_page(&node),
+            children: outline_items(destinations, node.get(names::FIRST), visited)?,
+        });
+        next = node.get(names::NEXT).cloned();
+    }
+    Ok(items)
+}
+
+/// Looks up the pages that destinations go to (Adobe, 2008, p. 365).
+struct Destinations<'b> {
+    file: &'b PdfFile<'b>,
+    pages: HashMap<IndirectRef, usize>,
+    /// Named destinations from the `/Dests` dictionary of the catalog.
+    dests: Object<'b>,
+    /// Named destinations from the `/Dests` name tree.
+    named: HashMap<Vec<u8>, Object<'b>>,
+}
+
+impl<'b> Destinations<'b> {
+    /// The page of an outline item, from either its destination or a go-to
+    /// action. Destinations that cannot be read are ignored.
+    fn item_page(&self, item: &Object<'b>) -> Option<usize> {
+        let dest = match item.get(names::DEST) {
+            Some(dest) => self.file.resolve_owned(dest).ok()?,
+            None => {
+                let action = self.file.resolve_owned(item.get(names::A)?).ok()?;
+                if !action.get_name(names::S).ok()?.matches(names::GO_TO) {
+                    return None;
+                }
+                self.file.resolve_owned(action.get(names::D)?).ok()?
+            }
+        };
+        self.page(&dest)
+    }
+
+    fn page(&self, dest: &Object<'b>) -> Option<usize> {
+        let dest = match dest {
+            Object::Name(name) => self.file.resolve_owned(self.dests.get(name)?).ok()?,
+            Object::String(string) => self.named.get(string.as_ref())?.clone(),
+            _ => dest.clone(),
+        };
+        // Named destinations may be wrapped in a dictionary
+        let dest = match dest.get(names::D) {
+            Some(inner) => self.file.resolve_owned(inner).ok()?,
+            None => dest,
+        };
+
+        match dest.as_array().ok()?.first()? {
+            Object::Indirect(reference) => self.pages.get(reference).copied(),
+            // Some writers use page numbers, as in remote destinations
+            &Object::Integer(i) => usize::try_from(i).ok().filter(|&i| i < self.pages.len()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+
+    fn string(text: &str) -> Object<'_> {
+        Object::String(text.as_bytes().into())
+    }
+
+    #[test]
+    fn should_read_outline_items() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &[b"", b""]);
+        // After the catalog, page tree and font, each page follows its content
+        let second_page = IndirectRef {
+            number: catalog.number + 6,
+            ..catalog
+        };
+        let dest = Object::Array(vec![Object::Indirect(second_page), name("Fit")]);
+
+        let outlines = builder.reserve();
+        let parent = builder.reserve();
+        let named = builder.add(dict(&[
+            (names::TITLE, string("By name")),
+            (names::DEST, name("Second")),
+            (names::NEXT, Object::Indirect(parent)),
+        ]));
+        let child = builder.add(dict(&[
+            (names::TITLE, string("By string")),
+            (names::DEST, string("second")),
+        ]));
+        builder.insert(
+            parent,
+            dict(&[
+                (names::TITLE, string("Parent")),
+                (names::FIRST, Object::Indirect(child)),
+            ]),
+        );
+        builder.insert(outlines, dict(&[(names::FIRST, Object::Indirect(named))]));
+        let pages = IndirectRef {
+            number: catalog.number + 1,
+            ..catalog
+        };
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(pages)),
+                (names::OUTLINES, Object::Indirect(outlines)),
+                (names::DESTS, dict(&[("Second", dest.clone())])),
+                (
+                    names::NAMES,
+                    dict(&[(
+                        names::DESTS,
+                        dict(&[(
+                            names::NAMES,
+                            Object::Array(vec![string("second"), dict(&[(names::D, dest)])]),
+                        )]),
+                    )]),
+                ),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        assert_eq!(
+            file.outline().unwrap(),
+            vec![
+                OutlineItem {
+                    title: "By name".into(),
+                    page: Some(1),
+                    children: vec![],
+                },
+                OutlineItem {
+                    title: "Parent".into(),
+                    page: None,
+                    children: vec![OutlineItem {
+                        title: "By string".into(),
+                        page: Some(1),
+                        children: vec![],
+                    }],
+                },
+            ]
+        );
+
+        let raw = test_support::hello_world(Default::default());
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+        assert_eq!(file.outline().unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,258 @@
+//! Reads output intents, which describe the colour characteristics of the
+//! device that a document is meant to be reproduced on (Adobe, 2008,
+//! p. 633-634), along with the ICC profiles that they embed.
+
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+
+/// The size of the fixed header at the start of an ICC profile.
+const ICC_HEADER_SIZE: usize = 128;
+
+/// An entry in the catalog's `/OutputIntents` array.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputIntent {
+    /// The standard that the intent is for, such as `GTS_PDFX` or
+    /// `GTS_PDFA1`.
+    pub subtype: String,
+    /// A human-readable description of the output condition.
+    pub output_condition: Option<String>,
+    /// The name of the output condition in a registry, such as
+    /// `FOGRA39`, or a custom name if there is an embedded profile.
+    pub output_condition_identifier: String,
+    /// The registry that the identifier is from, usually
+    /// `http://www.color.org`.
+    pub registry_name: Option<String>,
+    pub info: Option<String>,
+    pub profile: Option<IccProfile>,
+}
+
+/// An ICC profile from an output intent's `/DestOutputProfile` stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IccProfile {
+    pub reference: Option<IndirectRef>,
+    /// The number of colour components, from the stream's `/N` entry.
+    pub components: Option<i64>,
+    /// The decoded profile.
+    pub data: Vec<u8>,
+}
+
+/// The fields of an ICC profile header that are useful for checking it
+/// against the document, as defined by ICC.1:2010.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IccHeader {
+    /// The size of the profile, in bytes, as recorded in the header.
+    pub size: u32,
+    /// The major and minor version, such as `(2, 1)` or `(4, 3)`.
+    pub version: (u8, u8),
+    /// The device class, such as `prtr` for output devices or `mntr` for
+    /// displays.
+    pub device_class: String,
+    /// The colour space of the data, such as `RGB`, `CMYK` or `GRAY`.
+    pub color_space: String,
+    /// The profile connection space, `XYZ` or `Lab`.
+    pub connection_space: String,
+}
+
+impl<'a> PdfFile<'a> {
+    /// The output intents in the document catalog.
+    pub fn output_intents(&self) -> Result<Vec<OutputIntent>> {
+        let catalog = self.catalog()?;
+        let Some(intents) = catalog.get(names::OUTPUT_INTENTS) else {
+            return Ok(Vec::new());
+        };
+
+        let mut result = Vec::new();
+        for intent in &self.resolve_owned(intents)? {
+            let intent = self.resolve_owned(intent)?;
+            let text = |key: &str| match intent.get(key) {
+                Some(value) => self.resolve_owned(value)?.as_text().map(Some),
+                None => Ok(None),
+            };
+            let profile = match intent.get(names::DEST_OUTPUT_PROFILE) {
+                Some(stream) => {
+                    let reference = match stream {
+                        &Object::Indirect(reference) => Some(reference),
+                        _ => None,
+                    };
+                    let stream = self.resolve_owned(stream)?;
+                    let (dict, data) = stream.as_stream()?;
+                    let components = match dict.get(names::N.as_bytes()) {
+                        Some(n) => Some(self.resolve_owned(n)?.as_int()?),
+                        None => None,
+                    };
+                    Some(IccProfile {
+                        reference,
+                        components,
+                        data: data.into_owned(),
+                    })
+                }
+                None => None,
+            };
+            let subtype = self.resolve_owned(intent.get_required(names::S)?)?;
+            result.push(OutputIntent {
+                subtype: String::from_utf8_lossy(subtype.as_name()?).into_owned(),
+                output_condition: text(names::OUTPUT_CONDITION)?,
+                output_condition_identifier: text(names::OUTPUT_CONDITION_IDENTIFIER)?
+                    .unwrap_or_default(),
+                registry_name: text(names::REGISTRY_NAME)?,
+                info: text(names::INFO)?,
+                profile,
+            });
+        }
+        Ok(result)
+    }
+}
+
+impl IccProfile {
+    /// Reads the profile header, failing if the data is too short or lacks
+    /// the `acsp` signature.
+    pub fn header(&self) -> Result<IccHeader> {
+        let data = &self.data;
+        if data.len() < ICC_HEADER_SIZE || &data[36..40] != b"acsp" {
+            return Err(Error::Syntax(
+                "ICC profile has no header",
+                format!("{} bytes", data.len()),
+            ));
+        }
+        let signature = |start: usize| {
+            String::from_utf8_lossy(&data[start..start + 4])
+                .trim_end()
+                .to_owned()
+        };
+        Ok(IccHeader {
+            size: read_u32(data, 0).unwrap_or_default(),
+            version: (data[8], data[9] >> 4),
+            device_class: signature(12),
+            color_space: signature(16),
+            connection_space: signature(20),
+        })
+    }
+
+    /// The profile's description, from its `desc` tag, which is what tools
+    /// usually show as the profile's name.
+    pub fn description(&self) -> Option<String> {
+        let data = &self.data;
+        let count = read_u32(data, ICC_HEADER_SIZE)? as usize;
+        let tag = (0..count)
+            .map(|i| ICC_HEADER_SIZE + 4 + i * 12)
+            .find(|&entry| data.get(entry..entry + 4) == Some(b"desc"))?;
+        let offset = read_u32(data, tag + 4)? as usize;
+        let size = read_u32(data, tag + 8)? as usize;
+        let tag = data.get(offset..offset.checked_add(size)?)?;
+
+        match tag.get(..4)? {
+            // textDescriptionType, from version 2, starts with ASCII
+            b"desc" => {
+                let length = read_u32(tag, 8)? as usize;
+                let text = tag.get(12..12 + length)?;
+                let text = text.split(|&b| b == 0).next()?;
+                Some(String::from_utf8_lossy(text).into_owned())
+            }
+            // multiLocalizedUnicodeType, from version 4, where the first
+            // record is used
+            b"mluc" => {
+                if read_u32(tag, 8)? == 0 {
+                    return None;
+                }
+                let length = read_u32(tag, 20)? as usize;
+                let start = read_u32(tag, 24)? as usize;
+                let text = tag.get(start..start.checked_add(length)?)?;
+                let units = text
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>();
+                Some(String::from_utf16_lossy(&units))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::Dictionary;
+    use crate::test_support::{build_pages, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    /// A version 2 profile with only a `desc` tag.
+    fn profile(description: &str) -> Vec<u8> {
+        let mut tag = b"desc\0\0\0\0".to_vec();
+        tag.extend_from_slice(&(description.len() as u32 + 1).to_be_bytes());
+        tag.extend_from_slice(description.as_bytes());
+        tag.push(0);
+
+        let offset = ICC_HEADER_SIZE + 4 + 12;
+        let mut data = vec![0; ICC_HEADER_SIZE];
+        data[0..4].copy_from_slice(&((offset + tag.len()) as u32).to_be_bytes());
+        data[8] = 2;
+        data[9] = 0x10;
+        data[12..16].copy_from_slice(b"prtr");
+        data[16..20].copy_from_slice(b"CMYK");
+        data[20..24].copy_from_slice(b"Lab ");
+        data[36..40].copy_from_slice(b"acsp");
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(b"desc");
+        data.extend_from_slice(&(offset as u32).to_be_bytes());
+        data.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+        data.extend_from_slice(&tag);
+        data
+    }
+
+    #[test]
+    fn should_read_output_intents() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = build_pages(&mut builder, &[b""]);
+        let stream = builder.add(Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::from_iter([(
+                names::N.into(),
+                Object::Integer(4),
+            )]))),
+            Cow::Owned(profile("Coated FOGRA39")),
+        ));
+        let text = |text: &'static str| Object::String(Cow::Borrowed(text.as_bytes()));
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(IndirectRef::new(2, 0))),
+                (
+                    names::OUTPUT_INTENTS,
+                    Object::Array(vec![dict(&[
+                        (names::TYPE, name("OutputIntent")),
+                        (names::S, name("GTS_PDFX")),
+                        (names::OUTPUT_CONDITION_IDENTIFIER, text("FOGRA39")),
+                        (names::REGISTRY_NAME, text("http://www.color.org")),
+                        (names::DEST_OUTPUT_PROFILE, Object::Indirect(stream)),
+                    ])]),
+                ),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let intents = file.output_intents().unwrap();
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].subtype, "GTS_PDFX");
+        assert_eq!(intents[0].output_condition_identifier, "FOGRA39");
+        assert_eq!(intents[0].output_condition, None);
+
+        let profile = intents[0].profile.as_ref().unwrap();
+        assert_eq!(profile.reference, Some(stream));
+        assert_eq!(profile.components, Some(4));
+        let header = profile.header().unwrap();
+        assert_eq!(header.size as usize, profile.data.len());
+        assert_eq!(header.version, (2, 1));
+        assert_eq!(header.device_class, "prtr");
+        assert_eq!(header.color_space, "CMYK");
+        assert_eq!(header.connection_space, "Lab");
+        assert_eq!(profile.description().as_deref(), Some("Coated FOGRA39"));
+    }
+}
@@ -0,0 +1,569 @@
+use crate::content::cache::Resolved;
+use crate::document::dicts::Catalog;
+use crate::error::{Error, Result};
+use crate::geometry::{number, Rect};
+use crate::objects::{names, Dictionary, IndirectRef, Name, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::HashSet;
+
+/// Page attributes that may be inherited from an ancestor in the page tree
+/// (Adobe, 2008, p. 79).
+pub const INHERITABLE_KEYS: [&str; 4] = [
+    names::RESOURCES,
+    names::MEDIA_BOX,
+    names::CROP_BOX,
+    names::ROTATE,
+];
+
+/// A leaf of the page tree (Adobe, 2008, p. 77-79).
+#[derive(Clone)]
+pub struct Page<'a> {
+    file: &'a PdfFile<'a>,
+    reference: Option<IndirectRef>,
+    dict: Object<'a>,
+    /// The nearest value of each inheritable attribute in the ancestors of
+    /// this page.
+    inherited: Dictionary<'a>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Returns every page in the document, in order.
+    pub fn pages(&self) -> Result<Vec<Page<'_>>> {
+        let trailer = self.trailer()?;
+        let root = self.resolve_owned(trailer.get_required(names::ROOT)?)?;
+        let pages = root.get_required(names::PAGES)?;
+
+        let mut result = Vec::new();
+        let mut visited = HashSet::new();
+        collect_pages(self, pages, Dictionary::new(), &mut visited, &mut result)?;
+        Ok(result)
+    }
+}
+
+fn collect_pages<'a>(
+    file: &'a PdfFile<'a>,
+    node: &Object<'a>,
+    mut inherited: Dictionary<'a>,
+    visited: &mut HashSet<IndirectRef>,
+    result: &mut Vec<Page<'a>>,
+) -> Result<()> {
+    let reference = node.as_indirect().ok();
+    if let Some(reference) = reference {
+        if !visited.insert(reference) {
+            return Err(Error::Syntax(
+                "Page tree contains a cycle",
+                format!("{:?}", reference),
+            ));
+        }
+    }
+    let node = file.resolve_owned(node)?;
+
+    // Nodes without a type are treated as pages, since there is nothing else
+    // that they could be.
+    let is_page = node
+        .get(names::TYPE)
+        .and_then(|t| t.as_name().ok())
+        .is_none_or(|t| t.matches(names::PAGE))
+        && node.get(names::KIDS).is_none();
+    if is_page {
+        result.push(Page {
+            file,
+            reference,
+            dict: node,
+            inherited,
+        });
+        return Ok(());
+    }
+
+    for key in INHERITABLE_KEYS {
+        if let Some(value) = node.get(key) {
+            inherited.insert(Name::from(key), value.clone());
+        }
+    }
+    match file.resolve_owned(node.get_required(names::KIDS)?)? {
+        Object::Array(kids) => {
+            for kid in &kids {
+                collect_pages(file, kid, inherited.clone(), visited, result)?;
+            }
+        }
+        other => return Err(Error::Type(format!("Expected array got {:?}", other))),
+    }
+
+    Ok(())
+}
+
+impl<'a> Page<'a> {
+    pub fn file(&self) -> &'a PdfFile<'a> {
+        self.file
+    }
+
+    /// The page object, or `None` if the page was a direct object.
+    pub fn reference(&self) -> Option<IndirectRef> {
+        self.reference
+    }
+
+    pub fn dict(&self) -> &Object<'a> {
+        &self.dict
+    }
+
+    /// Looks up an attribute of the page, including inheritable attributes
+    /// that are only set on an ancestor.
+    pub fn attribute<K: AsRef<[u8]>>(&self, key: K) -> Option<&Object<'a>> {
+        let key = key.as_ref();
+        self.dict.get(key).or_else(|| self.inherited.get(key))
+    }
+
+    /// Looks up an attribute of the page, or of its nearest ancestor that has
+    /// it, by following `/Parent` links. This works for any key, including
+    /// ones that producers treat as inheritable though the spec does not.
+    pub fn inherited<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<Object<'a>>> {
+        let key = key.as_ref();
+        let mut node = self.dict.clone();
+        let mut visited = HashSet::new();
+        visited.extend(self.reference);
+        loop {
+            if let Some(value) = node.get(key) {
+                return self.file.resolve_owned(value).map(Some);
+            }
+            let parent = match node.get(names::PARENT) {
+                Some(&Object::Indirect(parent)) => parent,
+                Some(other) => {
+                    return Err(Error::Type(format!("Expected indirect got {:?}", other)))
+                }
+                None => return Ok(None),
+            };
+            if !visited.insert(parent) {
+                return Err(Error::Syntax(
+                    "Page tree contains a cycle",
+                    format!("{:?}", parent),
+                ));
+            }
+            node = self.file.object(parent)?;
+        }
+    }
+
+    /// The boundaries of the page (Adobe, 2008, p. 77).
+    pub fn media_box(&self) -> Result<Rect> {
+        let media_box = self
+            .attribute(names::MEDIA_BOX)
+            .ok_or_else(|| Error::MissingKey(names::MEDIA_BOX.into()))?;
+        Rect::from_object(&self.file.resolve_owned(media_box)?)
+    }
+
+    /// The region that the page is shown or printed in, which defaults to the
+    /// media box (Adobe, 2008, p. 77).
+    pub fn crop_box(&self) -> Result<Rect> {
+        match self.attribute(names::CROP_BOX) {
+            Some(crop_box) => Rect::from_object(&self.file.resolve_owned(crop_box)?),
+            None => self.media_box(),
+        }
+    }
+
+    /// The resource dictionary of the page, which is empty if the page has
+    /// no resources.
+    pub fn resources(&self) -> Result<Object<'a>> {
+        match self.attribute(names::RESOURCES) {
+            Some(resources) => self.file.resolve_owned(resources),
+            None => Ok(Object::Dictionary(Dictionary::new())),
+        }
+    }
+
+    /// Returns the resolved entries in one category of the page's resources,
+    /// such as `names::FONT`.
+    pub fn resources_of(&self, category: &str) -> Result<Vec<(Name<'a>, Object<'a>)>> {
+        self.resolve_resources(category, |value| self.file.resolve_owned(value))
+    }
+
+    /// Like `resources_of`, but leaves the data of streams as it is in the
+    /// file. Images are often encoded with filters that cannot be decoded,
+    /// such as `DCTDecode`, so this is needed to look at their dictionaries.
+    pub fn encoded_resources_of(&self, category: &str) -> Result<Vec<(Name<'a>, Object<'a>)>> {
+        self.resolve_resources(category, |value| self.file.resolve_encoded(value))
+    }
+
+    fn resolve_resources(
+        &self,
+        category: &str,
+        resolve: impl Fn(&Object<'a>) -> Result<Object<'a>>,
+    ) -> Result<Vec<(Name<'a>, Object<'a>)>> {
+        let resources = self.resources()?;
+        let Some(category) = resources.get(category) else {
+            return Ok(Vec::new());
+        };
+
+        match self.file.resolve_owned(category)? {
+            Object::Dictionary(dict) => {
+                let mut entries = dict
+                    .into_iter()
+                    .map(|(name, value)| Ok((name, resolve(&value)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Ok(entries)
+            }
+            other => Err(Error::Type(format!("Expected dict got {:?}", other))),
+        }
+    }
+
+    /// The natural language of the page's text, which is that of the whole
+    /// document (Adobe, 2008, p. 74). Structure elements may give other
+    /// languages for parts of the page, which are listed by
+    /// `structure_langs`.
+    pub fn lang(&self) -> Result<Option<String>> {
+        Catalog::from_file(self.file)?.lang()
+    }
+
+    /// The languages of the structure elements on this page, in the order
+    /// that they are first found in the structure tree. Elements inherit the
+    /// language and page of their ancestors (Adobe, 2008, p. 556, 577).
+    pub fn structure_langs(&self) -> Result<Vec<String>> {
+        let Some(page) = self.reference else {
+            return Ok(Vec::new());
+        };
+        let catalog = Catalog::from_file(self.file)?;
+        let Some(root) = catalog.dict().get(names::STRUCT_TREE_ROOT.as_bytes()) else {
+            return Ok(Vec::new());
+        };
+        let Some(kids) = self.file.resolve_owned(root)?.get(names::K).cloned() else {
+            return Ok(Vec::new());
+        };
+
+        let mut langs = Vec::new();
+        let mut walk = StructureWalk {
+            file: self.file,
+            page,
+            visited: HashSet::new(),
+            langs: &mut langs,
+        };
+        walk.visit(&kids, None, None)?;
+        Ok(langs)
+    }
+
+    /// The order to move between annotations with the tab key: `R` for rows,
+    /// `C` for columns or `S` for the structure order (Adobe, 2008, p. 80).
+    pub fn tabs(&self) -> Result<Option<String>> {
+        match self.own_entry(names::TABS)? {
+            Some(Object::Name(name)) => Ok(Some(String::from_utf8_lossy(&name).into_owned())),
+            Some(other) => Err(Error::Type(format!("Expected name got {:?}", other))),
+            None => Ok(None),
+        }
+    }
+
+    /// The size of a unit of user space in multiples of 1/72 inch, which
+    /// defaults to 1 (Adobe, 2008, p. 80).
+    pub fn user_unit(&self) -> Result<f64> {
+        match self.own_entry(names::USER_UNIT)? {
+            Some(user_unit) => number(&user_unit),
+            None => Ok(1.0),
+        }
+    }
+
+    /// How many seconds the page is shown for during a presentation before
+    /// moving to the next page (Adobe, 2008, p. 79).
+    pub fn duration(&self) -> Result<Option<f64>> {
+        self.own_entry(names::DUR)?
+            .map(|duration| number(&duration))
+            .transpose()
+    }
+
+    /// Resolves an entry that is not inheritable, treating null as absent.
+    pub(crate) fn own_entry(&self, key: &str) -> Result<Option<Object<'a>>> {
+        match self.dict.get(key) {
+            Some(value) => match self.file.resolve_owned(value)? {
+                Object::Null => Ok(None),
+                value => Ok(Some(value)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The decoded content streams of the page, joined together with a
+    /// newline between each stream. Operators may be split across streams,
+    /// but tokens may not, so the streams must be joined before they are
+    /// parsed (Adobe, 2008, p. 79).
+    pub fn content_bytes(&self) -> Result<Vec<u8>> {
+        let Some(contents) = self.dict.get(names::CONTENTS) else {
+            return Ok(Vec::new());
+        };
+
+        // The streams are decoded through the cache, as a page is often
+        // read by several analyses
+        match self.file.resolve_cached(contents)? {
+            Resolved::Other(Object::Array(streams)) => {
+                let mut data = Vec::new();
+                for (i, stream) in streams.iter().enumerate() {
+                    match self.file.resolve_cached(stream)? {
+                        Resolved::Stream(stream) => {
+                            if i > 0 {
+                                data.push(b'\n');
+                            }
+                            data.extend_from_slice(&stream);
+                        }
+                        Resolved::Other(Object::Null) => {}
+                        Resolved::Other(other) => {
+                            return Err(Error::Type(format!("Expected stream got {:?}", other)))
+                        }
+                    }
+                }
+                Ok(data)
+            }
+            Resolved::Stream(stream) => Ok(stream.to_vec()),
+            Resolved::Other(Object::Null) => Ok(Vec::new()),
+            Resolved::Other(other) => Err(Error::Type(format!("Expected stream got {:?}", other))),
+        }
+    }
+}
+
+/// Finds the languages of the structure elements on one page.
+struct StructureWalk<'f, 'a, 'l> {
+    file: &'f PdfFile<'a>,
+    page: IndirectRef,
+    visited: HashSet<IndirectRef>,
+    langs: &'l mut Vec<String>,
+}
+
+impl StructureWalk<'_, '_, '_> {
+    fn visit(
+        &mut self,
+        node: &Object,
+        page: Option<IndirectRef>,
+        lang: Option<&str>,
+    ) -> Result<()> {
+        if let &Object::Indirect(reference) = node {
+            if !self.visited.insert(reference) {
+                return Err(Error::Syntax(
+                    "Structure tree contains a cycle",
+                    format!("{:?}", reference),
+                ));
+            }
+        }
+
+        match self.file.resolve_owned(node)? {
+            Object::Array(kids) => {
+                for kid in &kids {
+                    self.visit(kid, page, lang)?;
+                }
+            }
+            // Structure elements, rather than marked-content or object
+            // references, have a structure type
+            element @ Object::Dictionary(_) if element.get(names::S).is_some() => {
+                let page = match element.get(names::PG) {
+                    Some(&Object::Indirect(reference)) => Some(reference),
+                    _ => page,
+                };
+                let own_lang = match element.get(names::LANG) {
+                    Some(Object::String(_)) => Some(element.get_text(names::LANG)?),
+                    _ => None,
+                };
+                let lang = own_lang.as_deref().or(lang);
+
+                if let Some(lang) = lang {
+                    if page == Some(self.page) && !self.langs.iter().any(|l| l == lang) {
+                        self.langs.push(lang.to_owned());
+                    }
+                }
+                if let Some(kids) = element.get(names::K) {
+                    self.visit(kids, page, lang)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+
+    #[test]
+    fn should_list_pages() {
+        let mut file = PdfFile::from_raw(test_support::multi_page(3));
+        file.load_xref_table().unwrap();
+
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(
+            pages[0].reference(),
+            Some(IndirectRef {
+                number: 5,
+                generation: 0
+            })
+        );
+        assert_eq!(pages[0].resources_of(names::FONT).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_join_content_streams() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[b""]);
+        let stream = |data: &'static [u8]| {
+            Object::Stream(Box::new(Object::Dictionary(Dictionary::new())), data.into())
+        };
+        let first = builder.add(stream(b"BT /F1 24 Tf"));
+        let second = builder.add(stream(b"72 720 Td (Hi) Tj ET"));
+        let contents = IndirectRef {
+            number: 4,
+            generation: 0,
+        };
+        builder.insert(
+            contents,
+            Object::Array(vec![Object::Indirect(first), Object::Indirect(second)]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        assert_eq!(
+            pages[0].content_bytes().unwrap(),
+            b"BT /F1 24 Tf\n72 720 Td (Hi) Tj ET"
+        );
+        assert_eq!(pages[0].extract_text().unwrap(), "Hi");
+    }
+
+    #[test]
+    fn should_read_page_metadata() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &[b"", b""]);
+        let (pages, first, second) = (
+            IndirectRef::new(2, 0),
+            IndirectRef::new(5, 0),
+            IndirectRef::new(7, 0),
+        );
+        builder.insert(
+            first,
+            dict(&[
+                (names::TYPE, name(names::PAGE)),
+                (names::PARENT, Object::Indirect(pages)),
+                (names::TABS, name("S")),
+                (names::USER_UNIT, Object::Integer(2)),
+                (names::DUR, Object::Real(2.5)),
+            ]),
+        );
+
+        let span = |lang: &'static [u8], page| {
+            dict(&[
+                (names::S, name("Span")),
+                (names::PG, Object::Indirect(page)),
+                (names::LANG, Object::String(lang.into())),
+            ])
+        };
+        let document = dict(&[
+            (names::S, name("Document")),
+            (names::PG, Object::Indirect(first)),
+            (names::LANG, Object::String(b"en".as_slice().into())),
+            (
+                names::K,
+                Object::Array(vec![
+                    dict(&[(names::S, name("P")), (names::K, Object::Integer(0))]),
+                    span(b"fr", first),
+                    span(b"de", second),
+                ]),
+            ),
+        ]);
+        let root = builder.add(dict(&[(names::K, document)]));
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(pages)),
+                (names::LANG, Object::String(b"en-GB".as_slice().into())),
+                (names::STRUCT_TREE_ROOT, Object::Indirect(root)),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        assert_eq!(pages[0].lang(), Ok(Some("en-GB".into())));
+        assert_eq!(
+            pages[0].structure_langs(),
+            Ok(vec!["en".into(), "fr".into()])
+        );
+        assert_eq!(pages[1].structure_langs(), Ok(vec!["de".into()]));
+
+        assert_eq!(pages[0].tabs(), Ok(Some("S".into())));
+        assert_eq!(pages[0].user_unit(), Ok(2.0));
+        assert_eq!(pages[0].duration(), Ok(Some(2.5)));
+        assert_eq!(pages[1].tabs(), Ok(None));
+        assert_eq!(pages[1].user_unit(), Ok(1.0));
+        assert_eq!(pages[1].duration(), Ok(None));
+    }
+
+    #[test]
+    fn should_inherit_any_attribute() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &[b""]);
+        let pages = IndirectRef::new(2, 0);
+        let root = builder.add(dict(&[
+            (names::TYPE, name(names::PAGES)),
+            ("Custom", Object::Integer(1)),
+            (names::KIDS, Object::Array(vec![Object::Indirect(pages)])),
+            (names::COUNT, Object::Integer(1)),
+        ]));
+        let page = IndirectRef::new(5, 0);
+        builder.insert(
+            pages,
+            dict(&[
+                (names::TYPE, name(names::PAGES)),
+                (names::PARENT, Object::Indirect(root)),
+                (names::KIDS, Object::Array(vec![Object::Indirect(page)])),
+                (names::COUNT, Object::Integer(1)),
+            ]),
+        );
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(root)),
+            ]),
+        );
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+
+        let pages = file.pages().unwrap();
+        assert_eq!(pages[0].inherited("Custom"), Ok(Some(Object::Integer(1))));
+        assert_eq!(pages[0].inherited(names::TYPE), Ok(Some(name(names::PAGE))));
+        assert_eq!(pages[0].inherited("Missing"), Ok(None));
+    }
+
+    #[test]
+    fn should_stop_at_parent_cycles() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[b""]);
+        let pages = IndirectRef::new(2, 0);
+        builder.insert(
+            pages,
+            dict(&[
+                (names::TYPE, name(names::PAGES)),
+                (names::PARENT, Object::Indirect(pages)),
+                (
+                    names::KIDS,
+                    Object::Array(vec![Object::Indirect(IndirectRef::new(5, 0))]),
+                ),
+                (names::COUNT, Object::Integer(1)),
+            ]),
+        );
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+
+        let pages = file.pages().unwrap();
+        assert!(pages[0].inherited(names::MEDIA_BOX).unwrap().is_some());
+        assert!(matches!(
+            pages[0].inherited("Missing"),
+            Err(Error::Syntax("Page tree contains a cycle", _))
+        ));
+    }
+
+    #[test]
+    fn should_inherit_attributes() {
+        let mut file = PdfFile::from_raw(test_support::object_streams());
+        file.load_xref_table().unwrap();
+
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].attribute(names::MEDIA_BOX).is_some());
+        assert!(pages[0].attribute(names::CROP_BOX).is_none());
+    }
+}
@@ -0,0 +1,305 @@
+//! Reads portfolios, which are documents that present their attached files
+//! as a collection (Adobe, 2008, p. 588).
+
+use crate::error::Result;
+use crate::objects::{names, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::BTreeMap;
+
+/// A portfolio, from the `/Collection` dictionary in the catalog.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Portfolio {
+    /// The fields that are shown for each file, sorted by their order.
+    pub schema: Vec<SchemaField>,
+    /// The name of the file that is shown first, if it is not the document
+    /// itself.
+    pub initial_document: Option<String>,
+    /// How the files are shown, such as `D` for details, `T` for tiles or
+    /// `H` for hidden.
+    pub view: Option<String>,
+    /// The files in the portfolio, in the order of the embedded files name
+    /// tree.
+    pub entries: Vec<PortfolioEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaField {
+    /// The key that the field is stored under.
+    pub key: String,
+    /// The name that is shown for the field.
+    pub name: String,
+    /// Where the value comes from, such as `S`, `D` or `N` for text, dates
+    /// and numbers in the collection item, or `F`, `Desc` or `Size` for
+    /// properties of the file.
+    pub subtype: String,
+    pub order: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortfolioEntry {
+    /// The key of the file in the embedded files name tree.
+    pub name: String,
+    /// The name of the file from its file specification.
+    pub file_name: Option<String>,
+    /// The values of the schema fields, by key, as text. Dates are returned
+    /// as written.
+    pub fields: BTreeMap<String, String>,
+    /// The contents of the file, or `None` if the file is not embedded.
+    pub data: Option<Vec<u8>>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Reads the portfolio, if the document is one.
+    pub fn portfolio(&self) -> Result<Option<Portfolio>> {
+        let catalog = self.catalog()?;
+        let Some(collection) = catalog.get(names::COLLECTION) else {
+            return Ok(None);
+        };
+        let collection = self.resolve_owned(collection)?;
+
+        let mut portfolio = Portfolio {
+            initial_document: text_entry(self, &collection, names::D)?,
+            view: match collection.get(names::VIEW) {
+                Some(Object::Name(view)) => Some(String::from_utf8_lossy(view).into_owned()),
+                _ => None,
+            },
+            ..Default::default()
+        };
+
+        let schema = match collection.get(names::SCHEMA) {
+            Some(schema) => self.resolve_owned(schema)?,
+            None => Object::Null,
+        };
+        if let Object::Dictionary(schema) = schema {
+            for (key, field) in &schema {
+                let field = self.resolve_owned(field)?;
+                if !matches!(field, Object::Dictionary(_)) {
+                    continue;
+                }
+                let key = String::from_utf8_lossy(key).into_owned();
+                portfolio.schema.push(SchemaField {
+                    name: text_entry(self, &field, names::N)?.unwrap_or(key.clone()),
+                    key,
+                    subtype: match field.get(names::SUBTYPE) {
+                        Some(Object::Name(name)) => String::from_utf8_lossy(name).into_owned(),
+                        _ => String::new(),
+                    },
+                    order: field.get_int(names::O).ok(),
+                });
+            }
+            // Fields without an order go last
+            portfolio
+                .schema
+                .sort_by_key(|field| (field.order.is_none(), field.order, field.key.clone()));
+        }
+
+        let names_dict = match catalog.get(names::NAMES) {
+            Some(names_dict) => self.resolve_owned(names_dict)?,
+            None => Object::Null,
+        };
+        if let Some(tree) = names_dict.get(names::EMBEDDED_FILES) {
+            for (name, spec) in self.name_tree(tree)? {
+                let entry = portfolio_entry(self, &portfolio.schema, name, &spec)?;
+                portfolio.entries.push(entry);
+            }
+        }
+
+        Ok(Some(portfolio))
+    }
+}
+
+fn portfolio_entry(
+    file: &PdfFile,
+    schema: &[SchemaField],
+    name: Vec<u8>,
+    spec: &Object,
+) -> Result<PortfolioEntry> {
+    let file_spec = file.file_spec(spec)?;
+    let file_name = file_spec.name().map(String::from);
+
+    let stream = match file_spec.embedded {
+        Some(stream) => Some(file.object(stream)?),
+        None => None,
+    };
+    let params = match stream.as_ref().and_then(|stream| stream.get(names::PARAMS)) {
+        Some(params) => file.resolve_owned(params)?,
+        None => Object::Null,
+    };
+    let item = match spec.get(names::CI) {
+        Some(item) => file.resolve_owned(item)?,
+        None => Object::Null,
+    };
+
+    let mut fields = BTreeMap::new();
+    for field in schema {
+        let value = match field.subtype.as_str() {
+            "F" => file_name.clone(),
+            "Desc" => file_spec.description.clone(),
+            "Size" | "ModDate" | "CreationDate" => value_text(file, params.get(&field.subtype))?,
+            _ => value_text(file, item.get(&field.key))?,
+        };
+        if let Some(value) = value {
+            fields.insert(field.key.clone(), value);
+        }
+    }
+
+    let data = match stream {
+        Some(Object::Stream(_, data)) => Some(data.into_owned()),
+        _ => None,
+    };
+    Ok(PortfolioEntry {
+        name: String::from_utf8_lossy(&name).into_owned(),
+        file_name,
+        fields,
+        data,
+    })
+}
+
+fn text_entry(file: &PdfFile, dict: &Object, key: &str) -> Result<Option<String>> {
+    value_text(file, dict.get(key))
+}
+
+/// Converts a collection item value to text. Values may be text, numbers,
+/// or a subitem dictionary with a prefix (Adobe, 2008, p. 590).
+fn value_text(file: &PdfFile, value: Option<&Object>) -> Result<Option<String>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    Ok(match file.resolve_owned(value)? {
+        string @ Object::String(_) => Some(string.as_text()?),
+        Object::Integer(i) => Some(i.to_string()),
+        Object::Real(r) => Some(r.to_string()),
+        subitem @ Object::Dictionary(_) => {
+            let prefix = text_entry(file, &subitem, names::P)?.unwrap_or_default();
+            text_entry(file, &subitem, names::D)?.map(|value| prefix + &value)
+        }
+        _ => None,
+    })
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::IndirectRef;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    fn string(text: &str) -> Object<'_> {
+        Object::String(text.as_bytes().into())
+    }
+
+    #[test]
+    fn should_read_portfolio_entries() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let data = builder.add(Object::Stream(
+            Box::new(dict(&[
+                (names::TYPE, name(names::EMBEDDED_FILE)),
+                (names::PARAMS, dict(&[("Size", Object::Integer(5))])),
+            ])),
+            Cow::Borrowed(b"hello"),
+        ));
+        let first = builder.add(dict(&[
+            (names::TYPE, name(names::FILESPEC)),
+            (names::F, string("a.txt")),
+            (names::EF, dict(&[(names::F, Object::Indirect(data))])),
+            (
+                names::CI,
+                dict(&[(
+                    "Author",
+                    dict(&[(names::D, string("Ann")), (names::P, string("Dr. "))]),
+                )]),
+            ),
+        ]));
+        let second = dict(&[
+            (names::TYPE, name(names::FILESPEC)),
+            (names::UF, string("b.txt")),
+            (names::DESC, string("Not embedded")),
+        ]);
+
+        let field = |subtype, order| {
+            dict(&[
+                (names::SUBTYPE, name(subtype)),
+                (names::N, string(subtype)),
+                (names::O, Object::Integer(order)),
+            ])
+        };
+        let pages = IndirectRef {
+            number: catalog.number + 1,
+            ..catalog
+        };
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(pages)),
+                (
+                    names::COLLECTION,
+                    dict(&[
+                        (
+                            names::SCHEMA,
+                            dict(&[
+                                ("Author", field("S", 1)),
+                                ("File", field("F", 0)),
+                                ("Size", field("Size", 2)),
+                                ("About", field("Desc", 3)),
+                            ]),
+                        ),
+                        (names::D, string("b.txt")),
+                        (names::VIEW, name("D")),
+                    ]),
+                ),
+                (
+                    names::NAMES,
+                    dict(&[(
+                        names::EMBEDDED_FILES,
+                        dict(&[(
+                            names::NAMES,
+                            Object::Array(vec![
+                                string("a.txt"),
+                                Object::Indirect(first),
+                                string("b.txt"),
+                                second,
+                            ]),
+                        )]),
+                    )]),
+                ),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let portfolio = file.portfolio().unwrap().unwrap();
+        assert_eq!(portfolio.initial_document.as_deref(), Some("b.txt"));
+        assert_eq!(portfolio.view.as_deref(), Some("D"));
+        let keys = portfolio
+            .schema
+            .iter()
+            .map(|field| field.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["File", "Author", "Size", "About"]);
+
+        let [a, b] = portfolio.entries.as_slice() else {
+            panic!("Expected two entries, got {:?}", portfolio.entries);
+        };
+        assert_eq!(a.name, "a.txt");
+        assert_eq!(a.data.as_deref(), Some(b"hello".as_slice()));
+        assert_eq!(
+            a.fields,
+            BTreeMap::from([
+                ("File".to_owned(), "a.txt".to_owned()),
+                ("Author".to_owned(), "Dr. Ann".to_owned()),
+                ("Size".to_owned(), "5".to_owned()),
+            ])
+        );
+        assert_eq!(b.file_name.as_deref(), Some("b.txt"));
+        assert_eq!(b.data, None);
+        assert_eq!(b.fields["About"], "Not embedded");
+
+        let raw = test_support::hello_world(Default::default());
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+        assert_eq!(file.portfolio().unwrap(), None);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::document::page::Page;
+use crate::error::Result;
+use crate::objects::{names, Object};
+use std::collections::BTreeSet;
+
+/// Colour space families (Adobe, 2008, p. 139).
+const COLOR_SPACE_FAMILIES: [&str; 11] = [
+    "DeviceGray",
+    "DeviceRGB",
+    "DeviceCMYK",
+    "CalGray",
+    "CalRGB",
+    "Lab",
+    "ICCBased",
+    "Indexed",
+    "Pattern",
+    "Separation",
+    "DeviceN",
+];
+
+/// A summary of the resources that a page uses, for preflight checks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResourceReport {
+    pub fonts: Vec<FontUsage>,
+    pub image_count: usize,
+    /// The total size of the image data as it is stored in the file, in
+    /// bytes, before any filters are decoded.
+    pub image_bytes: usize,
+    /// The families of the colour spaces used by the page, such as
+    /// `DeviceRGB` or `ICCBased`.
+    pub color_spaces: BTreeSet<String>,
+    /// Whether the page uses a transparency group, soft masks, constant alpha
+    /// or blend modes (Adobe, 2008, p. 311).
+    pub uses_transparency: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FontUsage {
+    /// The name of the font in the page's resource dictionary.
+    pub resource_name: String,
+    pub base_font: Option<String>,
+    pub subtype: Option<String>,
+    /// Whether the font program is embedded in the file. Type 3 fonts are
+    /// always embedded, since their glyphs are content streams.
+    pub embedded: bool,
+}
+
+impl<'a> Page<'a> {
+    /// Summarizes the fonts, images, colour spaces and transparency used by
+    /// the page's own resources.
+    pub fn resource_report(&self) -> Result<ResourceReport> {
+        let mut report = ResourceReport::default();
+
+        for (name, font) in self.resources_of(names::FONT)? {
+            report.fonts.push(FontUsage {
+                resource_name: name_to_string(&name),
+                base_font: name_entry(&font, names::BASE_FONT),
+                subtype: name_entry(&font, names::SUBTYPE),
+                embedded: self.is_font_embedded(&font)?,
+            });
+        }
+
+        // Images are read without decoding them, since few of their filters
+        // can be decoded
+        for (_, x_object) in self.encoded_resources_of(names::X_OBJECT)? {
+            let Object::Stream(dict, data) = &x_object else {
+                continue;
+            };
+            if name_entry(dict, names::SUBTYPE).as_deref() != Some(names::IMAGE) {
+                continue;
+            }
+
+            report.image_count += 1;
+            report.image_bytes += data.len();
+            if let Some(color_space) = x_object.get(names::COLOR_SPACE) {
+                let color_space = self.file().resolve_owned(color_space)?;
+                report.color_spaces.extend(color_space_family(&color_space));
+            }
+            if x_object
+                .get(names::SOFT_MASK)
+                .is_some_and(|mask| *mask != Object::Null)
+            {
+                report.uses_transparency = true;
+            }
+        }
+
+        for (_, color_space) in self.resources_of(names::COLOR_SPACE)? {
+            report.color_spaces.extend(color_space_family(&color_space));
+        }
+
+        if let Some(group) = self.dict().get(names::GROUP) {
+            let group = self.file().resolve_owned(group)?;
+            if name_entry(&group, names::S).as_deref() == Some(names::TRANSPARENCY) {
+                report.uses_transparency = true;
+                if let Some(color_space) = group.get(names::CS) {
+                    let color_space = self.file().resolve_owned(color_space)?;
+                    report.color_spaces.extend(color_space_family(&color_space));
+                }
+            }
+        }
+        for (_, state) in self.resources_of(names::EXT_G_STATE)? {
+            if uses_transparency(&state) {
+                report.uses_transparency = true;
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn is_font_embedded(&self, font: &Object<'a>) -> Result<bool> {
+        let subtype = name_entry(font, names::SUBTYPE);
+        if subtype.as_deref() == Some(names::TYPE_3) {
+            return Ok(true);
+        }
+
+        // The font program of a composite font is in its descendant font
+        let font = if subtype.as_deref() == Some(names::TYPE_0) {
+            match font.get(names::DESCENDANT_FONTS) {
+                Some(descendants) => match self.file().resolve_owned(descendants)? {
+                    Object::Array(descendants) if !descendants.is_empty() => {
+                        self.file().resolve_owned(&descendants[0])?
+                    }
+                    _ => return Ok(false),
+                },
+                None => return Ok(false),
+            }
+        } else {
+            font.clone()
+        };
+
+        let Some(descriptor) = font.get(names::FONT_DESCRIPTOR) else {
+            return Ok(false);
+        };
+        let descriptor = self.file().resolve_owned(descriptor)?;
+        Ok([names::FONT_FILE, names::FONT_FILE_2, names::FONT_FILE_3]
+            .iter()
+            .any(|key| descriptor.get(key).is_some()))
+    }
+}
+
+fn name_to_string(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).into_owned()
+}
+
+fn name_entry(object: &Object, key: &str) -> Option<String> {
+    match object.get(key) {
+        Some(Object::Name(name)) => Some(name_to_string(name)),
+        _ => None,
+    }
+}
+
+/// Returns the family of a colour space, which is either a name or an array
+/// starting with a name. Names of colour space resources are ignored, since
+/// those resources are reported separately.
+fn color_space_family(color_space: &Object) -> Option<String> {
+    let family = match color_space {
+        Object::Name(name) => name,
+        Object::Array(array) => match array.first() {
+            Some(Object::Name(name)) => name,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let family = name_to_string(family);
+    COLOR_SPACE_FAMILIES
+        .contains(&family.as_str())
+        .then_some(family)
+}
+
+fn uses_transparency(state: &Object) -> bool {
+    let is_translucent = |key| match state.get(key) {
+        Some(Object::Integer(alpha)) => *alpha < 1,
+        Some(Object::Real(alpha)) => *alpha < 1.0,
+        _ => false,
+    };
+    let has_soft_mask = match state.get(names::SOFT_MASK) {
+        Some(Object::Name(name)) => !name.matches(names::NONE),
+        Some(Object::Null) | None => false,
+        Some(_) => true,
+    };
+    let has_blend_mode = match state.get(names::BLEND_MODE) {
+        Some(Object::Name(name)) => {
+            !name.matches(names::NORMAL) && !name.matches(names::COMPATIBLE)
+        }
+        Some(Object::Array(modes)) => modes.first().is_some_and(|mode| {
+            mode.as_name()
+                .is_ok_and(|name| !name.matches(names::NORMAL) && !name.matches(names::COMPATIBLE))
+        }),
+        _ => false,
+    };
+
+    is_translucent(names::STROKING_ALPHA)
+        || is_translucent(names::NON_STROKING_ALPHA)
+        || has_soft_mask
+        || has_blend_mode
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "write")]
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support;
+    #[cfg(feature = "write")]
+    use crate::writing::document::DocumentBuilder;
+    #[cfg(feature = "write")]
+    use std::borrow::Cow;
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_report_fonts() {
+        let mut builder = DocumentBuilder::new();
+        let font_file = builder.add(Object::Stream(
+            Box::new(test_support::dict(&[])),
+            Cow::Borrowed(b""),
+        ));
+        let descriptor = builder.add(test_support::dict(&[
+            (names::TYPE, test_support::name(names::FONT_DESCRIPTOR)),
+            (names::FONT_FILE_2, Object::Indirect(font_file)),
+        ]));
+        let font = builder.add(test_support::dict(&[
+            (names::TYPE, test_support::name(names::FONT)),
+            (names::SUBTYPE, test_support::name("TrueType")),
+            (names::BASE_FONT, test_support::name("Arial")),
+            (names::FONT_DESCRIPTOR, Object::Indirect(descriptor)),
+        ]));
+        let resources = test_support::dict(&[
+            (
+                names::FONT,
+                test_support::dict(&[("F1", Object::Indirect(font))]),
+            ),
+            (
+                names::EXT_G_STATE,
+                test_support::dict(&[(
+                    "GS0",
+                    test_support::dict(&[(names::NON_STROKING_ALPHA, Object::Real(0.5))]),
+                )]),
+            ),
+            (
+                names::COLOR_SPACE,
+                test_support::dict(&[("CS0", test_support::name("DeviceRGB"))]),
+            ),
+        ]);
+        let raw = test_support::single_page(builder, resources, test_support::HELLO_WORLD_CONTENT);
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let report = pages[0].resource_report().unwrap();
+        assert_eq!(report.fonts.len(), 1);
+        assert_eq!(report.fonts[0].subtype.as_deref(), Some("TrueType"));
+        assert!(report.fonts[0].embedded);
+        assert_eq!(report.image_count, 0);
+        assert!(report.uses_transparency);
+        assert_eq!(report.color_spaces, BTreeSet::from(["DeviceRGB".into()]));
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_report_unembedded_standard_fonts() {
+        let mut file = PdfFile::from_raw(test_support::multi_page(1));
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let report = pages[0].resource_report().unwrap();
+        assert_eq!(
+            report.fonts,
+            vec![FontUsage {
+                resource_name: "F1".into(),
+                base_font: Some("Helvetica".into()),
+                subtype: Some("Type1".into()),
+                embedded: false,
+            }]
+        );
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_report_images_without_decoding_them() {
+        let mut file = PdfFile::from_raw(test_support::jpeg_page());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let report = pages[0].resource_report().unwrap();
+        assert_eq!(report.image_count, 1);
+        assert_eq!(report.image_bytes, 12);
+        assert_eq!(report.color_spaces, BTreeSet::from(["DeviceRGB".into()]));
+    }
+
+    #[test]
+    fn should_detect_transparency() {
+        let state = test_support::dict(&[(names::NON_STROKING_ALPHA, Object::Real(0.5))]);
+        assert!(uses_transparency(&state));
+        let state = test_support::dict(&[(names::BLEND_MODE, test_support::name("Normal"))]);
+        assert!(!uses_transparency(&state));
+        let state = test_support::dict(&[(names::SOFT_MASK, test_support::name("None"))]);
+        assert!(!uses_transparency(&state));
+    }
+}
@@ -0,0 +1,143 @@
+//! Reads earlier versions of files that have had incremental updates, which
+//! keep the original bytes of the file (Adobe, 2008, p. 48).
+
+use crate::error::{Error, Result};
+use crate::objects::{names, Object};
+use crate::parsing::pdf_file::{find_startxref, PdfFile};
+use crate::utils::slices::position_of_sequence;
+use std::collections::HashSet;
+
+const EOF_KEYWORD: &[u8] = b"%%EOF";
+
+/// One version of the file, from its start up to the end of an xref section
+/// and its trailer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Revision {
+    /// The length of the file as it was at this revision, including the
+    /// `%%EOF` marker.
+    pub length: usize,
+    pub xref_offset: usize,
+    pub trailer: Object<'static>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Lists the revisions of the file, from the original to the latest, by
+    /// following the `/Prev` entries of the trailers.
+    pub fn revisions(&self) -> Result<Vec<Revision>> {
+        let (_, mut xref_offset) = find_startxref(self.raw())
+            .ok_or(Error::Syntax("Could not find startxref keyword", "".into()))?;
+
+        let mut revisions = Vec::new();
+        let mut visited = HashSet::new();
+        loop {
+            if !visited.insert(xref_offset) {
+                return Err(Error::Syntax(
+                    "Xref sections form a cycle",
+                    xref_offset.to_string(),
+                ));
+            }
+            let length = revision_length(self.raw(), xref_offset)?;
+            let view = PdfFile::from_bytes(&self.raw()[..length]).with_options(self.options());
+            let trailer = view.trailer()?.into_owned();
+            let prev = trailer.get_int(names::PREV).ok();
+            revisions.push(Revision {
+                length,
+                xref_offset,
+                trailer,
+            });
+
+            match prev.map(usize::try_from) {
+                Some(Ok(prev)) => xref_offset = prev,
+                Some(Err(_)) => return Err(Error::Syntax("Invalid /Prev offset", "".into())),
+                None => break,
+            }
+        }
+
+        revisions.reverse();
+        Ok(revisions)
+    }
+
+    /// The document as it was at a revision, where 0 is the original. The
+    /// returned file has its xref table loaded.
+    pub fn at_revision(&self, index: usize) -> Result<PdfFile<'_>> {
+        let revisions = self.revisions()?;
+        let revision = revisions.get(index).ok_or(Error::Type(format!(
+            "Revision {} is past the last of {} revisions",
+            index,
+            revisions.len()
+        )))?;
+
+        let mut file =
+            PdfFile::from_bytes(&self.raw()[..revision.length]).with_options(self.options());
+        file.load_xref_table()?;
+        Ok(file)
+    }
+}
+
+/// Finds the end of the revision whose xref section is at the given offset,
+/// which is just after the next `%%EOF` marker and its end-of-line.
+fn revision_length(raw: &[u8], xref_offset: usize) -> Result<usize> {
+    let rest = raw.get(xref_offset..).ok_or(Error::Syntax(
+        "Xref offset is past the end of the file",
+        xref_offset.to_string(),
+    ))?;
+    let eof = position_of_sequence(rest, EOF_KEYWORD).ok_or(Error::Syntax(
+        "Could not find eof marker",
+        xref_offset.to_string(),
+    ))?;
+
+    let mut end = xref_offset + eof + EOF_KEYWORD.len();
+    if raw[end..].starts_with(b"\r") {
+        end += 1;
+    }
+    if raw[end..].starts_with(b"\n") {
+        end += 1;
+    }
+    Ok(end)
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::{IndirectRef, Name};
+    use crate::test_support;
+    use crate::writing::incremental::IncrementalUpdate;
+
+    #[test]
+    fn should_read_earlier_revisions() {
+        let original = test_support::hello_world(Default::default());
+        let mut file = PdfFile::from_bytes(&original);
+        file.load_xref_table().unwrap();
+
+        let catalog = IndirectRef {
+            number: 1,
+            generation: 0,
+        };
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        let mut root = update.object(catalog).unwrap();
+        if let Object::Dictionary(dict) = &mut root {
+            dict.insert(Name::from("Lang"), Object::String(b"en".as_slice().into()));
+        }
+        update.replace(catalog, root);
+        let updated = update.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_bytes(&updated);
+        file.load_xref_table().unwrap();
+        let revisions = file.revisions().unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].length, original.len());
+        assert_eq!(revisions[1].length, updated.len());
+        assert!(revisions[0].trailer.get(names::PREV).is_none());
+        assert_eq!(
+            revisions[1].trailer.get_int(names::PREV),
+            Ok(revisions[0].xref_offset as i64)
+        );
+
+        let before = file.at_revision(0).unwrap();
+        assert!(before.object(catalog).unwrap().get("Lang").is_none());
+        assert_eq!(before.pages().unwrap().len(), 1);
+        let after = file.at_revision(1).unwrap();
+        assert!(after.object(catalog).unwrap().get("Lang").is_some());
+        assert!(file.at_revision(2).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Finds constructs that are often abused by malicious documents, for
+//! triaging untrusted files before they are opened in a viewer.
+
+use crate::error::Result;
+use crate::objects::{names, Dictionary, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+
+/// File extensions that are treated as executable when they are attached to a
+/// document.
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "scr", "bat", "cmd", "pif", "msi", "dll", "ps1", "vbs", "vbe", "js", "jse",
+    "wsf", "hta", "jar", "sh", "app",
+];
+
+/// The kind of risky construct that was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Risk {
+    /// A JavaScript action, or a script in the `/JavaScript` name tree.
+    JavaScript,
+    /// A launch action, which runs an application or opens a file.
+    Launch,
+    /// An embedded file whose name looks like an executable.
+    EmbeddedExecutable,
+    /// An action that runs when the document is opened.
+    OpenAction,
+    /// Actions that run on events, such as a page being opened.
+    AdditionalActions,
+    /// A URI action, which opens a link.
+    Uri,
+    /// An XFA form, which viewers may render in place of the page content.
+    Xfa,
+}
+
+/// A risky construct, and where it was found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub risk: Risk,
+    /// The indirect object that contains the construct.
+    pub object: IndirectRef,
+    /// The script, file name or URI involved, where there is one.
+    pub detail: Option<String>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Looks through every object in the file for JavaScript, launch actions,
+    /// executable attachments, actions that run automatically, URI actions
+    /// and XFA forms. The xref table must already be loaded.
+    ///
+    /// Every object is checked, not just those reachable from the catalog,
+    /// since malicious files often hide objects that a viewer may still find.
+    /// Objects that can't be parsed are skipped. The findings are sorted by
+    /// object number.
+    pub fn security_scan(&self) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+        for (reference, entry) in self.xref_entries()? {
+            if !entry.is_in_use() {
+                continue;
+            }
+            let Ok(object) = self.object(reference) else {
+                continue;
+            };
+
+            let mut scan = Scan {
+                file: self,
+                object: reference,
+                findings: &mut findings,
+            };
+            scan.visit(&object);
+        }
+
+        findings.sort_by_key(|finding| {
+            (
+                finding.object.number,
+                finding.object.generation,
+                finding.risk,
+                finding.detail.clone(),
+            )
+        });
+        findings.dedup();
+        Ok(findings)
+    }
+}
+
+struct Scan<'s, 'a> {
+    file: &'s PdfFile<'a>,
+    object: IndirectRef,
+    findings: &'s mut Vec<Finding>,
+}
+
+impl<'s, 'a> Scan<'s, 'a> {
+    fn visit(&mut self, object: &Object) {
+        match object {
+            Object::Array(array) => array.iter().for_each(|item| self.visit(item)),
+            Object::Dictionary(dict) => self.visit_dict(dict),
+            Object::Stream(dict, _) => self.visit(dict),
+            _ => {}
+        }
+    }
+
+    fn visit_dict(&mut self, dict: &Dictionary) {
+        let get = |key: &str| dict.get(key.as_bytes());
+        let action_type = match get(names::S) {
+            Some(Object::Name(name)) => Some(name),
+            _ => None,
+        };
+
+        if action_type.is_some_and(|s| s.matches(names::JAVA_SCRIPT)) || get(names::JS).is_some() {
+            let script = get(names::JS).and_then(|js| self.text(js));
+            self.push(Risk::JavaScript, script);
+        }
+        if action_type.is_some_and(|s| s.matches(names::LAUNCH)) {
+            let file = get(names::F)
+                .or_else(|| get(names::WIN)?.get(names::F))
+                .and_then(|file| self.file_name(file));
+            self.push(Risk::Launch, file);
+        }
+        if action_type.is_some_and(|s| s.matches(names::URI)) {
+            let uri = get(names::URI).and_then(|uri| self.text(uri));
+            self.push(Risk::Uri, uri);
+        }
+
+        if get(names::EF).is_some() {
+            let file = self.file_name(&Object::Dictionary(dict.clone()));
+            if file.as_deref().is_some_and(is_executable) {
+                self.push(Risk::EmbeddedExecutable, file);
+            }
+        }
+
+        if let Some(action) = get(names::OPEN_ACTION) {
+            // A destination only changes the view, so only actions are risky
+            let action = self.file.resolve_owned(action).ok();
+            if let Some(Object::Dictionary(action)) = action {
+                let action_type = match action.get(names::S.as_bytes()) {
+                    Some(Object::Name(name)) => Some(String::from_utf8_lossy(name).into_owned()),
+                    _ => None,
+                };
+                self.push(Risk::OpenAction, action_type);
+            }
+        }
+        if get(names::AA).is_some() {
+            self.push(Risk::AdditionalActions, None);
+        }
+
+        if let Some(Object::Dictionary(names_dict)) = get(names::NAMES) {
+            if names_dict.contains_key(names::JAVA_SCRIPT.as_bytes()) {
+                self.push(Risk::JavaScript, None);
+            }
+        }
+        if get(names::XFA).is_some() {
+            self.push(Risk::Xfa, None);
+        }
+
+        dict.values().for_each(|value| self.visit(value));
+    }
+
+    fn push(&mut self, risk: Risk, detail: Option<String>) {
+        self.findings.push(Finding {
+            risk,
+            object: self.object,
+            detail,
+        });
+    }
+
+    /// Reads a text string or stream, such as a script.
+    fn text(&self, object: &Object) -> Option<String> {
+        match self.file.resolve_owned(object).ok()? {
+            Object::Stream(_, data) => Some(String::from_utf8_lossy(&data).into_owned()),
+            object => object.as_text().ok(),
+        }
+    }
+
+    /// Reads the name of the file in a file specification, which may be a
+    /// string or a dictionary.
+    fn file_name(&self, object: &Object) -> Option<String> {
+        let spec = self.file.file_spec(object).ok()?;
+        spec.name().map(String::from)
+    }
+}
+
+fn is_executable(file_name: &str) -> bool {
+    let Some((_, extension)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    EXECUTABLE_EXTENSIONS
+        .iter()
+        .any(|executable| extension.eq_ignore_ascii_case(executable))
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    fn string(text: &str) -> Object<'_> {
+        Object::String(text.as_bytes().into())
+    }
+
+    #[test]
+    fn should_find_risky_constructs() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let script = builder.add(Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::new())),
+            Cow::Borrowed(b"app.alert(1)"),
+        ));
+        let open_action = builder.add(dict(&[
+            (names::S, name(names::JAVA_SCRIPT)),
+            (names::JS, Object::Indirect(script)),
+        ]));
+        let launch = builder.add(dict(&[
+            (names::S, name(names::LAUNCH)),
+            (names::WIN, dict(&[(names::F, string("cmd.exe"))])),
+        ]));
+        let link = builder.add(dict(&[
+            (names::TYPE, name(names::ANNOT)),
+            (
+                names::A,
+                dict(&[
+                    (names::S, name(names::URI)),
+                    (names::URI, string("http://a.test/")),
+                ]),
+            ),
+        ]));
+        let embedded = builder.add(Object::Stream(
+            Box::new(dict(&[(names::TYPE, name(names::EMBEDDED_FILE))])),
+            Cow::Borrowed(b"MZ"),
+        ));
+        let payload = builder.add(dict(&[
+            (names::TYPE, name(names::FILESPEC)),
+            (names::F, string("invoice.pdf")),
+            (names::UF, string("invoice.PDF.exe")),
+            (names::EF, dict(&[(names::F, Object::Indirect(embedded))])),
+        ]));
+        let notes = builder.add(dict(&[
+            (names::TYPE, name(names::FILESPEC)),
+            (names::F, string("notes.txt")),
+            (names::EF, dict(&[(names::F, Object::Indirect(embedded))])),
+        ]));
+        // The page tree is reserved straight after the catalog
+        let pages = IndirectRef {
+            number: catalog.number + 1,
+            ..catalog
+        };
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(pages)),
+                (names::OPEN_ACTION, Object::Indirect(open_action)),
+                (
+                    names::ACRO_FORM,
+                    dict(&[(names::XFA, Object::Array(vec![]))]),
+                ),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let findings = file.security_scan().unwrap();
+        let found = |risk, object, detail: Option<&str>| Finding {
+            risk,
+            object,
+            detail: detail.map(String::from),
+        };
+        assert_eq!(
+            findings,
+            vec![
+                found(Risk::OpenAction, catalog, Some("JavaScript")),
+                found(Risk::Xfa, catalog, None),
+                found(Risk::JavaScript, open_action, Some("app.alert(1)")),
+                found(Risk::Launch, launch, Some("cmd.exe")),
+                found(Risk::Uri, link, Some("http://a.test/")),
+                found(Risk::EmbeddedExecutable, payload, Some("invoice.PDF.exe")),
+            ]
+        );
+        assert!(!findings.iter().any(|finding| finding.object == notes));
+    }
+
+    #[test]
+    fn should_find_nothing_in_plain_documents() {
+        let raw = test_support::hello_world(Default::default());
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+        assert_eq!(file.security_scan().unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,291 @@
+//! Finds digital signatures, so that edits which would invalidate them can be
+//! refused (Adobe, 2008, p. 466-474).
+
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::ops::Range;
+
+/// A signature, and the part of the file that it covers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedRevision {
+    /// The object that holds the signature dictionary.
+    pub signature: IndirectRef,
+    /// The ranges of bytes that are signed, from `/ByteRange`. These cover the
+    /// whole revision, except for the signature itself.
+    pub byte_ranges: Vec<Range<usize>>,
+    /// The name of the signer, if it is given.
+    pub signer: Option<String>,
+    /// The format of the signature, such as `adbe.pkcs7.detached`.
+    pub sub_filter: Option<String>,
+}
+
+impl SignedRevision {
+    /// The end of the signed revision. Anything after this was added later,
+    /// by incremental updates.
+    pub fn end(&self) -> usize {
+        self.byte_ranges
+            .iter()
+            .map(|range| range.end)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether changing the given bytes of the file would invalidate this
+    /// signature.
+    pub fn covers(&self, range: &Range<usize>) -> bool {
+        range.start < self.end()
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// Finds every signature in the file, ordered by the end of the revision
+    /// that they sign. The xref table must already be loaded.
+    ///
+    /// Signature dictionaries are found by their `/ByteRange` entry, whether
+    /// they are their own objects or the value of a signature field.
+    /// Signatures whose `/ByteRange` is malformed are left out, and recorded
+    /// as diagnostics.
+    pub fn signed_revisions(&self) -> Result<Vec<SignedRevision>> {
+        let mut signatures = Vec::new();
+        for (reference, entry) in self.xref_entries()? {
+            if !entry.is_in_use() {
+                continue;
+            }
+            let Ok(object) = self.object(reference) else {
+                continue;
+            };
+
+            // A signature field's value is usually its own object, which is
+            // then named as the signature, so that it is only listed once
+            let value = match object.get(names::V) {
+                Some(&Object::Indirect(value)) => self.object(value).ok().map(|dict| (value, dict)),
+                Some(value) => Some((reference, value.clone())),
+                None => None,
+            };
+            let found = [Some((reference, object)), value].into_iter().flatten();
+            for (reference, dict) in found {
+                match signed_revision(reference, &dict, self.raw().len()) {
+                    Ok(Some(signature)) => signatures.push(signature),
+                    Ok(None) => {}
+                    // One broken signature should not hide the others
+                    Err(err) => self.context().note_at(
+                        self.indirect_object_offset(reference).ok(),
+                        err,
+                        "Ignored the signature",
+                    ),
+                }
+            }
+        }
+
+        signatures.sort_by_key(|signature| (signature.end(), signature.signature.number));
+        signatures.dedup();
+        Ok(signatures)
+    }
+
+    /// The bytes that a signature covers, which are what the signature in
+    /// `/Contents` was computed over.
+    pub fn signed_bytes(&self, revision: &SignedRevision) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        for range in &revision.byte_ranges {
+            let part = self.raw().get(range.clone()).ok_or(Error::Syntax(
+                "Signed byte range is past the end of the file",
+                format!("{:?}", range),
+            ))?;
+            bytes.extend_from_slice(part);
+        }
+        Ok(bytes)
+    }
+
+    /// Returns `Error::InvalidatesSignature` if changing the given bytes of
+    /// the file would invalidate a signature. Changes after the last signed
+    /// revision, such as incremental updates, are allowed.
+    pub fn check_signatures(&self, range: Range<usize>) -> Result<()> {
+        match self
+            .signed_revisions()?
+            .iter()
+            .find(|signature| signature.covers(&range))
+        {
+            Some(signature) => Err(Error::InvalidatesSignature(signature.signature, range)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reads a signature dictionary, whose byte ranges must lie within a file of
+/// `file_len` bytes.
+fn signed_revision(
+    reference: IndirectRef,
+    dict: &Object,
+    file_len: usize,
+) -> Result<Option<SignedRevision>> {
+    let Some(Object::Array(byte_range)) = dict.get(names::BYTE_RANGE) else {
+        return Ok(None);
+    };
+    if dict.get(names::CONTENTS).is_none() {
+        return Ok(None);
+    }
+
+    // Pairs of offsets and lengths
+    let mut byte_ranges = Vec::new();
+    for pair in byte_range.chunks(2) {
+        let [offset, length] = pair else {
+            return Err(Error::Syntax(
+                "Byte range has an offset without a length",
+                format!("{:?}", byte_range),
+            ));
+        };
+        let (Ok(offset), Ok(length)) = (
+            usize::try_from(offset.as_int()?),
+            usize::try_from(length.as_int()?),
+        ) else {
+            return Err(Error::Syntax(
+                "Byte range is negative",
+                format!("{:?}", byte_range),
+            ));
+        };
+        let end = offset
+            .checked_add(length)
+            .filter(|&end| end <= file_len)
+            .ok_or_else(|| {
+                Error::Syntax(
+                    "Byte range is past the end of the file",
+                    format!("{:?}", byte_range),
+                )
+            })?;
+        byte_ranges.push(offset..end);
+    }
+
+    let text = |key| match dict.get(key) {
+        Some(Object::String(_)) => dict.get_text(key).ok(),
+        Some(Object::Name(name)) => Some(String::from_utf8_lossy(name).into_owned()),
+        _ => None,
+    };
+    Ok(Some(SignedRevision {
+        signature: reference,
+        byte_ranges,
+        signer: text(names::NAME),
+        sub_filter: text(names::SUB_FILTER),
+    }))
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use crate::writing::incremental::IncrementalUpdate;
+    use crate::writing::rewrite::{rewrite, RewriteOptions};
+    use std::borrow::Cow;
+
+    fn open(raw: Vec<u8>) -> PdfFile<'static> {
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    fn signature(byte_range: [i64; 4]) -> Object<'static> {
+        dict(&[
+            (names::TYPE, name("Sig")),
+            (names::SUB_FILTER, name("adbe.pkcs7.detached")),
+            (names::NAME, Object::String(Cow::Borrowed(b"Ann"))),
+            (
+                names::BYTE_RANGE,
+                Object::Array(byte_range.into_iter().map(Object::Integer).collect()),
+            ),
+            (names::CONTENTS, Object::String(Cow::Borrowed(&[0; 8]))),
+        ])
+    }
+
+    #[test]
+    fn should_find_signed_revisions() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let first = builder.add(signature([0, 10, 20, 30]));
+        let field = builder.add(dict(&[
+            ("FT", name("Sig")),
+            (names::V, signature([0, 5, 15, 85])),
+        ]));
+        let file = open(builder.to_bytes().unwrap());
+
+        let revisions = file.signed_revisions().unwrap();
+        assert_eq!(
+            revisions,
+            [
+                SignedRevision {
+                    signature: first,
+                    byte_ranges: vec![0..10, 20..50],
+                    signer: Some("Ann".into()),
+                    sub_filter: Some("adbe.pkcs7.detached".into()),
+                },
+                SignedRevision {
+                    signature: field,
+                    byte_ranges: vec![0..5, 15..100],
+                    signer: Some("Ann".into()),
+                    sub_filter: Some("adbe.pkcs7.detached".into()),
+                },
+            ]
+        );
+        assert_eq!(revisions[1].end(), 100);
+        let signed = file.signed_bytes(&revisions[0]).unwrap();
+        assert_eq!(signed, [&file.raw()[0..10], &file.raw()[20..50]].concat());
+
+        // Objects in the signed revision cannot be changed in place
+        assert!(matches!(
+            file.patch_object(catalog, b""),
+            Err(Error::InvalidatesSignature(_, _))
+        ));
+        assert!(rewrite(&file, RewriteOptions::default()).is_err());
+        let options = RewriteOptions {
+            invalidate_signatures: true,
+            ..Default::default()
+        };
+        assert!(rewrite(&file, options).is_ok());
+
+        // But incremental updates after it are fine
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        let added = update.add(Object::Integer(1));
+        let updated = open(update.to_bytes().unwrap());
+        assert_eq!(updated.signed_revisions().unwrap(), revisions);
+        assert!(updated.patch_object(added, b"").is_ok());
+    }
+
+    #[test]
+    fn should_allow_changes_to_unsigned_files() {
+        let file = open(test_support::hello_world(Default::default()));
+        assert_eq!(file.signed_revisions().unwrap(), vec![]);
+        assert!(file.check_signatures(0..file.raw().len()).is_ok());
+    }
+
+    #[test]
+    fn should_resolve_signature_values() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let value = builder.add(signature([0, 5, 15, 10]));
+        builder.add(dict(&[
+            ("FT", name("Sig")),
+            (names::V, Object::Indirect(value)),
+        ]));
+        let file = open(builder.to_bytes().unwrap());
+
+        let revisions = file.signed_revisions().unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].signature, value);
+        assert_eq!(revisions[0].byte_ranges, vec![0..5, 15..25]);
+    }
+
+    #[test]
+    fn should_skip_malformed_byte_ranges() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        builder.add(signature([i64::MAX, i64::MAX, 0, 1]));
+        builder.add(signature([0, -1, 0, 1]));
+        let valid = builder.add(signature([0, 10, 20, 30]));
+        let file = open(builder.to_bytes().unwrap());
+
+        let revisions = file.signed_revisions().unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].signature, valid);
+        assert_eq!(file.diagnostics().len(), 2);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::error::Result;
+use crate::objects::{names, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::BTreeMap;
+
+/// A summary of what takes up space in a file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub object_count: usize,
+    /// The objects grouped by their `/Type` and `/Subtype`.
+    pub by_kind: BTreeMap<ObjectKind, Usage>,
+    /// The streams grouped by filter. Streams with more than one filter are
+    /// counted under each of them, and streams without a filter are counted
+    /// under `None`.
+    pub by_filter: BTreeMap<Option<String>, StreamUsage>,
+    /// Every stream in the file.
+    pub streams: StreamUsage,
+}
+
+/// The `/Type` and `/Subtype` of an object, where it has them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectKind {
+    pub type_name: Option<String>,
+    pub subtype: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub count: usize,
+    /// The number of bytes that the objects take up in the file, including
+    /// their headers and stream data.
+    pub bytes: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamUsage {
+    pub count: usize,
+    /// The total size of the stream data as stored in the file.
+    pub encoded_bytes: usize,
+    /// The total size of the stream data once the filters are applied.
+    pub decoded_bytes: usize,
+}
+
+impl StreamUsage {
+    /// The decoded size divided by the encoded size, which is more than one
+    /// for streams that are compressed.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.encoded_bytes == 0 {
+            1.0
+        } else {
+            self.decoded_bytes as f64 / self.encoded_bytes as f64
+        }
+    }
+
+    fn add(&mut self, encoded_bytes: usize, decoded_bytes: usize) {
+        self.count += 1;
+        self.encoded_bytes += encoded_bytes;
+        self.decoded_bytes += decoded_bytes;
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// Counts the objects in the file, and the space that they take up. The
+    /// xref table must already be loaded.
+    ///
+    /// The size of each object is measured up to the start of the next
+    /// object, so it includes any padding between objects.
+    pub fn statistics(&self) -> Result<Statistics> {
+        let mut objects = self
+            .xref_entries()?
+            .into_iter()
+            .filter_map(|(reference, entry)| Some((entry.offset()?, reference)))
+            .collect::<Vec<_>>();
+        objects.sort_by_key(|&(offset, _)| offset);
+
+        let xref_offset = self.last_xref_offset()?;
+        let file_len = self.raw().len();
+
+        let mut statistics = Statistics::default();
+        for (i, &(offset, reference)) in objects.iter().enumerate() {
+            let end = objects
+                .get(i + 1)
+                .map(|&(offset, _)| offset)
+                .into_iter()
+                .chain([xref_offset, file_len])
+                .filter(|&end| end > offset)
+                .min()
+                .unwrap_or(offset);
+
+            let object = self.object(reference)?;
+            let kind = ObjectKind {
+                type_name: name_entry(&object, names::TYPE),
+                subtype: name_entry(&object, names::SUBTYPE),
+            };
+            let usage = statistics.by_kind.entry(kind).or_default();
+            usage.count += 1;
+            usage.bytes += end - offset;
+            statistics.object_count += 1;
+
+            if let Object::Stream(_, data) = &object {
+                let encoded_bytes = match object.get(names::LENGTH) {
+                    Some(length) => self.resolve_owned(length)?.as_int()? as usize,
+                    None => data.len(),
+                };
+                statistics.streams.add(encoded_bytes, data.len());
+                for filter in filters(&object) {
+                    statistics
+                        .by_filter
+                        .entry(filter)
+                        .or_default()
+                        .add(encoded_bytes, data.len());
+                }
+            }
+        }
+
+        Ok(statistics)
+    }
+}
+
+fn name_entry(object: &Object, key: &str) -> Option<String> {
+    match object.get(key) {
+        Some(Object::Name(name)) => Some(String::from_utf8_lossy(name).into_owned()),
+        _ => None,
+    }
+}
+
+fn filters(stream: &Object) -> Vec<Option<String>> {
+    let names = match stream.get(names::FILTER) {
+        Some(Object::Name(name)) => vec![name],
+        Some(Object::Array(array)) => array
+            .iter()
+            .filter_map(|filter| match filter {
+                Object::Name(name) => Some(name),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    if names.is_empty() {
+        vec![None]
+    } else {
+        names
+            .into_iter()
+            .map(|name| Some(String::from_utf8_lossy(name).into_owned()))
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    use crate::writing::objects::WriteOptions;
+
+    fn kind(type_name: Option<&str>, subtype: Option<&str>) -> ObjectKind {
+        ObjectKind {
+            type_name: type_name.map(String::from),
+            subtype: subtype.map(String::from),
+        }
+    }
+
+    #[test]
+    fn should_count_objects_by_kind() {
+        let mut file = PdfFile::from_raw(test_support::multi_page(2));
+        file.load_xref_table().unwrap();
+
+        let statistics = file.statistics().unwrap();
+        assert_eq!(statistics.object_count, 7);
+        assert_eq!(statistics.by_kind[&kind(Some("Page"), None)].count, 2);
+        assert_eq!(
+            statistics.by_kind[&kind(Some("Font"), Some("Type1"))].count,
+            1
+        );
+        assert_eq!(statistics.by_kind[&kind(None, None)].count, 2);
+
+        let bytes = statistics
+            .by_kind
+            .values()
+            .map(|usage| usage.bytes)
+            .sum::<usize>();
+        assert!(bytes < file.raw().len());
+        assert_eq!(statistics.streams.count, 2);
+        assert_eq!(statistics.by_filter[&None].count, 2);
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_measure_compression() {
+        let mut file = PdfFile::from_raw(test_support::hello_world(WriteOptions {
+            compress_streams: true,
+            ..Default::default()
+        }));
+        file.load_xref_table().unwrap();
+
+        let statistics = file.statistics().unwrap();
+        let flate = statistics.by_filter[&Some("FlateDecode".into())];
+        assert_eq!(flate.count, 1);
+        assert_eq!(flate.decoded_bytes, test_support::HELLO_WORLD_CONTENT.len());
+        assert_eq!(
+            flate.compression_ratio(),
+            statistics.streams.compression_ratio()
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Reads the transitions that a viewer shows when moving to a page in a
+//! presentation (Adobe, 2008, p. 380-382).
+
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::geometry::number;
+use crate::objects::{names, Object};
+
+/// The effect used to move to a page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransitionStyle {
+    Split,
+    Blinds,
+    Box,
+    Wipe,
+    Dissolve,
+    Glitter,
+    /// The new page replaces the old one, with no effect. This is the
+    /// default.
+    Replace,
+    Fly,
+    Push,
+    Cover,
+    Uncover,
+    Fade,
+    Other(String),
+}
+
+impl TransitionStyle {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "Split" => Self::Split,
+            "Blinds" => Self::Blinds,
+            "Box" => Self::Box,
+            "Wipe" => Self::Wipe,
+            "Dissolve" => Self::Dissolve,
+            "Glitter" => Self::Glitter,
+            "R" => Self::Replace,
+            "Fly" => Self::Fly,
+            "Push" => Self::Push,
+            "Cover" => Self::Cover,
+            "Uncover" => Self::Uncover,
+            "Fade" => Self::Fade,
+            other => Self::Other(other.into()),
+        }
+    }
+}
+
+/// A page transition dictionary, with the defaults filled in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub style: TransitionStyle,
+    /// How long the transition takes, in seconds.
+    pub duration: f64,
+    /// Whether a split or blinds effect is horizontal, rather than vertical.
+    pub horizontal: bool,
+    /// Whether a split or box effect moves inward from the edges of the page,
+    /// rather than outward from its centre.
+    pub inward: bool,
+    /// The direction that a wipe, glitter, fly, cover, uncover or push moves
+    /// in, in degrees counterclockwise from left to right. This is `None` for
+    /// a fly effect that has no direction.
+    pub direction: Option<i64>,
+    /// The scale that a fly effect starts or ends at.
+    pub scale: f64,
+    /// Whether the area that a fly effect flies in is opaque.
+    pub opaque: bool,
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self {
+            style: TransitionStyle::Replace,
+            duration: 1.0,
+            horizontal: true,
+            inward: true,
+            direction: Some(0),
+            scale: 1.0,
+            opaque: false,
+        }
+    }
+}
+
+impl<'a> Page<'a> {
+    /// The transition to show when moving to this page, from its `/Trans`
+    /// entry.
+    pub fn transition(&self) -> Result<Option<Transition>> {
+        let Some(dict) = self.own_entry(names::TRANS)? else {
+            return Ok(None);
+        };
+        let file = self.file();
+        let entry = |key: &str| -> Result<Option<Object>> {
+            match dict.get(key) {
+                Some(value) => file.resolve_owned(value).map(Some),
+                None => Ok(None),
+            }
+        };
+        let name = |key: &str| -> Result<Option<String>> {
+            match entry(key)? {
+                Some(Object::Name(name)) => Ok(Some(String::from_utf8_lossy(&name).into_owned())),
+                Some(other) => Err(Error::Type(format!("Expected name got {:?}", other))),
+                None => Ok(None),
+            }
+        };
+
+        let mut transition = Transition::default();
+        if let Some(style) = name(names::S)? {
+            transition.style = TransitionStyle::from_name(&style);
+        }
+        if let Some(duration) = entry(names::D)? {
+            transition.duration = number(&duration)?;
+        }
+        if let Some(dimension) = name(names::DM)? {
+            transition.horizontal = dimension != "V";
+        }
+        if let Some(motion) = name(names::M)? {
+            transition.inward = motion != "O";
+        }
+        match entry(names::DI)? {
+            Some(Object::Name(name)) if name.matches(names::NONE) => transition.direction = None,
+            Some(direction) => transition.direction = Some(number(&direction)? as i64),
+            None => {}
+        }
+        if let Some(scale) = entry(names::SS)? {
+            transition.scale = number(&scale)?;
+        }
+        if let Some(opaque) = entry(names::B)? {
+            transition.opaque = opaque.as_bool()?;
+        }
+        Ok(Some(transition))
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::IndirectRef;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support::{build_pages, dict, name};
+    use crate::writing::document::DocumentBuilder;
+
+    #[test]
+    fn should_read_transitions() {
+        let mut builder = DocumentBuilder::new();
+        build_pages(&mut builder, &[b"", b""]);
+        builder.insert(
+            IndirectRef::new(5, 0),
+            dict(&[
+                (names::TYPE, name(names::PAGE)),
+                (names::PARENT, Object::Indirect(IndirectRef::new(2, 0))),
+                (
+                    names::TRANS,
+                    dict(&[
+                        (names::S, name("Fly")),
+                        (names::D, Object::Real(0.5)),
+                        (names::DI, name(names::NONE)),
+                        (names::SS, Object::Real(0.25)),
+                        (names::B, Object::Boolean(true)),
+                    ]),
+                ),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        assert_eq!(
+            pages[0].transition().unwrap(),
+            Some(Transition {
+                style: TransitionStyle::Fly,
+                duration: 0.5,
+                direction: None,
+                scale: 0.25,
+                opaque: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(pages[1].transition().unwrap(), None);
+    }
+}
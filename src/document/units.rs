@@ -0,0 +1,162 @@
+//! Converts between user space and the page as it is shown, which is rotated
+//! by `/Rotate` and scaled by `/UserUnit`, and physical lengths such as
+//! millimetres (Adobe, 2008, p. 78-80).
+
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::geometry::Matrix;
+use crate::objects::names;
+
+/// A unit of physical length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthUnit {
+    /// 1/72 inch, which is the size of a unit of user space when
+    /// `/UserUnit` is 1.
+    Point,
+    Millimetre,
+    Inch,
+}
+
+impl LengthUnit {
+    pub fn per_inch(self) -> f64 {
+        match self {
+            LengthUnit::Point => 72.0,
+            LengthUnit::Millimetre => 25.4,
+            LengthUnit::Inch => 1.0,
+        }
+    }
+}
+
+impl<'a> Page<'a> {
+    /// How many degrees the page is turned clockwise when it is shown, which
+    /// is one of 0, 90, 180 or 270 (Adobe, 2008, p. 79).
+    pub fn rotation(&self) -> Result<i64> {
+        let Some(rotate) = self.attribute(names::ROTATE) else {
+            return Ok(0);
+        };
+        let rotate = self.file().resolve_owned(rotate)?.as_int()?;
+        if rotate % 90 != 0 {
+            return Err(Error::Type(format!(
+                "Page rotation must be a multiple of 90, got {}",
+                rotate
+            )));
+        }
+        Ok(rotate.rem_euclid(360))
+    }
+
+    /// Maps user space to the page as it is shown, in points, with the
+    /// origin at the bottom left corner of the rotated crop box.
+    pub fn display_matrix(&self) -> Result<Matrix> {
+        let crop_box = self.crop_box()?;
+        let rotate = match self.rotation()? {
+            0 => Matrix::translate(-crop_box.x0, -crop_box.y0),
+            90 => Matrix::new(0.0, -1.0, 1.0, 0.0, -crop_box.y0, crop_box.x1),
+            180 => Matrix::new(-1.0, 0.0, 0.0, -1.0, crop_box.x1, crop_box.y1),
+            _ => Matrix::new(0.0, 1.0, -1.0, 0.0, crop_box.y1, -crop_box.x0),
+        };
+        let user_unit = self.user_unit()?;
+        Ok(rotate * Matrix::scale(user_unit, user_unit))
+    }
+
+    /// Converts a point in user space to the page as it is shown, in points.
+    pub fn to_display(&self, x: f64, y: f64) -> Result<(f64, f64)> {
+        Ok(self.display_matrix()?.transform(x, y))
+    }
+
+    /// Converts a point on the page as it is shown, in points, to user space.
+    pub fn from_display(&self, x: f64, y: f64) -> Result<(f64, f64)> {
+        Ok(self.display_inverse()?.transform(x, y))
+    }
+
+    fn display_inverse(&self) -> Result<Matrix> {
+        self.display_matrix()?
+            .inverse()
+            .ok_or_else(|| Error::Type(format!("Page has a zero /UserUnit {:?}", self.reference())))
+    }
+
+    /// Converts points in user space, such as the vertices of an annotation,
+    /// to the page as it is shown.
+    pub fn points_to_display(&self, points: &[(f64, f64)]) -> Result<Vec<(f64, f64)>> {
+        let matrix = self.display_matrix()?;
+        Ok(points
+            .iter()
+            .map(|&(x, y)| matrix.transform(x, y))
+            .collect())
+    }
+
+    /// Converts points on the page as it is shown, such as where a user drew
+    /// with a pen, to user space.
+    pub fn points_from_display(&self, points: &[(f64, f64)]) -> Result<Vec<(f64, f64)>> {
+        let inverse = self.display_inverse()?;
+        Ok(points
+            .iter()
+            .map(|&(x, y)| inverse.transform(x, y))
+            .collect())
+    }
+
+    /// The width and height of the page as it is shown.
+    pub fn display_size(&self, unit: LengthUnit) -> Result<(f64, f64)> {
+        let crop_box = self.crop_box()?;
+        let (width, height) = match self.rotation()? {
+            90 | 270 => (crop_box.height(), crop_box.width()),
+            _ => (crop_box.width(), crop_box.height()),
+        };
+        Ok((self.to_length(width, unit)?, self.to_length(height, unit)?))
+    }
+
+    /// Converts a distance in user space to a physical length.
+    pub fn to_length(&self, distance: f64, unit: LengthUnit) -> Result<f64> {
+        Ok(distance * self.user_unit()? / 72.0 * unit.per_inch())
+    }
+
+    /// Converts a physical length to a distance in user space.
+    pub fn from_length(&self, length: f64, unit: LengthUnit) -> Result<f64> {
+        Ok(length / unit.per_inch() * 72.0 / self.user_unit()?)
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::{IndirectRef, Object};
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+
+    #[test]
+    fn should_convert_between_units() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(&mut builder, &[b"", b""]);
+        builder.insert(
+            IndirectRef::new(5, 0),
+            dict(&[
+                (names::TYPE, name(names::PAGE)),
+                (names::PARENT, Object::Indirect(IndirectRef::new(2, 0))),
+                (
+                    names::MEDIA_BOX,
+                    Object::Array([0, 0, 612, 792].map(Object::Integer).to_vec()),
+                ),
+                (names::ROTATE, Object::Integer(-270)),
+                (names::USER_UNIT, Object::Integer(2)),
+            ]),
+        );
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        let page = &pages[0];
+        assert_eq!(page.rotation(), Ok(90));
+        // The bottom left corner is turned to the top left
+        assert_eq!(page.to_display(0.0, 0.0), Ok((0.0, 1224.0)));
+        assert_eq!(page.to_display(612.0, 792.0), Ok((1584.0, 0.0)));
+        assert_eq!(page.from_display(1584.0, 0.0), Ok((612.0, 792.0)));
+        assert_eq!(page.display_size(LengthUnit::Inch), Ok((22.0, 17.0)));
+        assert_eq!(page.to_length(36.0, LengthUnit::Millimetre), Ok(25.4));
+        assert_eq!(page.from_length(25.4, LengthUnit::Millimetre), Ok(36.0));
+
+        let page = &pages[1];
+        assert_eq!(page.rotation(), Ok(0));
+        assert_eq!(page.to_display(72.0, 72.0), Ok((72.0, 72.0)));
+        assert_eq!(page.display_size(LengthUnit::Point), Ok((612.0, 792.0)));
+    }
+}
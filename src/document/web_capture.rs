@@ -0,0 +1,325 @@
+//! Reads the web capture information that tools which convert web pages to
+//! PDF leave in a document (Adobe, 2008, p. 751-757).
+//!
+//! Captured documents are often written by old crawlers, so this is lenient:
+//! commands and content sets that are malformed are skipped rather than
+//! failing the whole read.
+
+use crate::document::page::Page;
+use crate::error::Result;
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+
+/// The web capture information dictionary, from the catalog's `/SpiderInfo`
+/// entry, along with the content sets in the `/IDS` and `/URLS` name trees.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WebCapture {
+    /// The version of web capture that the document was made with.
+    pub version: Option<f64>,
+    /// The commands that were used to capture the pages, in order.
+    pub commands: Vec<CaptureCommand>,
+    pub content_sets: Vec<ContentSet>,
+}
+
+/// A request for a URL that added pages to the document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaptureCommand {
+    pub reference: Option<IndirectRef>,
+    pub url: String,
+    /// How many levels of links were followed from the URL.
+    pub levels: i64,
+    pub flags: i64,
+    /// The content type of the data posted to the URL, for form submissions.
+    pub content_type: Option<String>,
+}
+
+/// Whether a content set holds pages or images.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContentSetKind {
+    /// A page set, made from one HTML page or other source.
+    Pages,
+    /// An image set, made from one image on the web.
+    Images,
+    Other(String),
+}
+
+/// The objects that were made from one source on the web.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContentSet {
+    pub kind: ContentSetKind,
+    /// The digital identifier of the source, which is an MD5 digest.
+    pub id: Vec<u8>,
+    /// The pages or image XObjects in the set.
+    pub objects: Vec<IndirectRef>,
+    /// The URLs that the source was retrieved from.
+    pub urls: Vec<String>,
+    /// The title of the HTML page, for page sets.
+    pub title: Option<String>,
+    /// The content type of the source, such as `text/html`.
+    pub content_type: Option<String>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Reads the web capture information, or `None` if the document was not
+    /// made by capturing web pages.
+    pub fn web_capture(&self) -> Result<Option<WebCapture>> {
+        let catalog = self.catalog()?;
+        let Some(spider_info) = catalog.get(names::SPIDER_INFO) else {
+            return Ok(None);
+        };
+        let spider_info = self.resolve_owned(spider_info)?;
+        if !matches!(spider_info, Object::Dictionary(_)) {
+            return Ok(None);
+        }
+
+        let mut capture = WebCapture {
+            version: match spider_info.get(names::V) {
+                Some(&Object::Integer(version)) => Some(version as f64),
+                Some(&Object::Real(version)) => Some(version),
+                _ => None,
+            },
+            ..Default::default()
+        };
+        if let Some(commands) = spider_info.get(names::C) {
+            for command in &self.resolve_owned(commands)? {
+                if let Some(command) = self.capture_command(command)? {
+                    capture.commands.push(command);
+                }
+            }
+        }
+
+        if let Some(name_dict) = catalog.get(names::NAMES) {
+            let name_dict = self.resolve_owned(name_dict)?;
+            for key in [names::IDS, names::URLS] {
+                let Some(tree) = name_dict.get(key) else {
+                    continue;
+                };
+                for (_, value) in self.name_tree(tree)? {
+                    // Each source may map to one content set or several
+                    let sets = match value {
+                        Object::Array(sets) => sets,
+                        set => vec![set],
+                    };
+                    for set in &sets {
+                        if let Some(set) = self.content_set(set)? {
+                            if !capture.content_sets.contains(&set) {
+                                capture.content_sets.push(set);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Some(capture))
+    }
+
+    fn capture_command(&self, command: &Object) -> Result<Option<CaptureCommand>> {
+        let reference = command.as_indirect().ok();
+        let command = self.resolve_owned(command)?;
+        let Some(Ok(url)) = command.get(names::URL).map(|url| url.as_text()) else {
+            return Ok(None);
+        };
+        let int = |key: &str, default: i64| match command.get(key) {
+            Some(&Object::Integer(value)) => value,
+            _ => default,
+        };
+        Ok(Some(CaptureCommand {
+            reference,
+            url,
+            levels: int(names::L, 1),
+            flags: int(names::F, 0),
+            content_type: command.get(names::CT).and_then(|ct| ct.as_text().ok()),
+        }))
+    }
+
+    fn content_set(&self, set: &Object) -> Result<Option<ContentSet>> {
+        let set = self.resolve_owned(set)?;
+        if !matches!(set, Object::Dictionary(_)) {
+            return Ok(None);
+        }
+        let kind = match set.get(names::S) {
+            Some(Object::Name(kind)) if kind.matches("SPS") => ContentSetKind::Pages,
+            Some(Object::Name(kind)) if kind.matches("SIS") => ContentSetKind::Images,
+            Some(Object::Name(kind)) => {
+                ContentSetKind::Other(String::from_utf8_lossy(kind).into_owned())
+            }
+            _ => return Ok(None),
+        };
+
+        let mut urls = Vec::new();
+        if let Some(source_info) = set.get(names::SI) {
+            let source_info = self.resolve_owned(source_info)?;
+            let sources = match source_info {
+                Object::Array(sources) => sources,
+                source => vec![source],
+            };
+            for source in &sources {
+                let source = self.resolve_owned(source)?;
+                let Some(alias) = source.get(names::AU) else {
+                    continue;
+                };
+                // A URL alias dictionary records the redirects that were
+                // followed, with the URL that was first asked for in /U
+                let alias = self.resolve_owned(alias)?;
+                let url = match &alias {
+                    Object::Dictionary(_) => alias.get("U").and_then(|u| u.as_text().ok()),
+                    other => other.as_text().ok(),
+                };
+                urls.extend(url);
+            }
+        }
+
+        Ok(Some(ContentSet {
+            kind,
+            id: match set.get(names::ID) {
+                Some(Object::String(id)) => id.to_vec(),
+                _ => Vec::new(),
+            },
+            objects: match set.get(names::O) {
+                Some(objects) => self
+                    .resolve_owned(objects)?
+                    .into_iter()
+                    .filter_map(|object| object.as_indirect().ok())
+                    .collect(),
+                None => Vec::new(),
+            },
+            urls,
+            title: set.get(names::T).and_then(|title| title.as_text().ok()),
+            content_type: set.get(names::CT).and_then(|ct| ct.as_text().ok()),
+        }))
+    }
+}
+
+impl<'a> Page<'a> {
+    /// The digital identifier of the content set that the page was captured
+    /// in, from its `/ID` entry.
+    pub fn web_capture_id(&self) -> Result<Option<Vec<u8>>> {
+        Ok(match self.own_entry(names::ID)? {
+            Some(Object::String(id)) => Some(id.into_owned()),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::{build_pages, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    fn text(text: &'static str) -> Object<'static> {
+        Object::String(Cow::Borrowed(text.as_bytes()))
+    }
+
+    #[test]
+    fn should_read_web_capture() {
+        let mut builder = DocumentBuilder::new();
+        let catalog = build_pages(&mut builder, &[b""]);
+        let page = IndirectRef::new(5, 0);
+        let command = builder.add(dict(&[
+            (names::URL, text("https://example.com/")),
+            (names::L, Object::Integer(2)),
+        ]));
+        let set = builder.add(dict(&[
+            (names::TYPE, name("SpiderContentSet")),
+            (names::S, name("SPS")),
+            (names::ID, text("0123456789abcdef")),
+            (names::O, Object::Array(vec![Object::Indirect(page)])),
+            (
+                names::SI,
+                dict(&[
+                    (names::AU, dict(&[("U", text("http://example.com/"))])),
+                    (names::C, Object::Indirect(command)),
+                ]),
+            ),
+            (names::T, text("Example")),
+            (names::CT, text("text/html")),
+        ]));
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(IndirectRef::new(2, 0))),
+                (
+                    names::SPIDER_INFO,
+                    dict(&[
+                        (names::V, Object::Real(1.0)),
+                        (
+                            names::C,
+                            Object::Array(vec![Object::Indirect(command), Object::Integer(7)]),
+                        ),
+                    ]),
+                ),
+                (
+                    names::NAMES,
+                    dict(&[
+                        (
+                            names::IDS,
+                            dict(&[(
+                                names::NAMES,
+                                Object::Array(vec![
+                                    text("0123456789abcdef"),
+                                    Object::Indirect(set),
+                                ]),
+                            )]),
+                        ),
+                        (
+                            names::URLS,
+                            dict(&[(
+                                names::NAMES,
+                                Object::Array(vec![
+                                    text("http://example.com/"),
+                                    Object::Array(vec![Object::Indirect(set), Object::Null]),
+                                ]),
+                            )]),
+                        ),
+                    ]),
+                ),
+            ]),
+        );
+        builder.insert(
+            page,
+            dict(&[
+                (names::TYPE, name(names::PAGE)),
+                (names::PARENT, Object::Indirect(IndirectRef::new(2, 0))),
+                (names::ID, text("0123456789abcdef")),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let capture = file.web_capture().unwrap().unwrap();
+        assert_eq!(capture.version, Some(1.0));
+        // The malformed command is skipped
+        assert_eq!(
+            capture.commands,
+            [CaptureCommand {
+                reference: Some(command),
+                url: "https://example.com/".into(),
+                levels: 2,
+                flags: 0,
+                content_type: None,
+            }]
+        );
+        assert_eq!(
+            capture.content_sets,
+            [ContentSet {
+                kind: ContentSetKind::Pages,
+                id: b"0123456789abcdef".to_vec(),
+                objects: vec![page],
+                urls: vec!["http://example.com/".into()],
+                title: Some("Example".into()),
+                content_type: Some("text/html".into()),
+            }]
+        );
+        assert_eq!(
+            file.pages().unwrap()[0].web_capture_id().unwrap(),
+            Some(b"0123456789abcdef".to_vec())
+        );
+
+        // The capture keeps its objects reachable from the catalog
+        let orphans = file.reference_graph().unwrap().orphans();
+        assert!(!orphans.contains(&command) && !orphans.contains(&set));
+    }
+}
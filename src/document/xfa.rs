@@ -0,0 +1,223 @@
+//! Reads the XML of XFA forms (Adobe, 2008, p. 672), so that form data can be
+//! extracted from documents that can't be rendered without an XFA processor.
+
+use crate::error::{Error, Result};
+use crate::objects::{names, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::ops::Range;
+
+/// The XML Data Package (XDP) of an XFA form.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Xfa {
+    /// The whole package, made by joining the packets in order.
+    pub xml: Vec<u8>,
+    /// The named packets, such as `template` and `datasets`, and where they
+    /// are in `xml`. This is empty if the package is a single stream.
+    pub packets: Vec<(String, Range<usize>)>,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Reads the XFA form from `/AcroForm /XFA`, if the document has one.
+    pub fn xfa(&self) -> Result<Option<Xfa>> {
+        let catalog = self.catalog()?;
+        let Some(acro_form) = catalog.get(names::ACRO_FORM) else {
+            return Ok(None);
+        };
+        let acro_form = self.resolve_owned(acro_form)?;
+        let Some(xfa) = acro_form.get(names::XFA) else {
+            return Ok(None);
+        };
+
+        let mut result = Xfa::default();
+        match self.resolve_owned(xfa)? {
+            Object::Stream(_, data) => result.xml = data.into_owned(),
+            Object::Array(array) => {
+                // Alternating packet names and streams
+                for pair in array.chunks(2) {
+                    let [name, stream] = pair else {
+                        return Err(Error::Syntax(
+                            "XFA array has a packet name without a stream",
+                            format!("{:?}", pair),
+                        ));
+                    };
+                    let name = self.resolve_owned(name)?.as_text()?;
+                    let stream = self.resolve_owned(stream)?;
+                    let (_, data) = stream.as_stream()?;
+
+                    let start = result.xml.len();
+                    result.xml.extend_from_slice(&data);
+                    result.packets.push((name, start..result.xml.len()));
+                }
+            }
+            other => {
+                return Err(Error::Type(format!(
+                    "Expected XFA stream or array, found {:?}",
+                    other
+                )))
+            }
+        }
+        Ok(Some(result))
+    }
+}
+
+impl Xfa {
+    /// Returns the XML of a packet. If the package doesn't name its packets,
+    /// the first element with that name, ignoring any namespace prefix, is
+    /// found instead. This is a plain text search rather than an XML parser,
+    /// so it can be fooled by comments and CDATA sections.
+    pub fn packet(&self, name: &str) -> Option<&[u8]> {
+        if let Some((_, range)) = self.packets.iter().find(|(n, _)| n == name) {
+            return Some(&self.xml[range.clone()]);
+        }
+        find_element(&self.xml, name.as_bytes()).map(|range| &self.xml[range])
+    }
+
+    /// The form's layout and logic.
+    pub fn template(&self) -> Option<&[u8]> {
+        self.packet("template")
+    }
+
+    /// The data that has been entered into the form.
+    pub fn datasets(&self) -> Option<&[u8]> {
+        self.packet("datasets")
+    }
+}
+
+/// Finds an element such as `<xfa:datasets ...>...</xfa:datasets>` by its
+/// local name.
+fn find_element(xml: &[u8], local_name: &[u8]) -> Option<Range<usize>> {
+    let mut from = 0;
+    while let Some(i) = xml[from..].iter().position(|&b| b == b'<') {
+        let start = from + i;
+        from = start + 1;
+
+        let tag_name_len = xml[from..]
+            .iter()
+            .position(|&b| b.is_ascii_whitespace() || b == b'>' || b == b'/')?;
+        let tag_name = &xml[from..from + tag_name_len];
+        let local = match tag_name.iter().rposition(|&b| b == b':') {
+            Some(colon) => &tag_name[colon + 1..],
+            None => tag_name,
+        };
+        if local != local_name {
+            continue;
+        }
+
+        let open_end = start + xml[start..].iter().position(|&b| b == b'>')? + 1;
+        if xml[open_end - 2] == b'/' {
+            return Some(start..open_end);
+        }
+        let close = [b"</", tag_name, b">"].concat();
+        let end = xml[open_end..]
+            .windows(close.len())
+            .position(|window| window == close)?;
+        return Some(start..open_end + end + close.len());
+    }
+    None
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::objects::{Dictionary, IndirectRef};
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use std::borrow::Cow;
+
+    const DATASETS: &[u8] = b"<xfa:datasets xmlns:xfa=\"http://www.xfa.org/schema/xfa-data/1.0/\"><xfa:data><form><name>Ann</name></form></xfa:data></xfa:datasets>";
+
+    fn stream(data: &[u8]) -> Object<'_> {
+        Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::new())),
+            Cow::Borrowed(data),
+        )
+    }
+
+    fn with_xfa(xfa: impl FnOnce(&mut DocumentBuilder<'static>) -> Object<'static>) -> Vec<u8> {
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let xfa = xfa(&mut builder);
+        let acro_form = builder.add(dict(&[
+            ("Fields", Object::Array(vec![])),
+            (names::XFA, xfa),
+        ]));
+        let pages = IndirectRef {
+            number: catalog.number + 1,
+            ..catalog
+        };
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(pages)),
+                (names::ACRO_FORM, Object::Indirect(acro_form)),
+            ]),
+        );
+        builder.to_bytes().unwrap()
+    }
+
+    #[test]
+    fn should_join_xfa_packets() {
+        let raw = with_xfa(|builder| {
+            let packets = [
+                (
+                    "preamble",
+                    b"<xdp:xdp xmlns:xdp=\"http://ns.adobe.com/xdp/\">".as_slice(),
+                ),
+                ("template", b"<template><subform name=\"form\"/></template>"),
+                ("datasets", DATASETS),
+                ("postamble", b"</xdp:xdp>"),
+            ];
+            let mut array = Vec::new();
+            for (packet, data) in packets {
+                array.push(Object::String(packet.as_bytes().into()));
+                array.push(Object::Indirect(builder.add(stream(data))));
+            }
+            Object::Array(array)
+        });
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+
+        let xfa = file.xfa().unwrap().unwrap();
+        assert_eq!(xfa.packets.len(), 4);
+        assert!(xfa.xml.starts_with(b"<xdp:xdp"));
+        assert!(xfa.xml.ends_with(b"</xdp:xdp>"));
+        assert_eq!(xfa.datasets(), Some(DATASETS));
+        assert_eq!(
+            xfa.template(),
+            Some(b"<template><subform name=\"form\"/></template>".as_slice())
+        );
+        assert_eq!(xfa.packet("config"), None);
+    }
+
+    #[test]
+    fn should_find_packets_in_single_streams() {
+        let raw = with_xfa(|builder| {
+            let xml = [
+                b"<?xml version=\"1.0\"?><xdp:xdp xmlns:xdp=\"http://ns.adobe.com/xdp/\">"
+                    .as_slice(),
+                b"<config/>",
+                DATASETS,
+                b"</xdp:xdp>",
+            ]
+            .concat();
+            Object::Indirect(builder.add(Object::Stream(
+                Box::new(Object::Dictionary(Dictionary::new())),
+                Cow::Owned(xml),
+            )))
+        });
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+
+        let xfa = file.xfa().unwrap().unwrap();
+        assert!(xfa.packets.is_empty());
+        assert_eq!(xfa.datasets(), Some(DATASETS));
+        assert_eq!(xfa.packet("config"), Some(b"<config/>".as_slice()));
+        assert_eq!(xfa.template(), None);
+
+        let raw = test_support::hello_world(Default::default());
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+        assert_eq!(file.xfa().unwrap(), None);
+    }
+}
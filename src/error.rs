@@ -1,14 +1,23 @@
 use crate::objects::IndirectRef;
 use std::io;
 use std::num::{ParseFloatError, ParseIntError};
+use std::ops::Range;
 use std::result;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Error {
     EOF,
+    FilterDecode(&'static str, String),
     IO(String),
+    InvalidReference(i64, i64),
+    /// Changing a range of bytes would invalidate the signature in an object.
+    InvalidatesSignature(IndirectRef, Range<usize>),
+    MissingKey(String),
     NotLoaded(&'static str),
+    NullValue(String),
     ObjectNotFound(IndirectRef),
+    /// Processing a document panicked, with the message of the panic.
+    Panic(String),
     ParseFloat(ParseFloatError),
     ParseInt(ParseIntError),
     Syntax(&'static str, String),
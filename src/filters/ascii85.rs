@@ -0,0 +1,115 @@
+use crate::error::{Error, Result};
+use crate::utils::chars::is_whitespace_char;
+
+/// How many characters are written on each line.
+const LINE_LENGTH: usize = 75;
+
+/// Decodes an `ASCII85Decode` stream (Adobe, 2008, p. 26). Whitespace is
+/// ignored, and `z` stands for four zero bytes.
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    let error = |message: String| Error::FilterDecode("ASCII85Decode", message);
+
+    let mut out = Vec::with_capacity(data.len() * 4 / 5);
+    let mut group = [0u8; 5];
+    let mut len = 0;
+    for &c in data {
+        match c {
+            b'~' => break,
+            c if is_whitespace_char(c) => {}
+            b'z' if len == 0 => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                group[len] = c - b'!';
+                len += 1;
+                if len == 5 {
+                    out.extend_from_slice(
+                        &decode_group(&group)
+                            .ok_or_else(|| error(format!("Group out of range {:?}", group)))?,
+                    );
+                    len = 0;
+                }
+            }
+            c => return Err(error(format!("Invalid character {:?}", c as char))),
+        }
+    }
+
+    // A final partial group is padded with the highest digit
+    match len {
+        0 => {}
+        1 => return Err(error("Final group has only one character".into())),
+        _ => {
+            group[len..].fill(b'u' - b'!');
+            let bytes = decode_group(&group)
+                .ok_or_else(|| error(format!("Group out of range {:?}", group)))?;
+            out.extend_from_slice(&bytes[..len - 1]);
+        }
+    }
+    Ok(out)
+}
+
+fn decode_group(group: &[u8; 5]) -> Option<[u8; 4]> {
+    let value = group.iter().try_fold(0u32, |value, &digit| {
+        value.checked_mul(85)?.checked_add(digit.into())
+    })?;
+    Some(value.to_be_bytes())
+}
+
+/// Encodes data for an `ASCII85Decode` stream, with line breaks so that the
+/// output is safe to send by email.
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 5 / 4 + data.len() / 60 + 3);
+    let mut line = 0;
+    let mut push = |out: &mut Vec<u8>, chars: &[u8]| {
+        if line + chars.len() > LINE_LENGTH {
+            out.push(b'\n');
+            line = 0;
+        }
+        out.extend_from_slice(chars);
+        line += chars.len();
+    };
+
+    for chunk in data.chunks(4) {
+        let mut bytes = [0; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(bytes);
+        if value == 0 && chunk.len() == 4 {
+            push(&mut out, b"z");
+            continue;
+        }
+
+        let mut group = [0; 5];
+        for digit in group.iter_mut().rev() {
+            *digit = (value % 85) as u8 + b'!';
+            value /= 85;
+        }
+        // A partial group of n bytes is written as n + 1 characters
+        push(&mut out, &group[..chunk.len() + 1]);
+    }
+    push(&mut out, b"~>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_ascii85() {
+        assert_eq!(decode(b"87cURD]j7BEbo80~>").unwrap(), b"Hello world!");
+        assert_eq!(decode(b"z 87cU\nRDZ~>").unwrap(), b"\0\0\0\0Hello");
+        assert!(decode(b"s8W-\"~>").is_err());
+        assert!(decode(b"87cUR{~>").is_err());
+
+        for len in 0..12 {
+            let data = (0..len).map(|i| (i * 97) as u8).collect::<Vec<_>>();
+            let encoded = encode(&data);
+            assert!(encoded.is_ascii());
+            assert_eq!(decode(&encoded).unwrap(), data);
+        }
+        let data = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let encoded = encode(&data);
+        assert!(encoded
+            .split(|&c| c == b'\n')
+            .all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(decode(&encoded).unwrap(), data);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::{Error, Result};
+use crate::utils::chars::is_whitespace_char;
+
+/// How many hex digits are written on each line.
+const LINE_LENGTH: usize = 64;
+
+/// Decodes an `ASCIIHexDecode` stream (Adobe, 2008, p. 25). Whitespace is
+/// ignored, and if the data ends with an odd digit a zero is assumed after it.
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() / 2);
+    let mut high = None;
+    for &c in data {
+        let digit = match c {
+            b'>' => break,
+            c if is_whitespace_char(c) => continue,
+            c => (c as char).to_digit(16).ok_or_else(|| {
+                Error::FilterDecode(
+                    "ASCIIHexDecode",
+                    format!("Invalid character {:?}", c as char),
+                )
+            })? as u8,
+        };
+        match high.take() {
+            Some(high) => out.push(high << 4 | digit),
+            None => high = Some(digit),
+        }
+    }
+    if let Some(high) = high {
+        out.push(high << 4);
+    }
+    Ok(out)
+}
+
+/// Encodes data for an `ASCIIHexDecode` stream, with line breaks so that the
+/// output is easy to read and safe to send by email.
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() * 2 + data.len() / 32 + 1);
+    for (i, c) in data.iter().enumerate() {
+        if i > 0 && i * 2 % LINE_LENGTH == 0 {
+            out.push(b'\n');
+        }
+        out.extend_from_slice(format!("{:02X}", c).as_bytes());
+    }
+    out.push(b'>');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_ascii_hex() {
+        assert_eq!(decode(b"48 65 6c\n6C6F>").unwrap(), b"Hello");
+        assert_eq!(decode(b"7>ignored").unwrap(), b"\x70");
+        assert!(decode(b"4G>").is_err());
+
+        let data = (0..=255).collect::<Vec<u8>>();
+        let encoded = encode(&data);
+        assert!(encoded.is_ascii());
+        assert_eq!(decode(&encoded).unwrap(), data);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::error::{Error, Result};
+use crate::filters::CountingWriter;
+use std::io::Write;
+use std::sync::OnceLock;
+
+/// How much decoded data is held in memory at once when inflating into a
+/// sink.
+#[cfg(feature = "miniz_oxide")]
+const CHUNK_SIZE: usize = 32 * 1024;
+
+/// A zlib/deflate implementation that can be used to decode `FlateDecode`
+/// streams (Adobe, 2008, p. 25).
+pub trait FlateBackend: Send + Sync {
+    /// Inflates data that is wrapped in a zlib header and checksum.
+    fn inflate_zlib(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Inflates raw deflate data, without any zlib wrapper.
+    fn inflate_raw(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Deflates data, wrapping it in a zlib header and checksum.
+    fn deflate_zlib(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    /// Inflates zlib data into a sink, returning how many bytes were written.
+    /// Backends should do this in chunks, but by default all of the data is
+    /// inflated in memory first.
+    fn inflate_zlib_into(&self, data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+        let inflated = self.inflate_zlib(data)?;
+        sink.write_all(&inflated)?;
+        Ok(inflated.len() as u64)
+    }
+
+    /// Inflates raw deflate data into a sink, like `inflate_zlib_into`.
+    fn inflate_raw_into(&self, data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+        let inflated = self.inflate_raw(data)?;
+        sink.write_all(&inflated)?;
+        Ok(inflated.len() as u64)
+    }
+}
+
+/// A backend using the pure rust `miniz_oxide` crate.
+#[cfg(feature = "miniz_oxide")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinizOxideBackend;
+
+#[cfg(feature = "miniz_oxide")]
+impl FlateBackend for MinizOxideBackend {
+    fn inflate_zlib(&self, data: &[u8]) -> Result<Vec<u8>> {
+        miniz_oxide::inflate::decompress_to_vec_zlib(data)
+            .map_err(|err| Error::FilterDecode("FlateDecode", format!("{:?}", err.status)))
+    }
+
+    fn inflate_raw(&self, data: &[u8]) -> Result<Vec<u8>> {
+        miniz_oxide::inflate::decompress_to_vec(data)
+            .map_err(|err| Error::FilterDecode("FlateDecode", format!("{:?}", err.status)))
+    }
+
+    fn deflate_zlib(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(miniz_oxide::deflate::compress_to_vec_zlib(data, 6))
+    }
+
+    fn inflate_zlib_into(&self, data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+        miniz_inflate_into(miniz_oxide::DataFormat::Zlib, data, sink)
+    }
+
+    fn inflate_raw_into(&self, data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+        miniz_inflate_into(miniz_oxide::DataFormat::Raw, data, sink)
+    }
+}
+
+/// Inflates data a chunk at a time with the streaming `miniz_oxide` API.
+#[cfg(feature = "miniz_oxide")]
+fn miniz_inflate_into(
+    format: miniz_oxide::DataFormat,
+    mut data: &[u8],
+    sink: &mut dyn Write,
+) -> Result<u64> {
+    use miniz_oxide::inflate::stream::{inflate, InflateState};
+    use miniz_oxide::{MZFlush, MZStatus};
+
+    let mut state = InflateState::new_boxed(format);
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut written = 0;
+    loop {
+        let result = inflate(&mut state, data, &mut buf, MZFlush::None);
+        data = &data[result.bytes_consumed..];
+        sink.write_all(&buf[..result.bytes_written])?;
+        written += result.bytes_written as u64;
+
+        match result.status {
+            Ok(MZStatus::StreamEnd) => return Ok(written),
+            Ok(_) => {}
+            Err(err) => return Err(Error::FilterDecode("FlateDecode", format!("{:?}", err))),
+        }
+    }
+}
+
+/// A backend using the `flate2` crate.
+#[cfg(feature = "flate2")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Flate2Backend;
+
+#[cfg(feature = "flate2")]
+impl FlateBackend for Flate2Backend {
+    fn inflate_zlib(&self, data: &[u8]) -> Result<Vec<u8>> {
+        use std::io::Read;
+
+        let mut buf = Vec::new();
+        flate2::read::ZlibDecoder::new(data)
+            .read_to_end(&mut buf)
+            .map_err(|err| Error::FilterDecode("FlateDecode", err.to_string()))?;
+        Ok(buf)
+    }
+
+    fn inflate_raw(&self, data: &[u8]) -> Result<Vec<u8>> {
+        use std::io::Read;
+
+        let mut buf = Vec::new();
+        flate2::read::DeflateDecoder::new(data)
+            .read_to_end(&mut buf)
+            .map_err(|err| Error::FilterDecode("FlateDecode", err.to_string()))?;
+        Ok(buf)
+    }
+
+    fn deflate_zlib(&self, data: &[u8]) -> Result<Vec<u8>> {
+        use std::io::Read;
+
+        let mut buf = Vec::new();
+        flate2::read::ZlibEncoder::new(data, flate2::Compression::default())
+            .read_to_end(&mut buf)
+            .map_err(|err| Error::FilterDecode("FlateDecode", err.to_string()))?;
+        Ok(buf)
+    }
+
+    fn inflate_zlib_into(&self, data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+        let mut sink = CountingWriter::new(sink);
+        let mut decoder = flate2::write::ZlibDecoder::new(&mut sink);
+        write_until_end(&mut decoder, data)
+            .and_then(|_| decoder.try_finish())
+            .map_err(|err| Error::FilterDecode("FlateDecode", err.to_string()))?;
+        drop(decoder);
+        Ok(sink.count())
+    }
+
+    fn inflate_raw_into(&self, data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+        let mut sink = CountingWriter::new(sink);
+        let mut decoder = flate2::write::DeflateDecoder::new(&mut sink);
+        write_until_end(&mut decoder, data)
+            .and_then(|_| decoder.try_finish())
+            .map_err(|err| Error::FilterDecode("FlateDecode", err.to_string()))?;
+        drop(decoder);
+        Ok(sink.count())
+    }
+}
+
+/// Writes data to a `flate2` decoder, which stops accepting data at the end
+/// of the deflate stream. Anything after that, such as a checksum when the
+/// data is inflated as raw deflate, is ignored.
+#[cfg(feature = "flate2")]
+fn write_until_end<W: Write>(decoder: &mut W, mut data: &[u8]) -> std::io::Result<()> {
+    while !data.is_empty() {
+        match decoder.write(data)? {
+            0 => break,
+            written => data = &data[written..],
+        }
+    }
+    Ok(())
+}
+
+/// Used when the crate is built without any flate backend, so that streams
+/// fail to decode instead of failing to compile.
+#[cfg(not(any(feature = "miniz_oxide", feature = "flate2")))]
+struct MissingBackend;
+
+#[cfg(not(any(feature = "miniz_oxide", feature = "flate2")))]
+impl FlateBackend for MissingBackend {
+    fn inflate_zlib(&self, _data: &[u8]) -> Result<Vec<u8>> {
+        Err(Error::FilterDecode(
+            "FlateDecode",
+            "No flate backend is available".into(),
+        ))
+    }
+
+    fn inflate_raw(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.inflate_zlib(data)
+    }
+
+    fn deflate_zlib(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.inflate_zlib(data)
+    }
+}
+
+static BACKEND: OnceLock<&'static dyn FlateBackend> = OnceLock::new();
+
+/// Sets the backend used to decode all `FlateDecode` streams.
+///
+/// This can only be done once, and must be done before any streams are
+/// decoded. Returns false if a backend had already been chosen.
+pub fn set_backend(backend: &'static dyn FlateBackend) -> bool {
+    BACKEND.set(backend).is_ok()
+}
+
+/// Returns the backend used to decode `FlateDecode` streams.
+pub fn backend() -> &'static dyn FlateBackend {
+    *BACKEND.get_or_init(default_backend)
+}
+
+fn default_backend() -> &'static dyn FlateBackend {
+    #[cfg(feature = "miniz_oxide")]
+    {
+        &MinizOxideBackend
+    }
+    #[cfg(all(feature = "flate2", not(feature = "miniz_oxide")))]
+    {
+        &Flate2Backend
+    }
+    #[cfg(not(any(feature = "miniz_oxide", feature = "flate2")))]
+    {
+        &MissingBackend
+    }
+}
+
+/// Returns true if the data starts with a valid zlib header (RFC 1950, p. 4).
+pub fn has_zlib_header(data: &[u8]) -> bool {
+    match data {
+        [cmf, flg, ..] => cmf & 0x0F == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+/// Decodes a `FlateDecode` stream using the current backend.
+///
+/// Broken files sometimes omit the zlib header, or corrupt it, so if the
+/// data cannot be decoded as zlib it is retried as raw deflate data.
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    decode_with(backend(), data)
+}
+
+/// Encodes data for a `FlateDecode` stream using the current backend.
+pub fn encode(data: &[u8]) -> Result<Vec<u8>> {
+    backend().deflate_zlib(data)
+}
+
+/// Decodes a `FlateDecode` stream using the given backend.
+pub fn decode_with(backend: &dyn FlateBackend, data: &[u8]) -> Result<Vec<u8>> {
+    if !has_zlib_header(data) {
+        return backend.inflate_raw(data);
+    }
+
+    backend
+        .inflate_zlib(data)
+        .or_else(|err| backend.inflate_raw(&data[2..]).map_err(|_| err))
+}
+
+/// Decodes a `FlateDecode` stream into a sink using the current backend,
+/// returning how many bytes were written.
+///
+/// Like `decode`, data that cannot be decoded as zlib is retried as raw
+/// deflate data, but only if nothing has been written to the sink yet.
+pub fn decode_into(data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+    let backend = backend();
+    if !has_zlib_header(data) {
+        return backend.inflate_raw_into(data, sink);
+    }
+
+    let mut counting = CountingWriter::new(sink);
+    match backend.inflate_zlib_into(data, &mut counting) {
+        Err(err) if counting.count() == 0 => backend
+            .inflate_raw_into(&data[2..], &mut counting)
+            .map_err(|_| err),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "Hello, world!" compressed with zlib
+    const ZLIB: &[u8] = b"x\x9c\xf3H\xcd\xc9\xc9\xd7Q(\xcf/\xcaIQ\x04\x00\x20^\x04\x8a";
+
+    #[test]
+    fn should_detect_zlib_header() {
+        assert!(has_zlib_header(ZLIB));
+        assert!(!has_zlib_header(&ZLIB[2..]));
+        assert!(!has_zlib_header(b""));
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_decode_zlib() {
+        assert_eq!(decode(ZLIB).unwrap(), b"Hello, world!");
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_decode_raw_deflate() {
+        assert_eq!(decode(&ZLIB[2..]).unwrap(), b"Hello, world!");
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_round_trip() {
+        let data = b"Hello, world! Hello, world! Hello, world!";
+        let encoded = encode(data).unwrap();
+        assert!(has_zlib_header(&encoded));
+        assert_eq!(decode(&encoded).unwrap(), data);
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_decode_into_sink_in_chunks() {
+        let data = b"Hello, world! ".repeat(10_000);
+        let encoded = encode(&data).unwrap();
+        let mut sink = Vec::new();
+        assert_eq!(decode_into(&encoded, &mut sink), Ok(data.len() as u64));
+        assert_eq!(sink, data);
+
+        let mut sink = Vec::new();
+        assert_eq!(decode_into(&ZLIB[2..], &mut sink), Ok(13));
+        assert_eq!(sink, b"Hello, world!");
+        assert!(decode_into(b"x\x9c\xff\xff\xff\xff", &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn should_fail_on_corrupt_data() {
+        assert!(matches!(
+            decode(b"x\x9c\xff\xff\xff\xff"),
+            Err(Error::FilterDecode("FlateDecode", _))
+        ));
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn should_decode_with_flate2() {
+        assert_eq!(decode_with(&Flate2Backend, ZLIB).unwrap(), b"Hello, world!");
+        assert_eq!(
+            decode_with(&Flate2Backend, &ZLIB[2..]).unwrap(),
+            b"Hello, world!"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::error::{Error, Result};
+use crate::objects::{names, Name, Object};
+use crate::utils::trace::trace_span;
+use std::io::{self, Write};
+
+pub mod ascii85;
+pub mod ascii_hex;
+pub mod flate;
+
+/// Decodes data with one of the supported filters.
+pub fn decode(filter: &Name, data: &[u8]) -> Result<Vec<u8>> {
+    trace_span!("decode", filter = %String::from_utf8_lossy(filter), size = data.len());
+    if filter.matches(names::FLATE_DECODE) {
+        flate::decode(data)
+    } else if filter.matches(names::ASCII_HEX_DECODE) {
+        ascii_hex::decode(data)
+    } else if filter.matches(names::ASCII_85_DECODE) {
+        ascii85::decode(data)
+    } else {
+        Err(Error::UnknownFilter(String::from_utf8_lossy(filter).into()))
+    }
+}
+
+/// Decodes data with one of the supported filters into a sink, returning how
+/// many bytes were written. Only `FlateDecode` is decoded in chunks, since the
+/// other filters only ever expand their data.
+pub fn decode_into(filter: &Name, data: &[u8], sink: &mut dyn Write) -> Result<u64> {
+    if filter.matches(names::FLATE_DECODE) {
+        trace_span!("decode", filter = names::FLATE_DECODE, size = data.len());
+        flate::decode_into(data, sink)
+    } else {
+        let decoded = decode(filter, data)?;
+        sink.write_all(&decoded)?;
+        Ok(decoded.len() as u64)
+    }
+}
+
+/// Encodes data with one of the supported filters.
+pub fn encode(filter: &Name, data: &[u8]) -> Result<Vec<u8>> {
+    if filter.matches(names::FLATE_DECODE) {
+        flate::encode(data)
+    } else if filter.matches(names::ASCII_HEX_DECODE) {
+        Ok(ascii_hex::encode(data))
+    } else if filter.matches(names::ASCII_85_DECODE) {
+        Ok(ascii85::encode(data))
+    } else {
+        Err(Error::UnknownFilter(String::from_utf8_lossy(filter).into()))
+    }
+}
+
+/// Encodes data with a chain of filters, so that decoding it with the same
+/// filters gives back the data. Since the filters are decoded in order, they
+/// are applied here from last to first (Adobe, 2008, p. 22).
+pub fn encode_chain(filters: &Object, data: &[u8]) -> Result<Vec<u8>> {
+    let mut data = data.to_vec();
+    for filter in filters.into_iter().collect::<Vec<_>>().into_iter().rev() {
+        data = encode(filter.as_name()?, &data)?;
+    }
+    Ok(data)
+}
+
+/// Counts the bytes that are written through it.
+pub(crate) struct CountingWriter<'w> {
+    inner: &'w mut dyn Write,
+    count: u64,
+}
+
+impl<'w> CountingWriter<'w> {
+    pub fn new(inner: &'w mut dyn Write) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
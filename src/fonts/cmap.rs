@@ -0,0 +1,266 @@
+//! CMaps, which map character codes to CIDs or to Unicode (Adobe, 2008,
+//! p. 272-274 and 293-297).
+
+use crate::content::operators::parse_operators;
+use crate::error::{Error, Result};
+use crate::objects::{names, Object};
+use crate::parsing::context::ParseContext;
+use std::collections::HashMap;
+
+/// The largest range that is expanded, to limit memory use on broken files.
+const MAX_RANGE_LENGTH: u32 = 0x10000;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CMap {
+    /// Ranges of valid codes, as pairs of equal-length byte strings.
+    codespace_ranges: Vec<(Vec<u8>, Vec<u8>)>,
+    unicode: HashMap<u32, String>,
+    cids: HashMap<u32, u32>,
+    /// Whether the CMap is for vertical writing, as set by `/WMode 1`.
+    pub vertical: bool,
+}
+
+impl CMap {
+    /// The `Identity-H` and `Identity-V` CMaps, which map two-byte codes to
+    /// the same CIDs.
+    pub fn identity() -> Self {
+        Self {
+            codespace_ranges: vec![(vec![0x00, 0x00], vec![0xFF, 0xFF])],
+            ..Default::default()
+        }
+    }
+
+    pub fn parse(raw: &[u8], context: &ParseContext) -> Result<Self> {
+        let mut cmap = Self::default();
+
+        for operator in parse_operators(raw, context)? {
+            match operator.operator {
+                b"endcodespacerange" => {
+                    for pair in operator.operands.chunks_exact(2) {
+                        let low = pair[0].as_string()?.into_owned();
+                        let high = pair[1].as_string()?.into_owned();
+                        if low.len() != high.len() || low.is_empty() || low.len() > 4 {
+                            return Err(Error::Syntax(
+                                "Invalid codespace range",
+                                format!("{:?}", pair),
+                            ));
+                        }
+                        cmap.codespace_ranges.push((low, high));
+                    }
+                }
+                b"endbfchar" => {
+                    for pair in operator.operands.chunks_exact(2) {
+                        cmap.unicode
+                            .insert(code(&pair[0])?, decode_utf16(&pair[1].as_string()?));
+                    }
+                }
+                b"endbfrange" => {
+                    for triple in operator.operands.chunks_exact(3) {
+                        let (low, high) = range(&triple[0], &triple[1])?;
+                        match &triple[2] {
+                            Object::Array(destinations) => {
+                                for (code, destination) in (low..=high).zip(destinations) {
+                                    cmap.unicode
+                                        .insert(code, decode_utf16(&destination.as_string()?));
+                                }
+                            }
+                            destination => {
+                                let mut destination = destination.as_string()?.into_owned();
+                                for code in low..=high {
+                                    cmap.unicode.insert(code, decode_utf16(&destination));
+                                    increment(&mut destination);
+                                }
+                            }
+                        }
+                    }
+                }
+                b"endcidchar" => {
+                    for pair in operator.operands.chunks_exact(2) {
+                        cmap.cids.insert(code(&pair[0])?, cid(&pair[1])?);
+                    }
+                }
+                b"def" => {
+                    if let [Object::Name(key), Object::Integer(1)] = operator.operands.as_slice() {
+                        if key.matches(names::W_MODE) {
+                            cmap.vertical = true;
+                        }
+                    }
+                }
+                b"endcidrange" => {
+                    for triple in operator.operands.chunks_exact(3) {
+                        let (low, high) = range(&triple[0], &triple[1])?;
+                        let start = cid(&triple[2])?;
+                        for code in low..=high {
+                            cmap.cids.insert(code, start + (code - low));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(cmap)
+    }
+
+    /// Reads the next code from a string, returning the code and its length
+    /// in bytes. Codes that do not match any codespace range are read as a
+    /// single byte.
+    pub fn next_code(&self, bytes: &[u8]) -> (u32, usize) {
+        for length in 1..=4 {
+            let Some(prefix) = bytes.get(..length) else {
+                break;
+            };
+            let in_range = |(low, high): &(Vec<u8>, Vec<u8>)| {
+                low.len() == length
+                    && prefix
+                        .iter()
+                        .zip(low.iter().zip(high))
+                        .all(|(c, (low, high))| (low..=high).contains(&c))
+            };
+            if self.codespace_ranges.iter().any(in_range) {
+                return (to_code(prefix), length);
+            }
+        }
+
+        match bytes.first() {
+            Some(&c) => (c as u32, 1),
+            None => (0, 0),
+        }
+    }
+
+    pub fn to_unicode(&self, code: u32) -> Option<&str> {
+        self.unicode.get(&code).map(String::as_str)
+    }
+
+    /// Iterates over the codes that are mapped to Unicode.
+    pub fn unicode_mappings(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.unicode
+            .iter()
+            .map(|(&code, text)| (code, text.as_str()))
+    }
+
+    /// Maps a code to a CID, using the code itself if it is not mapped.
+    pub fn to_cid(&self, code: u32) -> u32 {
+        self.cids.get(&code).copied().unwrap_or(code)
+    }
+}
+
+fn to_code(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |code, &c| (code << 8) | c as u32)
+}
+
+fn code(object: &Object) -> Result<u32> {
+    let bytes = object.as_string()?;
+    if bytes.len() > 4 {
+        return Err(Error::Syntax(
+            "Character code is too long",
+            format!("{:?}", object),
+        ));
+    }
+    Ok(to_code(&bytes))
+}
+
+fn cid(object: &Object) -> Result<u32> {
+    u32::try_from(object.as_int()?)
+        .map_err(|_| Error::Syntax("CID is out of range", format!("{:?}", object)))
+}
+
+fn range(low: &Object, high: &Object) -> Result<(u32, u32)> {
+    let (low, high) = (code(low)?, code(high)?);
+    if low > high || high - low >= MAX_RANGE_LENGTH {
+        return Err(Error::Syntax(
+            "Invalid code range",
+            format!("{:X} to {:X}", low, high),
+        ));
+    }
+    Ok((low, high))
+}
+
+/// Increments the last byte of a destination string, as is done for each
+/// code in a `bfrange` (Adobe, 2008, p. 295).
+fn increment(destination: &mut [u8]) {
+    if let Some(last) = destination.last_mut() {
+        *last = last.wrapping_add(1);
+    }
+}
+
+fn decode_utf16(bytes: &[u8]) -> String {
+    let units = bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TO_UNICODE: &[u8] = b"/CIDInit/ProcSet findresource begin
+12 dict begin
+begincmap
+/CIDSystemInfo<< /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def
+/CMapName/Adobe-Identity-UCS def
+1 begincodespacerange
+<0000> <FFFF>
+endcodespacerange
+2 beginbfchar
+<0001> <0048>
+<0002> <D83DDE00>
+endbfchar
+2 beginbfrange
+<0010> <0012> <0061>
+<0020> <0021> [<0041> <00660066>]
+endbfrange
+endcmap
+CMapName currentdict /CMap defineresource pop
+end
+end
+";
+
+    #[test]
+    fn should_map_codes_to_unicode() {
+        let cmap = CMap::parse(TO_UNICODE, &ParseContext::default()).unwrap();
+        assert_eq!(cmap.to_unicode(1), Some("H"));
+        assert_eq!(cmap.to_unicode(2), Some("\u{1F600}"));
+        assert_eq!(cmap.to_unicode(0x12), Some("c"));
+        assert_eq!(cmap.to_unicode(0x21), Some("ff"));
+        assert_eq!(cmap.to_unicode(0x13), None);
+    }
+
+    #[test]
+    fn should_read_codes_using_codespace_ranges() {
+        let cmap = CMap::parse(TO_UNICODE, &ParseContext::default()).unwrap();
+        assert_eq!(cmap.next_code(b"\x00\x01\x00"), (1, 2));
+        assert_eq!(cmap.next_code(b"\x01"), (1, 1));
+
+        let cmap = CMap::parse(
+            b"2 begincodespacerange <00> <80> <8140> <9FFC> endcodespacerange",
+            &ParseContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmap.next_code(b"\x41\x81\x40"), (0x41, 1));
+        assert_eq!(cmap.next_code(b"\x81\x40"), (0x8140, 2));
+    }
+
+    #[test]
+    fn should_map_codes_to_cids() {
+        let cmap = CMap::parse(
+            b"1 begincidrange <0020> <0030> 100 endcidrange 1 begincidchar <0040> 7 endcidchar",
+            &ParseContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmap.to_cid(0x22), 102);
+        assert_eq!(cmap.to_cid(0x40), 7);
+        assert_eq!(cmap.to_cid(0x41), 0x41);
+    }
+
+    #[test]
+    fn should_read_writing_mode() {
+        let cmap = CMap::parse(b"/WMode 1 def", &ParseContext::default()).unwrap();
+        assert!(cmap.vertical);
+        let cmap = CMap::parse(b"/WMode 0 def", &ParseContext::default()).unwrap();
+        assert!(!cmap.vertical);
+    }
+}
@@ -0,0 +1,717 @@
+use crate::content::operators::parse_operators;
+use crate::error::Result;
+use crate::fonts::cmap::CMap;
+#[cfg(feature = "outlines")]
+use crate::fonts::outline::{self, GlyphLookup, GlyphOutline};
+use crate::geometry::{number, Matrix, Rect};
+use crate::objects::{names, Object};
+use crate::parsing::context::ParseContext;
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::HashMap;
+
+/// The information about a font that is needed to position and extract text
+/// (Adobe, 2008, p. 253-283).
+/// How a glyph is placed in vertical writing, in glyph space units
+/// (Adobe, 2008, p. 271).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VerticalMetrics {
+    /// The vertical displacement, which is usually negative.
+    pub w1y: f64,
+    /// The position vector, from the origin used for horizontal writing to
+    /// the origin used for vertical writing.
+    pub v: (f64, f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Font {
+    pub subtype: Option<String>,
+    pub base_font: Option<String>,
+    /// The CMap used to read codes from composite fonts, which is `None` for
+    /// simple fonts.
+    encoding: Option<CMap>,
+    /// Names of glyphs that differ from the base encoding of a simple font.
+    differences: HashMap<u32, String>,
+    to_unicode: Option<CMap>,
+    first_char: u32,
+    widths: Vec<f64>,
+    /// Widths of composite fonts, indexed by CID.
+    cid_widths: HashMap<u32, f64>,
+    default_width: f64,
+    /// The maximum height above the baseline, in glyph space units.
+    pub ascent: f64,
+    /// The maximum depth below the baseline, which is negative.
+    pub descent: f64,
+    /// Maps glyph space to text space, which is a scale of one thousandth
+    /// except for Type 3 fonts (Adobe, 2008, p. 259).
+    pub font_matrix: Matrix,
+    /// Maps CIDs to glyph indices in an embedded TrueType font, or `None` if
+    /// they are the same.
+    cid_to_gid: Option<Vec<u16>>,
+    /// Whether the font is written vertically (Adobe, 2008, p. 273).
+    vertical: bool,
+    /// Vertical metrics of composite fonts, indexed by CID.
+    cid_vertical_metrics: HashMap<u32, VerticalMetrics>,
+    /// The `/DW2` entry of a vertical font, which is the y component of the
+    /// position vector followed by the vertical displacement.
+    default_vertical_metrics: (f64, f64),
+    /// The glyph procedures of a Type 3 font, by character code.
+    char_procs: HashMap<u32, Vec<u8>>,
+    /// The bounding boxes that Type 3 glyph procedures declare with `d1`.
+    glyph_boxes: HashMap<u32, Rect>,
+    /// The embedded TrueType or CFF font program, if there is one.
+    #[cfg(feature = "outlines")]
+    program: Option<Vec<u8>>,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self {
+            subtype: None,
+            base_font: None,
+            encoding: None,
+            differences: HashMap::new(),
+            to_unicode: None,
+            first_char: 0,
+            widths: Vec::new(),
+            cid_widths: HashMap::new(),
+            default_width: 0.0,
+            ascent: 800.0,
+            descent: -200.0,
+            font_matrix: Matrix::scale(0.001, 0.001),
+            cid_to_gid: None,
+            vertical: false,
+            cid_vertical_metrics: HashMap::new(),
+            default_vertical_metrics: (880.0, -1000.0),
+            char_procs: HashMap::new(),
+            glyph_boxes: HashMap::new(),
+            #[cfg(feature = "outlines")]
+            program: None,
+        }
+    }
+}
+
+impl Font {
+    /// Loads a font from its font dictionary.
+    pub fn load<'a>(file: &'a PdfFile<'a>, dict: &Object<'a>) -> Result<Self> {
+        let name_entry = |object: &Object, key| match object.get(key) {
+            Some(Object::Name(name)) => Some(String::from_utf8_lossy(name).into_owned()),
+            _ => None,
+        };
+
+        let mut font = Font {
+            subtype: name_entry(dict, names::SUBTYPE),
+            base_font: name_entry(dict, names::BASE_FONT),
+            ..Default::default()
+        };
+
+        if let Some(to_unicode) = dict.get(names::TO_UNICODE) {
+            if let Object::Stream(_, data) = file.resolve_owned(to_unicode)? {
+                font.to_unicode = Some(CMap::parse(&data, &ParseContext::default())?);
+            }
+        }
+
+        let descriptor_dict;
+        if font.subtype.as_deref() == Some(names::TYPE_0) {
+            let encoding = match dict.get(names::ENCODING) {
+                Some(encoding @ Object::Indirect(_)) => match file.resolve_owned(encoding)? {
+                    Object::Stream(stream_dict, data) => {
+                        let mut cmap = CMap::parse(&data, &ParseContext::default())?;
+                        if let Some(Object::Integer(1)) = stream_dict.get(names::W_MODE) {
+                            cmap.vertical = true;
+                        }
+                        cmap
+                    }
+                    _ => CMap::identity(),
+                },
+                // Predefined CMaps other than Identity-H and Identity-V are
+                // not supported, so read them as two-byte codes.
+                Some(Object::Name(name)) => {
+                    // Vertical predefined CMaps, such as Identity-V, end in -V
+                    let mut cmap = CMap::identity();
+                    cmap.vertical = name.ends_with(b"-V");
+                    cmap
+                }
+                _ => CMap::identity(),
+            };
+            font.vertical = encoding.vertical;
+            font.encoding = Some(encoding);
+
+            let descendant = match dict.get(names::DESCENDANT_FONTS) {
+                Some(descendants) => match file.resolve_owned(descendants)? {
+                    Object::Array(descendants) if !descendants.is_empty() => {
+                        file.resolve_owned(&descendants[0])?
+                    }
+                    _ => Object::Null,
+                },
+                None => Object::Null,
+            };
+            font.default_width = match descendant.get(names::DW) {
+                Some(width) => number(width)?,
+                None => 1000.0,
+            };
+            if let Some(widths) = descendant.get(names::W) {
+                font.cid_widths = parse_cid_widths(&file.resolve_owned(widths)?)?;
+            }
+            if let Some(Object::Array(metrics)) = descendant.get(names::DW2) {
+                if let [v_y, w1y] = metrics.as_slice() {
+                    font.default_vertical_metrics = (number(v_y)?, number(w1y)?);
+                }
+            }
+            if let Some(metrics) = descendant.get(names::W2) {
+                font.cid_vertical_metrics =
+                    parse_cid_vertical_metrics(&file.resolve_owned(metrics)?)?;
+            }
+
+            let is_true_type = matches!(
+                descendant.get(names::SUBTYPE),
+                Some(Object::Name(subtype)) if subtype.matches(names::CID_FONT_TYPE_2)
+            );
+            if let (true, Some(map)) = (is_true_type, descendant.get(names::CID_TO_GID_MAP)) {
+                if let Object::Stream(_, data) = file.resolve_owned(map)? {
+                    font.cid_to_gid = Some(
+                        data.chunks_exact(2)
+                            .map(|gid| u16::from_be_bytes([gid[0], gid[1]]))
+                            .collect(),
+                    );
+                }
+            }
+            descriptor_dict = descendant;
+        } else {
+            if let Some(first_char) = dict.get(names::FIRST_CHAR) {
+                font.first_char = first_char.as_int()?.try_into().unwrap_or(0);
+            }
+            if let Some(widths) = dict.get(names::WIDTHS) {
+                if let Object::Array(widths) = file.resolve_owned(widths)? {
+                    font.widths = widths
+                        .iter()
+                        .map(|width| number(&file.resolve_owned(width)?))
+                        .collect::<Result<_>>()?;
+                }
+            }
+            if let Some(encoding) = dict.get(names::ENCODING) {
+                let encoding = file.resolve_owned(encoding)?;
+                if let Some(Object::Array(differences)) = encoding.get(names::DIFFERENCES) {
+                    font.differences = parse_differences(differences);
+                }
+            }
+            // The standard 14 fonts may be used without widths, so guess an
+            // average width rather than placing every glyph at the same spot
+            if font.widths.is_empty() {
+                font.default_width = 500.0;
+            }
+            descriptor_dict = dict.clone();
+
+            if font.subtype.as_deref() == Some(names::TYPE_3) {
+                font.load_type_3(file, dict)?;
+            }
+        }
+
+        if let Some(descriptor) = descriptor_dict.get(names::FONT_DESCRIPTOR) {
+            let descriptor = file.resolve_owned(descriptor)?;
+            if let Some(width) = descriptor.get(names::MISSING_WIDTH) {
+                font.default_width = number(width)?;
+            }
+            // Some producers write zero for these, which is not useful
+            match descriptor.get(names::ASCENT).map(number) {
+                Some(Ok(ascent)) if ascent != 0.0 => font.ascent = ascent,
+                _ => {}
+            }
+            match descriptor.get(names::DESCENT).map(number) {
+                Some(Ok(descent)) if descent != 0.0 => font.descent = descent,
+                _ => {}
+            }
+            #[cfg(feature = "outlines")]
+            for key in [names::FONT_FILE_2, names::FONT_FILE_3] {
+                if let Some(program) = descriptor.get(key) {
+                    if let Object::Stream(_, data) = file.resolve_owned(program)? {
+                        font.program = Some(data.into_owned());
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(font)
+    }
+
+    /// Loads the parts of a Type 3 font that differ from other simple fonts
+    /// (Adobe, 2008, p. 259-262).
+    fn load_type_3<'a>(&mut self, file: &'a PdfFile<'a>, dict: &Object<'a>) -> Result<()> {
+        if let Some(matrix) = dict.get(names::FONT_MATRIX) {
+            if let Object::Array(matrix) = file.resolve_owned(matrix)? {
+                self.font_matrix = Matrix::from_objects(&matrix)?;
+            }
+        }
+        if let Some(bbox) = dict.get(names::FONT_BBOX) {
+            let bbox = Rect::from_object(&file.resolve_owned(bbox)?)?;
+            if bbox.height() > 0.0 {
+                self.ascent = bbox.y1;
+                self.descent = bbox.y0;
+            }
+        }
+
+        let Some(char_procs) = dict.get(names::CHAR_PROCS) else {
+            return Ok(());
+        };
+        let char_procs = file.resolve_owned(char_procs)?;
+        for (&code, glyph_name) in &self.differences {
+            let Some(char_proc) = char_procs.get(glyph_name) else {
+                continue;
+            };
+            let Object::Stream(_, data) = file.resolve_owned(char_proc)? else {
+                continue;
+            };
+
+            // Glyph procedures start with d0 or d1, and d1 also gives the
+            // glyph's bounding box
+            let operators = parse_operators(&data, &ParseContext::default())?;
+            if let Some(operator) = operators.first() {
+                if let (b"d1", [_, _, llx, lly, urx, ury]) =
+                    (operator.operator, operator.operands.as_slice())
+                {
+                    let bbox = Rect::new(number(llx)?, number(lly)?, number(urx)?, number(ury)?);
+                    self.glyph_boxes.insert(code, bbox);
+                }
+            }
+            self.char_procs.insert(code, data.into_owned());
+        }
+
+        Ok(())
+    }
+
+    pub fn is_composite(&self) -> bool {
+        self.encoding.is_some()
+    }
+
+    /// Splits a string into character codes, returning each code and the
+    /// number of bytes it used.
+    pub fn codes(&self, bytes: &[u8]) -> Vec<(u32, usize)> {
+        let mut codes = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let (code, length) = match &self.encoding {
+                Some(encoding) => encoding.next_code(rest),
+                None => (rest[0] as u32, 1),
+            };
+            codes.push((code, length));
+            rest = &rest[length.min(rest.len())..];
+        }
+        codes
+    }
+
+    /// Returns the text for a character code, which is empty if it is unknown.
+    pub fn to_unicode(&self, code: u32) -> String {
+        if let Some(text) = self
+            .to_unicode
+            .as_ref()
+            .and_then(|cmap| cmap.to_unicode(code))
+        {
+            return text.into();
+        }
+        if self.is_composite() {
+            return String::new();
+        }
+        if let Some(glyph_name) = self.differences.get(&code) {
+            if let Some(c) = glyph_name_to_char(glyph_name) {
+                return c.into();
+            }
+        }
+
+        // Treat the standard encodings as Latin-1, which they mostly agree with
+        char::from_u32(code)
+            .filter(|c| !c.is_control())
+            .map(String::from)
+            .unwrap_or_default()
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.vertical
+    }
+
+    /// Maps a character code to the index of a glyph in an embedded TrueType
+    /// font, through the `/CIDToGIDMap` of a composite font (Adobe, 2008,
+    /// p. 270). Returns `None` for simple fonts.
+    pub fn to_gid(&self, code: u32) -> Option<u32> {
+        let cid = self.encoding.as_ref()?.to_cid(code);
+        match &self.cid_to_gid {
+            Some(map) => Some(map.get(cid as usize).copied().unwrap_or(0).into()),
+            None => Some(cid),
+        }
+    }
+
+    /// How a glyph is placed in vertical writing. Glyphs without metrics of
+    /// their own are centred horizontally (Adobe, 2008, p. 272).
+    pub fn vertical_metrics(&self, code: u32) -> VerticalMetrics {
+        let cid = match &self.encoding {
+            Some(encoding) => encoding.to_cid(code),
+            None => code,
+        };
+        self.cid_vertical_metrics
+            .get(&cid)
+            .copied()
+            .unwrap_or_else(|| VerticalMetrics {
+                w1y: self.default_vertical_metrics.1,
+                v: (self.width(code) / 2.0, self.default_vertical_metrics.0),
+            })
+    }
+
+    /// The content stream that draws a glyph of a Type 3 font.
+    pub fn char_proc(&self, code: u32) -> Option<&[u8]> {
+        self.char_procs.get(&code).map(Vec::as_slice)
+    }
+
+    /// The bounding box of a glyph in glyph space, which is the declared box
+    /// for Type 3 glyphs and otherwise spans the font's descent to its ascent.
+    pub fn glyph_bbox(&self, code: u32) -> Rect {
+        match self.glyph_boxes.get(&code) {
+            Some(&bbox) => bbox,
+            None => Rect::new(0.0, self.descent, self.width(code), self.ascent),
+        }
+    }
+
+    /// The outline of a glyph from the embedded font program, or `None` if the
+    /// font has no TrueType or CFF program or the glyph is empty.
+    #[cfg(feature = "outlines")]
+    pub fn glyph_outline(&self, code: u32) -> Option<GlyphOutline> {
+        let program = self.program.as_deref()?;
+        let text = self.to_unicode(code);
+        let mut chars = text.chars();
+        let lookup = GlyphLookup {
+            gid: self.to_gid(code),
+            name: self.differences.get(&code).map(String::as_str),
+            code,
+            unicode: chars.next().filter(|_| chars.next().is_none()),
+        };
+        outline::glyph_outline(program, &lookup)
+    }
+
+    /// The horizontal distance that a glyph moves the text position, in text
+    /// space units for a font size of one.
+    pub fn advance(&self, code: u32) -> f64 {
+        self.width(code) * self.font_matrix.a
+    }
+
+    /// Measures the horizontal advance of a string of character codes at a
+    /// font size, ignoring character and word spacing.
+    pub fn measure(&self, bytes: &[u8], size: f64) -> f64 {
+        self.measure_codes(self.codes(bytes).into_iter().map(|(code, _)| code), size)
+    }
+
+    pub fn measure_codes(&self, codes: impl IntoIterator<Item = u32>, size: f64) -> f64 {
+        codes
+            .into_iter()
+            .map(|code| self.advance(code))
+            .sum::<f64>()
+            * size
+    }
+
+    /// Like `measure`, but for text, which is mapped back to character codes
+    /// using the font's encoding. Characters that the font has no code for
+    /// are measured using the default width.
+    pub fn measure_text(&self, text: &str, size: f64) -> f64 {
+        let mut codes = HashMap::new();
+        if !self.is_composite() {
+            codes.extend((0..=255).filter_map(|code| {
+                let c = char::from_u32(code).filter(|c| !c.is_control())?;
+                Some((c, code))
+            }));
+            for (&code, glyph_name) in &self.differences {
+                if let Some(c) = glyph_name_to_char(glyph_name) {
+                    codes.insert(c, code);
+                }
+            }
+        }
+        if let Some(to_unicode) = &self.to_unicode {
+            for (code, text) in to_unicode.unicode_mappings() {
+                let mut chars = text.chars();
+                if let (Some(c), None) = (chars.next(), chars.next()) {
+                    codes.insert(c, code);
+                }
+            }
+        }
+
+        let advance = text
+            .chars()
+            .map(|c| match codes.get(&c) {
+                Some(&code) => self.advance(code),
+                None => self.default_width * self.font_matrix.a,
+            })
+            .sum::<f64>();
+        advance * size
+    }
+
+    /// Returns the width of a glyph, in glyph space units (thousandths of the
+    /// font size, except in Type 3 fonts).
+    pub fn width(&self, code: u32) -> f64 {
+        match &self.encoding {
+            Some(encoding) => {
+                let cid = encoding.to_cid(code);
+                self.cid_widths
+                    .get(&cid)
+                    .copied()
+                    .unwrap_or(self.default_width)
+            }
+            None => code
+                .checked_sub(self.first_char)
+                .and_then(|index| self.widths.get(index as usize))
+                .copied()
+                .unwrap_or(self.default_width),
+        }
+    }
+}
+
+/// Parses the `W` array of a CIDFont, which has entries of the form
+/// `c [w1 w2 ...]` or `c_first c_last w` (Adobe, 2008, p. 270).
+fn parse_cid_widths(widths: &Object) -> Result<HashMap<u32, f64>> {
+    let mut result = HashMap::new();
+    let Object::Array(widths) = widths else {
+        return Ok(result);
+    };
+
+    let mut i = 0;
+    while i + 1 < widths.len() {
+        let first = u32::try_from(widths[i].as_int()?).unwrap_or(0);
+        if let Object::Array(list) = &widths[i + 1] {
+            for (cid, width) in (first..).zip(list) {
+                result.insert(cid, number(width)?);
+            }
+            i += 2;
+        } else if let Some(width) = widths.get(i + 2) {
+            let last = u32::try_from(widths[i + 1].as_int()?).unwrap_or(0);
+            let width = number(width)?;
+            for cid in first..=last.min(first.saturating_add(0xFFFF)) {
+                result.insert(cid, width);
+            }
+            i += 3;
+        } else {
+            break;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Parses the `W2` array of a CIDFont, which has entries of the form
+/// `c [w1y v1x v1y ...]` or `c_first c_last w1y v1x v1y` (Adobe, 2008, p. 273).
+fn parse_cid_vertical_metrics(metrics: &Object) -> Result<HashMap<u32, VerticalMetrics>> {
+    let mut result = HashMap::new();
+    let Object::Array(metrics) = metrics else {
+        return Ok(result);
+    };
+
+    let to_metrics = |values: &[Object]| -> Result<VerticalMetrics> {
+        Ok(VerticalMetrics {
+            w1y: number(&values[0])?,
+            v: (number(&values[1])?, number(&values[2])?),
+        })
+    };
+
+    let mut i = 0;
+    while i + 1 < metrics.len() {
+        let first = u32::try_from(metrics[i].as_int()?).unwrap_or(0);
+        if let Object::Array(list) = &metrics[i + 1] {
+            for (cid, values) in (first..).zip(list.chunks_exact(3)) {
+                result.insert(cid, to_metrics(values)?);
+            }
+            i += 2;
+        } else if let Some(values) = metrics.get(i + 2..i + 5) {
+            let last = u32::try_from(metrics[i + 1].as_int()?).unwrap_or(0);
+            let values = to_metrics(values)?;
+            for cid in first..=last.min(first.saturating_add(0xFFFF)) {
+                result.insert(cid, values);
+            }
+            i += 5;
+        } else {
+            break;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Parses the `Differences` array of an encoding dictionary, which is a list
+/// of codes each followed by glyph names for consecutive codes
+/// (Adobe, 2008, p. 263).
+fn parse_differences(differences: &[Object]) -> HashMap<u32, String> {
+    let mut result = HashMap::new();
+    let mut code = 0;
+    for item in differences {
+        match item {
+            Object::Integer(start) => code = u32::try_from(*start).unwrap_or(0),
+            Object::Name(name) => {
+                result.insert(code, String::from_utf8_lossy(name).into_owned());
+                code += 1;
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Maps a glyph name to a character, for the names that can be worked out
+/// without the full Adobe Glyph List.
+fn glyph_name_to_char(name: &str) -> Option<char> {
+    let named = match name {
+        "space" => ' ',
+        "period" => '.',
+        "comma" => ',',
+        "hyphen" | "minus" => '-',
+        "colon" => ':',
+        "semicolon" => ';',
+        "quoteright" => '\u{2019}',
+        "quoteleft" => '\u{2018}',
+        "fi" => '\u{FB01}',
+        "fl" => '\u{FB02}',
+        "zero" => '0',
+        "one" => '1',
+        "two" => '2',
+        "three" => '3',
+        "four" => '4',
+        "five" => '5',
+        "six" => '6',
+        "seven" => '7',
+        "eight" => '8',
+        "nine" => '9',
+        _ => {
+            let mut chars = name.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => name
+                    .strip_prefix("uni")
+                    .filter(|hex| hex.len() == 4)
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32),
+            };
+        }
+    };
+    Some(named)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_load_simple_fonts() {
+        let mut file = PdfFile::read_file("./examples/hello-world.pdf").unwrap();
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let (_, font) = pages[0].resources_of(names::FONT).unwrap().remove(0);
+
+        let font = Font::load(&file, &font).unwrap();
+        assert!(!font.is_composite());
+        assert_eq!(font.to_unicode(1), "H");
+        assert_eq!(font.to_unicode(6), " ");
+        assert_eq!(font.codes(b"\x01\x02"), vec![(1, 1), (2, 1)]);
+        assert!(font.width(1) > 0.0);
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_measure_text() {
+        let mut file = PdfFile::read_file("./examples/hello-world.pdf").unwrap();
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let (_, font) = pages[0].resources_of(names::FONT).unwrap().remove(0);
+        let font = Font::load(&file, &font).unwrap();
+
+        let expected = (font.width(1) + font.width(2)) / 1000.0 * 12.0;
+        assert_eq!(font.measure(b"\x01\x02", 12.0), expected);
+        assert_eq!(font.measure_codes([1, 2], 12.0), expected);
+        assert_eq!(font.measure_text("He", 12.0), expected);
+
+        let default = Font {
+            default_width: 500.0,
+            ..Default::default()
+        };
+        assert_eq!(default.measure_text("ab", 10.0), 10.0);
+    }
+
+    #[cfg(all(feature = "outlines", any(feature = "miniz_oxide", feature = "flate2")))]
+    #[test]
+    fn should_read_glyph_outlines() {
+        use crate::fonts::outline::PathSegment;
+
+        let mut file = PdfFile::read_file("./examples/hello-world.pdf").unwrap();
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        let (_, font) = pages[0].resources_of(names::FONT).unwrap().remove(0);
+        let font = Font::load(&file, &font).unwrap();
+
+        // "H" is made of straight lines, and sits on the baseline
+        let outline = font.glyph_outline(1).unwrap();
+        assert!(matches!(outline.segments[0], PathSegment::MoveTo(_, _)));
+        assert!(outline.segments.contains(&PathSegment::Close));
+        assert_eq!(outline.bbox.y0, 0.0);
+        assert!(outline.bbox.x1 <= font.width(1));
+        assert!(outline.bbox.y1 > 600.0 && outline.bbox.y1 < font.ascent);
+
+        // The space has no outline
+        assert_eq!(font.glyph_outline(6), None);
+        assert_eq!(Font::default().glyph_outline(1), None);
+    }
+
+    #[test]
+    fn should_parse_cid_widths() {
+        let widths = Object::Array(vec![
+            Object::Integer(1),
+            Object::Array(vec![Object::Integer(500), Object::Integer(600)]),
+            Object::Integer(10),
+            Object::Integer(12),
+            Object::Integer(250),
+        ]);
+        let widths = parse_cid_widths(&widths).unwrap();
+        assert_eq!(widths.get(&2), Some(&600.0));
+        assert_eq!(widths.get(&12), Some(&250.0));
+        assert_eq!(widths.get(&13), None);
+    }
+
+    #[test]
+    fn should_parse_cid_vertical_metrics() {
+        let metrics = Object::Array(vec![
+            Object::Integer(1),
+            Object::Array(
+                [-1000, 500, 880, -900, 250, 800]
+                    .map(Object::Integer)
+                    .to_vec(),
+            ),
+            Object::Integer(10),
+            Object::Integer(12),
+            Object::Integer(-500),
+            Object::Integer(250),
+            Object::Integer(440),
+        ]);
+        let metrics = parse_cid_vertical_metrics(&metrics).unwrap();
+        assert_eq!(
+            metrics.get(&2),
+            Some(&VerticalMetrics {
+                w1y: -900.0,
+                v: (250.0, 800.0)
+            })
+        );
+        assert_eq!(metrics[&12].w1y, -500.0);
+        assert_eq!(metrics.get(&13), None);
+    }
+
+    #[test]
+    fn should_map_cids_to_glyphs() {
+        let mut font = Font {
+            encoding: Some(CMap::identity()),
+            ..Default::default()
+        };
+        assert_eq!(font.to_gid(7), Some(7));
+        font.cid_to_gid = Some(vec![0, 5, 9]);
+        assert_eq!(font.to_gid(2), Some(9));
+        assert_eq!(font.to_gid(3), Some(0));
+        assert_eq!(Font::default().to_gid(2), None);
+    }
+
+    #[test]
+    fn should_map_glyph_names() {
+        assert_eq!(glyph_name_to_char("A"), Some('A'));
+        assert_eq!(glyph_name_to_char("uni00E9"), Some('é'));
+        assert_eq!(glyph_name_to_char("space"), Some(' '));
+        assert_eq!(glyph_name_to_char("bullet.alt"), None);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod cmap;
+pub mod font;
+#[cfg(feature = "outlines")]
+pub mod outline;
+
+pub use font::Font;
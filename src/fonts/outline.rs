@@ -0,0 +1,151 @@
+use crate::geometry::Rect;
+use ttf_parser::{cff, GlyphId, OutlineBuilder, PlatformId};
+
+/// A part of the outline of a glyph, in glyph space units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathSegment {
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    /// A quadratic Bézier curve, as used by TrueType fonts.
+    QuadTo(f64, f64, f64, f64),
+    /// A cubic Bézier curve, as used by CFF fonts.
+    CurveTo(f64, f64, f64, f64, f64, f64),
+    Close,
+}
+
+/// The outline of a glyph from an embedded font program, scaled to glyph
+/// space (thousandths of text space) so that it can be drawn with the same
+/// matrix as the glyph's width.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphOutline {
+    pub segments: Vec<PathSegment>,
+    pub bbox: Rect,
+}
+
+/// The ways to find a glyph in a font program, in the order that they are
+/// tried (Adobe, 2008, p. 265).
+pub(crate) struct GlyphLookup<'a> {
+    /// The glyph index of a composite font, which is used on its own.
+    pub gid: Option<u32>,
+    /// The glyph name from the `/Differences` of a simple font.
+    pub name: Option<&'a str>,
+    pub code: u32,
+    pub unicode: Option<char>,
+}
+
+/// Reads the outline of a glyph from an embedded TrueType, OpenType or bare
+/// CFF font program. Type 1 programs are not supported.
+pub(crate) fn glyph_outline(program: &[u8], lookup: &GlyphLookup) -> Option<GlyphOutline> {
+    if let Ok(face) = ttf_parser::Face::parse(program, 0) {
+        let gid = match lookup.gid {
+            Some(gid) => Some(GlyphId(u16::try_from(gid).ok()?)),
+            None => simple_glyph_index(&face, lookup),
+        }?;
+        let scale = 1000.0 / face.units_per_em() as f64;
+        let mut builder = SegmentBuilder::new(scale);
+        let bbox = face.outline_glyph(gid, &mut builder)?;
+        return Some(builder.finish(bbox));
+    }
+
+    let table = cff::Table::parse(program)?;
+    let gid = match lookup.gid {
+        Some(gid) => GlyphId(u16::try_from(gid).ok()?),
+        None => lookup
+            .name
+            .and_then(|name| table.glyph_index_by_name(name))
+            .or_else(|| table.glyph_index(u8::try_from(lookup.code).ok()?))?,
+    };
+    let mut builder = SegmentBuilder::new(table.matrix().sx as f64 * 1000.0);
+    let bbox = table.outline(gid, &mut builder).ok()?;
+    Some(builder.finish(bbox))
+}
+
+/// Finds the glyph for a code of a simple TrueType font, using the glyph name
+/// or whichever `cmap` subtable the font has (Adobe, 2008, p. 265).
+fn simple_glyph_index(face: &ttf_parser::Face, lookup: &GlyphLookup) -> Option<GlyphId> {
+    if let Some(gid) = lookup.name.and_then(|name| face.glyph_index_by_name(name)) {
+        return Some(gid);
+    }
+    if let Some(gid) = lookup.unicode.and_then(|c| face.glyph_index(c)) {
+        return Some(gid);
+    }
+
+    let subtables = face.tables().cmap?.subtables;
+    subtables.into_iter().find_map(|subtable| {
+        match (subtable.platform_id, subtable.encoding_id) {
+            // Symbolic fonts map codes into the private use area
+            (PlatformId::Windows, 0) => subtable
+                .glyph_index(0xF000 + lookup.code)
+                .or_else(|| subtable.glyph_index(lookup.code)),
+            (PlatformId::Macintosh, 0) => subtable.glyph_index(lookup.code),
+            _ => None,
+        }
+    })
+}
+
+struct SegmentBuilder {
+    scale: f64,
+    segments: Vec<PathSegment>,
+}
+
+impl SegmentBuilder {
+    fn new(scale: f64) -> Self {
+        Self {
+            scale,
+            segments: Vec::new(),
+        }
+    }
+
+    fn finish(self, bbox: ttf_parser::Rect) -> GlyphOutline {
+        let s = self.scale;
+        GlyphOutline {
+            segments: self.segments,
+            bbox: Rect::new(
+                bbox.x_min as f64 * s,
+                bbox.y_min as f64 * s,
+                bbox.x_max as f64 * s,
+                bbox.y_max as f64 * s,
+            ),
+        }
+    }
+}
+
+impl OutlineBuilder for SegmentBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let s = self.scale;
+        self.segments
+            .push(PathSegment::MoveTo(x as f64 * s, y as f64 * s));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let s = self.scale;
+        self.segments
+            .push(PathSegment::LineTo(x as f64 * s, y as f64 * s));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let s = self.scale;
+        self.segments.push(PathSegment::QuadTo(
+            x1 as f64 * s,
+            y1 as f64 * s,
+            x as f64 * s,
+            y as f64 * s,
+        ));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let s = self.scale;
+        self.segments.push(PathSegment::CurveTo(
+            x1 as f64 * s,
+            y1 as f64 * s,
+            x2 as f64 * s,
+            y2 as f64 * s,
+            x as f64 * s,
+            y as f64 * s,
+        ));
+    }
+
+    fn close(&mut self) {
+        self.segments.push(PathSegment::Close);
+    }
+}
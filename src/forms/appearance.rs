@@ -0,0 +1,586 @@
+//! Generates the appearances of form fields from their values, for viewers
+//! that only show what is in `/AP` (Adobe, 2008, p. 409-410, 439-440).
+
+use super::{Field, COMB, MULTILINE, PASSWORD};
+use crate::annotations::appearance::Appearance;
+use crate::content::operators::parse_operators;
+use crate::document::dicts::Catalog;
+use crate::error::Result;
+use crate::fonts::font::Font;
+use crate::geometry::{number, number_object, Rect};
+use crate::objects::{names, Dictionary, Name, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::borrow::Cow;
+
+/// The font size that automatically sized text starts from, and that other
+/// text is drawn at for a default appearance of `0 Tf`.
+const AUTO_FONT_SIZE: f64 = 12.0;
+
+/// The smallest size that text is shrunk to, so that it stays legible.
+const MIN_AUTO_FONT_SIZE: f64 = 4.0;
+
+/// The space left between the border of a field and its text.
+const PADDING: f64 = 2.0;
+
+/// A parsed default appearance string, such as `/Helv 12 Tf 0 g`
+/// (Adobe, 2008, p. 439).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefaultAppearance {
+    /// The name of the font in the form's resources.
+    pub font: String,
+    /// The font size, where 0 means that the text is sized to fit the field.
+    pub size: f64,
+    /// The text colour, as gray, RGB or CMYK components.
+    pub color: Vec<f64>,
+}
+
+impl Default for DefaultAppearance {
+    fn default() -> Self {
+        Self {
+            font: "Helv".into(),
+            size: 0.0,
+            color: vec![0.0],
+        }
+    }
+}
+
+impl DefaultAppearance {
+    /// Reads the font and colour operators of a default appearance string,
+    /// ignoring any others.
+    pub fn parse(default_appearance: &str) -> Result<Self> {
+        let mut result = Self::default();
+        let operators = parse_operators(default_appearance.as_bytes(), &Default::default())?;
+        for operator in operators {
+            match (operator.operator, operator.operands.as_slice()) {
+                (b"Tf", [Object::Name(font), size]) => {
+                    result.font = String::from_utf8_lossy(font).into_owned();
+                    result.size = number(size)?;
+                }
+                (b"g" | b"rg" | b"k", components) => {
+                    result.color = components.iter().map(number).collect::<Result<_>>()?;
+                }
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// The size to draw text at where it is not sized to fit, which
+    /// replaces automatic sizing with a fixed size.
+    pub fn font_size(&self) -> f64 {
+        if self.size > 0.0 {
+            self.size
+        } else {
+            AUTO_FONT_SIZE
+        }
+    }
+}
+
+/// How a widget's background and border are drawn, from its appearance
+/// characteristics and border style (Adobe, 2008, p. 386, 409).
+#[derive(Clone, Debug, PartialEq)]
+pub struct WidgetStyle {
+    /// The size of the widget, from its `/Rect`.
+    pub width: f64,
+    pub height: f64,
+    /// The background colour, which is empty for none.
+    pub background: Vec<f64>,
+    /// The border colour, which is empty for none.
+    pub border_color: Vec<f64>,
+    pub border_width: f64,
+    /// The border style, such as `S` for solid, `D` for dashed or `U` for
+    /// underlined.
+    pub border_style: String,
+    pub dash: Vec<f64>,
+    /// The caption of a push button.
+    pub caption: Option<String>,
+}
+
+impl WidgetStyle {
+    pub fn from_widget(file: &PdfFile, widget: &Object) -> Result<Self> {
+        let rect = Rect::from_object(&file.resolve_owned(widget.get_required(names::RECT)?)?)?;
+        let characteristics = match widget.get(names::MK) {
+            Some(mk) => file.resolve_owned(mk)?,
+            None => Object::Null,
+        };
+        let border = match widget.get(names::BS) {
+            Some(bs) => file.resolve_owned(bs)?,
+            None => Object::Null,
+        };
+        let numbers = |object: Option<&Object>| -> Result<Vec<f64>> {
+            match object {
+                Some(object) => file
+                    .resolve_owned(object)?
+                    .into_iter()
+                    .map(number)
+                    .collect(),
+                None => Ok(Vec::new()),
+            }
+        };
+
+        Ok(Self {
+            width: rect.width(),
+            height: rect.height(),
+            background: numbers(characteristics.get(names::BG))?,
+            border_color: numbers(characteristics.get(names::BC))?,
+            border_width: match border.get(names::W) {
+                Some(width) => number(width)?,
+                None => 1.0,
+            },
+            border_style: match border.get(names::S) {
+                Some(Object::Name(style)) => String::from_utf8_lossy(style).into_owned(),
+                _ => "S".into(),
+            },
+            dash: match border.get(names::D) {
+                Some(dash) => numbers(Some(dash))?,
+                None => vec![3.0],
+            },
+            caption: characteristics.get_text(names::CAPTION).ok(),
+        })
+    }
+
+    /// The area inside the border, where the widget's content goes.
+    pub fn content_rect(&self) -> Rect {
+        let inset = self.border_width;
+        Rect::new(inset, inset, self.width - inset, self.height - inset)
+    }
+
+    /// Starts an appearance with the widget's background and border.
+    pub fn frame<'a>(&self) -> Appearance<'a> {
+        let mut appearance = Appearance::new(self.width, self.height);
+        let bbox = appearance.bbox;
+        if appearance.set_color(&self.background, false) {
+            appearance.fill_rect(bbox);
+        }
+        if self.border_width > 0.0 && appearance.set_color(&self.border_color, true) {
+            match self.border_style.as_str() {
+                "U" => {
+                    let y = self.border_width / 2.0;
+                    appearance.push_numbers(b"w", &[self.border_width]);
+                    appearance.push_numbers(b"m", &[0.0, y]);
+                    appearance.push_numbers(b"l", &[self.width, y]);
+                    appearance.push(b"S", vec![]);
+                }
+                "D" => {
+                    let dash = self.dash.iter().map(|&n| number_object(n)).collect();
+                    appearance.push(b"d", vec![Object::Array(dash), Object::Integer(0)]);
+                    appearance.stroke_rect(bbox, self.border_width);
+                    appearance.push(b"d", vec![Object::Array(vec![]), Object::Integer(0)]);
+                }
+                _ => appearance.stroke_rect(bbox, self.border_width),
+            }
+        }
+        appearance
+    }
+}
+
+/// A font from the form's default resources, along with its metrics.
+#[derive(Clone, Debug)]
+pub struct FieldFont<'a> {
+    pub name: Name<'a>,
+    /// The font dictionary, or a reference to it.
+    pub resource: Object<'a>,
+    pub metrics: Font,
+}
+
+impl<'a> FieldFont<'a> {
+    /// Looks up a font in the `/DR` entry of the form, falling back to
+    /// Helvetica if it is not there.
+    pub fn load(file: &'a PdfFile<'a>, name: &str) -> Result<Self> {
+        let resources = match Catalog::from_file(file)?.acro_form()? {
+            Some(acro_form) => match acro_form.get(names::DR) {
+                Some(resources) => file.resolve_owned(resources)?,
+                None => Object::Null,
+            },
+            None => Object::Null,
+        };
+        let fonts = match resources.get(names::FONT) {
+            Some(fonts) => file.resolve_owned(fonts)?,
+            None => Object::Null,
+        };
+
+        let resource = match fonts.get(name) {
+            Some(font) => font.clone(),
+            None => helvetica(),
+        };
+        let metrics = Font::load(file, &file.resolve_owned(&resource)?)?;
+        Ok(Self {
+            name: Name(Cow::Owned(name.as_bytes().to_vec())),
+            resource,
+            metrics,
+        })
+    }
+
+    /// Encodes text for a simple font with a Latin encoding, replacing
+    /// characters that it cannot show with question marks.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        text.chars()
+            .map(|c| u8::try_from(c).unwrap_or(b'?'))
+            .collect()
+    }
+
+    pub fn measure(&self, text: &str, size: f64) -> f64 {
+        self.metrics.measure_text(text, size)
+    }
+
+    /// The distance from the bottom of a line to its baseline.
+    pub fn descent(&self, size: f64) -> f64 {
+        -self.metrics.descent * size / 1000.0
+    }
+
+    /// The height of a line, from the lowest descender to the highest
+    /// ascender.
+    pub fn line_height(&self, size: f64) -> f64 {
+        (self.metrics.ascent - self.metrics.descent) * size / 1000.0
+    }
+}
+
+fn helvetica<'a>() -> Object<'a> {
+    let dict = [
+        (names::TYPE, names::FONT),
+        (names::SUBTYPE, "Type1"),
+        (names::BASE_FONT, "Helvetica"),
+        (names::ENCODING, "WinAnsiEncoding"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (Name::from(key), Object::Name(Name::from(value))))
+    .collect::<Dictionary>();
+    Object::Dictionary(dict)
+}
+
+/// Starts the text of a field, marked as variable text and clipped to the
+/// inside of its border (Adobe, 2008, p. 440).
+fn begin_text<'a>(
+    appearance: &mut Appearance<'a>,
+    style: &WidgetStyle,
+    default_appearance: &DefaultAppearance,
+    font: &FieldFont<'a>,
+    size: f64,
+) {
+    let clip = style.content_rect();
+    appearance.add_resource(names::FONT, font.name.clone(), font.resource.clone());
+    appearance.push(b"BMC", vec![Object::Name(Name::from("Tx"))]);
+    appearance.push(b"q", vec![]);
+    appearance.push_numbers(b"re", &[clip.x0, clip.y0, clip.width(), clip.height()]);
+    appearance.push(b"W", vec![]);
+    appearance.push(b"n", vec![]);
+    appearance.push(b"BT", vec![]);
+    appearance.push(
+        b"Tf",
+        vec![Object::Name(font.name.clone()), number_object(size)],
+    );
+    appearance.set_color(&default_appearance.color, false);
+}
+
+fn end_text(appearance: &mut Appearance) {
+    appearance.push(b"ET", vec![]);
+    appearance.push(b"Q", vec![]);
+    appearance.push(b"EMC", vec![]);
+}
+
+/// Shows lines of text, each at its own position.
+fn show_lines<'a>(
+    appearance: &mut Appearance<'a>,
+    font: &FieldFont<'a>,
+    lines: &[(String, f64, f64)],
+) {
+    // Each `Td` moves relative to the start of the previous line
+    let (mut x0, mut y0) = (0.0, 0.0);
+    for (text, x, y) in lines {
+        appearance.push_numbers(b"Td", &[x - x0, y - y0]);
+        appearance.push(b"Tj", vec![Object::String(Cow::Owned(font.encode(text)))]);
+        (x0, y0) = (*x, *y);
+    }
+}
+
+/// How the text of a field is laid out, from its flags, `/Q` and `/MaxLen`
+/// (Adobe, 2008, p. 439, 448).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextLayout {
+    /// The alignment of the text: 0 for left, 1 for centred and 2 for right.
+    pub quadding: i64,
+    pub multiline: bool,
+    /// The number of cells in a comb field, which spaces its characters
+    /// evenly across the field.
+    pub comb: Option<i64>,
+}
+
+impl TextLayout {
+    pub fn from_field(field: &Field) -> Self {
+        let multiline = field.has_flag(MULTILINE);
+        let comb = match field.has_flag(COMB) && !multiline && !field.has_flag(PASSWORD) {
+            true => field.max_len.filter(|&cells| cells > 0),
+            false => None,
+        };
+        Self {
+            quadding: field.quadding,
+            multiline,
+            comb,
+        }
+    }
+
+    /// The horizontal position of a line of the given width.
+    fn align(&self, inside: Rect, width: f64) -> f64 {
+        match self.quadding {
+            1 => inside.x0 + (inside.width() - width) / 2.0,
+            2 => inside.x1 - PADDING - width,
+            _ => inside.x0 + PADDING,
+        }
+    }
+}
+
+/// Breaks text into lines that fit a width, at its line breaks and at spaces.
+/// Words that are wider than a line are left to be clipped.
+pub(crate) fn wrap(font: &FieldFont, text: &str, size: f64, width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.replace("\r\n", "\n").split(['\r', '\n']) {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = match line.is_empty() {
+                true => word.to_owned(),
+                false => format!("{} {}", line, word),
+            };
+            if line.is_empty() || font.measure(&candidate, size) <= width {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_owned()));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// The largest font size at which a line of text fits in a box, for a
+/// default appearance of `0 Tf`, given the width of the text at size 1.
+fn fit_line(font: &FieldFont, text_width: f64, width: f64, height: f64) -> f64 {
+    let mut size = height / font.line_height(1.0);
+    if text_width > 0.0 {
+        size = size.min(width / text_width);
+    }
+    size.max(MIN_AUTO_FONT_SIZE)
+}
+
+/// The appearance of a text or choice field, which shows its text on one line
+/// centred vertically, wrapped from the top of a multiline field, or one
+/// character per cell of a comb field.
+pub fn text_appearance<'a>(
+    style: &WidgetStyle,
+    default_appearance: &DefaultAppearance,
+    font: &FieldFont<'a>,
+    layout: &TextLayout,
+    text: &str,
+) -> Appearance<'a> {
+    let mut appearance = style.frame();
+    let inside = style.content_rect();
+    let (width, height) = (
+        inside.width() - 2.0 * PADDING,
+        inside.height() - 2.0 * PADDING,
+    );
+    let auto = default_appearance.size <= 0.0;
+
+    let mut lines = Vec::new();
+    let size = if layout.multiline {
+        let mut size = default_appearance.font_size();
+        let mut wrapped = wrap(font, text, size, width);
+        while auto
+            && size > MIN_AUTO_FONT_SIZE
+            && wrapped.len() as f64 * font.line_height(size) > height
+        {
+            size -= 0.5;
+            wrapped = wrap(font, text, size, width);
+        }
+
+        let top = inside.y1 - PADDING - font.line_height(size) + font.descent(size);
+        for (i, line) in wrapped.into_iter().enumerate() {
+            let x = layout.align(inside, font.measure(&line, size));
+            let y = top - i as f64 * font.line_height(size);
+            lines.push((line, x, y));
+        }
+        size
+    } else {
+        let text = text.replace(['\r', '\n'], " ");
+        let size = match (auto, layout.comb) {
+            (false, _) => default_appearance.size,
+            (true, None) => fit_line(font, font.measure(&text, 1.0), width, height),
+            (true, Some(cells)) => {
+                let widest = text
+                    .chars()
+                    .map(|c| font.measure(&c.to_string(), 1.0))
+                    .fold(0.0, f64::max);
+                fit_line(font, widest, style.width / cells as f64, height)
+            }
+        };
+
+        let y = (style.height - font.line_height(size)) / 2.0 + font.descent(size);
+        match layout.comb {
+            Some(cells) => {
+                let cell = style.width / cells as f64;
+                for (i, c) in text.chars().take(cells as usize).enumerate() {
+                    let c = c.to_string();
+                    let x = i as f64 * cell + (cell - font.measure(&c, size)) / 2.0;
+                    lines.push((c, x, y));
+                }
+            }
+            None => {
+                let x = layout.align(inside, font.measure(&text, size));
+                lines.push((text, x, y));
+            }
+        }
+        size
+    };
+
+    begin_text(&mut appearance, style, default_appearance, font, size);
+    show_lines(&mut appearance, font, &lines);
+    end_text(&mut appearance);
+    appearance
+}
+
+/// The appearance of a push button, with its caption centred.
+pub fn pushbutton_appearance<'a>(
+    style: &WidgetStyle,
+    default_appearance: &DefaultAppearance,
+    font: &FieldFont<'a>,
+) -> Appearance<'a> {
+    let Some(caption) = &style.caption else {
+        return style.frame();
+    };
+    let mut appearance = style.frame();
+    let size = default_appearance.font_size();
+    begin_text(&mut appearance, style, default_appearance, font, size);
+
+    let x = (style.width - font.measure(caption, size)) / 2.0;
+    let y = (style.height - font.line_height(size)) / 2.0 + font.descent(size);
+    show_lines(&mut appearance, font, &[(caption.clone(), x, y)]);
+    end_text(&mut appearance);
+    appearance
+}
+
+/// The appearance of a check box in its on or off state, which is a check
+/// mark drawn in the text colour.
+pub fn check_appearance<'a>(
+    style: &WidgetStyle,
+    default_appearance: &DefaultAppearance,
+    on: bool,
+) -> Appearance<'a> {
+    let mut appearance = style.frame();
+    if !on {
+        return appearance;
+    }
+
+    let inside = style.content_rect();
+    let size = inside.width().min(inside.height()) - 2.0 * PADDING;
+    let (x, y) = (
+        inside.x0 + (inside.width() - size) / 2.0,
+        inside.y0 + (inside.height() - size) / 2.0,
+    );
+    if !appearance.set_color(&default_appearance.color, true) {
+        appearance.set_color(&[0.0], true);
+    }
+    appearance.push_numbers(b"w", &[size / 8.0]);
+    appearance.push_numbers(b"m", &[x + 0.15 * size, y + 0.5 * size]);
+    appearance.push_numbers(b"l", &[x + 0.4 * size, y + 0.2 * size]);
+    appearance.push_numbers(b"l", &[x + 0.85 * size, y + 0.85 * size]);
+    appearance.push(b"S", vec![]);
+    appearance
+}
+
+/// The appearance of a radio button in its on or off state, which is a dot
+/// drawn in the text colour.
+pub fn radio_appearance<'a>(
+    style: &WidgetStyle,
+    default_appearance: &DefaultAppearance,
+    on: bool,
+) -> Appearance<'a> {
+    let mut appearance = style.frame();
+    if !on {
+        return appearance;
+    }
+
+    let inside = style.content_rect();
+    let radius = inside.width().min(inside.height()) / 4.0;
+    let (x, y) = ((inside.x0 + inside.x1) / 2.0, (inside.y0 + inside.y1) / 2.0);
+    if !appearance.set_color(&default_appearance.color, false) {
+        appearance.set_color(&[0.0], false);
+    }
+    appearance.ellipse(Rect::new(x - radius, y - radius, x + radius, y + radius));
+    appearance.push(b"f", vec![]);
+    appearance
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::form;
+
+    fn style() -> WidgetStyle {
+        WidgetStyle {
+            width: 100.0,
+            height: 40.0,
+            background: Vec::new(),
+            border_color: Vec::new(),
+            border_width: 1.0,
+            border_style: "S".into(),
+            dash: Vec::new(),
+            caption: None,
+        }
+    }
+
+    /// The operands of each operator with the given name, as numbers.
+    fn operands(appearance: &Appearance, operator: &[u8]) -> Vec<Vec<f64>> {
+        appearance
+            .operators
+            .iter()
+            .filter(|op| op.operator == operator)
+            .map(|op| op.operands.iter().filter_map(|o| number(o).ok()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn should_lay_out_field_text() {
+        let mut file = PdfFile::from_raw(form());
+        file.load_xref_table().unwrap();
+        let font = FieldFont::load(&file, "Helv").unwrap();
+        let fixed = DefaultAppearance::parse("/Helv 10 Tf 0 g").unwrap();
+        let auto = DefaultAppearance::parse("/Helv 0 Tf 0 g").unwrap();
+        assert_eq!(fixed.size, 10.0);
+
+        let multiline = TextLayout {
+            multiline: true,
+            ..Default::default()
+        };
+        let text = "The quick brown fox jumps over the lazy dog";
+        let appearance = text_appearance(&style(), &fixed, &font, &multiline, text);
+        let moves = operands(&appearance, b"Td");
+        assert!(moves.len() > 1);
+        assert_eq!(moves[1], [0.0, -font.line_height(10.0)]);
+
+        let right = TextLayout {
+            quadding: 2,
+            ..Default::default()
+        };
+        let appearance = text_appearance(&style(), &fixed, &font, &right, "Hi");
+        let x = operands(&appearance, b"Td")[0][0];
+        assert!((x + font.measure("Hi", 10.0) - 97.0).abs() < 0.01);
+
+        let comb = TextLayout {
+            comb: Some(5),
+            ..Default::default()
+        };
+        let appearance = text_appearance(&style(), &fixed, &font, &comb, "123456");
+        let moves = operands(&appearance, b"Td");
+        assert_eq!(moves.len(), 5);
+        assert!((moves[0][0] + font.measure("1", 10.0) / 2.0 - 10.0).abs() < 0.01);
+        assert_eq!(moves[1][0], 20.0);
+
+        let appearance = text_appearance(&style(), &auto, &font, &Default::default(), "Hi");
+        let size = operands(&appearance, b"Tf")[0][0];
+        assert!((font.line_height(size) - 34.0).abs() < 0.01, "{}", size);
+
+        let long = text.repeat(4);
+        let appearance = text_appearance(&style(), &auto, &font, &multiline, &long);
+        let size = operands(&appearance, b"Tf")[0][0];
+        let lines = operands(&appearance, b"Tj").len() as f64;
+        assert!(size < 12.0 && lines * font.line_height(size) <= 34.0);
+    }
+}
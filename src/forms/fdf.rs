@@ -0,0 +1,411 @@
+//! Exchanges form data with other tools as FDF (Adobe, 2008, p. 464-475) or
+//! XFDF, without rewriting the document.
+
+use super::{Field, FieldType};
+use crate::error::{Error, Result};
+use crate::objects::{names, Dictionary, Name, Object};
+use crate::parsing::context::ParseOptions;
+use crate::parsing::pdf_file::PdfFile;
+use crate::utils::text::encode_text_string;
+#[cfg(feature = "write")]
+use crate::writing::incremental::IncrementalUpdate;
+use crate::writing::objects::{write_object, WriteOptions};
+use std::borrow::Cow;
+
+/// The value of one field, as exchanged in form data. Check boxes and radio
+/// buttons have the name of their selected state, or an export value when
+/// imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldData {
+    /// The fully qualified name of the field.
+    pub name: String,
+    pub value: String,
+}
+
+/// The values of the fields that have one, leaving out push buttons and
+/// signatures.
+pub fn form_data(file: &PdfFile) -> Result<Vec<FieldData>> {
+    let mut data = Vec::new();
+    for field in file.fields()? {
+        if !holds_value(&field) {
+            continue;
+        }
+        if let Some(value) = field.text() {
+            data.push(FieldData {
+                name: field.name,
+                value,
+            });
+        }
+    }
+    Ok(data)
+}
+
+fn holds_value(field: &Field) -> bool {
+    match field.field_type {
+        Some(FieldType::Text | FieldType::Choice) => true,
+        Some(FieldType::Button) => !field.is_pushbutton(),
+        _ => false,
+    }
+}
+
+/// Writes the values of a document's fields as an FDF file, which names each
+/// field by its fully qualified name.
+pub fn export_fdf(file: &PdfFile) -> Result<Vec<u8>> {
+    let mut fields = Vec::new();
+    for field in file.fields()? {
+        if !holds_value(&field) {
+            continue;
+        }
+        let Some(value) = field.value else {
+            continue;
+        };
+        let entries = [
+            (
+                Name::from(names::T),
+                Object::String(Cow::Owned(encode_text_string(&field.name))),
+            ),
+            (Name::from(names::V), value),
+        ];
+        fields.push(Object::Dictionary(entries.into_iter().collect()));
+    }
+    let fdf = [(Name::from(names::FIELDS), Object::Array(fields))]
+        .into_iter()
+        .collect::<Dictionary>();
+    let catalog = [(Name::from(names::FDF), Object::Dictionary(fdf))]
+        .into_iter()
+        .collect::<Dictionary>();
+
+    // FDF files need neither an xref table nor a file identifier (Adobe,
+    // 2008, p. 464-465)
+    let mut out = b"%FDF-1.2\n%\xe2\xe3\xcf\xd3\n1 0 obj\n".to_vec();
+    write_object(
+        &mut out,
+        &Object::Dictionary(catalog),
+        &WriteOptions::default(),
+    )?;
+    out.extend_from_slice(b"\nendobj\ntrailer\n<</Root 1 0 R>>\n%%EOF\n");
+    Ok(out)
+}
+
+/// Reads the field values from an FDF file, joining the partial names of
+/// nested fields with periods.
+pub fn import_fdf(data: &[u8]) -> Result<Vec<FieldData>> {
+    // FDF files usually have no xref table, so the objects are found by
+    // scanning, and the catalog is whichever one has an `/FDF` entry
+    let mut file = PdfFile::from_bytes(data).with_options(ParseOptions::lenient());
+    file.load_xref_table()?;
+    let mut fdf = None;
+    for (reference, _) in file.xref_entries()? {
+        if let Ok(object) = file.object(reference) {
+            if let Some(entry) = object.get(names::FDF) {
+                fdf = Some(file.resolve_owned(entry)?);
+                break;
+            }
+        }
+    }
+    let fdf = fdf.ok_or_else(|| Error::Type("No FDF dictionary".into()))?;
+
+    let mut data = Vec::new();
+    if let Some(fields) = fdf.get(names::FIELDS) {
+        for field in &file.resolve_owned(fields)? {
+            collect_fdf_fields(&file, field, "", 0, &mut data)?;
+        }
+    }
+    Ok(data)
+}
+
+/// The deepest nesting of fields that is read, which stops reference cycles.
+const MAX_DEPTH: usize = 32;
+
+fn collect_fdf_fields(
+    file: &PdfFile,
+    field: &Object,
+    parent: &str,
+    depth: usize,
+    data: &mut Vec<FieldData>,
+) -> Result<()> {
+    if depth > MAX_DEPTH {
+        return Err(Error::Syntax(
+            "FDF fields are nested too deeply",
+            parent.into(),
+        ));
+    }
+    let field = file.resolve_owned(field)?;
+    let partial = field.get_text(names::T)?;
+    let name = match parent {
+        "" => partial,
+        parent => format!("{}.{}", parent, partial),
+    };
+
+    if let Some(value) = field.get(names::V) {
+        let value = match file.resolve_owned(value)? {
+            Object::Name(state) => String::from_utf8_lossy(&state).into_owned(),
+            Object::Array(values) => match values.first() {
+                Some(value) => file.resolve_owned(value)?.as_text()?,
+                None => String::new(),
+            },
+            value => value.as_text()?,
+        };
+        data.push(FieldData {
+            name: name.clone(),
+            value,
+        });
+    }
+    if let Some(kids) = field.get(names::KIDS) {
+        for kid in &file.resolve_owned(kids)? {
+            collect_fdf_fields(file, kid, &name, depth + 1, data)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the values of a document's fields as XFDF, nesting each field
+/// inside the fields that its name is qualified by.
+pub fn export_xfdf(file: &PdfFile) -> Result<String> {
+    let mut xml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<xfdf xmlns=\"http://ns.adobe.com/xfdf/\" xml:space=\"preserve\">\n",
+        "<fields>\n",
+    ));
+    let data = form_data(file)?;
+    let mut open: Vec<&str> = Vec::new();
+    for field in &data {
+        let parts = field.name.split('.').collect::<Vec<_>>();
+        let shared = open
+            .iter()
+            .zip(&parts)
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(parts.len() - 1);
+        for _ in shared..open.len() {
+            xml.push_str("</field>\n");
+        }
+        open.truncate(shared);
+        for part in &parts[shared..parts.len() - 1] {
+            xml.push_str(&format!("<field name=\"{}\">\n", escape_xml(part)));
+            open.push(part);
+        }
+        xml.push_str(&format!(
+            "<field name=\"{}\"><value>{}</value></field>\n",
+            escape_xml(parts[parts.len() - 1]),
+            escape_xml(&field.value),
+        ));
+    }
+    for _ in open {
+        xml.push_str("</field>\n");
+    }
+    xml.push_str("</fields>\n</xfdf>\n");
+    Ok(xml)
+}
+
+/// Reads the field values from XFDF, joining the names of nested `field`
+/// elements with periods. This is a plain scan of the tags rather than a full
+/// XML parser, and a field with several values keeps only the last.
+pub fn import_xfdf(xml: &str) -> Result<Vec<FieldData>> {
+    let mut data = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let end = rest
+            .find('>')
+            .ok_or_else(|| Error::Syntax("Unterminated XFDF tag", rest.into()))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with("!--") && !tag.ends_with("--") {
+            // The comment holds a `>`, so it ends further on
+            let close = rest.find("-->").map_or(rest.len(), |i| i + 3);
+            rest = &rest[close..];
+        } else if tag.starts_with("!--") {
+        } else if tag == "/field" {
+            names.pop();
+        } else if tag.split([' ', '\t', '\r', '\n', '/']).next() == Some("field") {
+            let name = attribute(tag, "name")
+                .ok_or_else(|| Error::Syntax("XFDF field has no name", tag.into()))?;
+            if !tag.ends_with('/') {
+                names.push(unescape_xml(name));
+            }
+        } else if tag == "value" {
+            let close = rest
+                .find("</value>")
+                .ok_or_else(|| Error::Syntax("Unterminated XFDF value", rest.into()))?;
+            let value = unescape_xml(&rest[..close]);
+            rest = &rest[close..];
+
+            let name = names.join(".");
+            match data.iter_mut().find(|f: &&mut FieldData| f.name == name) {
+                Some(field) => field.value = value,
+                None => data.push(FieldData { name, value }),
+            }
+        }
+    }
+    Ok(data)
+}
+
+/// Finds the value of an attribute in the text of a start tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(i) = rest.find(name) {
+        let before = rest[..i].chars().next_back();
+        let after = rest[i + name.len()..].trim_start();
+        rest = &rest[i + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(after) = after.strip_prefix('=') else {
+            continue;
+        };
+        let after = after.trim_start();
+        let quote = after.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let value = &after[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_xml(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|d| d.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            // Leave anything that is not an entity as it is
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[cfg(feature = "write")]
+impl<'a> IncrementalUpdate<'a> {
+    /// Sets the values of fields from imported form data, regenerating their
+    /// appearances. Returns an error if the document has no field with one of
+    /// the names.
+    pub fn fill_form(&mut self, data: &[FieldData]) -> Result<()> {
+        let fields = self.file().fields()?;
+        for entry in data {
+            let field = fields
+                .iter()
+                .find(|field| field.name == entry.name)
+                .ok_or_else(|| Error::Type(format!("No field named {:?}", entry.name)))?;
+            self.set_field_value(field, &entry.value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::form;
+
+    fn filled() -> Vec<u8> {
+        let mut file = PdfFile::from_raw(form());
+        file.load_xref_table().unwrap();
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        update
+            .fill_form(&[
+                FieldData {
+                    name: "name".into(),
+                    value: "Ada & <Co>".into(),
+                },
+                FieldData {
+                    name: "colour".into(),
+                    value: "Blue".into(),
+                },
+            ])
+            .unwrap();
+        update.to_bytes().unwrap()
+    }
+
+    #[test]
+    fn should_round_trip_fdf() {
+        let mut file = PdfFile::from_raw(filled());
+        file.load_xref_table().unwrap();
+        let fdf = export_fdf(&file).unwrap();
+        assert!(fdf.starts_with(b"%FDF-1.2"));
+
+        let data = import_fdf(&fdf).unwrap();
+        assert_eq!(data, form_data(&file).unwrap());
+        assert_eq!(data[0].value, "Ada & <Co>");
+        assert_eq!(data[1].value, "1");
+
+        let nested = b"%FDF-1.2\n1 0 obj\n<< /FDF << /Fields [<< /T (a) /Kids [<< /T (b) /V /Yes >>] >>] >> >>\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF\n";
+        assert_eq!(
+            import_fdf(nested).unwrap(),
+            [FieldData {
+                name: "a.b".into(),
+                value: "Yes".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_round_trip_xfdf() {
+        let mut file = PdfFile::from_raw(filled());
+        file.load_xref_table().unwrap();
+        let xfdf = export_xfdf(&file).unwrap();
+        assert!(xfdf.contains("<field name=\"name\"><value>Ada &amp; &lt;Co&gt;</value></field>"));
+        assert_eq!(import_xfdf(&xfdf).unwrap(), form_data(&file).unwrap());
+
+        let nested = r#"<xfdf><fields><field name="a"><!-- <field name="x"> -->
+            <field name='b'><value>1 &#x26; 2</value></field></field></fields></xfdf>"#;
+        assert_eq!(
+            import_xfdf(nested).unwrap(),
+            [FieldData {
+                name: "a.b".into(),
+                value: "1 & 2".into(),
+            }]
+        );
+
+        // Import into a fresh copy of the form
+        let mut original = PdfFile::from_raw(form());
+        original.load_xref_table().unwrap();
+        let mut update = IncrementalUpdate::new(&original).unwrap();
+        update.fill_form(&import_xfdf(&xfdf).unwrap()).unwrap();
+        let mut refilled = PdfFile::from_raw(update.to_bytes().unwrap());
+        refilled.load_xref_table().unwrap();
+        assert_eq!(form_data(&refilled).unwrap(), form_data(&file).unwrap());
+    }
+}
@@ -0,0 +1,199 @@
+//! Sets the values of form fields in an incremental update, regenerating the
+//! appearances of their widgets to match.
+
+use super::appearance::{
+    check_appearance, pushbutton_appearance, radio_appearance, text_appearance, DefaultAppearance,
+    FieldFont, TextLayout, WidgetStyle,
+};
+use super::{Field, FieldType};
+use crate::error::{Error, Result};
+use crate::objects::{names, Dictionary, Name, Object};
+use crate::utils::text::encode_text_string;
+use crate::writing::incremental::IncrementalUpdate;
+use std::borrow::Cow;
+
+impl<'a> IncrementalUpdate<'a> {
+    /// Sets the value of a field and regenerates the appearances of its
+    /// widgets. Buttons take the name of the state to select, such as `Yes`
+    /// or `Off`.
+    pub fn set_field_value(&mut self, field: &Field, value: &str) -> Result<()> {
+        let value = match field.field_type {
+            Some(FieldType::Text | FieldType::Choice) => {
+                Object::String(Cow::Owned(encode_text_string(value)))
+            }
+            Some(FieldType::Button) if !field.is_pushbutton() => {
+                let state = field.state_for(value).ok_or_else(|| {
+                    Error::Type(format!("Field {:?} has no state {:?}", field.name, value))
+                })?;
+                Object::Name(Name(Cow::Owned(state.into_bytes())))
+            }
+            _ => {
+                return Err(Error::Type(format!(
+                    "Field {:?} does not hold a value",
+                    field.name
+                )))
+            }
+        };
+
+        let mut dict = self.object(field.reference)?;
+        let Object::Dictionary(entries) = &mut dict else {
+            return Err(Error::Type(format!("Expected field got {:?}", dict)));
+        };
+        entries.insert(Name::from(names::V), value.clone());
+        self.replace(field.reference, dict);
+
+        let field = Field {
+            value: Some(value.to_static()),
+            ..field.clone()
+        };
+        self.generate_appearances(&field)
+    }
+
+    /// Replaces the normal appearance of each widget of a field with one that
+    /// shows its current value (Adobe, 2008, p. 439-440).
+    pub fn generate_appearances(&mut self, field: &Field) -> Result<()> {
+        let file = self.file();
+        let default_appearance =
+            DefaultAppearance::parse(field.default_appearance.as_deref().unwrap_or_default())?;
+        let text = field.text().unwrap_or_default();
+        let layout = TextLayout::from_field(field);
+
+        for (i, &reference) in field.widgets.iter().enumerate() {
+            let mut widget = self.object(reference)?;
+            let style = WidgetStyle::from_widget(file, &widget)?;
+            let (normal, state) = match field.field_type {
+                Some(FieldType::Text | FieldType::Choice) => {
+                    let font = FieldFont::load(file, &default_appearance.font)?;
+                    let appearance =
+                        text_appearance(&style, &default_appearance, &font, &layout, &text);
+                    (Object::Indirect(self.add(appearance.to_stream()?)), None)
+                }
+                Some(FieldType::Button) if field.is_pushbutton() => {
+                    let font = FieldFont::load(file, &default_appearance.font)?;
+                    let appearance = pushbutton_appearance(&style, &default_appearance, &font);
+                    (Object::Indirect(self.add(appearance.to_stream()?)), None)
+                }
+                Some(FieldType::Button) => {
+                    let on_state = match field.button_states.get(i) {
+                        Some(state) => Name(Cow::Owned(state.on.clone().into_bytes())),
+                        None => Name::from("Yes"),
+                    };
+                    let draw = match field.is_radio() {
+                        true => radio_appearance,
+                        false => check_appearance,
+                    };
+                    let on = draw(&style, &default_appearance, true);
+                    let off = draw(&style, &default_appearance, false);
+                    let states = [
+                        (
+                            on_state.clone(),
+                            Object::Indirect(self.add(on.to_stream()?)),
+                        ),
+                        (
+                            Name::from(names::OFF),
+                            Object::Indirect(self.add(off.to_stream()?)),
+                        ),
+                    ];
+                    // Every widget whose on state is the value is selected, so
+                    // that the widgets of a radio button group stay consistent
+                    let state = match text == String::from_utf8_lossy(&on_state) {
+                        true => on_state,
+                        false => Name::from(names::OFF),
+                    };
+                    (
+                        Object::Dictionary(states.into_iter().collect()),
+                        Some(state),
+                    )
+                }
+                _ => continue,
+            };
+
+            let mut appearances = match widget.get(names::AP) {
+                Some(ap) => self.resolve(ap)?,
+                None => Object::Dictionary(Dictionary::new()),
+            };
+            let (Object::Dictionary(entries), Object::Dictionary(ap)) =
+                (&mut widget, &mut appearances)
+            else {
+                return Err(Error::Type(format!("Expected widget got {:?}", widget)));
+            };
+            ap.insert(Name::from(names::N), normal);
+            entries.insert(Name::from(names::AP), appearances);
+            if let Some(state) = state {
+                entries.insert(Name::from(names::AS), Object::Name(state));
+            }
+            self.replace(reference, widget);
+        }
+        Ok(())
+    }
+
+    /// Resolves an object against the latest version of each object in the
+    /// update.
+    fn resolve(&self, object: &Object<'a>) -> Result<Object<'a>> {
+        match object {
+            &Object::Indirect(reference) => self.object(reference),
+            object => Ok(object.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::{names, Object};
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support::form;
+    use crate::writing::incremental::IncrementalUpdate;
+
+    fn normal_appearance<'a>(file: &'a PdfFile<'a>, widget: &Object<'a>) -> Object<'a> {
+        let appearances = file.resolve_owned(widget.get(names::AP).unwrap()).unwrap();
+        file.resolve_owned(appearances.get(names::N).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_fill_fields_with_appearances() {
+        let mut file = PdfFile::from_raw(form());
+        file.load_xref_table().unwrap();
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        update
+            .set_field_value(&file.field("name").unwrap(), "Ada")
+            .unwrap();
+        update
+            .set_field_value(&file.field("agree").unwrap(), "Yes")
+            .unwrap();
+        update
+            .generate_appearances(&file.field("submit").unwrap())
+            .unwrap();
+        let updated = update.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_raw(updated);
+        file.load_xref_table().unwrap();
+        let name = file.field("name").unwrap();
+        assert_eq!(name.text().as_deref(), Some("Ada"));
+        let widget = file.object(name.widgets[0]).unwrap();
+        let stream = normal_appearance(&file, &widget);
+        let (dict, content) = stream.as_stream().unwrap();
+        let content = String::from_utf8_lossy(&content);
+        assert!(content.contains("/Tx BMC"), "{}", content);
+        assert!(content.contains("/Helv 10 Tf"), "{}", content);
+        assert!(content.contains("(Ada) Tj"), "{}", content);
+        assert!(dict[names::RESOURCES.as_bytes()].get(names::FONT).is_some());
+
+        let agree = file.field("agree").unwrap();
+        assert_eq!(agree.text().as_deref(), Some("Yes"));
+        let widget = file.object(agree.widgets[0]).unwrap();
+        assert!(widget.get_name(names::AS).unwrap().matches("Yes"));
+        let states = normal_appearance(&file, &widget);
+        let on = file.resolve_owned(states.get("Yes").unwrap()).unwrap();
+        assert!(on.as_stream().unwrap().1.ends_with(b"S\n"));
+
+        let submit = file.field("submit").unwrap();
+        let widget = file.object(submit.widgets[0]).unwrap();
+        let stream = normal_appearance(&file, &widget);
+        assert!(String::from_utf8_lossy(&stream.as_stream().unwrap().1).contains("(Submit) Tj"));
+        assert!(IncrementalUpdate::new(&file)
+            .unwrap()
+            .set_field_value(&submit, "Push")
+            .is_err());
+    }
+}
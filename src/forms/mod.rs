@@ -0,0 +1,404 @@
+//! Reads and fills the fields of interactive forms (Adobe, 2008, p. 430-461).
+
+pub mod appearance;
+pub mod fdf;
+#[cfg(feature = "write")]
+pub mod fill;
+
+use crate::document::dicts::Catalog;
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use std::collections::HashSet;
+
+// Field flags (Adobe, 2008, p. 432, 444-448)
+pub const READ_ONLY: i64 = 1 << 0;
+pub const REQUIRED: i64 = 1 << 1;
+pub const NO_EXPORT: i64 = 1 << 2;
+pub const MULTILINE: i64 = 1 << 12;
+pub const PASSWORD: i64 = 1 << 13;
+pub const NO_TOGGLE_TO_OFF: i64 = 1 << 14;
+pub const RADIO: i64 = 1 << 15;
+pub const PUSHBUTTON: i64 = 1 << 16;
+pub const COMB: i64 = 1 << 24;
+pub const RADIOS_IN_UNISON: i64 = 1 << 25;
+
+/// The kind of a field, from its `/FT` entry (Adobe, 2008, p. 432).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    Button,
+    Text,
+    Choice,
+    Signature,
+}
+
+impl FieldType {
+    fn from_object(object: &Object) -> Option<Self> {
+        match object {
+            Object::Name(name) if name.matches("Btn") => Some(Self::Button),
+            Object::Name(name) if name.matches("Tx") => Some(Self::Text),
+            Object::Name(name) if name.matches("Ch") => Some(Self::Choice),
+            Object::Name(name) if name.matches("Sig") => Some(Self::Signature),
+            _ => None,
+        }
+    }
+}
+
+/// A terminal field, which holds a value, along with the attributes that it
+/// inherits from its ancestors (Adobe, 2008, p. 431-436).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub reference: IndirectRef,
+    /// The fully qualified name, which joins the partial names of the field
+    /// and its ancestors with periods.
+    pub name: String,
+    pub field_type: Option<FieldType>,
+    pub flags: i64,
+    pub value: Option<Object<'static>>,
+    /// The default appearance string, such as `/Helv 12 Tf 0 g`, which falls
+    /// back to the one in the interactive form dictionary.
+    pub default_appearance: Option<String>,
+    /// The alignment of the text: 0 for left, 1 for centred and 2 for right.
+    pub quadding: i64,
+    pub max_len: Option<i64>,
+    /// The export values from `/Opt`, which for a check box or radio button
+    /// belong to its widgets in order (Adobe, 2008, p. 443).
+    pub options: Vec<String>,
+    /// The widget annotations that show the field, which may include the
+    /// field itself.
+    pub widgets: Vec<IndirectRef>,
+    /// The states of the widgets of a check box or radio button, in the same
+    /// order as `widgets`.
+    pub button_states: Vec<ButtonState>,
+}
+
+/// The appearance states of a check box or radio button widget
+/// (Adobe, 2008, p. 440-443).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ButtonState {
+    /// The state that turns the widget on, which is the name in its normal
+    /// appearance dictionary other than `Off`, or `Yes` if it has none.
+    pub on: String,
+    /// The current state, from `/AS`.
+    pub current: Option<String>,
+}
+
+impl Field {
+    pub fn has_flag(&self, flag: i64) -> bool {
+        self.flags & flag != 0
+    }
+
+    pub fn is_pushbutton(&self) -> bool {
+        self.field_type == Some(FieldType::Button) && self.has_flag(PUSHBUTTON)
+    }
+
+    pub fn is_radio(&self) -> bool {
+        self.field_type == Some(FieldType::Button)
+            && self.has_flag(RADIO)
+            && !self.has_flag(PUSHBUTTON)
+    }
+
+    pub fn is_checkbox(&self) -> bool {
+        self.field_type == Some(FieldType::Button) && self.flags & (RADIO | PUSHBUTTON) == 0
+    }
+
+    /// The value as text, for text and choice fields, or the name of the
+    /// selected state of a button.
+    pub fn text(&self) -> Option<String> {
+        match &self.value {
+            Some(value @ Object::String(_)) => value.as_text().ok(),
+            Some(Object::Name(name)) => Some(String::from_utf8_lossy(name).into_owned()),
+            _ => None,
+        }
+    }
+
+    /// The export value of an appearance state, which comes from `/Opt` when
+    /// the field has one, or is the state name itself otherwise. The `Off`
+    /// state has no export value.
+    pub fn export_value(&self, state: &str) -> Option<String> {
+        if state == names::OFF {
+            return None;
+        }
+        match self.button_states.iter().position(|s| s.on == state) {
+            Some(i) => Some(self.options.get(i).map_or(state, String::as_str).into()),
+            None if self.options.is_empty() => Some(state.into()),
+            None => None,
+        }
+    }
+
+    /// The appearance state that selects a value, which may be an export
+    /// value or the name of a state.
+    pub fn state_for(&self, value: &str) -> Option<String> {
+        if value == names::OFF {
+            return Some(value.into());
+        }
+        if let Some(i) = self.options.iter().position(|option| option == value) {
+            return self.button_states.get(i).map(|state| state.on.clone());
+        }
+        self.button_states
+            .iter()
+            .find(|state| state.on == value)
+            .map(|state| state.on.clone())
+    }
+
+    /// The export value of the selected check box or radio button, from the
+    /// field's value or else from the state of its widgets.
+    pub fn selected(&self) -> Option<String> {
+        let state = match &self.value {
+            Some(Object::Name(name)) => String::from_utf8_lossy(name).into_owned(),
+            _ => self
+                .button_states
+                .iter()
+                .find(|state| state.current.as_ref() == Some(&state.on))?
+                .on
+                .clone(),
+        };
+        self.export_value(&state)
+    }
+}
+
+/// The attributes that a field passes on to its descendants.
+#[derive(Clone, Default)]
+struct Inherited {
+    name: String,
+    field_type: Option<FieldType>,
+    flags: i64,
+    value: Option<Object<'static>>,
+    default_appearance: Option<String>,
+    quadding: i64,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Lists the terminal fields of the form, in the order of the field tree,
+    /// or nothing if the document has no form.
+    pub fn fields(&self) -> Result<Vec<Field>> {
+        let Some(acro_form) = Catalog::from_file(self)?.acro_form()? else {
+            return Ok(Vec::new());
+        };
+        let inherited = Inherited {
+            default_appearance: acro_form.get_text(names::DA).ok(),
+            quadding: acro_form.get_int(names::Q).unwrap_or(0),
+            ..Default::default()
+        };
+
+        let mut fields = Vec::new();
+        let mut visited = HashSet::new();
+        if let Some(roots) = acro_form.get(names::FIELDS) {
+            for root in &self.resolve_owned(roots)? {
+                collect_fields(self, root, &inherited, &mut visited, &mut fields)?;
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Finds a field by its fully qualified name.
+    pub fn field(&self, name: &str) -> Result<Field> {
+        self.fields()?
+            .into_iter()
+            .find(|field| field.name == name)
+            .ok_or_else(|| Error::Type(format!("No field named {:?}", name)))
+    }
+}
+
+fn collect_fields(
+    file: &PdfFile,
+    node: &Object,
+    parent: &Inherited,
+    visited: &mut HashSet<IndirectRef>,
+    fields: &mut Vec<Field>,
+) -> Result<()> {
+    let &Object::Indirect(reference) = node else {
+        return Err(Error::Type(format!(
+            "Expected field reference got {:?}",
+            node
+        )));
+    };
+    if !visited.insert(reference) {
+        return Err(Error::Syntax(
+            "Field tree contains a cycle",
+            format!("{:?}", reference),
+        ));
+    }
+    let dict = file.object(reference)?;
+
+    let mut inherited = parent.clone();
+    if let Some(Object::String(_)) = dict.get(names::T) {
+        let partial = dict.get_text(names::T)?;
+        inherited.name = match parent.name.as_str() {
+            "" => partial,
+            name => format!("{}.{}", name, partial),
+        };
+    }
+    if let Some(field_type) = dict.get(names::FT) {
+        inherited.field_type = FieldType::from_object(field_type);
+    }
+    if let Some(flags) = dict.get(names::FF) {
+        inherited.flags = file.resolve_owned(flags)?.as_int()?;
+    }
+    if let Some(value) = dict.get(names::V) {
+        inherited.value = Some(file.resolve_owned(value)?.to_static());
+    }
+    if let Ok(default_appearance) = dict.get_text(names::DA) {
+        inherited.default_appearance = Some(default_appearance);
+    }
+    if let Ok(quadding) = dict.get_int(names::Q) {
+        inherited.quadding = quadding;
+    }
+
+    // Kids without a partial name are the field's widgets, rather than
+    // fields of their own (Adobe, 2008, p. 434)
+    let mut widgets = Vec::new();
+    if dict
+        .get_name(names::SUBTYPE)
+        .is_ok_and(|s| s.matches(names::WIDGET))
+    {
+        widgets.push(reference);
+    }
+    let kids = match dict.get(names::KIDS) {
+        Some(kids) => file.resolve_owned(kids)?,
+        None => Object::Array(Vec::new()),
+    };
+    let mut has_field_kids = false;
+    for kid in &kids {
+        let &Object::Indirect(kid_reference) = kid else {
+            continue;
+        };
+        if file.object(kid_reference)?.get(names::T).is_some() {
+            has_field_kids = true;
+            collect_fields(file, kid, &inherited, visited, fields)?;
+        } else {
+            widgets.push(kid_reference);
+        }
+    }
+
+    if !has_field_kids || !widgets.is_empty() {
+        let button_states = match inherited.field_type {
+            Some(FieldType::Button) => widgets
+                .iter()
+                .map(|&widget| button_state(file, &file.object(widget)?))
+                .collect::<Result<_>>()?,
+            _ => Vec::new(),
+        };
+        let options = match dict.get(names::OPT) {
+            Some(options) => file
+                .resolve_owned(options)?
+                .into_iter()
+                .map(|option| export_value(file, option))
+                .collect::<Result<_>>()?,
+            None => Vec::new(),
+        };
+        fields.push(Field {
+            reference,
+            name: inherited.name,
+            field_type: inherited.field_type,
+            flags: inherited.flags,
+            value: inherited.value,
+            default_appearance: inherited.default_appearance,
+            quadding: inherited.quadding,
+            max_len: dict.get_int(names::MAX_LEN).ok(),
+            options,
+            widgets,
+            button_states,
+        });
+    }
+    Ok(())
+}
+
+fn button_state(file: &PdfFile, widget: &Object) -> Result<ButtonState> {
+    let normal = match widget.get(names::AP) {
+        Some(appearances) => match file.resolve_owned(appearances)?.get(names::N) {
+            Some(normal) => file.resolve_owned(normal)?,
+            None => Object::Null,
+        },
+        None => Object::Null,
+    };
+    let on = match normal {
+        Object::Dictionary(states) => states
+            .into_keys()
+            .find(|state| !state.matches(names::OFF))
+            .map(|state| String::from_utf8_lossy(&state).into_owned()),
+        _ => None,
+    };
+    let current = match widget.get(names::AS) {
+        Some(Object::Name(state)) => Some(String::from_utf8_lossy(state).into_owned()),
+        _ => None,
+    };
+    Ok(ButtonState {
+        on: on.unwrap_or_else(|| "Yes".into()),
+        current,
+    })
+}
+
+/// Reads an entry of `/Opt`, which is either an export value or a pair of an
+/// export value and the text to display (Adobe, 2008, p. 445).
+fn export_value(file: &PdfFile, option: &Object) -> Result<String> {
+    match file.resolve_owned(option)? {
+        Object::Array(pair) => match pair.first() {
+            Some(value) => file.resolve_owned(value)?.as_text(),
+            None => Err(Error::Type("Empty option".into())),
+        },
+        value => value.as_text(),
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support::form;
+    use crate::writing::incremental::IncrementalUpdate;
+
+    #[test]
+    fn should_list_fields() {
+        let mut file = PdfFile::from_raw(form());
+        file.load_xref_table().unwrap();
+        let fields = file.fields().unwrap();
+
+        let names = fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["name", "agree", "submit", "colour"]);
+        assert_eq!(fields[0].field_type, Some(FieldType::Text));
+        assert_eq!(
+            fields[0].default_appearance.as_deref(),
+            Some("/Helv 10 Tf 0 g")
+        );
+        assert!(fields[1].is_checkbox());
+        assert!(fields[2].is_pushbutton());
+        assert!(fields[3].is_radio());
+        assert_eq!(fields[3].widgets.len(), 2);
+        assert_eq!(fields[0].widgets, [fields[0].reference]);
+        assert!(file.field("missing").is_err());
+    }
+
+    #[test]
+    fn should_map_button_states_to_export_values() {
+        let mut file = PdfFile::from_raw(form());
+        file.load_xref_table().unwrap();
+        let colour = file.field("colour").unwrap();
+        assert_eq!(colour.options, ["Red", "Blue"]);
+        assert_eq!(colour.button_states[1].on, "1");
+        assert_eq!(colour.state_for("Blue").as_deref(), Some("1"));
+        assert_eq!(colour.state_for("1").as_deref(), Some("1"));
+        assert_eq!(colour.state_for("Green"), None);
+        assert_eq!(colour.export_value("0").as_deref(), Some("Red"));
+        assert_eq!(colour.selected(), None);
+
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        update.set_field_value(&colour, "Blue").unwrap();
+        assert!(update.set_field_value(&colour, "Green").is_err());
+        let updated = update.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_raw(updated);
+        file.load_xref_table().unwrap();
+        let colour = file.field("colour").unwrap();
+        assert_eq!(colour.selected().as_deref(), Some("Blue"));
+        let current = colour
+            .button_states
+            .iter()
+            .map(|state| state.current.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(current, [Some("Off"), Some("1")]);
+
+        let agree = file.field("agree").unwrap();
+        assert_eq!(agree.selected(), None);
+        assert_eq!(agree.state_for("Yes").as_deref(), Some("Yes"));
+    }
+}
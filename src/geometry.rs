@@ -0,0 +1,348 @@
+//! Coordinates and transformations in user space (Adobe, 2008, p. 115-121).
+
+use crate::error::{Error, Result};
+use crate::objects::Object;
+use std::ops::Mul;
+
+pub mod fixed;
+
+/// The matrix that the content interpreters keep their state in, which is
+/// fixed-point with the `fixed-point` feature. Positions are converted to
+/// `f64` as they leave the interpreter.
+#[cfg(feature = "fixed-point")]
+pub(crate) type ContentMatrix = fixed::FixedMatrix;
+#[cfg(not(feature = "fixed-point"))]
+pub(crate) type ContentMatrix = Matrix;
+
+/// Converts an interpreter's matrix for use outside of the interpreter.
+pub(crate) fn to_matrix(matrix: ContentMatrix) -> Matrix {
+    #[cfg(feature = "fixed-point")]
+    return matrix.into();
+    #[cfg(not(feature = "fixed-point"))]
+    matrix
+}
+
+/// An affine transformation `[a b c d e f]` (Adobe, 2008, p. 119).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Matrix {
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub const fn translate(x: f64, y: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub const fn scale(x: f64, y: f64) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Reads a matrix from six numeric objects, such as the operands of `cm`.
+    pub fn from_objects(objects: &[Object]) -> Result<Self> {
+        match objects {
+            [a, b, c, d, e, f] => Ok(Self::new(
+                number(a)?,
+                number(b)?,
+                number(c)?,
+                number(d)?,
+                number(e)?,
+                number(f)?,
+            )),
+            _ => Err(Error::Type(format!("Expected matrix got {:?}", objects))),
+        }
+    }
+
+    /// Converts the matrix to six numeric objects, such as the operands of
+    /// `cm`. Whole numbers are written as integers.
+    pub fn to_objects(&self) -> Vec<Object<'static>> {
+        [self.a, self.b, self.c, self.d, self.e, self.f]
+            .into_iter()
+            .map(number_object)
+            .collect()
+    }
+
+    pub fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// The transformation that undoes this one, or `None` if it collapses
+    /// space onto a line or point.
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let (a, b) = (self.d / determinant, -self.b / determinant);
+        let (c, d) = (-self.c / determinant, self.a / determinant);
+        Some(Self::new(
+            a,
+            b,
+            c,
+            d,
+            -(self.e * a + self.f * c),
+            -(self.e * b + self.f * d),
+        ))
+    }
+
+    /// Transforms a rectangle, returning the smallest rectangle that contains
+    /// the result.
+    pub fn transform_rect(&self, rect: &Rect) -> Rect {
+        let corners = [
+            self.transform(rect.x0, rect.y0),
+            self.transform(rect.x1, rect.y0),
+            self.transform(rect.x0, rect.y1),
+            self.transform(rect.x1, rect.y1),
+        ];
+        corners[1..]
+            .iter()
+            .fold(Rect::point(corners[0].0, corners[0].1), |rect, &(x, y)| {
+                rect.union(&Rect::point(x, y))
+            })
+    }
+}
+
+/// Concatenates two transformations, so that `self` is applied first.
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Matrix) -> Matrix {
+        Matrix::new(
+            self.a * rhs.a + self.b * rhs.c,
+            self.a * rhs.b + self.b * rhs.d,
+            self.c * rhs.a + self.d * rhs.c,
+            self.c * rhs.b + self.d * rhs.d,
+            self.e * rhs.a + self.f * rhs.c + rhs.e,
+            self.e * rhs.b + self.f * rhs.d + rhs.f,
+        )
+    }
+}
+
+/// A rectangle with its corners normalized, so that `x0 <= x1` and
+/// `y0 <= y1` (Adobe, 2008, p. 87).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+}
+
+impl Rect {
+    pub fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        Self {
+            x0: x0.min(x1),
+            y0: y0.min(y1),
+            x1: x0.max(x1),
+            y1: y0.max(y1),
+        }
+    }
+
+    pub fn point(x: f64, y: f64) -> Self {
+        Self::new(x, y, x, y)
+    }
+
+    /// Reads a rectangle from an array of four numbers.
+    pub fn from_object(object: &Object) -> Result<Self> {
+        match object {
+            Object::Array(array) => match array.as_slice() {
+                [x0, y0, x1, y1] => Ok(Self::new(
+                    number(x0)?,
+                    number(y0)?,
+                    number(x1)?,
+                    number(y1)?,
+                )),
+                _ => Err(Error::Type(format!("Expected rectangle got {:?}", object))),
+            },
+            _ => Err(Error::Type(format!("Expected rectangle got {:?}", object))),
+        }
+    }
+
+    /// Converts the rectangle to an array of four numbers.
+    pub fn to_object(&self) -> Object<'static> {
+        Object::Array(
+            [self.x0, self.y0, self.x1, self.y1]
+                .into_iter()
+                .map(number_object)
+                .collect(),
+        )
+    }
+
+    pub fn width(&self) -> f64 {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> f64 {
+        self.y1 - self.y0
+    }
+
+    /// The smallest rectangle that contains the points, or `None` if there
+    /// are none.
+    pub fn bounding(points: &[(f64, f64)]) -> Option<Rect> {
+        points
+            .iter()
+            .map(|&(x, y)| Rect::point(x, y))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    /// Returns true if the rectangles overlap when projected onto the x axis.
+    pub fn overlaps_horizontally(&self, other: &Rect) -> bool {
+        self.x0 <= other.x1 && other.x0 <= self.x1
+    }
+}
+
+/// A quadrilateral, in the order used by the `QuadPoints` of text markup
+/// annotations: top left, top right, bottom left, bottom right
+/// (Adobe, 2008, p. 404).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quad {
+    pub points: [(f64, f64); 4],
+}
+
+impl Quad {
+    pub fn from_rect(rect: &Rect) -> Self {
+        Self {
+            points: [
+                (rect.x0, rect.y1),
+                (rect.x1, rect.y1),
+                (rect.x0, rect.y0),
+                (rect.x1, rect.y0),
+            ],
+        }
+    }
+
+    /// The smallest rectangle that contains the quadrilateral.
+    pub fn bounds(&self) -> Rect {
+        let [first, rest @ ..] = self.points;
+        rest.iter()
+            .fold(Rect::point(first.0, first.1), |rect, &(x, y)| {
+                rect.union(&Rect::point(x, y))
+            })
+    }
+}
+
+/// Reads an integer or real object as a number.
+pub fn number(object: &Object) -> Result<f64> {
+    match object {
+        Object::Integer(int) => Ok(*int as f64),
+        Object::Real(real) => Ok(*real),
+        _ => Err(Error::Type(format!("Expected number got {:?}", object))),
+    }
+}
+
+/// Reads a flat array of coordinates, such as the vertices of a polygon
+/// annotation, as points.
+pub fn points_from_object(object: &Object) -> Result<Vec<(f64, f64)>> {
+    let Object::Array(coordinates) = object else {
+        return Err(Error::Type(format!("Expected array got {:?}", object)));
+    };
+    if coordinates.len() % 2 != 0 {
+        return Err(Error::Syntax(
+            "Odd number of coordinates",
+            format!("{:?}", object),
+        ));
+    }
+    coordinates
+        .chunks(2)
+        .map(|pair| Ok((number(&pair[0])?, number(&pair[1])?)))
+        .collect()
+}
+
+/// Converts points to a flat array of coordinates.
+pub fn points_to_object(points: &[(f64, f64)]) -> Object<'static> {
+    Object::Array(
+        points
+            .iter()
+            .flat_map(|&(x, y)| [number_object(x), number_object(y)])
+            .collect(),
+    )
+}
+
+/// Converts a number to an object, as an integer if it is a whole number.
+pub fn number_object(n: f64) -> Object<'static> {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Object::Integer(n as i64)
+    } else {
+        Object::Real(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_concatenate_matrices() {
+        let matrix = Matrix::scale(2.0, 3.0) * Matrix::translate(10.0, 20.0);
+        assert_eq!(matrix.transform(1.0, 1.0), (12.0, 23.0));
+
+        let matrix = Matrix::translate(10.0, 20.0) * Matrix::scale(2.0, 3.0);
+        assert_eq!(matrix.transform(1.0, 1.0), (22.0, 63.0));
+    }
+
+    #[test]
+    fn should_invert_matrices() {
+        let matrix = Matrix::new(0.0, 2.0, -2.0, 0.0, 10.0, 20.0);
+        let inverse = matrix.inverse().unwrap();
+        assert_eq!(matrix * inverse, Matrix::IDENTITY);
+        assert_eq!(inverse.transform(10.0, 22.0), (1.0, 0.0));
+        assert_eq!(Matrix::scale(0.0, 1.0).inverse(), None);
+    }
+
+    #[test]
+    fn should_normalize_rectangles() {
+        let rect = Rect::from_object(&Object::Array(vec![
+            Object::Integer(10),
+            Object::Real(20.5),
+            Object::Integer(0),
+            Object::Integer(0),
+        ]))
+        .unwrap();
+        assert_eq!(rect, Rect::new(0.0, 0.0, 10.0, 20.5));
+        assert_eq!(rect.width(), 10.0);
+    }
+
+    #[test]
+    fn should_convert_rectangles_to_quads() {
+        let rect = Rect::new(1.0, 2.0, 3.0, 4.0);
+        let quad = Quad::from_rect(&rect);
+        assert_eq!(quad.points[0], (1.0, 4.0));
+        assert_eq!(quad.points[3], (3.0, 2.0));
+        assert_eq!(quad.bounds(), rect);
+    }
+
+    #[test]
+    fn should_transform_rectangles() {
+        let rotate = Matrix::new(0.0, 1.0, -1.0, 0.0, 0.0, 0.0);
+        let rect = rotate.transform_rect(&Rect::new(0.0, 0.0, 2.0, 1.0));
+        assert_eq!(rect, Rect::new(-1.0, 0.0, 0.0, 2.0));
+    }
+}
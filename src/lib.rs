@@ -0,0 +1,15 @@
+pub mod annotations;
+pub mod batch;
+pub mod content;
+pub mod document;
+pub mod error;
+pub mod filters;
+pub mod forms;
+pub mod fonts;
+pub mod geometry;
+pub mod objects;
+pub mod parsing;
+#[cfg(test)]
+mod test_support;
+pub mod utils;
+pub mod writing;
@@ -1,8 +1,255 @@
-pub mod error;
-pub mod objects;
-pub mod parsing;
-pub mod utils;
+//! The `bdf` command-line tool, which runs the library's features on files.
 
-fn main() {
-    println!("Hello, world!");
+use bdf::content::layout::LayoutOptions;
+use bdf::parsing::context::ParseOptions;
+use bdf::parsing::pdf_file::PdfFile;
+use bdf::writing::rewrite::{self, RewriteOptions};
+use std::fmt::Debug;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::{fs, io};
+
+const USAGE: &str = "\
+Usage: bdf <command> [options]
+
+Commands:
+  text <file> [--pages <ranges>] [--raw | --layout] [--output-dir <dir>]
+      Extracts the text of a document. Pages are numbered from 1, and ranges
+      are separated by commas, such as 1,3-5,10-. Text is in reading order,
+      in the order it is drawn with --raw, or laid out as on the page with
+      --layout. Pages are written to stdout separated by form feeds, or to
+      page-<n>.txt files in the output directory.
+
+  decompress <input> <output> [--invalidate-signatures]
+      Rewrites a document with every stream uncompressed and a classic xref
+      table, so that it can be diffed and edited by hand. Signed documents
+      are refused unless their signatures may be invalidated.
+
+  repair <input> <output> [--invalidate-signatures]
+      Reads a damaged document leniently and writes a valid copy, listing
+      each problem that was found and how it was repaired.
+
+  json <file> [--depth <n>]
+      Writes the objects that can be reached from the catalog as JSON, to be
+      explored with tools such as jq. Objects more than the given number of
+      references from the catalog, 8 by default, are left as references.
+";
+
+type CliResult<T> = Result<T, String>;
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(String::as_str) {
+        Some("text") => text(&args[1..]),
+        Some("decompress") => decompress(&args[1..]),
+        Some("repair") => repair(&args[1..]),
+        Some("json") => json(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("expected a command\n\n{}", USAGE)),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("bdf: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// How the text of a page is arranged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TextMode {
+    Reading,
+    Raw,
+    Layout,
+}
+
+fn text(args: &[String]) -> CliResult<()> {
+    let mut path = None;
+    let mut pages = None;
+    let mut mode = TextMode::Reading;
+    let mut output_dir = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pages" => pages = Some(value(&mut args, arg)?),
+            "--raw" => mode = TextMode::Raw,
+            "--layout" => mode = TextMode::Layout,
+            "--output-dir" => output_dir = Some(PathBuf::from(value(&mut args, arg)?)),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+    let path = path.ok_or("expected a file")?;
+
+    let mut file = PdfFile::read_file(path).map_err(describe)?;
+    file.load_xref_table().map_err(describe)?;
+    let all_pages = file.pages().map_err(describe)?;
+    let selected = match pages {
+        Some(ranges) => parse_page_ranges(ranges, all_pages.len())?,
+        None => (0..all_pages.len()).collect(),
+    };
+
+    let options = LayoutOptions::default();
+    let mut stdout = io::stdout().lock();
+    for (i, &index) in selected.iter().enumerate() {
+        let page = &all_pages[index];
+        let text = match mode {
+            TextMode::Reading => page.extract_text(),
+            TextMode::Raw => page.extract_raw_text(&options),
+            TextMode::Layout => page.extract_layout_text(&options),
+        }
+        .map_err(|err| format!("page {}: {:?}", index + 1, err))?;
+
+        match &output_dir {
+            Some(dir) => {
+                let name = dir.join(format!("page-{}.txt", index + 1));
+                fs::write(&name, text + "\n").map_err(describe)?;
+            }
+            None => {
+                if i > 0 {
+                    write!(stdout, "\x0c").map_err(describe)?;
+                }
+                writeln!(stdout, "{}", text).map_err(describe)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decompress(args: &[String]) -> CliResult<()> {
+    let (input, output, options) = rewrite_args(args)?;
+    let mut file = PdfFile::read_file(input).map_err(describe)?;
+    file.load_xref_table().map_err(describe)?;
+    let builder = rewrite::decompress(&file, options).map_err(describe)?;
+    fs::write(output, builder.to_bytes().map_err(describe)?).map_err(describe)
+}
+
+fn repair(args: &[String]) -> CliResult<()> {
+    let (input, output, options) = rewrite_args(args)?;
+    let mut file = PdfFile::read_file(input)
+        .map_err(describe)?
+        .with_options(ParseOptions::lenient());
+    file.load_xref_table().map_err(describe)?;
+    let repaired = rewrite::repair(&file, options).map_err(describe)?;
+    fs::write(output, repaired.builder.to_bytes().map_err(describe)?).map_err(describe)?;
+
+    for diagnostic in &repaired.repairs {
+        match diagnostic.offset {
+            Some(offset) => eprint!("at byte {}: ", offset),
+            None => eprint!("in file: "),
+        }
+        eprintln!("{} ({:?})", diagnostic.repair, diagnostic.error);
+    }
+    eprintln!("Problems repaired: {}", repaired.repairs.len());
+    Ok(())
+}
+
+fn json(args: &[String]) -> CliResult<()> {
+    let mut path = None;
+    let mut depth = 8;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => {
+                let n = value(&mut args, arg)?;
+                depth = n.parse().map_err(|_| format!("invalid depth {:?}", n))?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+    let path = path.ok_or("expected a file")?;
+
+    let mut file = PdfFile::read_file(path).map_err(describe)?;
+    file.load_xref_table().map_err(describe)?;
+    let tree = file.to_json_tree(depth).map_err(describe)?;
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &tree).map_err(describe)?;
+    writeln!(stdout).map_err(describe)
+}
+
+/// Reads the arguments of a command that rewrites an input file to an output
+/// file.
+fn rewrite_args(args: &[String]) -> CliResult<(&str, &str, RewriteOptions)> {
+    let mut paths = Vec::new();
+    let mut options = RewriteOptions::default();
+    for arg in args {
+        match arg.as_str() {
+            "--invalidate-signatures" => options.invalidate_signatures = true,
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => paths.push(arg.as_str()),
+        }
+    }
+    match paths[..] {
+        [input, output] => Ok((input, output, options)),
+        _ => Err("expected an input and an output file".into()),
+    }
+}
+
+/// Takes the value of an option that needs one.
+fn value<'a>(args: &mut impl Iterator<Item = &'a String>, option: &str) -> CliResult<&'a str> {
+    args.next()
+        .map(String::as_str)
+        .ok_or_else(|| format!("{} needs a value", option))
+}
+
+/// Parses page ranges such as `1,3-5,10-` into page indices, numbering pages
+/// from 1. A range without a start begins at the first page, and one without
+/// an end finishes at the last page.
+fn parse_page_ranges(ranges: &str, page_count: usize) -> CliResult<Vec<usize>> {
+    let number = |n: &str, default: usize| -> CliResult<usize> {
+        if n.is_empty() {
+            return Ok(default);
+        }
+        match n.trim().parse() {
+            Ok(n) if (1..=page_count).contains(&n) => Ok(n),
+            Ok(n) => Err(format!(
+                "page {} is out of range, as the document has {} pages",
+                n, page_count
+            )),
+            Err(_) => Err(format!("invalid page number {:?}", n)),
+        }
+    };
+
+    let mut pages = Vec::new();
+    for range in ranges.split(',') {
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (number(start, 1)?, number(end, page_count)?),
+            None => {
+                let page = number(range, 0)?;
+                (page, page)
+            }
+        };
+        if start == 0 || start > end {
+            return Err(format!("invalid page range {:?}", range));
+        }
+        pages.extend(start - 1..end);
+    }
+    Ok(pages)
+}
+
+fn describe(err: impl Debug) -> String {
+    format!("{:?}", err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_page_ranges() {
+        assert_eq!(parse_page_ranges("3-5", 10).unwrap(), vec![2, 3, 4]);
+        assert_eq!(parse_page_ranges("1,9-", 10).unwrap(), vec![0, 8, 9]);
+        assert_eq!(parse_page_ranges("-2", 10).unwrap(), vec![0, 1]);
+        assert!(parse_page_ranges("11", 10).is_err());
+        assert!(parse_page_ranges("5-3", 10).is_err());
+        assert!(parse_page_ranges("", 10).is_err());
+    }
 }
@@ -1,29 +1,177 @@
 use crate::error::{Error, Result};
+use crate::filters;
+use crate::utils::text::{decode_text_string, encode_text_string};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
 use std::ops::Index;
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub mod names;
+
+pub use names::Name;
+
+/// A reference to an indirect object (Adobe, 2008, p. 21). References are
+/// ordered by object number and then generation number.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct IndirectRef {
     pub number: u32,
     pub generation: u16,
 }
 
+impl IndirectRef {
+    pub const fn new(number: u32, generation: u16) -> Self {
+        Self { number, generation }
+    }
+
+    /// Creates a reference from parsed numbers, checking that they fit within
+    /// the ranges allowed for object and generation numbers.
+    pub fn checked(number: i64, generation: i64) -> Result<Self> {
+        match (u32::try_from(number), u16::try_from(generation)) {
+            (Ok(number), Ok(generation)) => Ok(Self { number, generation }),
+            _ => Err(Error::InvalidReference(number, generation)),
+        }
+    }
+}
+
+/// Formats the reference as it is written in a file, such as `12 0 R`.
+impl fmt::Display for IndirectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} R", self.number, self.generation)
+    }
+}
+
+/// Parses a reference as it is written in a file, such as `12 0 R`. The `R`
+/// may be left out, which is handy for command line arguments.
+impl FromStr for IndirectRef {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Syntax("Invalid indirect reference", s.to_owned());
+        let mut parts = s.split_whitespace();
+        let number = parts.next().ok_or_else(invalid)?.parse::<i64>()?;
+        let generation = parts.next().ok_or_else(invalid)?.parse::<i64>()?;
+        match (parts.next(), parts.next()) {
+            (None | Some("R"), None) => Self::checked(number, generation),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+pub type Dictionary<'a> = HashMap<Name<'a>, Object<'a>>;
+
+/// The entries of a dictionary sorted by key, which gives a stable order for
+/// writing or hashing, unlike iterating over the map itself.
+pub fn sorted_entries<'b, 'a>(dict: &'b Dictionary<'a>) -> Vec<(&'b Name<'a>, &'b Object<'a>)> {
+    let mut entries = dict.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| *key);
+    entries
+}
+
+/// A PDF object (Adobe, 2008, p. 13-24).
+///
+/// Objects are equal if they have the same structure and values. Dictionaries
+/// are equal regardless of the order that their entries were read in, an
+/// `Integer` is never equal to a `Real`, even with the same value, and a `NaN`
+/// real is never equal to anything. Borrowed and owned data are equal if the
+/// bytes are the same. Use [`Object::approx_eq`] to compare numbers by value.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Object<'a> {
     Boolean(bool),
-    Integer(usize),
+    Integer(i64),
     Real(f64),
     String(Cow<'a, [u8]>),
-    Name(Cow<'a, [u8]>),
+    Name(Name<'a>),
     Array(Vec<Object<'a>>),
-    Dictionary(HashMap<Cow<'a, [u8]>, Object<'a>>),
+    Dictionary(Dictionary<'a>),
     Stream(Box<Object<'a>>, Cow<'a, [u8]>),
     Null,
     Indirect(IndirectRef),
 }
 
 impl<'a> Object<'a> {
+    /// Copies any data that is borrowed from the file, so that the object can
+    /// outlive it.
+    pub fn into_owned(self) -> Object<'static> {
+        match self {
+            Object::Boolean(boolean) => Object::Boolean(boolean),
+            Object::Integer(int) => Object::Integer(int),
+            Object::Real(real) => Object::Real(real),
+            Object::String(string) => Object::String(Cow::Owned(string.into_owned())),
+            Object::Name(name) => Object::Name(name.into_owned()),
+            Object::Array(array) => {
+                Object::Array(array.into_iter().map(Object::into_owned).collect())
+            }
+            Object::Dictionary(dict) => Object::Dictionary(
+                dict.into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+            Object::Stream(dict, data) => {
+                Object::Stream(Box::new(dict.into_owned()), Cow::Owned(data.into_owned()))
+            }
+            Object::Null => Object::Null,
+            Object::Indirect(reference) => Object::Indirect(reference),
+        }
+    }
+
+    /// Compares objects as they would be read by a viewer, which is useful
+    /// for checking that files made by different writers hold the same
+    /// objects. Numbers are compared by value, so `1` equals `1.0`, and are
+    /// equal if they differ by at most `tolerance`, which absorbs the rounding
+    /// of reals when they are written. `NaN` equals `NaN`. Everything else is
+    /// compared as by `==`.
+    pub fn approx_eq(&self, other: &Object, tolerance: f64) -> bool {
+        match (self, other) {
+            (Object::Integer(a), Object::Integer(b)) => a == b,
+            (Object::Integer(_) | Object::Real(_), Object::Integer(_) | Object::Real(_)) => {
+                let (Ok(a), Ok(b)) = (f64::try_from(self), f64::try_from(other)) else {
+                    return false;
+                };
+                (a.is_nan() && b.is_nan()) || (a - b).abs() <= tolerance
+            }
+            (Object::Array(a), Object::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, tolerance))
+            }
+            (Object::Dictionary(a), Object::Dictionary(b)) => {
+                a.len() == b.len()
+                    && a.iter().all(|(key, a)| {
+                        b.get(key.as_bytes())
+                            .is_some_and(|b| a.approx_eq(b, tolerance))
+                    })
+            }
+            (Object::Stream(a, a_data), Object::Stream(b, b_data)) => {
+                a_data == b_data && a.approx_eq(b, tolerance)
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Copies an object and everything that it contains into owned storage,
+    /// such as a resolved dictionary that should outlive its file. Unlike
+    /// `into_owned`, this only copies this object, not whatever owns it.
+    pub fn to_static(&self) -> Object<'static> {
+        match self {
+            Object::Boolean(boolean) => Object::Boolean(*boolean),
+            Object::Integer(int) => Object::Integer(*int),
+            Object::Real(real) => Object::Real(*real),
+            Object::String(string) => Object::String(Cow::Owned(string.to_vec())),
+            Object::Name(name) => Object::Name(Name(Cow::Owned(name.to_vec()))),
+            Object::Array(array) => Object::Array(array.iter().map(Object::to_static).collect()),
+            Object::Dictionary(dict) => Object::Dictionary(
+                dict.iter()
+                    .map(|(key, value)| (Name(Cow::Owned(key.to_vec())), value.to_static()))
+                    .collect(),
+            ),
+            Object::Stream(dict, data) => {
+                Object::Stream(Box::new(dict.to_static()), Cow::Owned(data.to_vec()))
+            }
+            Object::Null => Object::Null,
+            Object::Indirect(reference) => Object::Indirect(*reference),
+        }
+    }
+
     pub fn as_bool(&self) -> Result<bool> {
         if let Object::Boolean(boolean) = self {
             Ok(*boolean)
@@ -32,7 +180,7 @@ impl<'a> Object<'a> {
         }
     }
 
-    pub fn as_int(&self) -> Result<usize> {
+    pub fn as_int(&self) -> Result<i64> {
         if let Object::Integer(int) = self {
             Ok(*int)
         } else {
@@ -50,15 +198,26 @@ impl<'a> Object<'a> {
 
     pub fn as_string(&'a self) -> Result<Cow<'a, [u8]>> {
         if let Object::String(string) = self {
-            Ok(Cow::Borrowed(&string))
+            Ok(Cow::Borrowed(string))
+        } else {
+            Err(Error::Type(format!("Expected string got {:?}", self)))
+        }
+    }
+
+    /// Decodes a text string, such as an outline title or a document
+    /// information value. This is lenient, so only fails if the object is
+    /// not a string.
+    pub fn as_text(&self) -> Result<String> {
+        if let Object::String(string) = self {
+            Ok(decode_text_string(string))
         } else {
             Err(Error::Type(format!("Expected string got {:?}", self)))
         }
     }
 
-    pub fn as_name(&'a self) -> Result<Cow<'a, [u8]>> {
+    pub fn as_name(&'a self) -> Result<&'a Name<'a>> {
         if let Object::Name(name) = self {
-            Ok(Cow::Borrowed(&name))
+            Ok(name)
         } else {
             Err(Error::Type(format!("Expected name got {:?}", self)))
         }
@@ -72,7 +231,7 @@ impl<'a> Object<'a> {
         }
     }
 
-    pub fn as_dict(&'a self) -> Result<&'a HashMap<Cow<'a, [u8]>, Object<'a>>> {
+    pub fn as_dict(&'a self) -> Result<&'a Dictionary<'a>> {
         if let Object::Dictionary(dict) = self {
             Ok(dict)
         } else {
@@ -80,7 +239,7 @@ impl<'a> Object<'a> {
         }
     }
 
-    pub fn as_stream(&'a self) -> Result<(&'a HashMap<Cow<'a, [u8]>, Object<'a>>, Cow<'a, [u8]>)> {
+    pub fn as_stream(&'a self) -> Result<(&'a Dictionary<'a>, Cow<'a, [u8]>)> {
         if let Object::Stream(dict, stream) = self {
             Ok((dict.as_dict()?, Cow::Borrowed(stream)))
         } else {
@@ -88,6 +247,47 @@ impl<'a> Object<'a> {
         }
     }
 
+    /// Returns the dictionary and data of a stream as they are held, without
+    /// decoding them. For streams loaded with `PdfFile::encoded_object` or
+    /// `PdfFile::raw_stream` this is the data as it is written in the file,
+    /// which is what signatures cover, while streams parsed as usual have
+    /// already been decoded.
+    pub fn raw_stream(&'a self) -> Result<(&'a Dictionary<'a>, &'a [u8])> {
+        if let Object::Stream(dict, stream) = self {
+            Ok((dict.as_dict()?, stream))
+        } else {
+            Err(Error::Type(format!("Expected stream got {:?}", self)))
+        }
+    }
+
+    /// Decodes the data of a stream through its `/Filter` chain into a
+    /// writer, returning how many bytes were written.
+    ///
+    /// The parser decodes streams as they are read, so this is for objects
+    /// loaded with `PdfFile::encoded_object`, whose data is still as it is in
+    /// the file. The last filter is decoded in chunks, so the whole of the
+    /// decoded data is never held in memory, though any filters before it
+    /// are.
+    pub fn decode_stream_into<W: Write>(&self, mut w: W) -> Result<u64> {
+        let Object::Stream(dict, data) = self else {
+            return Err(Error::Type(format!("Expected stream got {:?}", self)));
+        };
+
+        let chain = dict[names::FILTER.as_bytes()]
+            .into_iter()
+            .collect::<Vec<_>>();
+        let Some((last, rest)) = chain.split_last() else {
+            w.write_all(data)?;
+            return Ok(data.len() as u64);
+        };
+
+        let mut data = Cow::Borrowed(&data[..]);
+        for filter in rest {
+            data = Cow::Owned(filters::decode(filter.as_name()?, &data)?);
+        }
+        filters::decode_into(last.as_name()?, &data, &mut w)
+    }
+
     pub fn as_null(&'a self) -> Result<()> {
         if let Object::Null = self {
             Ok(())
@@ -103,6 +303,86 @@ impl<'a> Object<'a> {
             Err(Error::Type(format!("Expected indirect got {:?}", self)))
         }
     }
+
+    /// Looks up a key in a dictionary or stream dictionary.
+    ///
+    /// Unlike indexing, this returns `None` if the key is absent, but
+    /// `Some(&Object::Null)` if the key is explicitly set to null.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&Object<'a>> {
+        match self {
+            Object::Dictionary(dict) => dict.get(key.as_ref()),
+            Object::Stream(dict, _) => dict.get(key),
+            _ => None,
+        }
+    }
+
+    /// Looks up a key that must be present and non-null.
+    pub fn get_required<K: AsRef<[u8]>>(&self, key: K) -> Result<&Object<'a>> {
+        let key = key.as_ref();
+        if !matches!(self, Object::Dictionary(_) | Object::Stream(..)) {
+            return Err(Error::Type(format!("Expected dict got {:?}", self)));
+        }
+
+        match self.get(key) {
+            None => Err(Error::MissingKey(String::from_utf8_lossy(key).into())),
+            Some(Object::Null) => Err(Error::NullValue(String::from_utf8_lossy(key).into())),
+            Some(value) => Ok(value),
+        }
+    }
+
+    /// Returns every indirect reference inside this object, including those
+    /// in nested arrays and dictionaries, without resolving them.
+    pub fn references(&self) -> Vec<IndirectRef> {
+        fn collect(object: &Object, out: &mut Vec<IndirectRef>) {
+            match object {
+                Object::Indirect(reference) => out.push(*reference),
+                Object::Array(array) => array.iter().for_each(|item| collect(item, out)),
+                Object::Dictionary(dict) => dict.values().for_each(|value| collect(value, out)),
+                Object::Stream(dict, _) => collect(dict, out),
+                _ => {}
+            }
+        }
+
+        let mut references = Vec::new();
+        collect(self, &mut references);
+        references
+    }
+
+    pub fn get_bool<K: AsRef<[u8]>>(&self, key: K) -> Result<bool> {
+        self.get_required(key)?.as_bool()
+    }
+
+    pub fn get_int<K: AsRef<[u8]>>(&self, key: K) -> Result<i64> {
+        self.get_required(key)?.as_int()
+    }
+
+    pub fn get_real<K: AsRef<[u8]>>(&self, key: K) -> Result<f64> {
+        self.get_required(key)?.as_real()
+    }
+
+    pub fn get_string<K: AsRef<[u8]>>(&'a self, key: K) -> Result<Cow<'a, [u8]>> {
+        self.get_required(key)?.as_string()
+    }
+
+    pub fn get_text<K: AsRef<[u8]>>(&self, key: K) -> Result<String> {
+        self.get_required(key)?.as_text()
+    }
+
+    pub fn get_name<K: AsRef<[u8]>>(&'a self, key: K) -> Result<&'a Name<'a>> {
+        self.get_required(key)?.as_name()
+    }
+
+    pub fn get_array<K: AsRef<[u8]>>(&'a self, key: K) -> Result<&'a [Object<'a>]> {
+        self.get_required(key)?.as_array()
+    }
+
+    pub fn get_dict<K: AsRef<[u8]>>(&'a self, key: K) -> Result<&'a Dictionary<'a>> {
+        self.get_required(key)?.as_dict()
+    }
+
+    pub fn get_indirect<K: AsRef<[u8]>>(&'a self, key: K) -> Result<IndirectRef> {
+        self.get_required(key)?.as_indirect()
+    }
 }
 
 impl<'a> Index<&'a [u8]> for Object<'a> {
@@ -110,7 +390,7 @@ impl<'a> Index<&'a [u8]> for Object<'a> {
 
     fn index(&self, index: &'a [u8]) -> &Object<'a> {
         if let Object::Dictionary(dict) = self {
-            dict.get(&Cow::Borrowed(index)).unwrap_or(&Object::Null)
+            dict.get(index).unwrap_or(&Object::Null)
         } else {
             &Object::Null
         }
@@ -123,10 +403,7 @@ impl<'a> IntoIterator for &'a Object<'a> {
 
     fn into_iter(self) -> ObjectIter<'a> {
         if let Object::Array(array) = self {
-            ObjectIter::Array {
-                array: &array,
-                index: 0,
-            }
+            ObjectIter::Array { array, index: 0 }
         } else if self == &Object::Null {
             ObjectIter::Single {
                 object: self,
@@ -141,6 +418,126 @@ impl<'a> IntoIterator for &'a Object<'a> {
     }
 }
 
+impl<'a> TryFrom<&Object<'a>> for bool {
+    type Error = Error;
+
+    fn try_from(object: &Object<'a>) -> Result<bool> {
+        object.as_bool()
+    }
+}
+
+impl<'a> TryFrom<&Object<'a>> for i64 {
+    type Error = Error;
+
+    fn try_from(object: &Object<'a>) -> Result<i64> {
+        object.as_int()
+    }
+}
+
+impl<'a> TryFrom<&Object<'a>> for f64 {
+    type Error = Error;
+
+    /// Accepts integers too, as they can be used wherever a real number is
+    /// expected (Adobe, 2008, p. 14).
+    fn try_from(object: &Object<'a>) -> Result<f64> {
+        match object {
+            Object::Integer(int) => Ok(*int as f64),
+            _ => object.as_real(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Object<'a>> for String {
+    type Error = Error;
+
+    /// Decodes a text string, as with `Object::as_text`.
+    fn try_from(object: &Object<'a>) -> Result<String> {
+        object.as_text()
+    }
+}
+
+impl<'a, 'b, T> TryFrom<&'b Object<'a>> for Vec<T>
+where
+    T: TryFrom<&'b Object<'a>, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(object: &'b Object<'a>) -> Result<Vec<T>> {
+        match object {
+            Object::Array(array) => array.iter().map(T::try_from).collect(),
+            _ => Err(Error::Type(format!("Expected array got {:?}", object))),
+        }
+    }
+}
+
+impl From<bool> for Object<'_> {
+    fn from(boolean: bool) -> Self {
+        Object::Boolean(boolean)
+    }
+}
+
+impl From<i64> for Object<'_> {
+    fn from(int: i64) -> Self {
+        Object::Integer(int)
+    }
+}
+
+impl From<i32> for Object<'_> {
+    fn from(int: i32) -> Self {
+        Object::Integer(int.into())
+    }
+}
+
+impl From<f64> for Object<'_> {
+    fn from(real: f64) -> Self {
+        Object::Real(real)
+    }
+}
+
+/// The string is encoded as a text string (Adobe, 2008, p. 86), so that it
+/// reads back as the same text.
+impl From<String> for Object<'_> {
+    fn from(string: String) -> Self {
+        Object::String(Cow::Owned(encode_text_string(&string)))
+    }
+}
+
+impl<'a> From<&'a str> for Object<'a> {
+    fn from(string: &'a str) -> Self {
+        let encoded = encode_text_string(string);
+        // Most text is the same in PDFDocEncoding, so can be borrowed
+        if encoded == string.as_bytes() {
+            Object::String(Cow::Borrowed(string.as_bytes()))
+        } else {
+            Object::String(Cow::Owned(encoded))
+        }
+    }
+}
+
+impl<'a> From<Name<'a>> for Object<'a> {
+    fn from(name: Name<'a>) -> Self {
+        Object::Name(name)
+    }
+}
+
+impl From<IndirectRef> for Object<'_> {
+    fn from(reference: IndirectRef) -> Self {
+        Object::Indirect(reference)
+    }
+}
+
+impl<'a> From<Dictionary<'a>> for Object<'a> {
+    fn from(dict: Dictionary<'a>) -> Self {
+        Object::Dictionary(dict)
+    }
+}
+
+impl<'a, T: Into<Object<'a>>> From<Vec<T>> for Object<'a> {
+    fn from(items: Vec<T>) -> Self {
+        Object::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
 pub enum ObjectIter<'a> {
     Array {
         array: &'a [Object<'a>],
@@ -181,8 +578,8 @@ mod tests {
     #[test]
     fn should_index_into_dictionary() {
         let dict = Object::Dictionary({
-            let mut dict = HashMap::<Cow<[u8]>, Object>::new();
-            dict.insert(Cow::Borrowed(b"Name"), Object::Boolean(true));
+            let mut dict = Dictionary::new();
+            dict.insert(Name::borrowed(b"Name"), Object::Boolean(true));
             dict
         });
 
@@ -191,10 +588,145 @@ mod tests {
         assert_eq!(Object::Null[b"NotFound"], Object::Null);
     }
 
+    #[test]
+    fn should_check_reference_ranges() {
+        assert_eq!(
+            IndirectRef::checked(12, 3),
+            Ok(IndirectRef {
+                number: 12,
+                generation: 3
+            })
+        );
+        assert_eq!(
+            IndirectRef::checked(1 << 32, 0),
+            Err(Error::InvalidReference(1 << 32, 0))
+        );
+        assert_eq!(
+            IndirectRef::checked(1, 65536),
+            Err(Error::InvalidReference(1, 65536))
+        );
+    }
+
+    #[test]
+    fn should_distinguish_missing_and_null() {
+        let dict = Object::Dictionary({
+            let mut dict = Dictionary::new();
+            dict.insert(Name::borrowed(b"Int"), Object::Integer(1));
+            dict.insert(Name::borrowed(b"Null"), Object::Null);
+            dict
+        });
+
+        assert_eq!(dict.get(b"Int"), Some(&Object::Integer(1)));
+        assert_eq!(dict.get(b"Null"), Some(&Object::Null));
+        assert_eq!(dict.get(b"NotFound"), None);
+        assert_eq!(Object::Integer(1).get(b"Int"), None);
+
+        assert_eq!(dict.get_int(b"Int"), Ok(1));
+        assert_eq!(dict.get_int(b"Null"), Err(Error::NullValue("Null".into())));
+        assert_eq!(
+            dict.get_int(b"NotFound"),
+            Err(Error::MissingKey("NotFound".into()))
+        );
+        assert!(matches!(dict.get_bool(b"Int"), Err(Error::Type(_))));
+        assert!(matches!(Object::Null.get_int(b"Int"), Err(Error::Type(_))));
+    }
+
+    #[test]
+    fn should_get_from_stream_dictionary() {
+        let stream = Object::Stream(
+            Box::new(Object::Dictionary({
+                let mut dict = Dictionary::new();
+                dict.insert(Name::borrowed(b"Length"), Object::Integer(13));
+                dict
+            })),
+            Cow::Borrowed(b"Hello, world!"),
+        );
+
+        assert_eq!(stream.get_int(names::LENGTH), Ok(13));
+    }
+
+    #[test]
+    fn should_convert_to_and_from_rust_types() {
+        let obj = Object::from(vec![1, 2, 3]);
+        assert_eq!(Vec::<i64>::try_from(&obj), Ok(vec![1, 2, 3]));
+        assert_eq!(Vec::<f64>::try_from(&obj), Ok(vec![1.0, 2.0, 3.0]));
+        assert!(matches!(Vec::<bool>::try_from(&obj), Err(Error::Type(_))));
+        assert!(matches!(i64::try_from(&obj), Err(Error::Type(_))));
+
+        assert_eq!(bool::try_from(&Object::from(true)), Ok(true));
+        assert_eq!(f64::try_from(&Object::from(0.5)), Ok(0.5));
+        assert!(i64::try_from(&Object::from(0.5)).is_err());
+        assert_eq!(String::try_from(&Object::from("Hello")), Ok("Hello".into()));
+        for text in ["Café", "€5 – “quoted”", "Привет", "日本語 🎉"] {
+            assert_eq!(String::try_from(&Object::from(text)), Ok(text.into()));
+            let owned = Object::from(text.to_owned());
+            assert_eq!(String::try_from(&owned), Ok(text.into()));
+        }
+        assert_eq!(Object::from("é"), Object::String(Cow::Borrowed(b"\xe9")));
+        assert_eq!(
+            Object::from(Name::from("Type")),
+            Object::Name(Name::borrowed(b"Type"))
+        );
+        assert_eq!(
+            Object::from(IndirectRef::new(1, 0)),
+            Object::Indirect(IndirectRef::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn should_compare_objects_approximately() {
+        let dict = |pairs: &[(&'static str, Object<'static>)]| {
+            Object::Dictionary(
+                pairs
+                    .iter()
+                    .map(|(key, value)| (Name::from(*key), value.clone()))
+                    .collect(),
+            )
+        };
+        let a = dict(&[
+            ("W", Object::Integer(1)),
+            (
+                "C",
+                Object::Array(vec![Object::Real(0.333333), Object::Null]),
+            ),
+        ]);
+        let b = dict(&[
+            (
+                "C",
+                Object::Array(vec![Object::Real(1.0 / 3.0), Object::Null]),
+            ),
+            ("W", Object::Real(1.0)),
+        ]);
+        assert_ne!(a, b);
+        assert!(a.approx_eq(&b, 1e-5));
+        assert!(!a.approx_eq(&b, 1e-9));
+        assert!(!a.approx_eq(&dict(&[("W", Object::Integer(1))]), 1e-5));
+
+        assert_ne!(Object::Real(f64::NAN), Object::Real(f64::NAN));
+        assert!(Object::Real(f64::NAN).approx_eq(&Object::Real(f64::NAN), 0.0));
+        assert!(!Object::Integer(1).approx_eq(&Object::Boolean(true), 0.0));
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_copy_subtrees_to_static() {
+        let font = {
+            let raw = crate::test_support::hello_world(Default::default());
+            let mut file = crate::parsing::pdf_file::PdfFile::from_bytes(&raw);
+            file.load_xref_table().unwrap();
+            let object = file.object(IndirectRef::new(3, 0)).unwrap();
+            object.to_static()
+        };
+        assert_eq!(
+            font.get_name(names::BASE_FONT),
+            Ok(&Name::from("Helvetica"))
+        );
+    }
+
     #[test]
     fn should_cast_bool() {
         let obj = Object::Boolean(true);
-        assert_eq!(obj.as_bool().unwrap(), true);
+        assert!(obj.as_bool().unwrap());
     }
 
     #[test]
@@ -217,8 +749,8 @@ mod tests {
 
     #[test]
     fn should_cast_name() {
-        let obj = Object::Name(Cow::Borrowed(b"Hello, world!"));
-        assert_eq!(obj.as_name().unwrap(), Cow::Borrowed(b"Hello, world!"));
+        let obj = Object::Name(Name::borrowed(b"Hello, world!"));
+        assert_eq!(obj.as_name().unwrap(), &Name::borrowed(b"Hello, world!"));
     }
 
     #[test]
@@ -236,7 +768,7 @@ mod tests {
     #[test]
     fn should_cast_dict() {
         let mut dict = HashMap::new();
-        let key: Cow<[u8]> = Cow::Borrowed(b"Key");
+        let key = Name::borrowed(b"Key");
         dict.insert(key.clone(), Object::Integer(1));
 
         let obj = Object::Dictionary(dict);
@@ -246,7 +778,7 @@ mod tests {
     #[test]
     fn should_cast_stream() {
         let mut dict = HashMap::new();
-        let key: Cow<[u8]> = Cow::Borrowed(b"Key");
+        let key = Name::borrowed(b"Key");
         dict.insert(key.clone(), Object::Integer(1));
 
         let obj = Object::Stream(
@@ -278,4 +810,51 @@ mod tests {
             }
         );
     }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_decode_stream_into_writer() {
+        let data = b"Hello, world!";
+        let filters = Object::Array(vec![
+            Object::Name(Name::from(names::ASCII_HEX_DECODE)),
+            Object::Name(Name::from(names::FLATE_DECODE)),
+        ]);
+        let encoded = filters::encode_chain(&filters, data).unwrap();
+        let dict = HashMap::from([(Name::from(names::FILTER), filters)]);
+        let stream = Object::Stream(Box::new(Object::Dictionary(dict)), Cow::Owned(encoded));
+
+        let mut out = Vec::new();
+        assert_eq!(stream.decode_stream_into(&mut out), Ok(13));
+        assert_eq!(out, data);
+        assert!(Object::Null.decode_stream_into(&mut out).is_err());
+    }
+
+    #[test]
+    fn should_format_and_parse_references() {
+        let reference = IndirectRef::new(12, 3);
+        assert_eq!(reference.to_string(), "12 3 R");
+        assert_eq!("12 3 R".parse(), Ok(reference));
+        assert_eq!(" 12  3 ".parse(), Ok(reference));
+        assert!("12 3 X".parse::<IndirectRef>().is_err());
+        assert!("12".parse::<IndirectRef>().is_err());
+        assert_eq!(
+            "-1 0 R".parse::<IndirectRef>(),
+            Err(Error::InvalidReference(-1, 0))
+        );
+
+        let mut references = vec![
+            IndirectRef::new(2, 0),
+            IndirectRef::new(1, 1),
+            IndirectRef::new(1, 0),
+        ];
+        references.sort();
+        assert_eq!(
+            references,
+            [
+                IndirectRef::new(1, 0),
+                IndirectRef::new(1, 1),
+                IndirectRef::new(2, 0)
+            ]
+        );
+    }
 }
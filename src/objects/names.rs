@@ -0,0 +1,369 @@
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::ops::Deref;
+
+/// A name object (Adobe, 2008, p. 16).
+///
+/// Any `#xx` escapes have already been decoded by the parser, so a name is
+/// always compared using its canonical bytes, which are case-sensitive.
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Name<'a>(pub Cow<'a, [u8]>);
+
+impl<'a> Name<'a> {
+    pub const fn borrowed(bytes: &'a [u8]) -> Self {
+        Self(Cow::Borrowed(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns true if this name is exactly the given canonical name, which
+    /// should not include the leading `/` (e.g. `name.matches(FLATE_DECODE)`).
+    pub fn matches(&self, canonical: &str) -> bool {
+        self.as_bytes() == canonical.as_bytes()
+    }
+
+    pub fn into_owned(self) -> Name<'static> {
+        Name(Cow::Owned(self.0.into_owned()))
+    }
+}
+
+impl<'a> Deref for Name<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> AsRef<[u8]> for Name<'a> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> Borrow<[u8]> for Name<'a> {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> From<Cow<'a, [u8]>> for Name<'a> {
+    fn from(bytes: Cow<'a, [u8]>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Name<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self::borrowed(bytes)
+    }
+}
+
+impl<'a> From<&'a str> for Name<'a> {
+    fn from(name: &'a str) -> Self {
+        Self::borrowed(name.as_bytes())
+    }
+}
+
+impl<'a> fmt::Debug for Name<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}", String::from_utf8_lossy(&self.0))
+    }
+}
+
+// Dictionary keys
+pub const TYPE: &str = "Type";
+pub const SUBTYPE: &str = "Subtype";
+pub const FILTER: &str = "Filter";
+pub const DECODE_PARMS: &str = "DecodeParms";
+pub const LENGTH: &str = "Length";
+pub const SIZE: &str = "Size";
+pub const ROOT: &str = "Root";
+pub const INFO: &str = "Info";
+pub const ENCRYPT: &str = "Encrypt";
+pub const ID: &str = "ID";
+pub const PREV: &str = "Prev";
+pub const PAGES: &str = "Pages";
+pub const VERSION: &str = "Version";
+pub const KIDS: &str = "Kids";
+pub const PARENT: &str = "Parent";
+pub const COUNT: &str = "Count";
+pub const CONTENTS: &str = "Contents";
+pub const RESOURCES: &str = "Resources";
+pub const MEDIA_BOX: &str = "MediaBox";
+pub const CROP_BOX: &str = "CropBox";
+pub const BLEED_BOX: &str = "BleedBox";
+pub const TRIM_BOX: &str = "TrimBox";
+pub const ART_BOX: &str = "ArtBox";
+pub const ROTATE: &str = "Rotate";
+pub const TABS: &str = "Tabs";
+pub const USER_UNIT: &str = "UserUnit";
+pub const DUR: &str = "Dur";
+pub const TRANS: &str = "Trans";
+pub const B: &str = "B";
+pub const GROUP: &str = "Group";
+pub const S: &str = "S";
+pub const CS: &str = "CS";
+
+// Encryption dictionary keys (Adobe, 2008, p. 56-63)
+pub const SUB_FILTER: &str = "SubFilter";
+pub const V: &str = "V";
+pub const R: &str = "R";
+pub const CF: &str = "CF";
+pub const STM_F: &str = "StmF";
+pub const CFM: &str = "CFM";
+pub const U: &str = "U";
+pub const ENCRYPT_METADATA: &str = "EncryptMetadata";
+
+// Signature dictionary keys (Adobe, 2008, p. 475)
+pub const BYTE_RANGE: &str = "ByteRange";
+pub const NAME: &str = "Name";
+
+// Annotation dictionary keys (Adobe, 2008, p. 381-384)
+pub const ANNOTS: &str = "Annots";
+pub const RECT: &str = "Rect";
+pub const QUAD_POINTS: &str = "QuadPoints";
+pub const C: &str = "C";
+pub const P: &str = "P";
+pub const F: &str = "F";
+pub const AP: &str = "AP";
+pub const NM: &str = "NM";
+pub const N: &str = "N";
+pub const BBOX: &str = "BBox";
+pub const MATRIX: &str = "Matrix";
+
+// Markup annotation keys (Adobe, 2008, p. 390-391, 396-402)
+pub const SUBJ: &str = "Subj";
+pub const M: &str = "M";
+pub const CREATION_DATE: &str = "CreationDate";
+pub const IC: &str = "IC";
+pub const OPEN: &str = "Open";
+pub const INK_LIST: &str = "InkList";
+pub const VERTICES: &str = "Vertices";
+pub const POPUP: &str = "Popup";
+pub const IRT: &str = "IRT";
+pub const RT: &str = "RT";
+pub const STATE: &str = "State";
+pub const STATE_MODEL: &str = "StateModel";
+
+// Action and document-level keys (Adobe, 2008, p. 414-422)
+pub const OPEN_ACTION: &str = "OpenAction";
+pub const A: &str = "A";
+pub const AA: &str = "AA";
+pub const JS: &str = "JS";
+pub const URI: &str = "URI";
+pub const WIN: &str = "Win";
+pub const NAMES: &str = "Names";
+pub const ACRO_FORM: &str = "AcroForm";
+pub const PAGE_LAYOUT: &str = "PageLayout";
+pub const PAGE_MODE: &str = "PageMode";
+pub const LANG: &str = "Lang";
+pub const XFA: &str = "XFA";
+pub const THREADS: &str = "Threads";
+
+// Article thread and bead keys (Adobe, 2008, p. 378-379)
+pub const I: &str = "I";
+
+// Transition dictionary keys (Adobe, 2008, p. 380-382)
+pub const DM: &str = "Dm";
+pub const DI: &str = "Di";
+pub const SS: &str = "SS";
+
+// Output intent keys (Adobe, 2008, p. 633-634)
+pub const OUTPUT_INTENTS: &str = "OutputIntents";
+pub const OUTPUT_CONDITION: &str = "OutputCondition";
+pub const OUTPUT_CONDITION_IDENTIFIER: &str = "OutputConditionIdentifier";
+pub const REGISTRY_NAME: &str = "RegistryName";
+pub const DEST_OUTPUT_PROFILE: &str = "DestOutputProfile";
+
+// Web capture keys (Adobe, 2008, p. 751-757)
+pub const SPIDER_INFO: &str = "SpiderInfo";
+pub const IDS: &str = "IDS";
+pub const URLS: &str = "URLS";
+pub const SI: &str = "SI";
+pub const AU: &str = "AU";
+pub const CT: &str = "CT";
+pub const TS: &str = "TS";
+pub const URL: &str = "URL";
+pub const L: &str = "L";
+
+// Structure tree keys (Adobe, 2008, p. 555-557)
+pub const STRUCT_TREE_ROOT: &str = "StructTreeRoot";
+pub const K: &str = "K";
+pub const PG: &str = "Pg";
+
+// Interactive form keys (Adobe, 2008, p. 431-440)
+pub const FIELDS: &str = "Fields";
+pub const FT: &str = "FT";
+pub const FF: &str = "Ff";
+pub const T: &str = "T";
+pub const DV: &str = "DV";
+pub const DA: &str = "DA";
+pub const DR: &str = "DR";
+pub const Q: &str = "Q";
+pub const MAX_LEN: &str = "MaxLen";
+pub const OPT: &str = "Opt";
+
+// Forms Data Format keys (Adobe, 2008, p. 464-469)
+pub const FDF: &str = "FDF";
+
+// Widget annotation keys (Adobe, 2008, p. 409-410)
+pub const AS: &str = "AS";
+pub const MK: &str = "MK";
+pub const BS: &str = "BS";
+pub const BC: &str = "BC";
+pub const BG: &str = "BG";
+/// The caption in an appearance characteristics dictionary, which shares its
+/// key with `STROKING_ALPHA`.
+pub const CAPTION: &str = "CA";
+
+// File specification keys (Adobe, 2008, p. 182)
+pub const FS: &str = "FS";
+pub const UF: &str = "UF";
+pub const DOS: &str = "DOS";
+pub const MAC: &str = "Mac";
+pub const UNIX: &str = "Unix";
+pub const EF: &str = "EF";
+pub const DESC: &str = "Desc";
+pub const CI: &str = "CI";
+pub const PARAMS: &str = "Params";
+pub const EMBEDDED_FILES: &str = "EmbeddedFiles";
+
+// Collection dictionary keys (Adobe, 2008, p. 588-590)
+pub const COLLECTION: &str = "Collection";
+pub const SCHEMA: &str = "Schema";
+pub const VIEW: &str = "View";
+pub const D: &str = "D";
+pub const O: &str = "O";
+
+// Outline and destination keys (Adobe, 2008, p. 366-368)
+pub const OUTLINES: &str = "Outlines";
+pub const FIRST: &str = "First";
+pub const NEXT: &str = "Next";
+pub const TITLE: &str = "Title";
+pub const DEST: &str = "Dest";
+pub const DESTS: &str = "Dests";
+
+// Page-piece dictionary keys (Adobe, 2008, p. 726)
+pub const PIECE_INFO: &str = "PieceInfo";
+pub const LAST_MODIFIED: &str = "LastModified";
+pub const PRIVATE: &str = "Private";
+
+// Resource categories (Adobe, 2008, p. 83)
+pub const EXT_G_STATE: &str = "ExtGState";
+pub const COLOR_SPACE: &str = "ColorSpace";
+pub const PATTERN: &str = "Pattern";
+pub const SHADING: &str = "Shading";
+pub const X_OBJECT: &str = "XObject";
+pub const FONT: &str = "Font";
+pub const PROC_SET: &str = "ProcSet";
+pub const PROPERTIES: &str = "Properties";
+
+// Font dictionary keys
+pub const BASE_FONT: &str = "BaseFont";
+pub const DESCENDANT_FONTS: &str = "DescendantFonts";
+pub const FONT_DESCRIPTOR: &str = "FontDescriptor";
+pub const FONT_FILE: &str = "FontFile";
+pub const FONT_FILE_2: &str = "FontFile2";
+pub const FONT_FILE_3: &str = "FontFile3";
+pub const TO_UNICODE: &str = "ToUnicode";
+pub const ENCODING: &str = "Encoding";
+pub const DIFFERENCES: &str = "Differences";
+pub const FIRST_CHAR: &str = "FirstChar";
+pub const LAST_CHAR: &str = "LastChar";
+pub const WIDTHS: &str = "Widths";
+pub const MISSING_WIDTH: &str = "MissingWidth";
+pub const ASCENT: &str = "Ascent";
+pub const DESCENT: &str = "Descent";
+pub const W: &str = "W";
+pub const DW: &str = "DW";
+pub const FONT_MATRIX: &str = "FontMatrix";
+pub const FONT_BBOX: &str = "FontBBox";
+pub const CHAR_PROCS: &str = "CharProcs";
+pub const CID_TO_GID_MAP: &str = "CIDToGIDMap";
+pub const W2: &str = "W2";
+pub const DW2: &str = "DW2";
+pub const W_MODE: &str = "WMode";
+
+// Graphics state parameter keys (Adobe, 2008, p. 128)
+pub const STROKING_ALPHA: &str = "CA";
+pub const NON_STROKING_ALPHA: &str = "ca";
+pub const SOFT_MASK: &str = "SMask";
+pub const BLEND_MODE: &str = "BM";
+
+// Cross-reference stream and object stream keys (Adobe, 2008, p. 47-51)
+pub const X_REF_STM: &str = "XRefStm";
+pub const INDEX: &str = "Index";
+pub const PREDICTOR: &str = "Predictor";
+pub const COLUMNS: &str = "Columns";
+
+// Values of the Type key
+pub const CATALOG: &str = "Catalog";
+pub const PAGE: &str = "Page";
+pub const ANNOT: &str = "Annot";
+pub const FILESPEC: &str = "Filespec";
+pub const EMBEDDED_FILE: &str = "EmbeddedFile";
+pub const OBJ_STM: &str = "ObjStm";
+pub const X_REF: &str = "XRef";
+
+// Values of the Subtype key
+pub const IMAGE: &str = "Image";
+pub const FORM: &str = "Form";
+pub const TYPE_0: &str = "Type0";
+pub const TYPE_3: &str = "Type3";
+pub const CID_FONT_TYPE_2: &str = "CIDFontType2";
+pub const HIGHLIGHT: &str = "Highlight";
+pub const WIDGET: &str = "Widget";
+pub const TEXT: &str = "Text";
+pub const FREE_TEXT: &str = "FreeText";
+pub const SQUARE: &str = "Square";
+pub const CIRCLE: &str = "Circle";
+pub const STAMP: &str = "Stamp";
+pub const INK: &str = "Ink";
+pub const POLYGON: &str = "Polygon";
+pub const POLY_LINE: &str = "PolyLine";
+
+// Action types (Adobe, 2008, p. 417)
+pub const JAVA_SCRIPT: &str = "JavaScript";
+pub const LAUNCH: &str = "Launch";
+pub const GO_TO: &str = "GoTo";
+pub const GO_TO_R: &str = "GoToR";
+
+// Other values
+pub const TRANSPARENCY: &str = "Transparency";
+pub const NONE: &str = "None";
+pub const NORMAL: &str = "Normal";
+pub const COMPATIBLE: &str = "Compatible";
+pub const MULTIPLY: &str = "Multiply";
+pub const OFF: &str = "Off";
+
+// Standard filters (Adobe, 2008, p. 23)
+pub const FLATE_DECODE: &str = "FlateDecode";
+pub const ASCII_HEX_DECODE: &str = "ASCIIHexDecode";
+pub const ASCII_85_DECODE: &str = "ASCII85Decode";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_canonical_name() {
+        assert!(Name::borrowed(b"FlateDecode").matches(FLATE_DECODE));
+        assert!(!Name::borrowed(b"flatedecode").matches(FLATE_DECODE));
+        assert!(!Name::borrowed(b"Flate").matches(FLATE_DECODE));
+    }
+
+    #[test]
+    fn should_match_decoded_escapes() {
+        // `/Lime#20Green` is decoded by the parser before it becomes a name
+        let name = Name::from(Cow::Owned(b"Lime Green".to_vec()));
+        assert!(name.matches("Lime Green"));
+    }
+
+    #[test]
+    fn should_debug_as_name() {
+        assert_eq!(format!("{:?}", Name::borrowed(b"Type")), "/Type");
+    }
+}
@@ -0,0 +1,102 @@
+use crate::parsing::context::{ParseContext, ParseOptions};
+use crate::parsing::keywords::{EOF_MARKER, PDF_HEADER};
+use crate::parsing::pdf_file::PdfFile;
+use crate::parsing::tokens::{parse_token, parse_whitespace_with_comments};
+
+/// A comment, which the parser otherwise treats as whitespace (Adobe, 2008,
+/// p. 13).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comment<'a> {
+    /// The offset of the `%` that starts the comment.
+    pub offset: usize,
+    /// The text of the comment, without the `%` or the end of line.
+    pub text: &'a [u8],
+}
+
+impl<'a> PdfFile<'a> {
+    /// Finds the comments in the file, such as markers left by the tools that
+    /// wrote it. The `%PDF-` header and `%%EOF` markers are not included, and
+    /// neither is anything inside strings or streams.
+    pub fn comments(&self) -> Vec<Comment<'_>> {
+        scan_comments(self.raw())
+            .into_iter()
+            .filter(|comment| {
+                let marker = |keyword: &[u8]| comment.text == keyword.trim_ascii_end();
+                let header = comment.offset == 0 && comment.text.starts_with(&PDF_HEADER[1..]);
+                !header && !marker(&EOF_MARKER[1..])
+            })
+            .collect()
+    }
+}
+
+/// Lexes some data, such as a file or content stream, collecting its
+/// comments. Bytes that are not valid tokens are skipped.
+pub fn scan_comments<'a>(raw: &'a [u8]) -> Vec<Comment<'a>> {
+    let context = ParseContext::with_options(ParseOptions::lenient());
+    let start = raw.as_ptr() as usize;
+
+    let mut comments = Vec::new();
+    let mut rest = raw;
+    while !rest.is_empty() {
+        let mut on_comment = |text: &'a [u8]| {
+            comments.push(Comment {
+                offset: text.as_ptr() as usize - start,
+                text: &text[1..],
+            })
+        };
+        match parse_whitespace_with_comments(rest, &mut on_comment) {
+            Ok(((), next)) => rest = next,
+            Err(_) => break,
+        }
+
+        rest = match parse_token(rest, &context) {
+            Ok((_, next)) if next.len() < rest.len() => next,
+            _ => &rest[1..],
+        };
+    }
+
+    comments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_scan_comments() {
+        let raw = b"1 0 obj % first\n<< /A (not % a comment) >>\n%second\nendobj\n";
+        let comments = scan_comments(raw);
+        assert_eq!(
+            comments,
+            vec![
+                Comment {
+                    offset: 8,
+                    text: b" first"
+                },
+                Comment {
+                    offset: 43,
+                    text: b"second"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_skip_header_and_eof_markers() {
+        let file = PdfFile::from_raw(
+            b"%PDF-1.7\n%Generated by hand\n1 0 obj\nnull\nendobj\n%%EOF\n".to_vec(),
+        );
+        let comments = file.comments();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].offset, 9);
+        assert_eq!(comments[0].text, b"Generated by hand");
+    }
+
+    #[test]
+    fn should_not_scan_streams() {
+        let raw = b"<< /Length 5 >>\nstream\n%data\nendstream\n% after\n";
+        let comments = scan_comments(raw);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].text, b" after");
+    }
+}
@@ -0,0 +1,269 @@
+use crate::error::{Error, Result};
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+/// How the parser should react to files that do not follow the spec.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Return an error for any problem with the file.
+    #[default]
+    Strict,
+    /// Repair problems where possible, recording a diagnostic for each repair.
+    Lenient,
+}
+
+/// Whether parsed strings, names and streams borrow from the file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Ownership {
+    /// Borrow from the file where the data is as it is in the file, and copy
+    /// it where it has to be unescaped or decoded.
+    #[default]
+    Mixed,
+    /// Always borrow from the file, returning an error for data that would
+    /// have to be copied, such as escaped strings or filtered streams.
+    Borrowed,
+    /// Always copy, so that every `Cow` in a parsed object is owned.
+    Owned,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub mode: ParseMode,
+    /// The largest stream, once decoded, that is kept in memory when parsing
+    /// objects. Larger streams are an error, and can instead be decoded into a
+    /// sink with `PdfFile::decode_stream_to` or spilled to a temporary file
+    /// with `PdfFile::spill_stream`.
+    pub max_stream_size: Option<usize>,
+    pub ownership: Ownership,
+}
+
+impl ParseOptions {
+    pub const fn strict() -> Self {
+        Self {
+            mode: ParseMode::Strict,
+            max_stream_size: None,
+            ownership: Ownership::Mixed,
+        }
+    }
+
+    pub const fn lenient() -> Self {
+        Self {
+            mode: ParseMode::Lenient,
+            max_stream_size: None,
+            ownership: Ownership::Mixed,
+        }
+    }
+
+    pub const fn with_max_stream_size(mut self, max_stream_size: usize) -> Self {
+        self.max_stream_size = Some(max_stream_size);
+        self
+    }
+
+    pub const fn with_ownership(mut self, ownership: Ownership) -> Self {
+        self.ownership = ownership;
+        self
+    }
+}
+
+/// A problem with the file that was repaired while parsing in lenient mode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The offset into the file at which the problem was found, if known.
+    pub offset: Option<usize>,
+    /// The error that would have been returned in strict mode.
+    pub error: Error,
+    /// What was done to carry on, such as rebuilding the xref table.
+    pub repair: String,
+}
+
+/// A thread-safe list of diagnostics, which may be appended to while the file
+/// is only borrowed immutably.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    inner: Mutex<Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, diagnostic: Diagnostic) {
+        self.inner
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(diagnostic);
+    }
+
+    pub fn to_vec(&self) -> Vec<Diagnostic> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+/// State that is shared by all of the parsers while parsing part of a file.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseContext<'s> {
+    pub options: ParseOptions,
+    /// The whole file, which is used to convert slices back into offsets.
+    source: &'s [u8],
+    diagnostics: Option<&'s Diagnostics>,
+    /// Leave stream data as it is in the file, rather than decoding it.
+    raw_streams: bool,
+}
+
+impl<'s> ParseContext<'s> {
+    pub fn new(options: ParseOptions, source: &'s [u8], diagnostics: &'s Diagnostics) -> Self {
+        Self {
+            options,
+            source,
+            diagnostics: Some(diagnostics),
+            raw_streams: false,
+        }
+    }
+
+    /// A context for parsing free-standing data, such as in tests.
+    pub const fn with_options(options: ParseOptions) -> Self {
+        Self {
+            options,
+            source: &[],
+            diagnostics: None,
+            raw_streams: false,
+        }
+    }
+
+    /// Borrows or copies parsed data as set by `ParseOptions::ownership`.
+    pub fn own<'a>(&self, data: Cow<'a, [u8]>, what: &'static str) -> Result<Cow<'a, [u8]>> {
+        match (self.options.ownership, data) {
+            (Ownership::Borrowed, Cow::Owned(_)) => Err(Error::Type(format!(
+                "{} could not be borrowed from the file",
+                what
+            ))),
+            (Ownership::Owned, Cow::Borrowed(data)) => Ok(Cow::Owned(data.to_vec())),
+            (_, data) => Ok(data),
+        }
+    }
+
+    /// A context that leaves stream data encoded, so that it can be decoded
+    /// elsewhere without keeping all of it in memory.
+    pub(crate) fn with_raw_streams(mut self) -> Self {
+        self.raw_streams = true;
+        self
+    }
+
+    pub(crate) fn raw_streams(&self) -> bool {
+        self.raw_streams
+    }
+
+    pub fn is_lenient(&self) -> bool {
+        self.options.mode == ParseMode::Lenient
+    }
+
+    /// Returns the offset of a slice into the file, if it is part of the file.
+    pub fn offset_of(&self, raw: &[u8]) -> Option<usize> {
+        let start = self.source.as_ptr() as usize;
+        let ptr = raw.as_ptr() as usize;
+        if start <= ptr && ptr + raw.len() <= start + self.source.len() && !self.source.is_empty() {
+            Some(ptr - start)
+        } else {
+            None
+        }
+    }
+
+    /// Handles a problem with the file. In strict mode the error is returned,
+    /// while in lenient mode it is recorded as a diagnostic, along with the
+    /// repair that the caller is about to make, so that the caller can carry
+    /// on. With the `tracing` feature, each repair is also logged as a
+    /// warning.
+    pub fn recover(&self, raw: &[u8], error: Error, repair: &str) -> Result<()> {
+        self.recover_at(self.offset_of(raw), error, repair)
+    }
+
+    /// Like `recover`, but for when the offset is already known.
+    pub fn recover_at(&self, offset: Option<usize>, error: Error, repair: &str) -> Result<()> {
+        match self.options.mode {
+            ParseMode::Strict => Err(error),
+            ParseMode::Lenient => {
+                self.record(offset, error, repair);
+                Ok(())
+            }
+        }
+    }
+
+    /// Records a diagnostic in either mode, for things that the spec allows
+    /// but which often mean that the file is corrupted, such as a hex string
+    /// with an odd number of digits.
+    pub fn note(&self, raw: &[u8], error: Error, repair: &str) {
+        self.record(self.offset_of(raw), error, repair);
+    }
+
+    /// Like `note`, but for when the offset is already known.
+    pub fn note_at(&self, offset: Option<usize>, error: Error, repair: &str) {
+        self.record(offset, error, repair);
+    }
+
+    fn record(&self, offset: Option<usize>, error: Error, repair: &str) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(offset, ?error, repair, "Repaired file");
+        if let Some(diagnostics) = self.diagnostics {
+            diagnostics.push(Diagnostic {
+                offset,
+                error,
+                repair: repair.to_owned(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_return_errors_in_strict_mode() {
+        let source = b"Hello, world!";
+        let diagnostics = Diagnostics::new();
+        let context = ParseContext::new(ParseOptions::strict(), source, &diagnostics);
+
+        let error = Error::Syntax("Test", "".into());
+        assert_eq!(
+            context.recover(&source[7..], error.clone(), "Skipped it"),
+            Err(error)
+        );
+        assert_eq!(diagnostics.to_vec(), vec![]);
+    }
+
+    #[test]
+    fn should_record_diagnostics_in_lenient_mode() {
+        let source = b"Hello, world!";
+        let diagnostics = Diagnostics::new();
+        let context = ParseContext::new(ParseOptions::lenient(), source, &diagnostics);
+
+        let error = Error::Syntax("Test", "".into());
+        assert_eq!(
+            context.recover(&source[7..], error.clone(), "Skipped it"),
+            Ok(())
+        );
+        assert_eq!(
+            diagnostics.to_vec(),
+            vec![Diagnostic {
+                offset: Some(7),
+                error,
+                repair: "Skipped it".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn should_not_find_offsets_outside_source() {
+        let source = b"Hello, world!";
+        let diagnostics = Diagnostics::new();
+        let context = ParseContext::new(ParseOptions::lenient(), source, &diagnostics);
+
+        assert_eq!(context.offset_of(source), Some(0));
+        assert_eq!(context.offset_of(b"Other"), None);
+        assert_eq!(ParseContext::default().offset_of(source), None);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::parsing::keywords::*;
+use crate::parsing::pdf_file::{find_trailer, PdfFile};
+use crate::utils::slices::position_of_sequence;
+use std::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+
+impl<'a> PdfFile<'a> {
+    /// Dumps a region of the file as hex and ASCII, with each line annotated
+    /// with the objects and other parts of the file that it overlaps. This is
+    /// meant for diagnosing files that cannot be parsed, so it never fails.
+    ///
+    /// Objects are located using the xref table if it is loaded, or else by
+    /// scanning the file for object headers.
+    pub fn debug_region(&self, offset: usize, len: usize) -> String {
+        let raw = self.raw();
+        let start = offset.min(raw.len());
+        let end = offset.saturating_add(len).min(raw.len());
+        let regions = self.regions();
+
+        let mut out = String::new();
+        for line_start in (start..end).step_by(BYTES_PER_LINE) {
+            let line_end = (line_start + BYTES_PER_LINE).min(end);
+            let bytes = &raw[line_start..line_end];
+
+            write!(out, "{:08x} ", line_start).unwrap();
+            for i in 0..BYTES_PER_LINE {
+                if i % 8 == 0 {
+                    out.push(' ');
+                }
+                match bytes.get(i) {
+                    Some(b) => write!(out, "{:02x} ", b).unwrap(),
+                    None => out.push_str("   "),
+                }
+            }
+
+            out.push_str(" |");
+            out.extend(bytes.iter().map(|&b| match b {
+                0x20..=0x7e => b as char,
+                _ => '.',
+            }));
+            out.push('|');
+
+            let labels = regions
+                .iter()
+                .filter(|region| region.start < line_end && line_start < region.end)
+                .map(|region| region.label.as_str())
+                .collect::<Vec<_>>();
+            if !labels.is_empty() {
+                out.push_str("  ");
+                out.push_str(&labels.join(", "));
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Finds the parts of the file, in order.
+    fn regions(&self) -> Vec<Region> {
+        let raw = self.raw();
+        let mut regions = Vec::new();
+
+        if let Some(end) = raw.iter().position(|&c| c == b'\n') {
+            regions.push(Region::new(0, end + 1, "header".into()));
+        }
+
+        let xref_table = match self.xref_table() {
+            Ok(xref_table) => xref_table,
+            Err(_) => self.rebuilt_xref_table(),
+        };
+        let mut objects = xref_table
+            .iter()
+            .filter_map(|(&reference, entry)| Some((entry.offset()?, reference)))
+            .filter(|&(offset, _)| offset < raw.len())
+            .collect::<Vec<_>>();
+        objects.sort_by_key(|&(offset, _)| offset);
+
+        let xref_offset = self.last_xref_offset().ok().filter(|&o| o < raw.len());
+        let trailer_offset = find_trailer(raw);
+
+        for (i, &(offset, reference)) in objects.iter().enumerate() {
+            // An object ends after its endobj keyword, or failing that where
+            // the next part of the file starts
+            let limit = objects
+                .get(i + 1)
+                .map(|&(offset, _)| offset)
+                .into_iter()
+                .chain(xref_offset)
+                .chain(trailer_offset)
+                .filter(|&o| o > offset)
+                .min()
+                .unwrap_or(raw.len());
+            let end = position_of_sequence(&raw[offset..limit], ENDOBJ_KEYWORD)
+                .map_or(limit, |i| offset + i + ENDOBJ_KEYWORD.len());
+            regions.push(Region::new(
+                offset,
+                end,
+                format!("object {} {}", reference.number, reference.generation),
+            ));
+        }
+
+        if let Some(xref_offset) = xref_offset {
+            let end = trailer_offset
+                .filter(|&o| o > xref_offset)
+                .unwrap_or(raw.len());
+            regions.push(Region::new(xref_offset, end, "xref table".into()));
+        }
+        if let Some(trailer_offset) = trailer_offset {
+            regions.push(Region::new(trailer_offset, raw.len(), "trailer".into()));
+        }
+
+        regions
+    }
+}
+
+struct Region {
+    start: usize,
+    end: usize,
+    label: String,
+}
+
+impl Region {
+    fn new(start: usize, end: usize, label: String) -> Self {
+        Self { start, end, label }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsing::pdf_file::PdfFile;
+
+    #[test]
+    fn should_dump_hex_and_ascii() {
+        let file = PdfFile::from_raw(b"%PDF-1.7\n1 0 obj\n<< >>\nendobj\n".to_vec());
+        let dump = file.debug_region(0, 20);
+        let lines = dump.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "00000000  25 50 44 46 2d 31 2e 37  0a 31 20 30 20 6f 62 6a  \
+             |%PDF-1.7.1 0 obj|  header, object 1 0"
+        );
+        assert!(lines[1].starts_with("00000010  0a 3c 3c 20 "));
+        assert!(lines[1].ends_with("|.<< |  object 1 0"));
+    }
+
+    #[cfg(feature = "write")]
+    #[test]
+    fn should_annotate_xref_and_trailer() {
+        let raw = crate::test_support::encrypted();
+        let len = raw.len();
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+
+        let offset = file.last_xref_offset().unwrap();
+        let dump = file.debug_region(offset - 16, 32);
+        let lines = dump.lines().collect::<Vec<_>>();
+        assert!(lines[0].ends_with("object 6 0"));
+        assert!(lines[1].ends_with("xref table"));
+
+        let dump = file.debug_region(len - 10, 100);
+        assert_eq!(dump.lines().count(), 1);
+        assert!(dump.trim_end().ends_with("trailer"));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::context::{ParseContext, ParseOptions};
+use crate::parsing::keywords::*;
+use crate::parsing::objects::{parse_dictionary, parse_indirect_object};
+use crate::parsing::pdf_file::{find_startxref, parse_xref_section, PdfFile, XrefEntry, XrefTable};
+use crate::parsing::source::PdfSource;
+use crate::parsing::tokens;
+use std::cell::RefCell;
+use std::fs::File;
+use std::path::Path;
+
+/// How much of the end of the file is read to find `startxref`, which should
+/// be in the last 1024 bytes (Adobe, 2008, p. 43). More is read if it is not
+/// found there.
+const TAIL_LENGTH: usize = 2048;
+
+/// How much is read at first when looking for the end of an object or xref
+/// section. This is doubled until the whole of it has been read.
+const INITIAL_WINDOW: usize = 4096;
+
+/// A file that is read on demand, so that only the xref table, the trailer and
+/// the objects that are asked for are ever read into memory. This is much
+/// faster than `PdfFile` for probing large files, but since every object is
+/// read separately it is slower for reading whole documents.
+///
+/// Only the last xref section is read, and objects are always copied out of
+/// the file.
+pub struct LazyPdfFile {
+    source: RefCell<Box<dyn PdfSource>>,
+    size: usize,
+    options: ParseOptions,
+    xref_table: XrefTable,
+    trailer: Object<'static>,
+}
+
+impl PdfFile<'static> {
+    /// Opens a file without reading all of it, see `LazyPdfFile`.
+    pub fn open_lazy<P: AsRef<Path>>(path: P) -> Result<LazyPdfFile> {
+        LazyPdfFile::from_source(File::open(path)?)
+    }
+}
+
+impl LazyPdfFile {
+    /// Reads the xref table and trailer from a source.
+    pub fn from_source<S: PdfSource + 'static>(mut source: S) -> Result<Self> {
+        let size = source.size()?;
+        let mut tail_length = TAIL_LENGTH;
+        let xref_offset = loop {
+            let tail_start = size.saturating_sub(tail_length);
+            let tail = source.read_at(tail_start, tail_length)?;
+            if let Some((_, xref_offset)) = find_startxref(&tail) {
+                break xref_offset;
+            }
+            if tail_start == 0 {
+                return Err(Error::Syntax("Could not find startxref keyword", "".into()));
+            }
+            tail_length *= 4;
+        };
+
+        let mut file = Self {
+            source: RefCell::new(Box::new(source)),
+            size,
+            options: ParseOptions::default(),
+            xref_table: XrefTable::new(),
+            trailer: Object::Null,
+        };
+        let context = ParseContext::default();
+        (file.xref_table, file.trailer) = file.read_from(xref_offset, |raw| {
+            let (xref_table, raw) = parse_xref_section(raw)?;
+            let ((), raw) = tokens::parse_whitespace(raw)?;
+            let (trailer_keyword, raw) = tokens::parse_keyword(raw)?;
+            if trailer_keyword != TRAILER_KEYWORD {
+                return Err(Error::Syntax("Could not find trailer keyword", "".into()));
+            }
+            let (trailer, _raw) = parse_dictionary(raw, &context)?;
+            Ok((xref_table, Object::Dictionary(trailer).into_owned()))
+        })?;
+
+        Ok(file)
+    }
+
+    /// Sets the options that are used when parsing objects. Diagnostics are
+    /// not recorded for lazily read files.
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The length of the file, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn xref_table(&self) -> &XrefTable {
+        &self.xref_table
+    }
+
+    pub fn trailer(&self) -> &Object<'static> {
+        &self.trailer
+    }
+
+    /// Reads the document catalog (Adobe, 2008, p. 71).
+    pub fn catalog(&self) -> Result<Object<'static>> {
+        self.resolve(self.trailer.get_required(names::ROOT)?)
+    }
+
+    /// Resolves an object if it is an indirect reference.
+    pub fn resolve(&self, object: &Object) -> Result<Object<'static>> {
+        match *object {
+            Object::Indirect(reference) => self.object(reference),
+            _ => Ok(object.to_static()),
+        }
+    }
+
+    /// Reads an indirect object from the source.
+    pub fn object(&self, reference: IndirectRef) -> Result<Object<'static>> {
+        let offset = match self.xref_table.get(&reference) {
+            Some(&XrefEntry::InUse(offset)) => offset,
+            Some(XrefEntry::Compressed { .. }) => {
+                return Err(Error::Syntax(
+                    "Objects in object streams are not supported",
+                    format!("{:?}", reference),
+                ))
+            }
+            Some(XrefEntry::Free) | None => return Err(Error::ObjectNotFound(reference)),
+        };
+
+        let context = ParseContext::with_options(self.options);
+        self.read_from(offset, |raw| {
+            let ((ind, object), _raw) = parse_indirect_object(raw, &context)?;
+            if ind != Some(reference) {
+                return Err(Error::Syntax(
+                    "Object number and generation number do not match values in xref table",
+                    format!("{:?} vs. {:?}", ind, reference),
+                ));
+            }
+            Ok(object.into_owned())
+        })
+    }
+
+    /// Reads more and more of the file from an offset, until `parse`
+    /// succeeds or the end of the file is reached.
+    fn read_from<T>(&self, offset: usize, parse: impl Fn(&[u8]) -> Result<T>) -> Result<T> {
+        if offset >= self.size {
+            return Err(Error::Syntax(
+                "Offset is past the end of the file",
+                offset.to_string(),
+            ));
+        }
+
+        let mut window = INITIAL_WINDOW;
+        loop {
+            let raw = self.source.borrow_mut().read_at(offset, window)?;
+            match parse(&raw) {
+                Ok(value) => return Ok(value),
+                Err(err) if offset + raw.len() >= self.size => return Err(err),
+                Err(_) => window *= 2,
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::writing::document::DocumentBuilder;
+    use std::cell::Cell;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::rc::Rc;
+
+    /// Counts how many bytes are read from a cursor.
+    struct Counting {
+        inner: Cursor<Vec<u8>>,
+        read: Rc<Cell<usize>>,
+    }
+
+    impl Read for Counting {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read.set(self.read.get() + n);
+            Ok(n)
+        }
+    }
+
+    impl Seek for Counting {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn should_open_files_lazily() {
+        let path = test_support::temp_file("lazy", &test_support::object_streams());
+        let file = PdfFile::open_lazy(&path).unwrap();
+        let catalog = file.catalog().unwrap();
+        assert!(catalog
+            .get_name(names::TYPE)
+            .unwrap()
+            .matches(names::CATALOG));
+
+        let pages = file.resolve(&catalog[b"Pages"]).unwrap();
+        assert_eq!(pages.get_int(names::COUNT).unwrap(), 1);
+        drop(file);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_only_read_what_is_needed() {
+        // Enough pages that the file is much bigger than the windows
+        let raw = test_support::multi_page(500);
+        let read = Rc::new(Cell::new(0));
+        let source = Counting {
+            inner: Cursor::new(raw.clone()),
+            read: read.clone(),
+        };
+
+        let file = LazyPdfFile::from_source(source).unwrap();
+        let catalog = file.catalog().unwrap();
+        assert!(read.get() < raw.len());
+
+        let mut eager = PdfFile::from_bytes(&raw);
+        eager.load_xref_table().unwrap();
+        assert_eq!(file.xref_table(), eager.xref_table().unwrap());
+        let reference = catalog.get_indirect(names::PAGES).unwrap();
+        assert_eq!(
+            file.object(reference).unwrap(),
+            eager.object(reference).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_read_objects_larger_than_the_window() {
+        let content = vec![b'%'; INITIAL_WINDOW * 3];
+        let raw = test_support::single_page(DocumentBuilder::new(), Object::Null, &content);
+
+        let file = LazyPdfFile::from_source(Cursor::new(raw)).unwrap();
+        let catalog = file.catalog().unwrap();
+        let pages = file.resolve(&catalog[b"Pages"]).unwrap();
+        let page = file
+            .resolve(&pages.get_array(names::KIDS).unwrap()[0])
+            .unwrap();
+        let contents = file.resolve(&page[b"Contents"]).unwrap();
+        assert_eq!(contents.as_stream().unwrap().1.as_ref(), content);
+    }
+}
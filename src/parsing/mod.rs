@@ -1,4 +1,13 @@
+pub mod comments;
+pub mod context;
+pub mod debug;
 pub mod keywords;
+pub mod lazy;
 pub mod objects;
 pub mod pdf_file;
+pub mod recovery;
+pub mod source;
+pub mod spill;
+pub mod tokenize;
 pub mod tokens;
+pub mod xref_stream;
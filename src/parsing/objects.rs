@@ -1,11 +1,24 @@
 use crate::error::{Error, Result};
-use crate::objects::IndirectRef;
-use crate::objects::Object;
-use crate::parsing::keywords::OBJ_KEYWORD;
-use crate::parsing::tokens::{parse_token, ParseResult, Token};
+use crate::filters;
+use crate::objects::{names, Dictionary, IndirectRef, Object};
+use crate::parsing::context::ParseContext;
+use crate::parsing::keywords::{ENDOBJ_KEYWORD, ENDSTREAM_KEYWORD, OBJ_KEYWORD};
+use crate::parsing::tokens::{self, parse_token, starts_with_endstream, ParseResult, Token};
+use crate::utils::chars::is_numeric_char;
+use smallvec::{Drain, SmallVec};
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::vec::Drain;
+
+/// The number of entries that a parse stack holds before it moves to the
+/// heap, which is enough for most objects and operands, since the arrays in
+/// documents are mostly short, such as rectangles and matrices.
+const INLINE_STACK_SIZE: usize = 16;
+
+/// How deeply arrays and dictionaries may be nested, so that a hostile file
+/// cannot overflow the stack, either while objects that are parsed directly
+/// recurse or when deeply nested objects are dropped. Real documents rarely
+/// nest more than a few levels.
+const MAX_NESTING: usize = 256;
 
 #[derive(Debug, PartialEq)]
 pub enum ParseStackEntry<'a> {
@@ -17,18 +30,41 @@ pub enum ParseStackEntry<'a> {
 use ParseStackEntry::*;
 
 pub struct ParseStack<'a> {
-    inner: Vec<ParseStackEntry<'a>>,
+    inner: SmallVec<[ParseStackEntry<'a>; INLINE_STACK_SIZE]>,
+    /// The number of arrays and dictionaries that have been begun but not
+    /// ended.
+    open: usize,
+}
+
+impl<'a> Default for ParseStack<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> ParseStack<'a> {
     pub fn new() -> Self {
-        Self { inner: Vec::new() }
+        Self {
+            inner: SmallVec::new(),
+            open: 0,
+        }
     }
 
     pub fn push(&mut self, entry: ParseStackEntry<'a>) {
         self.inner.push(entry)
     }
 
+    /// Pushes the start of an array or dictionary, failing if that would nest
+    /// them more than `MAX_NESTING` levels deep.
+    pub fn begin(&mut self, entry: ParseStackEntry<'a>) -> Result<()> {
+        if self.open >= MAX_NESTING {
+            return Err(nested_too_deeply());
+        }
+        self.open += 1;
+        self.push(entry);
+        Ok(())
+    }
+
     pub fn pop(&mut self) -> Option<ParseStackEntry<'a>> {
         self.inner.pop()
     }
@@ -41,10 +77,28 @@ impl<'a> ParseStack<'a> {
         }
     }
 
+    /// Removes every object from the stack, in the order they were pushed.
+    pub fn take_objects(&mut self) -> Result<Vec<Object<'a>>> {
+        self.open = 0;
+        let mut objects = Vec::with_capacity(self.inner.len());
+        for entry in self.inner.drain(..) {
+            match entry {
+                Obj(obj) => objects.push(obj),
+                _ => {
+                    return Err(Error::Syntax(
+                        "Unterminated array or dictionary",
+                        format!("{:?}", entry),
+                    ))
+                }
+            }
+        }
+        Ok(objects)
+    }
+
     pub fn pop_back_to(
         &mut self,
         start_entry: &ParseStackEntry<'a>,
-    ) -> Result<Drain<ParseStackEntry<'a>>> {
+    ) -> Result<Drain<'_, [ParseStackEntry<'a>; INLINE_STACK_SIZE]>> {
         // Find the index of the most recent start_entry
         let start = self.inner.len()
             - self
@@ -57,6 +111,7 @@ impl<'a> ParseStack<'a> {
                     format!("{:?}", start_entry),
                 ))?
             - 1;
+        self.open = self.open.saturating_sub(1);
         // Pop the array elements, in the right order
         let mut entries = self.inner.drain(start..);
         // Skip the starting marker
@@ -69,6 +124,7 @@ impl<'a> ParseStack<'a> {
 pub fn parse_object_until_keyword<'a>(
     mut raw: &'a [u8],
     end_keyword: &'static [u8],
+    context: &ParseContext,
 ) -> ParseResult<'a, (Option<IndirectRef>, Object<'a>)> {
     let mut indirect = None;
     let mut obj_handler = |stack: &mut ParseStack<'a>| -> Result<bool> {
@@ -91,24 +147,204 @@ pub fn parse_object_until_keyword<'a>(
     keyword_handlers.insert(OBJ_KEYWORD, &mut obj_handler);
     keyword_handlers.insert(end_keyword, &mut end_handler);
 
-    ((), raw) = parse(raw, &mut keyword_handlers)?;
+    ((), raw) = parse(raw, context, &mut keyword_handlers)?;
 
     let object = object.ok_or_else(|| Error::Syntax("Did not encounter end keyword", "".into()))?;
     Ok(((indirect, object), raw))
 }
 
+/// Parses an indirect object, `n g obj ... endobj`, returning its reference.
+/// Most indirect objects are dictionaries or streams, which are parsed by
+/// [`parse_dictionary`]; anything else goes through the generic parser.
+pub fn parse_indirect_object<'a>(
+    raw: &'a [u8],
+    context: &ParseContext,
+) -> ParseResult<'a, (Option<IndirectRef>, Object<'a>)> {
+    let Some((reference, body)) = object_header(raw) else {
+        return parse_object_until_keyword(raw, ENDOBJ_KEYWORD, context);
+    };
+    let ((), body) = tokens::parse_whitespace(body)?;
+    if !body.starts_with(b"<<") {
+        return parse_object_until_keyword(raw, ENDOBJ_KEYWORD, context);
+    }
+
+    let (dict, after_dict) = parse_dictionary(body, context)?;
+    let mut object = Object::Dictionary(dict);
+    let (mut token, mut rest) = parse_token(after_dict, context)?;
+    if let Token::Stream(stream) = token {
+        let data = &after_dict[stream.as_ptr() as usize - after_dict.as_ptr() as usize..];
+        (object, rest) = process_stream(object, data, stream.len(), rest, context)?;
+        (token, rest) = parse_token(rest, context)?;
+    }
+    match token {
+        Token::Keyword(ENDOBJ_KEYWORD) => Ok(((Some(reference), object), rest)),
+        other => Err(Error::Syntax(
+            "Did not encounter end keyword",
+            format!("{:?}", other),
+        )),
+    }
+}
+
+/// Reads the `n g obj` header of an indirect object, without the context, as
+/// numbers and keywords are never recovered.
+fn object_header(raw: &[u8]) -> Option<(IndirectRef, &[u8])> {
+    let (number, raw) = lookahead_integer(raw)?;
+    let (generation, raw) = lookahead_integer(raw)?;
+    let ((), raw) = tokens::parse_whitespace(raw).ok()?;
+    match tokens::parse_keyword(raw).ok()? {
+        (OBJ_KEYWORD, raw) => Some((IndirectRef::checked(number, generation).ok()?, raw)),
+        _ => None,
+    }
+}
+
+/// Parses the next token if it is an integer, without recording anything in
+/// the context.
+fn lookahead_integer(raw: &[u8]) -> Option<(i64, &[u8])> {
+    let ((), raw) = tokens::parse_whitespace(raw).ok()?;
+    if !is_numeric_char(*raw.first()?) {
+        return None;
+    }
+    match parse_token(raw, &ParseContext::default()).ok()? {
+        (Token::Integer(int), raw) => Some((int, raw)),
+        _ => None,
+    }
+}
+
+/// Parses a dictionary directly, for callers that know one comes next, such
+/// as trailers and the dictionaries of indirect objects and streams. This
+/// avoids the parse stack, as the structure is known as it is read. Returns
+/// the bytes after the closing `>>`.
+pub fn parse_dictionary<'a>(
+    raw: &'a [u8],
+    context: &ParseContext,
+) -> ParseResult<'a, Dictionary<'a>> {
+    match parse_token(raw, context)? {
+        (Token::BeginDictionary, raw) => parse_dictionary_entries(raw, context, 0),
+        (other, _) => Err(Error::Syntax(
+            "Expected a dictionary",
+            format!("{:?}", other),
+        )),
+    }
+}
+
+fn parse_dictionary_entries<'a>(
+    mut raw: &'a [u8],
+    context: &ParseContext,
+    depth: usize,
+) -> ParseResult<'a, Dictionary<'a>> {
+    let mut dict = Dictionary::new();
+    loop {
+        let (token, rest) = parse_token(raw, context)?;
+        let key = match token {
+            Token::EndDictionary => return Ok((dict, rest)),
+            Token::Name(key) => context.own(key, "Name")?.into(),
+            other => {
+                return Err(Error::Syntax(
+                    "Misplaced token inside dictionary",
+                    format!("{:?}", other),
+                ))
+            }
+        };
+        let (token, rest) = parse_token(rest, context)?;
+        let (value, rest) = parse_direct_object(token, rest, context, depth + 1)?;
+        dict.insert(key, value);
+        raw = rest;
+    }
+}
+
+/// Parses a single direct object that is not wrapped in `obj` and `endobj`,
+/// such as one inside an object stream (Adobe, 2008, p. 45).
+pub fn parse_object<'a>(raw: &'a [u8], context: &ParseContext) -> ParseResult<'a, Object<'a>> {
+    let (token, rest) = parse_token(raw, context)?;
+    parse_direct_object(token, rest, context, 0)
+}
+
+fn nested_too_deeply() -> Error {
+    Error::Syntax(
+        "Objects are nested too deeply",
+        format!("More than {} levels", MAX_NESTING),
+    )
+}
+
+/// Parses the rest of a direct object that starts with `token`, which may
+/// be a reference but not a stream. `depth` counts the arrays and
+/// dictionaries that the object is inside of.
+fn parse_direct_object<'a>(
+    token: Token<'a>,
+    raw: &'a [u8],
+    context: &ParseContext,
+    depth: usize,
+) -> ParseResult<'a, Object<'a>> {
+    if depth > MAX_NESTING {
+        return Err(nested_too_deeply());
+    }
+    let object = match token {
+        Token::Keyword(b"true") => Object::Boolean(true),
+        Token::Keyword(b"false") => Object::Boolean(false),
+        Token::Keyword(b"null") => Object::Null,
+        Token::Integer(number) => {
+            // Two integers followed by `R` are a reference
+            if let Some((generation, rest)) = lookahead_integer(raw) {
+                if let Ok(((), rest)) = tokens::parse_whitespace(rest) {
+                    if let Ok((b"R", rest)) = tokens::parse_keyword(rest) {
+                        let reference = IndirectRef::checked(number, generation)?;
+                        return Ok((Object::Indirect(reference), rest));
+                    }
+                }
+            }
+            Object::Integer(number)
+        }
+        Token::Real(x) => Object::Real(x),
+        Token::LiteralString(s) => Object::String(context.own(s, "String")?),
+        Token::HexadecimalString(s) => Object::String(context.own(s, "Hex string")?),
+        Token::Name(n) => Object::Name(context.own(n, "Name")?.into()),
+        Token::BeginArray => {
+            let mut array = Vec::new();
+            let mut raw = raw;
+            loop {
+                let (token, rest) = parse_token(raw, context)?;
+                if token == Token::EndArray {
+                    return Ok((Object::Array(array), rest));
+                }
+                let (value, rest) = parse_direct_object(token, rest, context, depth + 1)?;
+                array.push(value);
+                raw = rest;
+            }
+        }
+        Token::BeginDictionary => {
+            let (dict, rest) = parse_dictionary_entries(raw, context, depth)?;
+            return Ok((Object::Dictionary(dict), rest));
+        }
+        Token::Keyword(keyword) => {
+            return Err(Error::Syntax(
+                "Unrecognized keyword",
+                String::from_utf8_lossy(keyword).into(),
+            ))
+        }
+        other => {
+            return Err(Error::Syntax(
+                "Misplaced token inside dictionary",
+                format!("{:?}", other),
+            ))
+        }
+    };
+    Ok((object, raw))
+}
+
 pub type KeywordHandlerMap<'a, 'b> =
-    HashMap<&'static [u8], &'b mut (dyn FnMut(&mut ParseStack<'a>) -> Result<bool>)>;
+    HashMap<&'static [u8], &'b mut dyn FnMut(&mut ParseStack<'a>) -> Result<bool>>;
 
 pub fn parse<'a, 'b>(
     mut raw: &'a [u8],
+    context: &ParseContext,
     keyword_handlers: &mut KeywordHandlerMap<'a, 'b>,
 ) -> ParseResult<'a, ()> {
     let mut stack = ParseStack::new();
     let mut running = true;
 
     while running {
-        let (token, rest) = parse_token(raw)?;
+        let start = raw;
+        let (token, rest) = parse_token(raw, context)?;
         raw = rest;
 
         match token {
@@ -127,22 +363,32 @@ pub fn parse<'a, 'b>(
             Token::Real(x) => stack.push(Obj(Object::Real(x))),
 
             // String Objects
-            Token::LiteralString(s) => stack.push(Obj(Object::String(s))),
-            Token::HexadecimalString(s) => stack.push(Obj(Object::String(s))),
+            Token::LiteralString(s) => stack.push(Obj(Object::String(context.own(s, "String")?))),
+            Token::HexadecimalString(s) => {
+                stack.push(Obj(Object::String(context.own(s, "Hex string")?)))
+            }
 
             // Name Objects
-            Token::Name(n) => stack.push(Obj(Object::Name(n))),
+            Token::Name(n) => stack.push(Obj(Object::Name(context.own(n, "Name")?.into()))),
 
             // Array Objects
-            Token::BeginArray => stack.push(BeginArray),
+            Token::BeginArray => stack.begin(BeginArray)?,
             Token::EndArray => process_array(&mut stack)?,
 
             // Dictionary Objects
-            Token::BeginDictionary => stack.push(BeginDictionary),
+            Token::BeginDictionary => stack.begin(BeginDictionary)?,
             Token::EndDictionary => process_dictionary(&mut stack)?,
 
             // Stream Objects
-            Token::Stream(stream) => process_stream(&mut stack, stream)?,
+            Token::Stream(stream) => {
+                // The data may run past the first `endstream` keyword, so give
+                // the rest of the input to check against /Length
+                let data = &start[stream.as_ptr() as usize - start.as_ptr() as usize..];
+                let dict = stack.pop_obj()?;
+                let (stream, rest) = process_stream(dict, data, stream.len(), rest, context)?;
+                stack.push(Obj(stream));
+                raw = rest;
+            }
 
             // Null Object
             Token::Keyword(b"null") => stack.push(Obj(Object::Null)),
@@ -163,30 +409,29 @@ pub fn parse<'a, 'b>(
     Ok(((), raw))
 }
 
-fn process_array(stack: &mut ParseStack) -> Result<()> {
+pub(crate) fn process_array(stack: &mut ParseStack) -> Result<()> {
     // Pop the array elements, in the right order
     let entries = stack.pop_back_to(&BeginArray)?;
-    // Then unwrap them into objects
-    let objects = entries
-        .map(|entry| {
-            if let Obj(object) = entry {
-                Ok(object)
-            } else {
-                Err(Error::Syntax("Unrecognized token inside array", "".into()))
-            }
-        })
-        .collect::<Result<Vec<Object>>>()?;
+    // Then unwrap them into objects, allocating the array once at its size
+    let mut objects = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if let Obj(object) = entry {
+            objects.push(object);
+        } else {
+            return Err(Error::Syntax("Unrecognized token inside array", "".into()));
+        }
+    }
     // Push an Obj
     stack.push(Obj(Object::Array(objects)));
 
     Ok(())
 }
 
-fn process_dictionary<'a>(stack: &mut ParseStack<'a>) -> Result<()> {
+pub(crate) fn process_dictionary<'a>(stack: &mut ParseStack<'a>) -> Result<()> {
     // Pop the dictionary elements, in the right order
     let mut entries = stack.pop_back_to(&BeginDictionary)?;
     // Then unwrap them into key/value pairs
-    let mut dict = HashMap::<Cow<'a, [u8]>, Object<'a>>::with_capacity(entries.len() / 2);
+    let mut dict = Dictionary::with_capacity(entries.len() / 2);
     while let Some(entry) = entries.next() {
         let key = if let Obj(Object::Name(key)) = entry {
             key
@@ -218,22 +463,77 @@ fn process_dictionary<'a>(stack: &mut ParseStack<'a>) -> Result<()> {
     Ok(())
 }
 
-fn process_stream<'a>(stack: &mut ParseStack<'a>, stream: &'a [u8]) -> Result<()> {
-    let dict = stack.pop_obj()?;
-    let mut stream = Cow::Borrowed(stream);
+/// Processes a stream, whose data starts at the beginning of `data`. The
+/// tokenizer ends the stream at the first `endstream` keyword, which is at
+/// `first_end` and followed by `rest`, but the data may contain that keyword,
+/// so `/Length` is preferred where it points at a later one (Adobe, 2008,
+/// p. 19). Returns the stream and the bytes that follow it.
+fn process_stream<'a>(
+    dict: Object<'a>,
+    data: &'a [u8],
+    first_end: usize,
+    mut rest: &'a [u8],
+    context: &ParseContext,
+) -> ParseResult<'a, Object<'a>> {
+    let mut raw = &data[..first_end];
+
+    // The length may be an indirect object, in which case it cannot be checked
+    // until the object is resolved.
+    if let Some(&Object::Integer(length)) = dict.get(names::LENGTH) {
+        match usize::try_from(length)
+            .ok()
+            .and_then(|length| end_at_length(data, length))
+        {
+            Some((stream, after)) => (raw, rest) = (stream, after),
+            None => context.recover(
+                raw,
+                Error::Syntax(
+                    "Stream length does not match /Length",
+                    format!("{} vs. {}", length, raw.len()),
+                ),
+                "Ended the stream at the first endstream keyword",
+            )?,
+        }
+    }
+
+    let mut stream = Cow::Borrowed(raw);
 
-    for filter in &dict[b"Filter"] {
-        match filter.as_name()?.as_ref() {
-            b"FlateDecode" => {
-                stream = inflate::inflate_bytes_zlib(&stream).unwrap().into();
-            }
-            name => return Err(Error::UnknownFilter(String::from_utf8_lossy(name).into())),
+    if !context.raw_streams() {
+        for filter in &dict[names::FILTER.as_bytes()] {
+            stream = filters::decode(filter.as_name()?, &stream)?.into();
+            check_stream_size(stream.len(), context)?;
         }
     }
 
-    stack.push(Obj(Object::Stream(dict.into(), stream)));
+    let stream = context.own(stream, "Filtered stream")?;
+    Ok((Object::Stream(dict.into(), stream), rest))
+}
 
-    Ok(())
+/// Refuses decoded streams that are larger than the configured limit.
+fn check_stream_size(size: usize, context: &ParseContext) -> Result<()> {
+    match context.options.max_stream_size {
+        Some(max) if size > max => Err(Error::Type(format!(
+            "Decoded stream is {} bytes, which is more than the limit of {}",
+            size, max
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `length` bytes of data are followed by an optional EOL and the
+/// `endstream` keyword, which is not part of the data (Adobe, 2008, p. 19).
+/// Returns the data and the bytes after the keyword.
+fn end_at_length(data: &[u8], length: usize) -> Option<(&[u8], &[u8])> {
+    let after = data.get(length..)?;
+    let after = after
+        .strip_prefix(b"\r\n")
+        .or_else(|| after.strip_prefix(b"\n"))
+        .or_else(|| after.strip_prefix(b"\r"))
+        .unwrap_or(after);
+    if !starts_with_endstream(after) {
+        return None;
+    }
+    Some((&data[..length], &after[ENDSTREAM_KEYWORD.len()..]))
 }
 
 fn process_indirect(stack: &mut ParseStack) -> Result<()> {
@@ -241,10 +541,9 @@ fn process_indirect(stack: &mut ParseStack) -> Result<()> {
     let generation = stack.pop_obj()?.as_int()?;
     let number = stack.pop_obj()?.as_int()?;
 
-    stack.push(Obj(Object::Indirect(IndirectRef {
-        number: number as u32,
-        generation: generation as u16,
-    })));
+    stack.push(Obj(Object::Indirect(IndirectRef::checked(
+        number, generation,
+    )?)));
 
     Ok(())
 }
@@ -252,43 +551,78 @@ fn process_indirect(stack: &mut ParseStack) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::objects::Name;
+    use crate::parsing::context::{Ownership, ParseOptions};
+
+    const STRICT: ParseContext = ParseContext::with_options(ParseOptions::strict());
+
+    #[test]
+    fn should_borrow_or_copy_as_configured() {
+        let borrowed =
+            ParseContext::with_options(ParseOptions::strict().with_ownership(Ownership::Borrowed));
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(b"[(Hello) /Name] end ", b"end", &borrowed).unwrap();
+        let Object::Array(array) = obj else { panic!() };
+        assert!(matches!(array[0], Object::String(Cow::Borrowed(_))));
+        assert!(matches!(array[1], Object::Name(Name(Cow::Borrowed(_)))));
+        assert!(matches!(
+            parse_object_until_keyword(b"(Hello\\n) end ", b"end", &borrowed),
+            Err(Error::Type(_))
+        ));
+        assert!(parse_object_until_keyword(b"/A#42 end ", b"end", &borrowed).is_err());
+
+        let owned =
+            ParseContext::with_options(ParseOptions::strict().with_ownership(Ownership::Owned));
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(b"<< /Key (Hello) >> end ", b"end", &owned).unwrap();
+        let Object::Dictionary(dict) = obj else {
+            panic!()
+        };
+        let (key, value) = dict.into_iter().next().unwrap();
+        assert!(matches!(key, Name(Cow::Owned(_))));
+        assert!(matches!(value, Object::String(Cow::Owned(_))));
+    }
 
     #[test]
     fn should_parse_boolean() {
-        let ((_, obj), _raw) = parse_object_until_keyword(b"true end ", b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(b"true end ", b"end", &STRICT).unwrap();
         assert_eq!(obj, Object::Boolean(true));
 
-        let ((_, obj), _raw) = parse_object_until_keyword(b"false end ", b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(b"false end ", b"end", &STRICT).unwrap();
         assert_eq!(obj, Object::Boolean(false));
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn should_parse_numeric() {
-        let ((_, obj), _raw) = parse_object_until_keyword(b"42 end ", b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(b"42 end ", b"end", &STRICT).unwrap();
         assert_eq!(obj, Object::Integer(42));
 
-        let ((_, obj), _raw) = parse_object_until_keyword(b"+3.14 end ", b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(b"+3.14 end ", b"end", &STRICT).unwrap();
         assert_eq!(obj, Object::Real(3.14));
     }
 
     #[test]
     fn should_parse_string() {
-        let ((_, obj), _raw) = parse_object_until_keyword(b"(Hello, world!) end ", b"end").unwrap();
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(b"(Hello, world!) end ", b"end", &STRICT).unwrap();
         assert_eq!(obj, Object::String(Cow::Borrowed(b"Hello, world!")));
 
-        let ((_, obj), _raw) = parse_object_until_keyword(b"<616263> end ", b"end").unwrap();
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(b"<616263> end ", b"end", &STRICT).unwrap();
         assert_eq!(obj, Object::String(Cow::Borrowed(b"abc")));
     }
 
     #[test]
     fn should_parse_name() {
-        let ((_, obj), _raw) = parse_object_until_keyword(b"/Name end ", b"end").unwrap();
-        assert_eq!(obj, Object::Name(Cow::Borrowed(b"Name")));
+        let ((_, obj), _raw) = parse_object_until_keyword(b"/Name end ", b"end", &STRICT).unwrap();
+        assert_eq!(obj, Object::Name(Name::borrowed(b"Name")));
     }
 
     #[test]
     fn should_parse_array() {
-        let ((_, obj), _raw) = parse_object_until_keyword(b"[1 2 3] end ", b"end").unwrap();
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(b"[1 2 3] end ", b"end", &STRICT).unwrap();
         assert_eq!(
             obj,
             Object::Array(vec![
@@ -298,7 +632,8 @@ mod tests {
             ])
         );
 
-        let ((_, obj), _raw) = parse_object_until_keyword(b"[1[2]3] end ", b"end").unwrap();
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(b"[1[2]3] end ", b"end", &STRICT).unwrap();
         assert_eq!(
             obj,
             Object::Array(vec![
@@ -323,12 +658,12 @@ mod tests {
                                          /VeryLastItem (OK)
                                       >>
                     >> end ";
-        let ((_, obj), _raw) = parse_object_until_keyword(raw, b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(raw, b"end", &STRICT).unwrap();
 
-        assert_eq!(obj[b"Type"], Object::Name(Cow::Borrowed(b"Example")));
+        assert_eq!(obj[b"Type"], Object::Name(Name::borrowed(b"Example")));
         assert_eq!(
             obj[b"Subtype"],
-            Object::Name(Cow::Borrowed(b"DictionaryExample"))
+            Object::Name(Name::borrowed(b"DictionaryExample"))
         );
         assert_eq!(obj[b"Version"], Object::Real(0.01));
         assert_eq!(obj[b"IntegerItem"], Object::Integer(12));
@@ -347,10 +682,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_parse_dictionary_directly() {
+        let raw = b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612.0 792]
+                       /Resources << /Font << /F1 3 0 R >> /ProcSet [/PDF /Text] >>
+                       /Rotate -90 /Annots [4 0 R 5 1 R] /Title (Page 1) /Flag true
+                       /Empty [] /Nothing null >> end ";
+        let (dict, rest) = parse_dictionary(raw, &STRICT).unwrap();
+        assert_eq!(rest, b" end ");
+        let ((_, generic), _raw) = parse_object_until_keyword(raw, b"end", &STRICT).unwrap();
+        assert_eq!(Object::Dictionary(dict), generic);
+
+        assert!(parse_dictionary(b"[1 2] ", &STRICT).is_err());
+        assert!(parse_dictionary(b"<< /A >> ", &STRICT).is_err());
+        assert!(parse_dictionary(b"<< 1 2 >> ", &STRICT).is_err());
+        assert_eq!(
+            parse_dictionary(b"<< /A 1 70000 R >> ", &STRICT),
+            Err(Error::InvalidReference(1, 70000))
+        );
+    }
+
+    #[test]
+    fn should_reject_deeply_nested_objects() {
+        let nested = |depth: usize| {
+            let mut raw = b"1 0 obj << /A ".to_vec();
+            raw.extend(b"[".repeat(depth));
+            raw.extend(b"]".repeat(depth));
+            raw.extend(b" >> endobj ");
+            raw
+        };
+        assert!(parse_indirect_object(&nested(MAX_NESTING), &STRICT).is_ok());
+
+        let raw = nested(20_000);
+        assert_eq!(
+            parse_indirect_object(&raw, &STRICT).map(|_| ()),
+            Err(Error::Syntax(
+                "Objects are nested too deeply",
+                format!("More than {} levels", MAX_NESTING)
+            ))
+        );
+        let array = &raw[b"1 0 obj << /A ".len()..];
+        assert!(parse_object(array, &STRICT).is_err());
+    }
+
+    #[test]
+    fn should_reject_deeply_nested_arrays_on_the_parse_stack() {
+        // Bodies other than dictionaries go through the parse stack
+        let nested = |depth: usize, close: bool| {
+            let mut raw = b"2 0 obj ".to_vec();
+            raw.extend(b"[".repeat(depth));
+            if close {
+                raw.extend(b"]".repeat(depth));
+            }
+            raw.extend(b" endobj ");
+            raw
+        };
+        assert!(parse_indirect_object(&nested(MAX_NESTING, true), &STRICT).is_ok());
+        for raw in [nested(MAX_NESTING + 1, true), nested(1_000_000, false)] {
+            assert_eq!(
+                parse_indirect_object(&raw, &STRICT).map(|_| ()),
+                Err(Error::Syntax(
+                    "Objects are nested too deeply",
+                    format!("More than {} levels", MAX_NESTING)
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn should_parse_indirect_objects() {
+        let raw = b"7 0 obj\n<< /Length 5 >>\nstream\nHello\nendstream\nendobj\n";
+        let ((reference, stream), rest) = parse_indirect_object(raw, &STRICT).unwrap();
+        assert_eq!(reference, Some(IndirectRef::new(7, 0)));
+        assert_eq!(stream.as_stream().unwrap().1.as_ref(), b"Hello");
+        assert_eq!(rest, b"\n");
+
+        // Objects that are not dictionaries use the generic parser
+        let ((reference, array), _rest) =
+            parse_indirect_object(b"8 2 obj [1 2 0 R] endobj ", &STRICT).unwrap();
+        assert_eq!(reference, Some(IndirectRef::new(8, 2)));
+        assert_eq!(
+            array,
+            Object::Array(vec![
+                Object::Integer(1),
+                Object::Indirect(IndirectRef::new(2, 0))
+            ])
+        );
+
+        assert!(parse_indirect_object(b"9 0 obj << >> 5 endobj ", &STRICT).is_err());
+    }
+
     #[test]
     fn should_parse_stream() {
         let raw = b"<< >> stream\nHello, world!\nendstream end ";
-        let ((_, obj), _raw) = parse_object_until_keyword(raw, b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(raw, b"end", &STRICT).unwrap();
         assert_eq!(
             obj,
             Object::Stream(
@@ -360,15 +785,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_use_length_when_data_contains_endstream() {
+        let raw = b"<< /Length 15 >> stream\nendstream\nabcde\nendstream end ";
+        let ((_, obj), rest) = parse_object_until_keyword(raw, b"end", &STRICT).unwrap();
+        assert_eq!(obj.as_stream().unwrap().1.as_ref(), b"endstream\nabcde");
+        assert_eq!(rest, b" ");
+
+        // Without a usable length, the first keyword is used
+        let raw = b"<< /Length 99 >> stream\nendstream\nabcde\nendstream end ";
+        assert!(parse_object_until_keyword(raw, b"end", &STRICT).is_err());
+        let raw = b"<< /Length 2 0 R >> stream\nab\nendstream end ";
+        let ((_, obj), _rest) = parse_object_until_keyword(raw, b"end", &STRICT).unwrap();
+        assert_eq!(obj.as_stream().unwrap().1.as_ref(), b"ab\n");
+    }
+
+    #[test]
+    fn should_check_stream_length() {
+        let context = STRICT;
+        let raw = b"<< /Length 14 >> stream\nHello, world!\nendstream end ";
+        assert!(parse_object_until_keyword(raw, b"end", &context).is_ok());
+        let raw = b"<< /Length 13 >> stream\nHello, world!\r\nendstream end ";
+        assert!(parse_object_until_keyword(raw, b"end", &context).is_ok());
+
+        let raw = b"<< /Length 5 >> stream\nHello, world!\nendstream end ";
+        assert_eq!(
+            parse_object_until_keyword(raw, b"end", &context),
+            Err(Error::Syntax(
+                "Stream length does not match /Length",
+                "5 vs. 14".into()
+            ))
+        );
+
+        let context = ParseContext::with_options(ParseOptions::lenient());
+        let ((_, obj), _raw) = parse_object_until_keyword(raw, b"end", &context).unwrap();
+        assert_eq!(
+            obj.as_stream().unwrap().1,
+            Cow::Borrowed(b"Hello, world!\n")
+        );
+    }
+
     #[test]
     fn should_parse_null() {
-        let ((_, obj), _raw) = parse_object_until_keyword(b"null end ", b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(b"null end ", b"end", &STRICT).unwrap();
         assert_eq!(obj, Object::Null);
     }
 
     #[test]
     fn should_parse_indirect() {
-        let ((_, obj), _raw) = parse_object_until_keyword(b"12 0 R end ", b"end").unwrap();
+        let ((_, obj), _raw) = parse_object_until_keyword(b"12 0 R end ", b"end", &STRICT).unwrap();
         assert_eq!(
             obj,
             Object::Indirect(IndirectRef {
@@ -378,10 +843,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_reject_out_of_range_indirect() {
+        assert_eq!(
+            parse_object_until_keyword(b"4294967296 0 R end ", b"end", &STRICT),
+            Err(Error::InvalidReference(4294967296, 0))
+        );
+        assert_eq!(
+            parse_object_until_keyword(b"1 70000 R end ", b"end", &STRICT),
+            Err(Error::InvalidReference(1, 70000))
+        );
+    }
+
     #[test]
     fn should_parse_obj_keyword() {
         let ((ind, obj), _raw) =
-            parse_object_until_keyword(b"1 2 obj 12 0 R end ", b"end").unwrap();
+            parse_object_until_keyword(b"1 2 obj 12 0 R end ", b"end", &STRICT).unwrap();
         assert_eq!(
             ind,
             Some(IndirectRef {
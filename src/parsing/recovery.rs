@@ -0,0 +1,160 @@
+use crate::objects::IndirectRef;
+use crate::parsing::keywords::OBJ_KEYWORD;
+use crate::parsing::pdf_file::{XrefEntry, XrefTable};
+use crate::utils::chars::{is_name_char, is_whitespace_char};
+use crate::utils::slices::position_of_sequence;
+use std::collections::HashMap;
+
+/// Rebuilds the xref table by scanning the whole file for `N G obj` headers.
+///
+/// This is used in lenient mode when the xref table is missing or corrupt.
+/// Where an object is defined more than once, the last definition wins, as it
+/// would for an incremental update.
+pub fn scan_for_objects(raw: &[u8]) -> XrefTable {
+    // Later definitions replace earlier ones even if their generation number
+    // is different, so objects are keyed by number until the end
+    let mut latest = HashMap::new();
+
+    let mut start = 0;
+    while let Some(position) = position_of_sequence(&raw[start..], OBJ_KEYWORD) {
+        let keyword = start + position;
+        start = keyword + OBJ_KEYWORD.len();
+
+        // Skip longer keywords, such as `objects`
+        if raw.get(start).is_some_and(|&c| is_name_char(c)) {
+            continue;
+        }
+
+        if let Some((reference, offset)) = object_header_before(raw, keyword) {
+            latest.insert(reference.number, (reference, offset));
+        }
+    }
+
+    latest
+        .into_values()
+        .map(|(reference, offset)| (reference, XrefEntry::InUse(offset)))
+        .collect()
+}
+
+/// Parses the `N G` that should come before an `obj` keyword, working
+/// backwards from the keyword. Returns the reference and the offset of the
+/// start of the header.
+fn object_header_before(raw: &[u8], keyword: usize) -> Option<(IndirectRef, usize)> {
+    let skip_back = |mut i: usize, predicate: fn(u8) -> bool| {
+        while i > 0 && predicate(raw[i - 1]) {
+            i -= 1;
+        }
+        i
+    };
+    let is_digit = |c: u8| c.is_ascii_digit();
+
+    let generation_end = skip_back(keyword, is_whitespace_char);
+    let generation_start = skip_back(generation_end, is_digit);
+    let number_end = skip_back(generation_start, is_whitespace_char);
+    let number_start = skip_back(number_end, is_digit);
+
+    if generation_end == keyword
+        || generation_start == generation_end
+        || number_end == generation_start
+        || number_start == number_end
+    {
+        return None;
+    }
+    // The header must not be part of a longer token
+    if number_start > 0 && is_name_char(raw[number_start - 1]) {
+        return None;
+    }
+
+    let parse = |digits: &[u8]| std::str::from_utf8(digits).ok()?.parse().ok();
+    let reference = IndirectRef::checked(
+        parse(&raw[number_start..number_end])?,
+        parse(&raw[generation_start..generation_end])?,
+    )
+    .ok()?;
+
+    Some((reference, number_start))
+}
+
+/// Parses the `N G obj` header at the start of an object, returning the
+/// reference if there is one.
+pub fn object_header_at(raw: &[u8]) -> Option<IndirectRef> {
+    let mut end = 0;
+    for _ in 0..2 {
+        let start = end
+            + raw[end..]
+                .iter()
+                .take_while(|&&c| is_whitespace_char(c))
+                .count();
+        end = start
+            + raw[start..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+        if start == end {
+            return None;
+        }
+    }
+
+    let keyword = end
+        + raw[end..]
+            .iter()
+            .take_while(|&&c| is_whitespace_char(c))
+            .count();
+    let rest = raw.get(keyword..)?;
+    if !rest.starts_with(OBJ_KEYWORD) || rest.get(3).is_some_and(|&c| is_name_char(c)) {
+        return None;
+    }
+
+    object_header_before(raw, keyword).map(|(reference, _offset)| reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_scan_for_objects() {
+        let raw = b"%PDF-1.0\n1 0 obj\n<< >>\nendobj\n2 0 obj (objects) endobj\n 12 3 obj";
+        let xref_table = scan_for_objects(raw);
+
+        assert_eq!(xref_table.len(), 3);
+        assert_eq!(
+            xref_table[&IndirectRef::checked(1, 0).unwrap()],
+            XrefEntry::InUse(9)
+        );
+        assert_eq!(
+            xref_table[&IndirectRef::checked(2, 0).unwrap()],
+            XrefEntry::InUse(30)
+        );
+        assert_eq!(
+            xref_table[&IndirectRef::checked(12, 3).unwrap()],
+            XrefEntry::InUse(56)
+        );
+    }
+
+    #[test]
+    fn should_prefer_later_definitions() {
+        let raw = b"1 0 obj 1 endobj 1 0 obj 2 endobj";
+        let xref_table = scan_for_objects(raw);
+        assert_eq!(
+            xref_table[&IndirectRef::checked(1, 0).unwrap()],
+            XrefEntry::InUse(17)
+        );
+    }
+
+    #[test]
+    fn should_ignore_partial_headers() {
+        assert!(scan_for_objects(b"obj 0 obj x1 0 obj").is_empty());
+    }
+
+    #[test]
+    fn should_parse_object_header() {
+        assert_eq!(
+            object_header_at(b"\n12 0 obj\n<< >>"),
+            Some(IndirectRef::checked(12, 0).unwrap())
+        );
+        assert_eq!(object_header_at(b"bj\n12 0 obj"), None);
+        assert_eq!(object_header_at(b"12 0 R"), None);
+        assert_eq!(object_header_at(b"12 obj"), None);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::error::Result;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Somewhere that parts of a file can be read from on demand, rather than
+/// reading the whole file into memory first.
+///
+/// This is implemented for anything that can be read and seeked, such as a
+/// `File` or a `Cursor`.
+pub trait PdfSource {
+    /// The length of the file, in bytes.
+    fn size(&mut self) -> Result<usize>;
+
+    /// Reads up to `len` bytes from an offset, returning fewer if the end of
+    /// the file is reached first.
+    fn read_at(&mut self, offset: usize, len: usize) -> Result<Vec<u8>>;
+}
+
+impl<T: Read + Seek> PdfSource for T {
+    fn size(&mut self) -> Result<usize> {
+        Ok(self.seek(SeekFrom::End(0))? as usize)
+    }
+
+    fn read_at(&mut self, offset: usize, len: usize) -> Result<Vec<u8>> {
+        self.seek(SeekFrom::Start(offset as u64))?;
+        let mut buf = Vec::with_capacity(len);
+        self.take(len as u64).read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+}
@@ -0,0 +1,214 @@
+//! Decodes streams that are too large to keep in memory, such as huge images,
+//! into a sink or a temporary file instead, and gives access to the encoded
+//! data of streams as it is in the file.
+
+use crate::error::{Error, Result};
+use crate::objects::{IndirectRef, Object};
+use crate::parsing::context::Ownership;
+use crate::parsing::pdf_file::PdfFile;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Used to give each temporary file of this process a different name.
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl<'a> PdfFile<'a> {
+    /// Loads an indirect object without decoding its streams, so that their
+    /// data is as it is in the file. Such streams can be decoded piece by
+    /// piece with `Object::decode_stream_into`, and are not affected by
+    /// `ParseOptions::max_stream_size`.
+    pub fn encoded_object(&self, reference: IndirectRef) -> Result<Object<'_>> {
+        let (object, _) = self.locate_object(reference, &self.context().with_raw_streams())?;
+        Ok(object)
+    }
+
+    /// Like `PdfFile::resolve_owned`, but loads indirect objects with
+    /// `encoded_object`, so that streams are not decoded.
+    pub fn resolve_encoded<'b>(&'b self, object: &Object<'b>) -> Result<Object<'b>> {
+        match object {
+            &Object::Indirect(reference) => self.encoded_object(reference),
+            _ => Ok(object.clone()),
+        }
+    }
+
+    /// Loads a stream object without decoding it, along with where its data
+    /// is in the file, between the EOL after the `stream` keyword and the one
+    /// before `endstream`. The data always borrows from the file, whatever
+    /// `ParseOptions::ownership` is set to.
+    pub fn raw_stream(&self, reference: IndirectRef) -> Result<(Object<'_>, Range<usize>)> {
+        let mut context = self.context().with_raw_streams();
+        context.options.ownership = Ownership::Mixed;
+        let (object, _) = self.locate_object(reference, &context)?;
+        let (_, data) = object.raw_stream()?;
+        let start = context.offset_of(data).ok_or_else(|| {
+            Error::Type(format!("Data of {:?} is not part of the file", reference))
+        })?;
+        let range = start..start + data.len();
+        Ok((object, range))
+    }
+
+    /// Decodes a stream object and writes its data to a sink, returning how
+    /// many bytes were written. The xref table must already be loaded.
+    pub fn decode_stream_to<W: Write>(&self, reference: IndirectRef, sink: &mut W) -> Result<u64> {
+        self.encoded_object(reference)?.decode_stream_into(sink)
+    }
+
+    /// Decodes a stream object into a temporary file, which is deleted when
+    /// the returned `SpilledStream` is dropped.
+    pub fn spill_stream(&self, reference: IndirectRef) -> Result<SpilledStream> {
+        let path = std::env::temp_dir().join(format!(
+            "bdf-{}-{}.tmp",
+            process::id(),
+            SPILL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        // Removes the file if decoding fails
+        let mut spilled = SpilledStream { file, path, len: 0 };
+
+        let mut writer = io::BufWriter::new(&spilled.file);
+        spilled.len = self.decode_stream_to(reference, &mut writer)?;
+        writer.flush()?;
+        drop(writer);
+
+        spilled.file.rewind()?;
+        Ok(spilled)
+    }
+}
+
+/// The decoded data of a stream, in a temporary file that is read from the
+/// start.
+#[derive(Debug)]
+pub struct SpilledStream {
+    file: File,
+    path: PathBuf,
+    len: u64,
+}
+
+impl SpilledStream {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The length of the decoded data.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Read for SpilledStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Seek for SpilledStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+impl Drop for SpilledStream {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "write",
+    any(feature = "miniz_oxide", feature = "flate2")
+))]
+mod tests {
+    use super::*;
+    use crate::objects::names;
+    use crate::parsing::context::ParseOptions;
+    use crate::test_support::{self, dict, name};
+    use crate::writing::document::DocumentBuilder;
+    use crate::writing::objects::WriteOptions;
+    use std::borrow::Cow;
+
+    #[test]
+    fn should_spill_large_streams() {
+        let data = b"0123456789".repeat(1000);
+        let mut builder = DocumentBuilder::new().with_options(WriteOptions {
+            compress_streams: true,
+            ..Default::default()
+        });
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let image = builder.add(Object::Stream(
+            Box::new(dict(&[(names::TYPE, name("XObject"))])),
+            Cow::Owned(data.clone()),
+        ));
+        let raw = builder.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_bytes(&raw)
+            .with_options(ParseOptions::strict().with_max_stream_size(100));
+        file.load_xref_table().unwrap();
+        assert!(file.object(image).is_err());
+        // Small streams are still parsed
+        assert!(file.pages().unwrap()[0].extract_text().is_ok());
+
+        let mut sink = Vec::new();
+        assert_eq!(
+            file.decode_stream_to(image, &mut sink),
+            Ok(data.len() as u64)
+        );
+        assert_eq!(sink, data);
+        assert!(matches!(
+            file.encoded_object(image),
+            Ok(Object::Stream(_, encoded)) if encoded.len() < data.len()
+        ));
+
+        let mut spilled = file.spill_stream(image).unwrap();
+        let path = spilled.path().to_owned();
+        assert_eq!(spilled.len(), data.len() as u64);
+        let mut contents = Vec::new();
+        spilled.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, data);
+        drop(spilled);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn should_find_raw_stream_data() {
+        let mut builder = DocumentBuilder::new().with_options(WriteOptions {
+            compress_streams: true,
+            ..Default::default()
+        });
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let raw = builder.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_bytes(&raw).with_options(ParseOptions {
+            ownership: Ownership::Owned,
+            ..Default::default()
+        });
+        file.load_xref_table().unwrap();
+        // The content stream of the only page
+        let (stream, range) = file.raw_stream(IndirectRef::new(4, 0)).unwrap();
+        let (dict, data) = stream.raw_stream().unwrap();
+        assert_eq!(data, &raw[range.clone()]);
+        assert_eq!(
+            dict.get(names::LENGTH.as_bytes()),
+            Some(&Object::Integer(data.len() as i64))
+        );
+        assert!(raw[..range.start].ends_with(b"stream\n"));
+        assert!(raw[range.end..].starts_with(b"\nendstream"));
+
+        let mut decoded = Vec::new();
+        stream.decode_stream_into(&mut decoded).unwrap();
+        assert_eq!(decoded, test_support::HELLO_WORLD_CONTENT);
+        assert!(file.raw_stream(IndirectRef::new(1, 0)).is_err());
+    }
+}
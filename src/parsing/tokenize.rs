@@ -0,0 +1,133 @@
+use crate::error::{Error, Result};
+use crate::parsing::context::ParseContext;
+use crate::parsing::pdf_file::PdfFile;
+use crate::parsing::tokens::{parse_token, parse_whitespace, Token};
+use std::ops::Range;
+
+/// Where a token is in the file, from its first byte to just after its last.
+pub type Span = Range<usize>;
+
+/// An iterator over the tokens in part of a file, see `PdfFile::tokenize`.
+pub struct Tokens<'a> {
+    /// The rest of the file, which the last token may run into.
+    rest: &'a [u8],
+    /// The offset of `rest` in the file.
+    offset: usize,
+    /// Where the range ends, after which no more tokens are started.
+    end: usize,
+    context: ParseContext<'a>,
+    done: bool,
+}
+
+impl<'a> PdfFile<'a> {
+    /// Splits part of the file into tokens, without parsing them into
+    /// objects, for analyses such as finding every use of a name. Every token
+    /// that starts in the range is returned whole, even if it ends after it.
+    ///
+    /// Whitespace and comments are skipped, and streams are returned as a
+    /// single token. After an error nothing more is returned, since it is not
+    /// clear where the next token starts.
+    pub fn tokenize(&self, range: Range<usize>) -> Tokens<'_> {
+        let raw = self.raw();
+        let end = range.end.min(raw.len());
+        let start = range.start.min(end);
+        Tokens {
+            rest: &raw[start..],
+            offset: start,
+            end,
+            context: self.context(),
+            done: false,
+        }
+    }
+}
+
+impl<'a> Tokens<'a> {
+    fn next_token(&mut self) -> Result<Option<(Token<'a>, Span)>> {
+        if self.offset >= self.end {
+            return Ok(None);
+        }
+
+        let (token, rest) = parse_token(self.rest, &self.context)?;
+        let start = self.offset;
+        self.offset += self.rest.len() - rest.len();
+        self.rest = rest;
+        Ok(Some((token, start..self.offset)))
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<(Token<'a>, Span)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = match parse_whitespace(self.rest) {
+            // There is only whitespace left in the file
+            Err(Error::EOF) => Ok(None),
+            Err(err) => Err(err),
+            Ok(((), raw)) => {
+                self.offset += self.rest.len() - raw.len();
+                self.rest = raw;
+                self.next_token()
+            }
+        };
+
+        match result {
+            Ok(Some(token)) => Some(Ok(token)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_tokenize_ranges_with_spans() {
+        let raw = b"1 0 obj % comment\n<</S/JavaScript/JS(app.alert\\(1\\))>>\nendobj\n";
+        let file = PdfFile::from_bytes(raw);
+
+        let tokens = file
+            .tokenize(0..raw.len())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(tokens.len(), 10);
+        assert_eq!(tokens[0], (Token::Integer(1), 0..1));
+        assert_eq!(tokens[3], (Token::BeginDictionary, 18..20));
+        assert_eq!(tokens[9], (Token::Keyword(b"endobj"), 55..61));
+
+        let javascript = tokens
+            .iter()
+            .filter(
+                |(token, _)| matches!(token, Token::Name(name) if name.as_ref() == b"JavaScript"),
+            )
+            .map(|(_, span)| span.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(javascript, vec![22..33]);
+        assert_eq!(&raw[22..33], b"/JavaScript");
+    }
+
+    #[test]
+    fn should_stop_after_errors() {
+        let file = PdfFile::from_bytes(b"1 0 obj (unterminated");
+        let mut tokens = file.tokenize(2..5);
+        assert_eq!(tokens.next(), Some(Ok((Token::Integer(0), 2..3))));
+        assert_eq!(tokens.next(), Some(Ok((Token::Keyword(b"obj"), 4..7))));
+        assert_eq!(tokens.next(), None);
+
+        let mut tokens = file.tokenize(3..100);
+        assert!(tokens.next().unwrap().is_ok());
+        assert_eq!(tokens.next(), Some(Err(Error::EOF)));
+        assert_eq!(tokens.next(), None);
+    }
+}
@@ -1,9 +1,12 @@
 use crate::error::{Error, Result};
+use crate::parsing::context::ParseContext;
 use crate::parsing::keywords::{ENDSTREAM_KEYWORD, STREAM_KEYWORD};
 use crate::utils::chars::{
-  is_alphabetic_char, is_name_char, is_newline_char, is_numeric_char, is_whitespace_char, peek_char,
+  is_alphabetic_char, is_name_char, is_numeric_char, is_whitespace_char, peek_char, run_length,
+  DELIMETER_CHARACTERS,
 };
 use crate::utils::slices::position_of_sequence;
+use memchr::{memchr, memchr2, memchr3};
 use std::borrow::Cow;
 use std::cmp::min;
 use std::num::ParseIntError;
@@ -19,7 +22,7 @@ pub type ParseResult<'a, T> = Result<(T, &'a [u8])>;
 #[derive(Debug, PartialEq)]
 pub enum Token<'a> {
   Keyword(&'a [u8]),
-  Integer(usize),
+  Integer(i64),
   Real(f64),
   LiteralString(Cow<'a, [u8]>),
   HexadecimalString(Cow<'a, [u8]>),
@@ -31,19 +34,30 @@ pub enum Token<'a> {
   Stream(&'a [u8]),
 }
 
+/// Returns the start of the remaining bytes, for use in error messages.
+pub fn snippet(raw: &[u8]) -> String {
+  String::from_utf8_lossy(&raw[..min(5, raw.len())]).into()
+}
+
 /// Parses a block of whitespace, including comments (Adobe, 2008, p. 13).
-pub fn parse_whitespace(mut raw: &[u8]) -> ParseResult<()> {
+pub fn parse_whitespace(raw: &[u8]) -> ParseResult<'_, ()> {
+  parse_whitespace_with_comments(raw, &mut |_| {})
+}
+
+/// Like `parse_whitespace`, but passes each comment to `on_comment`, starting
+/// with the `%` and without the end of line.
+pub fn parse_whitespace_with_comments<'a>(
+  mut raw: &'a [u8],
+  on_comment: &mut impl FnMut(&'a [u8]),
+) -> ParseResult<'a, ()> {
   loop {
-    let next = peek_char(raw)?;
-    if is_whitespace_char(next) {
-      raw = &raw[1..];
-    } else if next == b'%' {
-      while !is_newline_char(peek_char(raw)?) {
-        raw = &raw[1..];
-      }
-    } else {
+    raw = &raw[run_length(raw, is_whitespace_char)?..];
+    if raw[0] != b'%' {
       break;
     }
+    let length = memchr2(b'\n', b'\r', raw).ok_or(Error::EOF)?;
+    on_comment(&raw[..length]);
+    raw = &raw[length..];
   }
 
   Ok(((), raw))
@@ -53,13 +67,10 @@ pub fn parse_whitespace(mut raw: &[u8]) -> ParseResult<()> {
 ///
 /// This is not used for parsing tokens, but is instead used to parse (some of)
 /// the numbers used in the trailer and xref table.
-pub fn parse_number<I: FromStr<Err = ParseIntError>>(raw: &[u8]) -> ParseResult<I> {
+pub fn parse_number<I: FromStr<Err = ParseIntError>>(raw: &[u8]) -> ParseResult<'_, I> {
   let ((), raw) = parse_whitespace(raw)?;
 
-  let mut length = 0;
-  while is_numeric_char(peek_char(&raw[length..])?) {
-    length += 1;
-  }
+  let length = run_length(raw, is_numeric_char)?;
 
   let number = String::from_utf8_lossy(&raw[..length]).parse()?;
 
@@ -67,69 +78,137 @@ pub fn parse_number<I: FromStr<Err = ParseIntError>>(raw: &[u8]) -> ParseResult<
 }
 
 /// Parses a keyword, which must consist exclusively of alphabetic characters.
-pub fn parse_keyword(raw: &[u8]) -> ParseResult<&[u8]> {
-  let mut length = 0;
-  while is_alphabetic_char(peek_char(&raw[length..])?) {
-    length += 1;
-  }
+pub fn parse_keyword(raw: &[u8]) -> ParseResult<'_, &'_ [u8]> {
+  let length = run_length(raw, is_alphabetic_char)?;
 
   Ok((&raw[..length], &raw[length..]))
 }
 
-/// Parses a numeric object, either as an int or as a float
+/// Parses a numeric object, either as an int or as a real
 /// (Adobe, 2008, p. 14).
-pub fn parse_numeric(raw: &[u8]) -> ParseResult<Token> {
-  let mut contains_decimal = false;
+///
+/// This follows the PDF grammar rather than Rust's: an optional sign followed
+/// by digits, with at most one decimal point that may appear anywhere
+/// (eg. `4.`, `-.002`, `+6.`). Exponential notation is not allowed.
+pub fn parse_numeric(raw: &[u8]) -> ParseResult<'_, Token<'_>> {
   let mut length = 0;
+  let mut decimal_position = None;
+  let mut digit_count = 0;
   while is_numeric_char(peek_char(&raw[length..])?) {
-    if raw[length] == b'.' {
-      contains_decimal = true;
+    let message = match raw[length] {
+      b'+' | b'-' if length > 0 => Some("Sign must be at the start of a numeric object"),
+      b'.' if decimal_position.is_some() => Some("Numeric object has more than one decimal point"),
+      b'.' => {
+        decimal_position = Some(length);
+        None
+      }
+      b'0'..=b'9' => {
+        digit_count += 1;
+        (digit_count > MAX_NUMERIC_DIGITS).then_some("Numeric object has too many digits")
+      }
+      _ => None,
+    };
+    if let Some(message) = message {
+      return Err(numeric_error(message, raw, length));
     }
 
     length += 1;
   }
+  let number = &raw[..length];
 
-  let token = if contains_decimal {
-    let number = String::from_utf8_lossy(&raw[..length]).parse()?;
-    Token::Real(number)
+  if digit_count == 0 {
+    return Err(numeric_error("Numeric object has no digits", raw, length));
+  }
+
+  let token = if let Some(decimal_position) = decimal_position {
+    let sign = match number[0] {
+      b'+' | b'-' => &number[..1],
+      _ => &number[..0],
+    };
+    let integer_part = &number[sign.len()..decimal_position];
+    let fractional_part = &number[decimal_position + 1..];
+
+    // Pad the number so that both parts are present (eg. `4.` becomes
+    // `04.00`), then let the standard library do the correctly rounded
+    // decimal to binary conversion.
+    let mut normalised = sign.to_vec();
+    normalised.push(b'0');
+    normalised.extend_from_slice(integer_part);
+    normalised.push(b'.');
+    normalised.extend_from_slice(fractional_part);
+    normalised.push(b'0');
+    Token::Real(String::from_utf8_lossy(&normalised).parse()?)
   } else {
-    let number = String::from_utf8_lossy(&raw[..length]).parse()?;
-    Token::Integer(number)
+    let integer = String::from_utf8_lossy(number)
+      .parse()
+      .map_err(|_| numeric_error("Integer is out of range", raw, 0))?;
+    Token::Integer(integer)
   };
 
   Ok((token, &raw[length..]))
 }
 
+/// The maximum number of digits in a numeric object. This is far more than
+/// the precision of an `f64`, but stops pathological inputs from allocating.
+pub const MAX_NUMERIC_DIGITS: usize = 64;
+
+/// Creates an error for an invalid numeric object, including the offset into
+/// the object at which the problem was found.
+fn numeric_error(message: &'static str, raw: &[u8], offset: usize) -> Error {
+  let mut length = offset;
+  while raw.get(length).copied().is_some_and(is_numeric_char) {
+    length += 1;
+  }
+
+  Error::Syntax(
+    message,
+    format!(
+      "{:?} at offset {}",
+      String::from_utf8_lossy(&raw[..length]),
+      offset
+    ),
+  )
+}
+
 /// Parses an escape sequence, such as those that may occur in a literal string
 /// (Adobe, 2008, p. 15).
-pub fn parse_escape_sequence(raw: &[u8]) -> ParseResult<Option<u8>> {
+///
+/// A backslash before any other character is ignored, which is recorded as a
+/// diagnostic.
+pub fn parse_escape_sequence<'a>(
+  raw: &'a [u8],
+  context: &ParseContext,
+) -> ParseResult<'a, Option<u8>> {
   if peek_char(raw)? != b'\\' {
     return Err(Error::Syntax(
       "Escape Sequence must start with a '\\'",
-      String::from_utf8_lossy(&raw[..5]).into(),
+      snippet(raw),
     ));
   }
 
-  // First try parsing an octal escape sequence
+  // First try parsing an octal escape sequence. A backslash at the end of
+  // the input has no digits, so is not one
   let first_non_octal_position = raw
     .iter()
     .skip(1)
     .take(3)
-    .position(|&c| c < b'0' || c >= b'8');
-  if first_non_octal_position != Some(0) {
-    let digit_count = match first_non_octal_position {
-      Some(n) => n,
-      None => min(3, raw.len() - 1),
-    };
-    let octal = String::from_utf8_lossy(&raw[1..1 + digit_count]);
-    let byte = u8::from_str_radix(&octal, 8)?;
+    .position(|&c| !(b'0'..b'8').contains(&c));
+  let digit_count = match first_non_octal_position {
+    Some(n) => n,
+    None => min(3, raw.len() - 1),
+  };
+  if digit_count > 0 {
+    // High-order overflow is ignored, so \777 is 0xFF (Adobe, 2008, p. 15)
+    let byte = raw[1..1 + digit_count]
+      .iter()
+      .fold(0u16, |byte, &digit| byte << 3 | (digit - b'0') as u16) as u8;
     return Ok((Some(byte), &raw[1 + digit_count..]));
   }
 
   let c = peek_char(&raw[1..])?;
   let (result, length) = match c {
     b'n' => (Some(b'\n'), 2),
-    b'r' => (Some(b'\n'), 2),
+    b'r' => (Some(b'\r'), 2),
     b't' => (Some(b'\t'), 2),
     // BACKSPACE (BS)
     b'b' => (Some(0x08), 2),
@@ -137,8 +216,9 @@ pub fn parse_escape_sequence(raw: &[u8]) -> ParseResult<Option<u8>> {
     b'f' => (Some(0x0C), 2),
     b'(' | b')' | b'\\' => (Some(c), 2),
     b'\n' => (None, 2),
+    // A backslash at the end of a line continues the string on the next line
     b'\r' => (
-      Some(b'\n'),
+      None,
       if peek_char(&raw[2..]) == Ok(b'\n') {
         3
       } else {
@@ -146,10 +226,12 @@ pub fn parse_escape_sequence(raw: &[u8]) -> ParseResult<Option<u8>> {
       },
     ),
     _ => {
-      return Err(Error::Syntax(
-        "Invalid escape sequence",
-        String::from_utf8_lossy(&raw[..5]).into(),
-      ));
+      context.note(
+        raw,
+        Error::Syntax("Invalid escape sequence", snippet(raw)),
+        "Kept the character without the backslash",
+      );
+      (Some(c), 2)
     }
   };
 
@@ -157,42 +239,43 @@ pub fn parse_escape_sequence(raw: &[u8]) -> ParseResult<Option<u8>> {
 }
 
 /// Parses a literal string (Adobe, 2008, p. 15-16).
-pub fn parse_literal_string(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
+pub fn parse_literal_string<'a>(
+  raw: &'a [u8],
+  context: &ParseContext,
+) -> ParseResult<'a, Cow<'a, [u8]>> {
   if raw[0] != b'(' {
     return Err(Error::Syntax(
       "Literal String must start with '('",
-      String::from_utf8_lossy(&raw[..5]).into(),
+      snippet(raw),
     ));
   }
 
   let mut length = 1;
   let mut depth = 1;
-  let mut requires_extra_processing = false;
 
   while depth > 0 {
-    match peek_char(&raw[length..])? {
+    // Skip to the next character that changes the depth or escapes one
+    length += raw
+      .get(length..)
+      .and_then(|rest| memchr3(b'(', b')', b'\\', rest))
+      .ok_or(Error::EOF)?;
+    match raw[length] {
       b'(' => depth += 1,
       b')' => depth -= 1,
-      b'\\' => {
-        requires_extra_processing = true;
-        length += 1;
-      }
-      b'\r' => {
-        requires_extra_processing = true;
-      }
-      _ => {}
+      _ => length += 1,
     }
     length += 1;
   }
+  let requires_extra_processing = memchr2(b'\\', b'\r', &raw[..length]).is_some();
 
   let string = if requires_extra_processing {
     let mut raw = &raw[1..length - 1];
     let mut bytes = Vec::with_capacity(length);
 
-    while raw.len() > 0 {
+    while !raw.is_empty() {
       match raw[0] {
         b'\\' => {
-          let (result, next) = parse_escape_sequence(raw)?;
+          let (result, next) = parse_escape_sequence(raw, context)?;
           if let Some(c) = result {
             bytes.push(c);
           }
@@ -221,38 +304,46 @@ pub fn parse_literal_string(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
 }
 
 /// Parses a hexadecimal string (Adobe, 2008, p. 15-16).
-pub fn parse_hexadecimal_string(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
+///
+/// A missing final digit is taken to be zero, which is recorded as a
+/// diagnostic. In lenient mode, characters that are not hex digits are skipped.
+pub fn parse_hexadecimal_string<'a>(
+  raw: &'a [u8],
+  context: &ParseContext,
+) -> ParseResult<'a, Cow<'a, [u8]>> {
   if raw[0] != b'<' {
     return Err(Error::Syntax(
       "Hexadecimal String must start with '<'",
-      String::from_utf8_lossy(&raw[..5]).into(),
+      snippet(raw),
     ));
   }
 
-  let length = raw.iter().position(|&c| c == b'>').ok_or(Error::Syntax(
+  let length = memchr(b'>', raw).ok_or(Error::Syntax(
     "Hexadecimal String must end with '>'",
-    String::from_utf8_lossy(&raw[..5]).into(),
+    snippet(raw),
   ))?
     + 1;
 
   let mut last = None;
   let mut hex = &raw[1..length - 1];
   let mut bytes = Vec::new();
-  while hex.len() > 0 {
+  while !hex.is_empty() {
     ((), hex) = parse_whitespace(hex)?;
 
     if let Ok(c) = peek_char(hex) {
-      match last {
-        None => {
-          last = Some(c);
-        }
-        Some(l) => {
-          let slice = [l, c];
-          let hex_for_byte = String::from_utf8_lossy(&slice);
-          bytes.push(u8::from_str_radix(&hex_for_byte, 16)?);
-
-          last = None;
-        }
+      match (c as char).to_digit(16) {
+        Some(digit) => match last {
+          None => last = Some(digit as u8),
+          Some(high) => {
+            bytes.push(high << 4 | digit as u8);
+            last = None;
+          }
+        },
+        None => context.recover(
+          hex,
+          Error::Syntax("Invalid hex digit", snippet(hex)),
+          "Skipped the character",
+        )?,
       }
 
       hex = &hex[1..];
@@ -260,10 +351,13 @@ pub fn parse_hexadecimal_string(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
   }
 
   // If there is a digit left over, pretend there is an additional zero
-  if let Some(l) = last {
-    let slice = [l, b'0'];
-    let hex_for_byte = String::from_utf8_lossy(&slice);
-    bytes.push(u8::from_str_radix(&hex_for_byte, 16)?);
+  if let Some(high) = last {
+    context.note(
+      raw,
+      Error::Syntax("Hexadecimal String has an odd number of digits", snippet(raw)),
+      "Padded the last digit with a zero",
+    );
+    bytes.push(high << 4);
   }
 
   let string = bytes.into();
@@ -271,24 +365,17 @@ pub fn parse_hexadecimal_string(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
 }
 
 /// Parses a name object (Adobe, 2008, p. 16).
-pub fn parse_name(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
+pub fn parse_name(raw: &[u8]) -> ParseResult<'_, Cow<'_, [u8]>> {
   if peek_char(raw)? != b'/' {
     return Err(Error::Syntax(
       "Name must start with a '/'",
-      String::from_utf8_lossy(&raw[..5]).into(),
+      snippet(raw),
     ));
   }
   let raw = &raw[1..];
 
-  let mut contains_escapes = false;
-  let mut length = 0;
-  while is_name_char(peek_char(&raw[length..])?) {
-    if raw[length] == b'#' {
-      contains_escapes = true;
-    }
-
-    length += 1;
-  }
+  let length = run_length(raw, is_name_char)?;
+  let contains_escapes = memchr(b'#', &raw[..length]).is_some();
 
   let name = if contains_escapes {
     let mut bytes = Vec::with_capacity(length);
@@ -296,6 +383,9 @@ pub fn parse_name(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
     while i < length {
       match raw[i] {
         b'#' => {
+          if i + 3 > length {
+            return Err(Error::Syntax("Incomplete escape in name", snippet(&raw[i..])));
+          }
           let hex = String::from_utf8_lossy(&raw[i + 1..i + 3]);
           bytes.push(u8::from_str_radix(&hex, 16)?);
           i += 3;
@@ -316,37 +406,62 @@ pub fn parse_name(raw: &[u8]) -> ParseResult<Cow<[u8]>> {
 
 /// Parses to the end of a stream, starting with the newline that follows the
 /// 'stream' keyword (Adobe, 2008, p. 19).
-pub fn parse_to_end_of_stream(mut raw: &[u8]) -> ParseResult<&[u8]> {
+///
+/// In lenient mode, a CR on its own is accepted as the EOL.
+pub fn parse_to_end_of_stream<'a>(
+  mut raw: &'a [u8],
+  context: &ParseContext,
+) -> ParseResult<'a, &'a [u8]> {
   // Parse the EOL following the 'stream' keyword
   match peek_char(raw)? {
     b'\n' => raw = &raw[1..],
     b'\r' => match peek_char(&raw[1..])? {
       b'\n' => raw = &raw[2..],
       _ => {
-        return Err(Error::Syntax(
-          "'stream' keyword must not be followed by just a CR",
-          String::from_utf8_lossy(&raw[..5]).into(),
-        ))
+        context.recover(
+          raw,
+          Error::Syntax(
+            "'stream' keyword must not be followed by just a CR",
+            snippet(raw),
+          ),
+          "Treated the CR as the end of line",
+        )?;
+        raw = &raw[1..];
       }
     },
     _ => {
       return Err(Error::Syntax(
         "'stream' keyword must be followed by an EOL",
-        String::from_utf8_lossy(&raw[..5]).into(),
+        snippet(raw),
       ))
     }
   }
 
-  // Find the end of the stream
-  if let Some(length) = position_of_sequence(raw, ENDSTREAM_KEYWORD) {
-    Ok((&raw[..length], &raw[length + ENDSTREAM_KEYWORD.len()..]))
-  } else {
-    Err(Error::EOF)
+  // Find the end of the stream, skipping 'endstream' where it is only the
+  // start of a longer token. The data may still contain the keyword, which
+  // the caller can check for using /Length.
+  let mut start = 0;
+  while let Some(i) = position_of_sequence(&raw[start..], ENDSTREAM_KEYWORD) {
+    let length = start + i;
+    if starts_with_endstream(&raw[length..]) {
+      return Ok((&raw[..length], &raw[length + ENDSTREAM_KEYWORD.len()..]));
+    }
+    start = length + 1;
   }
+  Err(Error::EOF)
+}
+
+/// Returns true if the bytes start with the 'endstream' keyword, followed by
+/// a delimiter, whitespace or the end of the input.
+pub fn starts_with_endstream(raw: &[u8]) -> bool {
+  raw.starts_with(ENDSTREAM_KEYWORD)
+    && raw
+      .get(ENDSTREAM_KEYWORD.len())
+      .is_none_or(|&c| is_whitespace_char(c) || DELIMETER_CHARACTERS.contains(&c))
 }
 
 /// Parses a token, automatically detecting its type.
-pub fn parse_token(raw: &[u8]) -> ParseResult<Token> {
+pub fn parse_token<'a>(raw: &'a [u8], context: &ParseContext) -> ParseResult<'a, Token<'a>> {
   let ((), raw) = parse_whitespace(raw)?;
 
   let first_char = peek_char(raw)?;
@@ -355,7 +470,7 @@ pub fn parse_token(raw: &[u8]) -> ParseResult<Token> {
   } else if is_alphabetic_char(first_char) {
     let (keyword, raw) = parse_keyword(raw)?;
     if keyword == STREAM_KEYWORD {
-      let (stream, raw) = parse_to_end_of_stream(raw)?;
+      let (stream, raw) = parse_to_end_of_stream(raw, context)?;
       Ok((Token::Stream(stream), raw))
     } else {
       Ok((Token::Keyword(keyword), raw))
@@ -364,14 +479,14 @@ pub fn parse_token(raw: &[u8]) -> ParseResult<Token> {
     let (name, raw) = parse_name(raw)?;
     Ok((Token::Name(name), raw))
   } else if first_char == b'(' {
-    let (string, raw) = parse_literal_string(raw)?;
+    let (string, raw) = parse_literal_string(raw, context)?;
     Ok((Token::LiteralString(string), raw))
   } else if first_char == b'<' {
     let second_char = peek_char(&raw[1..])?;
     if second_char == b'<' {
       Ok((Token::BeginDictionary, &raw[2..]))
     } else {
-      let (string, raw) = parse_hexadecimal_string(raw)?;
+      let (string, raw) = parse_hexadecimal_string(raw, context)?;
       Ok((Token::HexadecimalString(string), raw))
     }
   } else if first_char == b'>' {
@@ -381,7 +496,7 @@ pub fn parse_token(raw: &[u8]) -> ParseResult<Token> {
     } else {
       Err(Error::Syntax(
         "Expected a second '>'",
-        String::from_utf8_lossy(&raw[..5]).into(),
+        snippet(raw),
       ))
     }
   } else if first_char == b'[' {
@@ -391,7 +506,7 @@ pub fn parse_token(raw: &[u8]) -> ParseResult<Token> {
   } else {
     Err(Error::Syntax(
       "Unrecognised token",
-      String::from_utf8_lossy(&raw[..5]).into(),
+      snippet(raw),
     ))
   }
 }
@@ -399,6 +514,7 @@ pub fn parse_token(raw: &[u8]) -> ParseResult<Token> {
 #[cfg(test)]
 mod test {
   use super::*;
+  use crate::parsing::context::{Diagnostics, ParseOptions};
 
   macro_rules! assert_eq_cow {
     ($left:expr, $right:expr $(,)?) => {
@@ -418,6 +534,15 @@ mod test {
     assert_eq!(rest, b"Hello, world!");
   }
 
+  #[test]
+  fn should_pass_comments_to_callback() {
+    let mut comments = Vec::new();
+    let ((), rest) = parse_whitespace_with_comments(b"% One\r\n %Two\n1", &mut |c| comments.push(c))
+      .unwrap();
+    assert_eq!(rest, b"1");
+    assert_eq!(comments, vec![b"% One".as_slice(), b"%Two"]);
+  }
+
   #[test]
   fn should_parse_keyword() {
     let (keyword, rest) = parse_keyword(b"keyword  ").unwrap();
@@ -432,6 +557,95 @@ mod test {
     assert_eq!(rest, b"  ");
   }
 
+  #[test]
+  fn should_parse_numeric() {
+    const TEST_CASES: &[(&[u8], Token)] = &[
+      (b"123 ", Token::Integer(123)),
+      (b"43445 ", Token::Integer(43445)),
+      (b"+17 ", Token::Integer(17)),
+      (b"-98 ", Token::Integer(-98)),
+      (b"0 ", Token::Integer(0)),
+      (b"34.5 ", Token::Real(34.5)),
+      (b"-3.62 ", Token::Real(-3.62)),
+      (b"+123.6 ", Token::Real(123.6)),
+      (b"4. ", Token::Real(4.0)),
+      (b"-.002 ", Token::Real(-0.002)),
+      (b"0.0 ", Token::Real(0.0)),
+      (b"+6. ", Token::Real(6.0)),
+      (b".5 ", Token::Real(0.5)),
+    ];
+
+    for (raw, expected) in TEST_CASES {
+      let (token, rest) = parse_numeric(raw).unwrap();
+      assert_eq!(&token, expected);
+      assert_eq!(rest, b" ");
+    }
+  }
+
+  #[test]
+  fn should_not_parse_exponents() {
+    let (token, rest) = parse_numeric(b"1e5 ").unwrap();
+    assert_eq!(token, Token::Integer(1));
+    assert_eq!(rest, b"e5 ");
+  }
+
+  #[test]
+  fn should_reject_invalid_numeric() {
+    const TEST_CASES: &[(&[u8], &str, &str)] = &[
+      (b"+ ", "Numeric object has no digits", "\"+\" at offset 1"),
+      (b"-. ", "Numeric object has no digits", "\"-.\" at offset 2"),
+      (
+        b"+-+5..3 ",
+        "Sign must be at the start of a numeric object",
+        "\"+-+5..3\" at offset 1",
+      ),
+      (
+        b"5..3 ",
+        "Numeric object has more than one decimal point",
+        "\"5..3\" at offset 2",
+      ),
+      (
+        b"1-2 ",
+        "Sign must be at the start of a numeric object",
+        "\"1-2\" at offset 1",
+      ),
+    ];
+
+    for (raw, message, context) in TEST_CASES {
+      assert_eq!(
+        parse_numeric(raw),
+        Err(Error::Syntax(message, context.to_string()))
+      );
+    }
+  }
+
+  #[test]
+  fn should_reject_overflowing_numeric() {
+    assert_eq!(
+      parse_numeric(b"9223372036854775807 "),
+      Ok((Token::Integer(i64::MAX), &b" "[..]))
+    );
+    assert_eq!(
+      parse_numeric(b"-9223372036854775808 "),
+      Ok((Token::Integer(i64::MIN), &b" "[..]))
+    );
+    assert!(matches!(
+      parse_numeric(b"9223372036854775808 "),
+      Err(Error::Syntax("Integer is out of range", _))
+    ));
+
+    let long = [b'1'; MAX_NUMERIC_DIGITS + 1];
+    assert!(matches!(
+      parse_numeric(&long),
+      Err(Error::Syntax("Numeric object has too many digits", _))
+    ));
+    let long = [b'1'; MAX_NUMERIC_DIGITS];
+    assert!(matches!(
+      parse_numeric(&[&long[..], b".5 "].concat()),
+      Err(Error::Syntax("Numeric object has too many digits", _))
+    ));
+  }
+
   #[test]
   fn should_parse_literal_string() {
     const TEST_CASES: &[(&[u8], &str)] = &[
@@ -475,18 +689,131 @@ mod test {
     ];
 
     for (raw, expected) in TEST_CASES {
-      let (string, _raw) = parse_literal_string(raw).unwrap();
+      let (string, _raw) = parse_literal_string(raw, &ParseContext::default()).unwrap();
       assert_eq!(String::from_utf8_lossy(&string), Cow::Borrowed(*expected));
     }
   }
 
+  #[test]
+  fn should_not_count_escaped_parentheses() {
+    let (string, rest) = parse_literal_string(b"(a \\) b) c", &ParseContext::default()).unwrap();
+    assert_eq_cow!(string, b"a ) b");
+    assert_eq!(rest, b" c");
+    assert_eq!(
+      parse_literal_string(b"(a (b)", &ParseContext::default()),
+      Err(Error::EOF)
+    );
+    assert_eq!(
+      parse_literal_string(b"(a\\", &ParseContext::default()),
+      Err(Error::EOF)
+    );
+  }
+
+  #[test]
+  fn should_reject_trailing_backslash() {
+    let context = ParseContext::default();
+    assert_eq!(parse_escape_sequence(b"\\", &context), Err(Error::EOF));
+    assert_eq!(
+      parse_escape_sequence(b"\\7", &context),
+      Ok((Some(0o7), b"".as_slice()))
+    );
+  }
+
+  #[test]
+  fn should_ignore_octal_overflow() {
+    let raw = b"(\\777\\400\\5010)";
+    let (string, _raw) = parse_literal_string(raw, &ParseContext::default()).unwrap();
+    assert_eq_cow!(string, b"\xFF\x00\x410");
+  }
+
+  #[test]
+  fn should_handle_invalid_escapes() {
+    let raw = b"(\\d\\x)";
+    let diagnostics = Diagnostics::new();
+    let context = ParseContext::new(ParseOptions::strict(), raw, &diagnostics);
+    let (string, _raw) = parse_literal_string(raw, &context).unwrap();
+    assert_eq_cow!(string, b"dx");
+
+    let diagnostics = diagnostics.to_vec();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].offset, Some(1));
+    assert_eq!(
+      diagnostics[1].error,
+      Error::Syntax("Invalid escape sequence", "\\x".into())
+    );
+  }
+
+  #[test]
+  fn should_parse_escaped_line_endings() {
+    let raw = b"(a\\r\\n\\\r\nb\\\rc\\\nd)";
+    let (string, _raw) = parse_literal_string(raw, &ParseContext::default()).unwrap();
+    assert_eq_cow!(string, b"a\r\nbcd");
+  }
+
+  #[test]
+  fn should_reject_incomplete_name_escapes() {
+    assert!(matches!(
+      parse_name(b"/Name#2 "),
+      Err(Error::Syntax("Incomplete escape in name", _))
+    ));
+  }
+
+  #[test]
+  fn should_accept_cr_after_stream_in_lenient_mode() {
+    let raw = b"\rHello\nendstream";
+    assert!(matches!(
+      parse_to_end_of_stream(raw, &ParseContext::default()),
+      Err(Error::Syntax(_, _))
+    ));
+
+    let context = ParseContext::with_options(ParseOptions::lenient());
+    let (stream, _raw) = parse_to_end_of_stream(raw, &context).unwrap();
+    assert_eq!(stream, b"Hello\n");
+  }
+
+  #[test]
+  fn should_skip_longer_tokens_at_end_of_stream() {
+    let raw = b"\nendstreamed\nendstream>>";
+    let (stream, raw) = parse_to_end_of_stream(raw, &ParseContext::default()).unwrap();
+    assert_eq!(stream, b"endstreamed\n");
+    assert_eq!(raw, b">>");
+  }
+
   #[test]
   fn should_parse_hexadecimal_string() {
     let raw = b"<486 56C 6C6 F2C 206 1707>";
-    let (string, _raw) = parse_hexadecimal_string(raw).unwrap();
+    let (string, _raw) = parse_hexadecimal_string(raw, &ParseContext::default()).unwrap();
     assert_eq_cow!(String::from_utf8_lossy(&string), "Hello, app");
   }
 
+  #[test]
+  fn should_record_hexadecimal_string_repairs() {
+    let raw = b"<48 6G9 7>";
+    let diagnostics = Diagnostics::new();
+    let strict = ParseContext::new(ParseOptions::strict(), raw, &diagnostics);
+    assert_eq!(
+      parse_hexadecimal_string(raw, &strict),
+      Err(Error::Syntax("Invalid hex digit", "G9 7".into()))
+    );
+
+    let raw = b"<48 69 7>";
+    let strict = ParseContext::new(ParseOptions::strict(), raw, &diagnostics);
+    let (string, _raw) = parse_hexadecimal_string(raw, &strict).unwrap();
+    assert_eq_cow!(string, b"Hip");
+    assert_eq!(diagnostics.to_vec()[0].offset, Some(0));
+    assert_eq!(diagnostics.to_vec()[0].repair, "Padded the last digit with a zero");
+
+    let raw = b"<48 6G9 7>";
+    let diagnostics = Diagnostics::new();
+    let lenient = ParseContext::new(ParseOptions::lenient(), raw, &diagnostics);
+    let (string, _raw) = parse_hexadecimal_string(raw, &lenient).unwrap();
+    assert_eq_cow!(string, b"Hip");
+    let diagnostics = diagnostics.to_vec();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].offset, Some(5));
+    assert_eq!(diagnostics[0].repair, "Skipped the character");
+  }
+
   #[test]
   fn should_parse_name() {
     let raw = b"/Name1/ASomewhatLongerName/A;Name_With-Various***Characters?/1.2 ";
@@ -518,32 +845,33 @@ mod test {
 
   #[test]
   fn should_parse_token() {
+    let context = ParseContext::default();
     let raw = b"/one two +3 +4.0 5 -.6 (seven (7)) <8> [ ] << >> stream\ntesting\nendstream ";
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::Name(Cow::Borrowed(b"one")));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::Keyword(b"two"));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::Integer(3));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::Real(4.0));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::Integer(5));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::Real(-0.6));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::LiteralString(Cow::Borrowed(b"seven (7)")));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::HexadecimalString(Cow::Borrowed(&[0x80])));
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::BeginArray);
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::EndArray);
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::BeginDictionary);
-    let (token, raw) = parse_token(raw).unwrap();
+    let (token, raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::EndDictionary);
-    let (token, _raw) = parse_token(raw).unwrap();
+    let (token, _raw) = parse_token(raw, &context).unwrap();
     assert_eq!(token, Token::Stream(b"testing\n"));
   }
 }
@@ -0,0 +1,310 @@
+//! Reads cross-reference streams (Adobe, 2008, p. 48-51) and the object
+//! streams whose objects only they can locate (Adobe, 2008, p. 45-48).
+//!
+//! These are read for hybrid-reference files, whose trailers point to a
+//! cross-reference stream with `/XRefStm` alongside a classic xref table, so
+//! that older readers can still open them (Adobe, 2008, p. 67-68). Files
+//! that only have cross-reference streams are not supported yet.
+
+use crate::error::{Error, Result};
+use crate::objects::{names, IndirectRef, Object};
+use crate::parsing::context::ParseContext;
+use crate::parsing::objects::{parse_indirect_object, parse_object};
+use crate::parsing::pdf_file::{PdfFile, XrefEntry, XrefTable};
+use crate::parsing::tokens;
+use std::borrow::Cow;
+
+impl<'a> PdfFile<'a> {
+    /// Loads an object from an object stream. The decoded data of the object
+    /// stream is cached like that of any other stream.
+    pub(crate) fn compressed_object(
+        &self,
+        reference: IndirectRef,
+        stream: u32,
+        index: u32,
+    ) -> Result<Object<'_>> {
+        // Object streams cannot themselves be compressed, which also stops
+        // them from referring to each other in a cycle
+        let stream = IndirectRef::new(stream, 0);
+        if !matches!(self.xref_table()?.get(&stream), Some(XrefEntry::InUse(_))) {
+            return Err(Error::Syntax(
+                "Object stream is not in the xref table",
+                format!("{:?} in {:?}", reference, stream),
+            ));
+        }
+
+        let dict = self.encoded_object(stream)?;
+        let data = self.decoded_stream(stream)?;
+        let raw = object_in_stream(&dict, &data, reference, index)?;
+        let (object, _) = parse_object(raw, &self.context())?;
+        Ok(object.to_static())
+    }
+}
+
+/// Parses the cross-reference stream at the start of `raw` into its entries.
+pub(crate) fn parse_xref_stream(raw: &[u8], context: &ParseContext) -> Result<XrefTable> {
+    let ((_, stream), _) = parse_indirect_object(raw, context)?;
+    if !stream.get_name(names::TYPE)?.matches(names::X_REF) {
+        return Err(Error::Type(format!(
+            "Expected cross-reference stream got {:?}",
+            stream.get(names::TYPE)
+        )));
+    }
+    let (_, data) = stream.as_stream()?;
+    let data = undo_predictor(&stream, data)?;
+
+    let invalid =
+        |what: &Object| Error::Syntax("Invalid cross-reference stream", format!("{:?}", what));
+    let widths = stream
+        .get_array(names::W)?
+        .iter()
+        .map(|width| match width.as_int()? {
+            width @ 0..=8 => Ok(width as usize),
+            _ => Err(invalid(width)),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let &[type_width, second_width, third_width] = &widths[..] else {
+        return Err(invalid(stream.get_required(names::W)?));
+    };
+    let subsections = match stream.get(names::INDEX) {
+        Some(index) => index
+            .as_array()?
+            .chunks(2)
+            .map(|pair| match pair {
+                [first, count] => Ok((first.as_int()?, count.as_int()?)),
+                _ => Err(invalid(index)),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => vec![(0, stream.get_int(names::SIZE)?)],
+    };
+
+    let row_length = type_width + second_width + third_width;
+    let mut rows = data.chunks_exact(row_length.max(1));
+    let mut table = XrefTable::new();
+    for (first, count) in subsections {
+        for number in first..first.saturating_add(count) {
+            let row = rows.next().ok_or_else(|| {
+                Error::Syntax("Cross-reference stream is truncated", number.to_string())
+            })?;
+            let (kind, rest) = row.split_at(type_width);
+            let (second, third) = rest.split_at(second_width);
+            // The type defaults to 1 when its field is left out
+            let kind = if type_width == 0 { 1 } else { field(kind) };
+            let (second, third) = (field(second), field(third));
+
+            let invalid_entry = || {
+                Error::Syntax(
+                    "Invalid cross-reference stream entry",
+                    format!("{} {} {} for {}", kind, second, third, number),
+                )
+            };
+            let (generation, entry) = match kind {
+                0 => (third, XrefEntry::Free),
+                1 => (
+                    third,
+                    XrefEntry::InUse(usize::try_from(second).map_err(|_| invalid_entry())?),
+                ),
+                2 => (
+                    0,
+                    XrefEntry::Compressed {
+                        stream: u32::try_from(second).map_err(|_| invalid_entry())?,
+                        index: u32::try_from(third).map_err(|_| invalid_entry())?,
+                    },
+                ),
+                // Other types are references to the null object
+                _ => continue,
+            };
+            let generation = i64::try_from(generation).map_err(|_| invalid_entry())?;
+            table.insert(IndirectRef::checked(number, generation)?, entry);
+        }
+    }
+    Ok(table)
+}
+
+/// Reads a big-endian field of a cross-reference stream entry.
+fn field(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |n, &byte| n << 8 | byte as u64)
+}
+
+/// Finds an object in the decoded data of an object stream, returning the
+/// bytes from where it starts.
+fn object_in_stream<'d>(
+    stream: &Object,
+    data: &'d [u8],
+    reference: IndirectRef,
+    index: u32,
+) -> Result<&'d [u8]> {
+    if index as i64 >= stream.get_int(names::N)? {
+        return Err(Error::ObjectNotFound(reference));
+    }
+    let first = stream.get_int(names::FIRST)?;
+    let invalid = || Error::Syntax("Invalid object stream", format!("{:?}", reference));
+    let first = usize::try_from(first).map_err(|_| invalid())?;
+
+    // The stream starts with pairs of object numbers and offsets from
+    // `/First`, in the order of the objects' indices
+    let mut header = data;
+    for i in 0..=index {
+        let (number, rest) = tokens::parse_number::<u32>(header)?;
+        let (offset, rest) = tokens::parse_number::<usize>(rest)?;
+        if i == index {
+            if number != reference.number {
+                return Err(Error::Syntax(
+                    "Object number does not match the object stream",
+                    format!("{} vs. {:?}", number, reference),
+                ));
+            }
+            return data.get(first.saturating_add(offset)..).ok_or_else(invalid);
+        }
+        header = rest;
+    }
+    unreachable!()
+}
+
+/// Reverses the PNG predictors that cross-reference streams are usually
+/// encoded with (Adobe, 2008, p. 27), which the filters themselves leave in
+/// place. Each entry is a row, and each byte a sample.
+fn undo_predictor<'d>(stream: &Object, data: Cow<'d, [u8]>) -> Result<Cow<'d, [u8]>> {
+    let params = match stream.get(names::DECODE_PARMS) {
+        Some(Object::Array(params)) => params.first(),
+        params => params,
+    };
+    let predictor = match params.and_then(|params| params.get(names::PREDICTOR)) {
+        Some(predictor) => predictor.as_int()?,
+        None => 1,
+    };
+    match predictor {
+        1 => return Ok(data),
+        10..=15 => {}
+        other => {
+            return Err(Error::Syntax(
+                "Unsupported predictor for cross-reference stream",
+                other.to_string(),
+            ))
+        }
+    }
+    let columns = match params.and_then(|params| params.get(names::COLUMNS)) {
+        Some(columns) => columns.as_int()?,
+        None => 1,
+    };
+    // Each row is a filter byte followed by the columns, so the columns are
+    // checked against the data before anything is allocated for them
+    let columns = usize::try_from(columns)
+        .ok()
+        .filter(|&columns| {
+            columns > 0 && columns < data.len() && data.len().is_multiple_of(columns + 1)
+        })
+        .ok_or_else(|| {
+            Error::Syntax(
+                "Invalid predictor columns",
+                format!("{} for {} bytes", columns, data.len()),
+            )
+        })?;
+
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut previous = vec![0; columns];
+    for row in data.chunks(columns + 1) {
+        let (&filter, row) = row.split_first().unwrap();
+        let mut current = row.to_vec();
+        for i in 0..columns {
+            let left = if i > 0 { current[i - 1] } else { 0 };
+            let up = previous[i];
+            let up_left = if i > 0 { previous[i - 1] } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                other => {
+                    return Err(Error::FilterDecode(
+                        "FlateDecode",
+                        format!("Unknown PNG predictor {}", other),
+                    ))
+                }
+            };
+            current[i] = current[i].wrapping_add(predicted);
+        }
+        decoded.extend_from_slice(&current);
+        previous = current;
+    }
+    Ok(Cow::Owned(decoded))
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distances = [left, up, up_left].map(|n| (estimate - n as i16).abs());
+    if distances[0] <= distances[1] && distances[0] <= distances[2] {
+        left
+    } else if distances[1] <= distances[2] {
+        up
+    } else {
+        up_left
+    }
+}
+
+#[cfg(all(test, feature = "write"))]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn should_read_hybrid_files() {
+        let mut file = PdfFile::from_raw(test_support::object_streams());
+        file.load_xref_table().unwrap();
+        assert_eq!(
+            file.xref_table().unwrap()[&IndirectRef::new(3, 0)],
+            XrefEntry::Compressed {
+                stream: 6,
+                index: 0
+            }
+        );
+
+        let pages = file.pages().unwrap();
+        assert_eq!(
+            &pages[0].content_bytes().unwrap()[..],
+            test_support::HELLO_WORLD_CONTENT
+        );
+        let font = file.object(IndirectRef::new(3, 0)).unwrap();
+        assert!(font.get_name(names::TYPE).unwrap().matches(names::FONT));
+        assert!(file.raw_object_bytes(IndirectRef::new(3, 0)).is_err());
+        assert_eq!(file.diagnostics(), vec![]);
+    }
+
+    #[test]
+    fn should_undo_png_predictors() {
+        let stream = Object::Dictionary(
+            [(
+                names::DECODE_PARMS.into(),
+                test_support::dict(&[
+                    (names::PREDICTOR, Object::Integer(12)),
+                    (names::COLUMNS, Object::Integer(3)),
+                ]),
+            )]
+            .into(),
+        );
+        // Rows filtered with Up, then Sub, then Paeth
+        let data = [2, 1, 2, 3, 1, 2, 1, 1, 4, 0, 0, 0];
+        let decoded = undo_predictor(&stream, Cow::Borrowed(&data)).unwrap();
+        assert_eq!(&decoded[..], [1, 2, 3, 2, 3, 4, 2, 3, 4]);
+
+        let stream = Object::Dictionary(Default::default());
+        let decoded = undo_predictor(&stream, Cow::Borrowed(&data)).unwrap();
+        assert_eq!(&decoded[..], data);
+
+        for columns in [0, 4, 12, i64::MAX, -1] {
+            let stream = test_support::dict(&[(
+                names::DECODE_PARMS,
+                test_support::dict(&[
+                    (names::PREDICTOR, Object::Integer(12)),
+                    (names::COLUMNS, Object::Integer(columns)),
+                ]),
+            )]);
+            assert!(
+                undo_predictor(&stream, Cow::Borrowed(&data)).is_err(),
+                "{}",
+                columns
+            );
+        }
+    }
+}
@@ -0,0 +1,443 @@
+//! Generates small documents for tests, so that they do not have to be
+//! written by hand.
+
+#[cfg(feature = "write")]
+use crate::objects::{names, IndirectRef};
+use crate::objects::{Dictionary, Name, Object};
+#[cfg(feature = "write")]
+use crate::writing::document::DocumentBuilder;
+#[cfg(feature = "write")]
+use crate::writing::objects::WriteOptions;
+#[cfg(feature = "write")]
+use std::borrow::Cow;
+#[cfg(feature = "write")]
+use std::path::PathBuf;
+
+#[cfg(feature = "write")]
+pub const HELLO_WORLD_CONTENT: &[u8] = b"BT /F1 24 Tf 72 720 Td (Hello, world!) Tj ET";
+
+pub fn dict<'a>(entries: &[(&'a str, Object<'a>)]) -> Object<'a> {
+    let dict = entries
+        .iter()
+        .map(|(key, value)| (Name::from(*key), value.clone()))
+        .collect::<Dictionary>();
+    Object::Dictionary(dict)
+}
+
+pub fn name(name: &str) -> Object<'_> {
+    Object::Name(Name::from(name))
+}
+
+/// Writes a generated document to the temporary directory, for tests of
+/// functions that take a path. The name should be unique to the test.
+#[cfg(feature = "write")]
+pub fn temp_file(name: &str, raw: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bdf-{}-{}.pdf", std::process::id(), name));
+    std::fs::write(&path, raw).unwrap();
+    path
+}
+
+/// Builds a document in which each page shows a single line of text using
+/// Helvetica.
+#[cfg(feature = "write")]
+pub fn build_pages<'a>(builder: &mut DocumentBuilder<'a>, contents: &[&'a [u8]]) -> IndirectRef {
+    let catalog = builder.reserve();
+    let pages = builder.reserve();
+    let font = builder.add(dict(&[
+        (names::TYPE, name("Font")),
+        (names::SUBTYPE, name("Type1")),
+        ("BaseFont", name("Helvetica")),
+    ]));
+
+    let mut kids = Vec::new();
+    for content in contents {
+        let stream = Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::new())),
+            Cow::Borrowed(*content),
+        );
+        let content = builder.add(stream);
+        let page = builder.add(dict(&[
+            (names::TYPE, name(names::PAGE)),
+            (names::PARENT, Object::Indirect(pages)),
+            (
+                names::MEDIA_BOX,
+                Object::Array(vec![
+                    Object::Integer(0),
+                    Object::Integer(0),
+                    Object::Integer(612),
+                    Object::Integer(792),
+                ]),
+            ),
+            (
+                names::RESOURCES,
+                dict(&[("Font", dict(&[("F1", Object::Indirect(font))]))]),
+            ),
+            (names::CONTENTS, Object::Indirect(content)),
+        ]));
+        kids.push(Object::Indirect(page));
+    }
+
+    builder.insert(
+        pages,
+        dict(&[
+            (names::TYPE, name(names::PAGES)),
+            (names::COUNT, Object::Integer(kids.len() as i64)),
+            (names::KIDS, Object::Array(kids)),
+        ]),
+    );
+    builder.insert(
+        catalog,
+        dict(&[
+            (names::TYPE, name(names::CATALOG)),
+            (names::PAGES, Object::Indirect(pages)),
+        ]),
+    );
+    builder.set_root(catalog);
+
+    catalog
+}
+
+/// Finishes a document with a single page, which has the given resources and
+/// content. The resources may refer to objects already in the builder.
+#[cfg(feature = "write")]
+pub fn single_page<'a>(
+    mut builder: DocumentBuilder<'a>,
+    resources: Object<'a>,
+    content: &'a [u8],
+) -> Vec<u8> {
+    let content = builder.add(Object::Stream(
+        Box::new(Object::Dictionary(Dictionary::new())),
+        Cow::Borrowed(content),
+    ));
+    let pages = builder.reserve();
+    let page = builder.add(dict(&[
+        (names::TYPE, name(names::PAGE)),
+        (names::PARENT, Object::Indirect(pages)),
+        (names::RESOURCES, resources),
+        (names::CONTENTS, Object::Indirect(content)),
+    ]));
+    builder.insert(
+        pages,
+        dict(&[
+            (names::TYPE, name(names::PAGES)),
+            (names::KIDS, Object::Array(vec![Object::Indirect(page)])),
+            (names::COUNT, Object::Integer(1)),
+        ]),
+    );
+    let catalog = builder.add(dict(&[
+        (names::TYPE, name(names::CATALOG)),
+        (names::PAGES, Object::Indirect(pages)),
+    ]));
+    builder.set_root(catalog);
+    builder.to_bytes().unwrap()
+}
+
+/// A one page document that says "Hello, world!".
+#[cfg(feature = "write")]
+pub fn hello_world(options: WriteOptions) -> Vec<u8> {
+    let mut builder = DocumentBuilder::new().with_options(options);
+    build_pages(&mut builder, &[HELLO_WORLD_CONTENT]);
+    builder.to_bytes().unwrap()
+}
+
+/// The hello world document as a hybrid-reference file (Adobe, 2008, p. 67).
+/// The font is in an object stream, which only the cross-reference stream
+/// lists, and the xref table lists it as free for older readers. The catalog
+/// and page tree are written on their own.
+#[cfg(feature = "write")]
+pub fn object_streams() -> Vec<u8> {
+    hello_world(WriteOptions {
+        object_streams: true,
+        ..Default::default()
+    })
+}
+
+/// The hello world document as most producers now write it, with its
+/// streams compressed, including the object stream and the cross-reference
+/// stream (Adobe, 2008, p. 48-51).
+#[cfg(all(feature = "write", any(feature = "miniz_oxide", feature = "flate2")))]
+pub fn compressed_xref() -> Vec<u8> {
+    hello_world(WriteOptions {
+        compress_streams: true,
+        object_streams: true,
+        ..Default::default()
+    })
+}
+
+/// The permissions of the encrypted document, which allow everything except
+/// copying. The bits that are reserved must be set (Adobe, 2008, p. 61).
+#[cfg(feature = "write")]
+pub const ENCRYPTED_PERMISSIONS: i64 = !(1 | 2 | 16);
+
+/// The bytes that passwords are padded to 32 bytes with before they are
+/// hashed (Adobe, 2008, p. 62).
+#[cfg(feature = "write")]
+const PASSWORD_PADDING: [u8; 32] = [
+    0x28, 0xbf, 0x4e, 0x5e, 0x4e, 0x75, 0x8a, 0x41, 0x64, 0x00, 0x4e, 0x56, 0xff, 0xfa, 0x01, 0x08,
+    0x2e, 0x2e, 0x00, 0xb6, 0xd0, 0x68, 0x3e, 0x80, 0x2f, 0x0c, 0xa9, 0xfe, 0x64, 0x53, 0x69, 0x7a,
+];
+
+/// The hello world document encrypted with 40-bit RC4 by the standard
+/// security handler, revision 2 (Adobe, 2008, p. 58-63). The user password is
+/// empty and the owner password is `owner`. The content stream is the only
+/// object that needs encrypting, since the document has no strings.
+#[cfg(feature = "write")]
+pub fn encrypted() -> Vec<u8> {
+    let id = [7; 16];
+    let permissions = (ENCRYPTED_PERMISSIONS as i32).to_le_bytes();
+    let mut owner = b"owner".to_vec();
+    owner.extend_from_slice(&PASSWORD_PADDING[..32 - owner.len()]);
+    let owner_entry = rc4(&md5::compute(&owner)[..5], &PASSWORD_PADDING);
+
+    let mut context = md5::Context::new();
+    context.consume(PASSWORD_PADDING);
+    context.consume(&owner_entry);
+    context.consume(permissions);
+    context.consume(id);
+    let key = context.compute();
+    let user_entry = rc4(&key[..5], &PASSWORD_PADDING);
+
+    // Each object is encrypted with a key made from its number and
+    // generation. The content stream is object 4
+    let mut object_key = key[..5].to_vec();
+    object_key.extend_from_slice(&[4, 0, 0, 0, 0]);
+    let content = rc4(&md5::compute(&object_key)[..10], HELLO_WORLD_CONTENT);
+
+    let mut builder = DocumentBuilder::new().with_id(id);
+    build_pages(&mut builder, &[&content]);
+    let encrypt = builder.add(dict(&[
+        (names::FILTER, name("Standard")),
+        (names::V, Object::Integer(1)),
+        (names::R, Object::Integer(2)),
+        (names::O, Object::String(Cow::Owned(owner_entry))),
+        (names::U, Object::String(Cow::Owned(user_entry))),
+        (names::P, Object::Integer(ENCRYPTED_PERMISSIONS)),
+    ]));
+    builder.set_trailer_entry(names::ENCRYPT, Object::Indirect(encrypt));
+    builder.to_bytes().unwrap()
+}
+
+/// Encrypts or decrypts data with RC4, which is symmetric.
+#[cfg(feature = "write")]
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state = (0..=255).collect::<Vec<u8>>();
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+        state.swap(i, j as usize);
+    }
+
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|byte| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(state[i as usize]);
+            state.swap(i as usize, j as usize);
+            byte ^ state[state[i as usize].wrapping_add(state[j as usize]) as usize]
+        })
+        .collect()
+}
+
+/// A one page document that draws a JPEG image, `Im1`, above the hello world
+/// text. The image data is not a real JPEG, so it cannot be decoded, which
+/// most `DCTDecode` images cannot be by this crate either.
+#[cfg(feature = "write")]
+pub fn jpeg_page() -> Vec<u8> {
+    let mut builder = DocumentBuilder::new();
+    let font = builder.add(dict(&[
+        (names::TYPE, name(names::FONT)),
+        (names::SUBTYPE, name("Type1")),
+        (names::BASE_FONT, name("Helvetica")),
+    ]));
+    let image = builder.reserve();
+    builder.insert_raw(
+        image,
+        Cow::Borrowed(
+            b"\n<</Type/XObject/Subtype/Image/Width 1/Height 1/ColorSpace/DeviceRGB\
+            /BitsPerComponent 8/Filter/DCTDecode/Length 12>>\nstream\n\xff\xd8\xff\xe0 JFIF \xff\xd9\nendstream\n",
+        ),
+    );
+    let resources = dict(&[
+        (names::FONT, dict(&[("F1", Object::Indirect(font))])),
+        (names::X_OBJECT, dict(&[("Im1", Object::Indirect(image))])),
+    ]);
+    single_page(
+        builder,
+        resources,
+        b"q 100 0 0 100 72 600 cm /Im1 Do Q BT /F1 24 Tf 72 720 Td (Hello, world!) Tj ET",
+    )
+}
+
+/// A document with the given number of pages, each showing its page number.
+#[cfg(feature = "write")]
+pub fn multi_page(count: usize) -> Vec<u8> {
+    let contents = (1..=count)
+        .map(|i| format!("BT /F1 24 Tf 72 720 Td (Page {}) Tj ET", i).into_bytes())
+        .collect::<Vec<_>>();
+
+    let mut builder = DocumentBuilder::new();
+    build_pages(
+        &mut builder,
+        &contents.iter().map(|c| c.as_slice()).collect::<Vec<_>>(),
+    );
+    builder.to_bytes().unwrap()
+}
+
+/// A one page document with a form, which has a text field `name`, a check
+/// box `agree`, a push button `submit` and a radio button group `colour`
+/// whose buttons have the states `0` and `1` and export `Red` and `Blue`.
+#[cfg(feature = "write")]
+pub fn form() -> Vec<u8> {
+    let mut builder = DocumentBuilder::new();
+    let catalog = build_pages(&mut builder, &[b""]);
+    let page = IndirectRef {
+        number: 5,
+        generation: 0,
+    };
+    let font = IndirectRef {
+        number: 3,
+        generation: 0,
+    };
+    let rect = |x0: i64, y0: i64, x1: i64, y1: i64| {
+        Object::Array(vec![
+            Object::Integer(x0),
+            Object::Integer(y0),
+            Object::Integer(x1),
+            Object::Integer(y1),
+        ])
+    };
+    let empty = builder.add(Object::Stream(
+        Box::new(Object::Dictionary(Dictionary::new())),
+        Cow::Borrowed(b""),
+    ));
+    let states = |on: &'static str| {
+        dict(&[(
+            names::N,
+            dict(&[
+                (on, Object::Indirect(empty)),
+                ("Off", Object::Indirect(empty)),
+            ]),
+        )])
+    };
+    let widget = |extra: &[(&'static str, Object<'static>)]| {
+        let mut entries = vec![
+            (names::TYPE, name(names::ANNOT)),
+            (names::SUBTYPE, name(names::WIDGET)),
+            (names::P, Object::Indirect(page)),
+        ];
+        entries.extend_from_slice(extra);
+        dict(&entries)
+    };
+
+    let text = builder.add(widget(&[
+        (names::FT, name("Tx")),
+        (names::T, Object::String(Cow::Borrowed(b"name"))),
+        (names::RECT, rect(50, 700, 250, 720)),
+        (
+            names::MK,
+            dict(&[(names::BC, Object::Array(vec![Object::Integer(0)]))]),
+        ),
+    ]));
+    let checkbox = builder.add(widget(&[
+        (names::FT, name("Btn")),
+        (names::T, Object::String(Cow::Borrowed(b"agree"))),
+        (names::RECT, rect(50, 650, 64, 664)),
+        (names::AP, states("Yes")),
+        (names::AS, name("Off")),
+    ]));
+    let pushbutton = builder.add(widget(&[
+        (names::FT, name("Btn")),
+        (names::FF, Object::Integer(1 << 16)),
+        (names::T, Object::String(Cow::Borrowed(b"submit"))),
+        (names::RECT, rect(50, 600, 150, 620)),
+        (
+            names::MK,
+            dict(&[(names::CAPTION, Object::String(Cow::Borrowed(b"Submit")))]),
+        ),
+    ]));
+    let radio = builder.reserve();
+    let red = builder.add(widget(&[
+        (names::PARENT, Object::Indirect(radio)),
+        (names::RECT, rect(50, 550, 64, 564)),
+        (names::AP, states("0")),
+        (names::AS, name("Off")),
+    ]));
+    let blue = builder.add(widget(&[
+        (names::PARENT, Object::Indirect(radio)),
+        (names::RECT, rect(70, 550, 84, 564)),
+        (names::AP, states("1")),
+        (names::AS, name("Off")),
+    ]));
+    builder.insert(
+        radio,
+        dict(&[
+            (names::FT, name("Btn")),
+            (names::FF, Object::Integer(1 << 15 | 1 << 14)),
+            (names::T, Object::String(Cow::Borrowed(b"colour"))),
+            (
+                names::OPT,
+                Object::Array(vec![
+                    Object::String(Cow::Borrowed(b"Red")),
+                    Object::String(Cow::Borrowed(b"Blue")),
+                ]),
+            ),
+            (
+                names::KIDS,
+                Object::Array(vec![Object::Indirect(red), Object::Indirect(blue)]),
+            ),
+        ]),
+    );
+
+    let fields = [text, checkbox, pushbutton, radio];
+    let mut annots = fields[..3].to_vec();
+    annots.extend([red, blue]);
+    let to_array =
+        |refs: &[IndirectRef]| Object::Array(refs.iter().map(|&r| Object::Indirect(r)).collect());
+    builder.insert(
+        page,
+        dict(&[
+            (names::TYPE, name(names::PAGE)),
+            (
+                names::PARENT,
+                Object::Indirect(IndirectRef {
+                    number: 2,
+                    generation: 0,
+                }),
+            ),
+            (names::MEDIA_BOX, rect(0, 0, 612, 792)),
+            (
+                names::CONTENTS,
+                Object::Indirect(IndirectRef {
+                    number: 4,
+                    generation: 0,
+                }),
+            ),
+            (names::ANNOTS, to_array(&annots)),
+        ]),
+    );
+    builder.insert(
+        catalog,
+        dict(&[
+            (names::TYPE, name(names::CATALOG)),
+            (
+                names::PAGES,
+                Object::Indirect(IndirectRef {
+                    number: 2,
+                    generation: 0,
+                }),
+            ),
+            (
+                names::ACRO_FORM,
+                dict(&[
+                    (names::FIELDS, to_array(&fields)),
+                    (names::DA, Object::String(Cow::Borrowed(b"/Helv 10 Tf 0 g"))),
+                    (
+                        names::DR,
+                        dict(&[(names::FONT, dict(&[("Helv", Object::Indirect(font))]))]),
+                    ),
+                ]),
+            ),
+        ]),
+    );
+    builder.to_bytes().unwrap()
+}
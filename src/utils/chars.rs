@@ -15,6 +15,50 @@ pub const DELIMETER_CHARACTERS: [u8; 10] = *b"()<>[]{}/%";
 /// Characters which may be part of a numeric object token.
 pub const NUMERIC_CHARACTERS: [u8; 3] = *b"+-.";
 
+/// Bit flags for the classes that each character belongs to, so that a
+/// character can be classified with one table lookup rather than by comparing
+/// it against each character of a class.
+const WHITESPACE: u8 = 1;
+const NEWLINE: u8 = 2;
+const DELIMITER: u8 = 4;
+const NUMERIC: u8 = 8;
+const ALPHABETIC: u8 = 16;
+
+const CHAR_CLASSES: [u8; 256] = {
+    let mut classes = [0; 256];
+    let mut i = 0;
+    while i < WHITESPACE_CHARACTERS.len() {
+        classes[WHITESPACE_CHARACTERS[i] as usize] |= WHITESPACE;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < NEWLINE_CHARACTERS.len() {
+        classes[NEWLINE_CHARACTERS[i] as usize] |= NEWLINE;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < DELIMETER_CHARACTERS.len() {
+        classes[DELIMETER_CHARACTERS[i] as usize] |= DELIMITER;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < NUMERIC_CHARACTERS.len() {
+        classes[NUMERIC_CHARACTERS[i] as usize] |= NUMERIC;
+        i += 1;
+    }
+    let mut c = 0;
+    while c < 256 {
+        if (c as u8).is_ascii_digit() {
+            classes[c] |= NUMERIC;
+        }
+        if (c as u8).is_ascii_alphabetic() {
+            classes[c] |= ALPHABETIC;
+        }
+        c += 1;
+    }
+    classes
+};
+
 /// Returns the next byte in the buffer.
 ///
 /// If the buffer is empty, then returns `Err(Error::EOF)`.
@@ -26,33 +70,47 @@ pub fn peek_char(raw: &[u8]) -> Result<u8> {
 /// Returns true if the character constututes whitespace.
 #[inline]
 pub fn is_whitespace_char(c: u8) -> bool {
-    WHITESPACE_CHARACTERS.contains(&c)
+    CHAR_CLASSES[c as usize] & WHITESPACE != 0
 }
 
 /// Returns true if the character constututes a newline.
 #[inline]
 pub fn is_newline_char(c: u8) -> bool {
-    NEWLINE_CHARACTERS.contains(&c)
+    CHAR_CLASSES[c as usize] & NEWLINE != 0
 }
 
 /// Returns true if the character is from the roman alphabet.
 #[inline]
 pub fn is_alphabetic_char(c: u8) -> bool {
-    (b'a' <= c && c <= b'z') || (b'A' <= c && c <= b'Z')
+    CHAR_CLASSES[c as usize] & ALPHABETIC != 0
 }
 
 /// Returns true if the character is may be part of a name object token
 /// (excluding the initial `/`)
 #[inline]
 pub fn is_name_char(c: u8) -> bool {
-    !DELIMETER_CHARACTERS.contains(&c) && !is_whitespace_char(c)
+    CHAR_CLASSES[c as usize] & (DELIMITER | WHITESPACE) == 0
+}
+
+/// Returns true if the character is a delimiter.
+#[inline]
+pub fn is_delimiter_char(c: u8) -> bool {
+    CHAR_CLASSES[c as usize] & DELIMITER != 0
 }
 
 /// Returns true if the character is may be part of a numeric object token
 /// (0-9, +, -, .)
 #[inline]
 pub fn is_numeric_char(c: u8) -> bool {
-    NUMERIC_CHARACTERS.contains(&c) || (b'0' <= c && c <= b'9')
+    CHAR_CLASSES[c as usize] & NUMERIC != 0
+}
+
+/// Returns the length of the run of characters at the start of the buffer
+/// that match `is_class`, or `Err(Error::EOF)` if the run reaches the end of
+/// the buffer, as the token may continue past it.
+#[inline]
+pub fn run_length(raw: &[u8], is_class: impl Fn(u8) -> bool) -> Result<usize> {
+    raw.iter().position(|&c| !is_class(c)).ok_or(Error::EOF)
 }
 
 #[cfg(test)]
@@ -91,4 +149,12 @@ mod tests {
     char_detection_test!(alphabetic, "Hi");
     char_detection_test!(name, "Hi#20+-.");
     char_detection_test!(numeric, "20+-.");
+    char_detection_test!(delimiter, "/");
+
+    #[test]
+    fn should_measure_runs() {
+        assert_eq!(run_length(b"abc def", is_alphabetic_char), Ok(3));
+        assert_eq!(run_length(b" x", is_alphabetic_char), Ok(0));
+        assert_eq!(run_length(b"abc", is_alphabetic_char), Err(Error::EOF));
+    }
 }
@@ -1,2 +1,4 @@
 pub mod chars;
 pub mod slices;
+pub mod text;
+pub mod trace;
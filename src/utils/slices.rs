@@ -1,19 +1,28 @@
-pub fn position_of_sequence<T: Eq>(buf: &[T], seq: &[T]) -> Option<usize> {
-    let len = seq.len();
-    for i in 0..buf.len() - len {
-        if &buf[i..i + len] == seq {
-            return Some(i);
-        }
-    }
-    None
+use memchr::memmem;
+
+pub fn position_of_sequence(buf: &[u8], seq: &[u8]) -> Option<usize> {
+    memmem::find(buf, seq)
+}
+
+pub fn last_position_of_sequence(buf: &[u8], seq: &[u8]) -> Option<usize> {
+    memmem::rfind(buf, seq)
 }
 
-pub fn last_position_of_sequence<T: Eq>(buf: &[T], seq: &[T]) -> Option<usize> {
-    let len = seq.len();
-    for i in (0..buf.len() - len).rev() {
-        if &buf[i..i + len] == seq {
-            return Some(i);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_sequence_at_either_end() {
+        assert_eq!(position_of_sequence(b"abcabc", b"abc"), Some(0));
+        assert_eq!(position_of_sequence(b"xxabc", b"abc"), Some(2));
+        assert_eq!(last_position_of_sequence(b"abcabc", b"abc"), Some(3));
+        assert_eq!(last_position_of_sequence(b"abcxx", b"abc"), Some(0));
+    }
+
+    #[test]
+    fn should_not_find_sequence_in_short_buffer() {
+        assert_eq!(position_of_sequence(b"ab", b"abc"), None);
+        assert_eq!(last_position_of_sequence(b"", b"abc"), None);
     }
-    None
 }
@@ -0,0 +1,175 @@
+//! Encoding and decoding of text strings, which are used for outline titles,
+//! document information and annotation contents (Adobe, 2008, p. 86-88).
+
+/// Characters 0x18-0x1F of PDFDocEncoding (Adobe, 2008, p. 656).
+const PDF_DOC_LOW: [char; 8] = ['˘', 'ˇ', 'ˆ', '˙', '˝', '˛', '˚', '˜'];
+
+/// Characters 0x80-0xA0 of PDFDocEncoding, where 0x9F is undefined
+/// (Adobe, 2008, p. 656-657).
+const PDF_DOC_HIGH: [char; 33] = [
+    '•',
+    '†',
+    '‡',
+    '…',
+    '—',
+    '–',
+    'ƒ',
+    '⁄',
+    '‹',
+    '›',
+    '−',
+    '‰',
+    '„',
+    '“',
+    '”',
+    '‘',
+    '’',
+    '‚',
+    '™',
+    'ﬁ',
+    'ﬂ',
+    'Ł',
+    'Œ',
+    'Š',
+    'Ÿ',
+    'Ž',
+    'ı',
+    'ł',
+    'œ',
+    'š',
+    'ž',
+    char::REPLACEMENT_CHARACTER,
+    '€',
+];
+
+/// Decodes a text string, which is UTF-16BE if it starts with a byte order
+/// mark and PDFDocEncoding otherwise.
+///
+/// This never fails, since producers commonly get this wrong. Little-endian
+/// and UTF-8 byte order marks are also accepted, trailing NULs are dropped,
+/// and anything that cannot be decoded becomes U+FFFD.
+pub fn decode_text_string(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(b"\xfe\xff") {
+        decode_utf16(rest, u16::from_be_bytes)
+    } else if let Some(rest) = bytes.strip_prefix(b"\xff\xfe") {
+        decode_utf16(rest, u16::from_le_bytes)
+    } else if let Some(rest) = bytes.strip_prefix(b"\xef\xbb\xbf") {
+        String::from_utf8_lossy(rest).into_owned()
+    } else {
+        let end = bytes
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |last| last + 1);
+        bytes[..end].iter().map(|&b| pdf_doc_char(b)).collect()
+    }
+}
+
+/// Encodes a text string, as PDFDocEncoding if it can be and UTF-16BE with a
+/// byte order mark otherwise.
+pub fn encode_text_string(text: &str) -> Vec<u8> {
+    match text.chars().map(pdf_doc_byte).collect::<Option<Vec<_>>>() {
+        Some(bytes) => bytes,
+        None => {
+            let mut bytes = vec![0xFE, 0xFF];
+            bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+            bytes
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks(2).map(|pair| match *pair {
+        [high, low] => unit([high, low]),
+        // A stray byte at the end can't be part of a character
+        _ => 0xFFFD,
+    });
+
+    // Language codes are written between a pair of ESC characters
+    // (Adobe, 2008, p. 87)
+    let mut in_escape = false;
+    let text = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .filter(|&c| {
+            if c == '\u{1b}' {
+                in_escape = !in_escape;
+                return false;
+            }
+            !in_escape
+        })
+        .collect::<String>();
+    text.trim_end_matches('\0').to_owned()
+}
+
+fn pdf_doc_char(byte: u8) -> char {
+    match byte {
+        b'\t' | b'\n' | b'\r' => byte as char,
+        0x18..=0x1F => PDF_DOC_LOW[(byte - 0x18) as usize],
+        0x80..=0xA0 => PDF_DOC_HIGH[(byte - 0x80) as usize],
+        0x00..=0x17 | 0x7F | 0xAD => char::REPLACEMENT_CHARACTER,
+        // Everything else agrees with Latin-1
+        _ => byte as char,
+    }
+}
+
+/// The PDFDocEncoding byte for a character, if it has one.
+fn pdf_doc_byte(c: char) -> Option<u8> {
+    let byte = match u8::try_from(c) {
+        Ok(byte) => Some(byte),
+        Err(_) => PDF_DOC_LOW
+            .iter()
+            .position(|&low| low == c)
+            .map(|i| 0x18 + i as u8)
+            .or_else(|| {
+                PDF_DOC_HIGH
+                    .iter()
+                    .position(|&high| high == c)
+                    .map(|i| 0x80 + i as u8)
+            }),
+    }?;
+    (pdf_doc_char(byte) == c).then_some(byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_pdf_doc_encoding() {
+        assert_eq!(decode_text_string(b"Hello"), "Hello");
+        assert_eq!(decode_text_string(b"caf\xe9 \x93 \x80 \xa0"), "café ﬁ • €");
+        assert_eq!(decode_text_string(b"\x18\x01\x9f"), "˘\u{fffd}\u{fffd}");
+    }
+
+    #[test]
+    fn should_encode_text_strings() {
+        assert_eq!(encode_text_string("café •"), b"caf\xe9 \x80");
+        assert_eq!(encode_text_string("Ā"), b"\xfe\xff\x01\x00");
+        for text in ["Hello", "ﬁ € ˘", "😀 Ā", "\u{AD}"] {
+            assert_eq!(decode_text_string(&encode_text_string(text)), text);
+        }
+    }
+
+    #[test]
+    fn should_decode_utf16() {
+        assert_eq!(decode_text_string(b"\xfe\xff\x00H\x00i"), "Hi");
+        assert_eq!(decode_text_string(b"\xff\xfeH\x00i\x00"), "Hi");
+        assert_eq!(decode_text_string(b"\xfe\xff\xd8\x3d\xde\x00"), "😀");
+        assert_eq!(decode_text_string(b"\xef\xbb\xbfcaf\xc3\xa9"), "café");
+    }
+
+    #[test]
+    fn should_decode_malformed_strings_leniently() {
+        // A lone surrogate, then an odd number of bytes
+        assert_eq!(decode_text_string(b"\xfe\xff\xd8\x00\x00A"), "\u{fffd}A");
+        assert_eq!(decode_text_string(b"\xfe\xff\x00A\x01"), "A\u{fffd}");
+        // Trailing NULs and language escapes are dropped
+        assert_eq!(decode_text_string(b"\xfe\xff\x00A\x00\x00"), "A");
+        assert_eq!(decode_text_string(b"\xfe\xff\x01\x00"), "Ā");
+        assert_eq!(decode_text_string(b"A\0\0"), "A");
+        assert_eq!(
+            decode_text_string(b"\xfe\xff\x00\x1b\x00e\x00n\x00\x1b\x00A"),
+            "A"
+        );
+        assert_eq!(decode_text_string(b""), "");
+    }
+}
@@ -0,0 +1,13 @@
+//! Profiling hooks, which are only compiled in with the `tracing` feature.
+
+/// Enters a `tracing` span at debug level for the rest of the enclosing
+/// block. The arguments are the same as for `tracing::debug_span!`, and are
+/// ignored without the `tracing` feature.
+macro_rules! trace_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($args)*).entered();
+    };
+}
+
+pub(crate) use trace_span;
@@ -0,0 +1,185 @@
+//! Stores application data in the page-piece dictionary of the catalog
+//! (Adobe, 2008, p. 726), so that it survives being read and written by other
+//! tools.
+
+use crate::error::{Error, Result};
+use crate::objects::{names, Dictionary, Name, Object};
+use crate::writing::incremental::IncrementalUpdate;
+use std::borrow::Cow;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+impl<'a> IncrementalUpdate<'a> {
+    /// Stores data under an application's key, replacing any data already
+    /// stored under it. Keys should be namespaced, such as
+    /// `com.example.editor`, so that they do not clash with other
+    /// applications.
+    pub fn set_app_data(&mut self, key: &str, data: Vec<u8>) -> Result<()> {
+        let private = self.add(Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::new())),
+            Cow::Owned(data),
+        ));
+        let mut entry = Dictionary::new();
+        entry.insert(
+            Name::from(names::LAST_MODIFIED),
+            Object::String(Cow::Owned(pdf_date(SystemTime::now()).into_bytes())),
+        );
+        entry.insert(Name::from(names::PRIVATE), Object::Indirect(private));
+
+        let key = Name(Cow::Owned(key.as_bytes().to_vec()));
+        self.update_piece_info(|piece_info| {
+            piece_info.insert(key, Object::Dictionary(entry));
+        })
+    }
+
+    /// Serializes a value as JSON and stores it under an application's key.
+    #[cfg(feature = "serde")]
+    pub fn set_app_value<T: serde::Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        let data = serde_json::to_vec(value).map_err(|err| Error::Type(err.to_string()))?;
+        self.set_app_data(key, data)
+    }
+
+    /// Removes the data stored under an application's key, returning whether
+    /// there was any.
+    pub fn remove_app_data(&mut self, key: &str) -> Result<bool> {
+        let mut removed = false;
+        self.update_piece_info(|piece_info| {
+            removed = piece_info.remove(key.as_bytes()).is_some();
+        })?;
+        Ok(removed)
+    }
+
+    /// Edits the page-piece dictionary of the catalog, creating it if needed.
+    fn update_piece_info<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Dictionary<'a>),
+    {
+        let catalog_ref = self.file().trailer()?.get_indirect(names::ROOT)?;
+        let mut catalog = self.object(catalog_ref)?;
+        let Object::Dictionary(catalog_dict) = &mut catalog else {
+            return Err(Error::Type(format!("Expected dict got {:?}", catalog)));
+        };
+
+        // An indirect dictionary is edited in place, so that anything else
+        // that refers to it sees the change
+        match catalog_dict.get(names::PIECE_INFO.as_bytes()) {
+            Some(&Object::Indirect(reference)) => {
+                let mut piece_info = self.object(reference)?;
+                let Object::Dictionary(dict) = &mut piece_info else {
+                    return Err(Error::Type(format!("Expected dict got {:?}", piece_info)));
+                };
+                f(dict);
+                self.replace(reference, piece_info);
+            }
+            other => {
+                let mut piece_info = match other {
+                    Some(Object::Dictionary(dict)) => dict.clone(),
+                    _ => Dictionary::new(),
+                };
+                f(&mut piece_info);
+                catalog_dict.insert(
+                    Name::from(names::PIECE_INFO),
+                    Object::Dictionary(piece_info),
+                );
+                self.replace(catalog_ref, catalog);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Formats a time as a date string in UTC (Adobe, 2008, p. 95).
+pub(crate) fn pdf_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+
+    // Converts days since 1970 to a date in the proleptic Gregorian calendar
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "D:{:04}{:02}{:02}{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support;
+    use std::time::Duration;
+
+    fn open(raw: Vec<u8>) -> PdfFile<'static> {
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    #[test]
+    fn should_round_trip_app_data() {
+        let file = open(test_support::hello_world(Default::default()));
+        assert_eq!(file.app_data("com.example.a"), Ok(None));
+
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        update
+            .set_app_data("com.example.a", b"\x00first".to_vec())
+            .unwrap();
+        update
+            .set_app_data("com.example.b", b"second".to_vec())
+            .unwrap();
+        let file = open(update.to_bytes().unwrap());
+        assert_eq!(
+            file.app_data("com.example.a"),
+            Ok(Some(b"\x00first".to_vec()))
+        );
+        assert_eq!(file.app_data("com.example.b"), Ok(Some(b"second".to_vec())));
+
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        assert_eq!(update.remove_app_data("com.example.a"), Ok(true));
+        assert_eq!(update.remove_app_data("com.example.c"), Ok(false));
+        let file = open(update.to_bytes().unwrap());
+        assert_eq!(file.app_data("com.example.a"), Ok(None));
+        assert_eq!(file.app_data("com.example.b"), Ok(Some(b"second".to_vec())));
+        assert_eq!(file.diagnostics(), vec![]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn should_round_trip_app_values() {
+        let file = open(test_support::hello_world(Default::default()));
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        let value = vec![("zoom".to_owned(), 1.5), ("page".to_owned(), 3.0)];
+        update.set_app_value("com.example.a", &value).unwrap();
+
+        let file = open(update.to_bytes().unwrap());
+        assert_eq!(file.app_value("com.example.a"), Ok(Some(value)));
+        assert_eq!(file.app_value::<Vec<u8>>("com.example.b"), Ok(None));
+    }
+
+    #[test]
+    fn should_format_dates() {
+        assert_eq!(pdf_date(UNIX_EPOCH), "D:19700101000000Z");
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(pdf_date(time), "D:20240229123456Z");
+    }
+}
@@ -0,0 +1,573 @@
+use crate::error::Result;
+use crate::objects::{names, Dictionary, IndirectRef, Name, Object};
+use crate::writing::id::{generate_id, id_object, FileId};
+use crate::writing::object_map::ObjectMap;
+use crate::writing::objects::{write_object, WriteOptions};
+use crate::writing::xref_stream::{
+    is_compressible, object_stream, xref_stream, XrefLocation, OBJECTS_PER_STREAM,
+};
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+/// A comment that follows the header, so that tools which guess whether a file
+/// is text or binary treat it as binary (Adobe, 2008, p. 11).
+pub const BINARY_MARKER: &[u8] = b"%\xe2\xe3\xcf\xd3\n";
+
+/// Builds a new document from objects, and writes it out with a classic
+/// cross-reference table (Adobe, 2008, p. 40-43). If
+/// `WriteOptions::object_streams` is set, objects are packed into object
+/// streams, which a cross-reference stream lists alongside the table.
+///
+/// Encryption is not written, since the reader does not support it yet.
+#[derive(Clone, Debug)]
+pub struct DocumentBuilder<'a> {
+    version: &'static str,
+    options: WriteOptions,
+    objects: BTreeMap<u32, Option<Object<'a>>>,
+    /// Objects that are written exactly as they were read, by number. These
+    /// take the place of reserved objects.
+    raw: BTreeMap<u32, (u16, Cow<'a, [u8]>)>,
+    trailer: Dictionary<'a>,
+    id: Option<FileId>,
+}
+
+impl<'a> Default for DocumentBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> DocumentBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            version: "1.7",
+            options: WriteOptions::default(),
+            objects: BTreeMap::new(),
+            raw: BTreeMap::new(),
+            trailer: Dictionary::new(),
+            id: None,
+        }
+    }
+
+    /// Sets the version that is written in the header.
+    pub fn with_version(mut self, version: &'static str) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the options that are used when writing objects.
+    pub fn with_options(mut self, options: WriteOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Uses a fixed file identifier rather than generating one, so that the
+    /// output is deterministic.
+    pub fn with_id(mut self, id: FileId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Reserves an object number, so that objects can refer to an object
+    /// before it has been built.
+    pub fn reserve(&mut self) -> IndirectRef {
+        let number = self.objects.keys().next_back().map_or(1, |n| n + 1);
+        self.objects.insert(number, None);
+        reference(number)
+    }
+
+    /// Sets the value of a reserved object.
+    pub fn insert(&mut self, reference: IndirectRef, object: Object<'a>) {
+        self.raw.remove(&reference.number);
+        self.objects.insert(reference.number, Some(object));
+    }
+
+    /// Sets an object to bytes that are written as they are, between the
+    /// `obj` and `endobj` keywords, keeping the generation number of the
+    /// reference. This is used to copy objects from another file without
+    /// re-serializing them.
+    pub fn insert_raw(&mut self, reference: IndirectRef, body: Cow<'a, [u8]>) {
+        self.objects.insert(reference.number, None);
+        self.raw
+            .insert(reference.number, (reference.generation, body));
+    }
+
+    /// Adds an object, returning a reference to it.
+    pub fn add(&mut self, object: Object<'a>) -> IndirectRef {
+        let reference = self.reserve();
+        self.insert(reference, object);
+        reference
+    }
+
+    pub fn set_root(&mut self, root: IndirectRef) {
+        self.trailer
+            .insert(Name::from(names::ROOT), Object::Indirect(root));
+    }
+
+    pub fn set_info(&mut self, info: IndirectRef) {
+        self.trailer
+            .insert(Name::from(names::INFO), Object::Indirect(info));
+    }
+
+    /// Sets any other entry in the trailer. `/Size` is always overwritten
+    /// when the document is written, and `/ID` is generated unless it is set
+    /// here.
+    pub fn set_trailer_entry(&mut self, key: &'a str, value: Object<'a>) {
+        self.trailer.insert(Name::from(key), value);
+    }
+
+    /// Shares a single copy of streams and font dictionaries that are
+    /// identical, such as the fonts and images of documents that were merged
+    /// from the same template. References to the removed copies are rewritten,
+    /// and the number of objects that were removed is returned.
+    ///
+    /// Nothing is shared if there are raw objects, since the references
+    /// inside them cannot be rewritten.
+    pub fn deduplicate(&mut self) -> Result<usize> {
+        let mut removed = 0;
+        if !self.raw.is_empty() {
+            return Ok(removed);
+        }
+
+        // Objects only become identical once the objects that they refer to
+        // have been shared, so repeat until nothing changes
+        loop {
+            let mut canonical = HashMap::new();
+            let mut map = ObjectMap::new();
+            for (&number, object) in &self.objects {
+                let Some(object) = object.as_ref().filter(|o| is_shareable(o)) else {
+                    continue;
+                };
+
+                let mut key = Vec::new();
+                write_object(&mut key, object, &WriteOptions::default())?;
+                match canonical.entry(key) {
+                    Entry::Occupied(entry) => {
+                        map.insert(reference(number), reference(*entry.get()))
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(number);
+                    }
+                }
+            }
+            if map.is_empty() {
+                return Ok(removed);
+            }
+
+            for (old, _) in map.iter() {
+                self.objects.remove(&old.number);
+            }
+            for object in self.objects.values_mut().flatten() {
+                *object = map.remap(object);
+            }
+            for value in self.trailer.values_mut() {
+                *value = map.remap(value);
+            }
+            removed += map.len();
+        }
+    }
+
+    /// Writes the document. Objects that were reserved but never inserted are
+    /// written as free entries in the xref table.
+    pub fn write<W: Write>(&self, mut out: W) -> Result<()> {
+        let bytes = self.to_bytes()?;
+        out.write_all(&bytes)?;
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        out.extend_from_slice(format!("%PDF-{}\n", self.header_version()).as_bytes());
+        if self.options.ascii_armor.is_none() {
+            out.extend_from_slice(BINARY_MARKER);
+        }
+
+        let mut locations = BTreeMap::new();
+        let mut packed = Vec::new();
+        for (&number, object) in &self.objects {
+            if let Some((generation, body)) = self.raw.get(&number) {
+                locations.insert(number, XrefLocation::Offset(*generation, out.len()));
+                out.extend_from_slice(format!("{} {} obj", number, generation).as_bytes());
+                out.extend_from_slice(body);
+                out.extend_from_slice(b"endobj\n");
+                continue;
+            }
+            let Some(object) = object else { continue };
+            if self.options.object_streams && self.is_packable(number, object) {
+                packed.push((number, object));
+                continue;
+            }
+
+            locations.insert(number, XrefLocation::Offset(0, out.len()));
+            write_indirect(&mut out, number, object, &self.options)?;
+        }
+
+        let mut size = self.objects.keys().next_back().map_or(1, |n| n + 1);
+        for chunk in packed.chunks(OBJECTS_PER_STREAM) {
+            let stream = object_stream(chunk, &self.options)?;
+            for (index, &(number, _)) in chunk.iter().enumerate() {
+                locations.insert(number, XrefLocation::Compressed(size, index));
+            }
+            locations.insert(size, XrefLocation::Offset(0, out.len()));
+            write_indirect(&mut out, size, &stream, &self.options)?;
+            size += 1;
+        }
+
+        if !self.options.object_streams {
+            let xref_offset = out.len();
+            write_xref_table(&mut out, &locations, size);
+            let trailer = self.trailer(size, out.len());
+            out.extend_from_slice(b"trailer\n");
+            write_object(&mut out, &Object::Dictionary(trailer), &self.options)?;
+            out.extend_from_slice(format!("\nstartxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+            return Ok(out);
+        }
+
+        // The xref stream lists itself, so takes the last object number. It
+        // is followed by a table that lists the objects outside of object
+        // streams, and points to the stream with `/XRefStm`, so that readers
+        // without support for streams can still find what they can read
+        let stream_offset = out.len();
+        locations.insert(size, XrefLocation::Offset(0, stream_offset));
+        let mut trailer = self.trailer(size + 1, stream_offset);
+        let stream = xref_stream(&locations, size + 1, &trailer);
+        write_indirect(&mut out, size, &stream, &self.options)?;
+
+        let xref_offset = out.len();
+        write_xref_table(&mut out, &locations, size + 1);
+        trailer.insert(
+            Name::from(names::X_REF_STM),
+            Object::Integer(stream_offset as i64),
+        );
+        out.extend_from_slice(b"trailer\n");
+        write_object(&mut out, &Object::Dictionary(trailer), &self.options)?;
+        out.extend_from_slice(format!("\nstartxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+        Ok(out)
+    }
+
+    /// Whether an object is put in an object stream. The catalog, the page
+    /// tree and the encryption dictionary are always written on their own,
+    /// so that readers which only understand the classic xref table can
+    /// still find the pages of a hybrid-reference file (Adobe, 2008, p. 67).
+    fn is_packable(&self, number: u32, object: &Object) -> bool {
+        let in_trailer = [names::ROOT, names::ENCRYPT].iter().any(|key| {
+            matches!(
+                self.trailer.get(key.as_bytes()),
+                Some(Object::Indirect(r)) if r.number == number
+            )
+        });
+        let in_page_tree = object
+            .get_name(names::TYPE)
+            .is_ok_and(|t| t.matches(names::PAGES) || t.matches(names::PAGE));
+        is_compressible(object) && !in_trailer && !in_page_tree
+    }
+
+    /// The version that is written in the header, which is at least 1.5
+    /// when there are object streams (Adobe, 2008, p. 45).
+    fn header_version(&self) -> &'static str {
+        let parse = |version: &str| {
+            let (major, minor) = version.split_once('.')?;
+            Some((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?))
+        };
+        match parse(self.version) {
+            Some(version) if self.options.object_streams && version < (1, 5) => "1.5",
+            _ => self.version,
+        }
+    }
+
+    /// The trailer dictionary, with `/Size` set and an `/ID` generated from
+    /// the length of the file so far if one is not set.
+    fn trailer(&self, size: u32, len: usize) -> Dictionary<'a> {
+        let id = self.id.unwrap_or_else(|| {
+            let info = match self.trailer.get(names::INFO.as_bytes()) {
+                Some(Object::Indirect(info)) => self.objects.get(&info.number),
+                _ => None,
+            };
+            let info = match info {
+                Some(Some(Object::Dictionary(info))) => Some(info),
+                _ => None,
+            };
+            generate_id(None, len, info)
+        });
+
+        let mut trailer = self.trailer.clone();
+        trailer.insert(Name::from(names::SIZE), Object::Integer(size as i64));
+        if !trailer.contains_key(names::ID.as_bytes()) {
+            trailer.insert(Name::from(names::ID), id_object(&id, &id));
+        }
+        trailer
+    }
+}
+
+fn write_indirect(
+    out: &mut Vec<u8>,
+    number: u32,
+    object: &Object,
+    options: &WriteOptions,
+) -> Result<()> {
+    out.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+    write_object(out, object, options)?;
+    out.extend_from_slice(b"\nendobj\n");
+    Ok(())
+}
+
+/// Writes a classic xref table, in which objects inside of object streams
+/// cannot be listed, so are listed as free.
+fn write_xref_table(out: &mut Vec<u8>, locations: &BTreeMap<u32, XrefLocation>, size: u32) {
+    out.extend_from_slice(format!("xref\n0 {}\n", size).as_bytes());
+    out.extend_from_slice(b"0000000000 65535 f \n");
+    for number in 1..size {
+        match locations.get(&number) {
+            Some(XrefLocation::Offset(generation, offset)) => {
+                out.extend_from_slice(format!("{:010} {:05} n \n", offset, generation).as_bytes())
+            }
+            _ => out.extend_from_slice(b"0000000000 00000 f \n"),
+        }
+    }
+}
+
+fn reference(number: u32) -> IndirectRef {
+    IndirectRef {
+        number,
+        generation: 0,
+    }
+}
+
+fn is_shareable(object: &Object) -> bool {
+    match object {
+        Object::Stream(_, _) => true,
+        Object::Dictionary(dict) => matches!(
+            dict.get(names::TYPE.as_bytes()),
+            Some(Object::Name(t)) if t.matches(names::FONT) || t.matches(names::FONT_DESCRIPTOR)
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    use crate::parsing::context::ParseOptions;
+    use crate::parsing::pdf_file::PdfFile;
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    use crate::parsing::pdf_file::XrefEntry;
+    use crate::test_support;
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    use crate::writing::objects::AsciiArmor;
+
+    #[test]
+    fn should_write_readable_documents() {
+        let mut file = PdfFile::from_raw(test_support::hello_world(WriteOptions::default()));
+        assert_eq!(file.version().unwrap(), "1.7");
+        assert_eq!(&file.raw()[9..15], BINARY_MARKER);
+        file.load_xref_table().unwrap();
+
+        let trailer = file.trailer().unwrap();
+        assert_eq!(trailer.get_int(names::SIZE).unwrap(), 6);
+        let root = file.resolve(&trailer[b"Root"]).unwrap();
+        assert!(root.get_name(names::TYPE).unwrap().matches(names::CATALOG));
+        let pages = file.resolve(&root[b"Pages"]).unwrap();
+        let page = file
+            .resolve(&pages.get_array(names::KIDS).unwrap()[0])
+            .unwrap();
+        let contents = file.resolve(&page[b"Contents"]).unwrap();
+        let (_, data) = contents.as_stream().unwrap();
+        assert_eq!(data.as_ref(), test_support::HELLO_WORLD_CONTENT);
+        assert_eq!(file.diagnostics(), vec![]);
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_write_compressed_streams() {
+        let options = WriteOptions {
+            compress_streams: true,
+            ..Default::default()
+        };
+        let raw = test_support::hello_world(options);
+        assert!(raw.windows(12).any(|w| w == b"/FlateDecode"));
+
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        let contents = file
+            .resolve(&Object::Indirect(IndirectRef {
+                number: 4,
+                generation: 0,
+            }))
+            .unwrap();
+        let (_, data) = contents.as_stream().unwrap();
+        assert_eq!(data.as_ref(), test_support::HELLO_WORLD_CONTENT);
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_write_ascii_armored_files() {
+        for armor in [AsciiArmor::Hex, AsciiArmor::Ascii85] {
+            let options = WriteOptions {
+                compress_streams: true,
+                ascii_armor: Some(armor),
+                ..Default::default()
+            };
+            let raw = test_support::hello_world(options);
+            assert!(raw.is_ascii());
+            assert!(raw.windows(12).any(|w| w == b"/FlateDecode"));
+
+            let mut file = PdfFile::from_raw(raw);
+            file.load_xref_table().unwrap();
+            let pages = file.pages().unwrap();
+            assert_eq!(
+                pages[0].content_bytes().unwrap(),
+                test_support::HELLO_WORLD_CONTENT
+            );
+            let trailer = file.trailer().unwrap();
+            let id = trailer.get_array(names::ID).unwrap();
+            assert_eq!(id[0].as_string().unwrap().len(), 16);
+            assert_eq!(file.diagnostics(), vec![]);
+        }
+
+        // Text streams are left as they are
+        let options = WriteOptions {
+            ascii_armor: Some(AsciiArmor::Hex),
+            ..Default::default()
+        };
+        let raw = test_support::hello_world(options);
+        assert!(!raw.windows(15).any(|w| w == b"/ASCIIHexDecode"));
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_write_object_streams() {
+        let options = WriteOptions {
+            compress_streams: true,
+            object_streams: true,
+            ..Default::default()
+        };
+        let mut builder = DocumentBuilder::new()
+            .with_version("1.4")
+            .with_options(options);
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let raw = builder.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_raw(raw.clone()).with_options(ParseOptions::strict());
+        assert_eq!(file.version().unwrap(), "1.5");
+        file.load_xref_table().unwrap();
+        // The five objects are followed by the object stream and the xref
+        // stream. Streams, the catalog and the page tree are written on
+        // their own, which leaves only the font to be packed
+        let xref_table = file.xref_table().unwrap();
+        assert_eq!(xref_table.len(), 8);
+        for number in [1, 2, 4, 5] {
+            assert!(matches!(
+                xref_table[&IndirectRef::new(number, 0)],
+                XrefEntry::InUse(_)
+            ));
+        }
+        assert_eq!(
+            xref_table[&IndirectRef::new(3, 0)],
+            XrefEntry::Compressed {
+                stream: 6,
+                index: 0
+            }
+        );
+        let object_stream = file.object(IndirectRef::new(6, 0)).unwrap();
+        assert!(object_stream
+            .get_name(names::TYPE)
+            .unwrap()
+            .matches(names::OBJ_STM));
+        let xref_stream = file.object(IndirectRef::new(7, 0)).unwrap();
+        assert!(xref_stream
+            .get_name(names::TYPE)
+            .unwrap()
+            .matches(names::X_REF));
+
+        let trailer = file.trailer().unwrap();
+        assert_eq!(trailer.get_int(names::SIZE).unwrap(), 8);
+        assert!(trailer.get(names::X_REF_STM).is_some());
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].extract_text().unwrap(), "Hello, world!");
+        assert_eq!(file.diagnostics(), vec![]);
+
+        // A reader that only understands the classic table can still find
+        // the pages, though not the packed font
+        let start = raw.windows(8).rposition(|w| w == b"/XRefStm").unwrap();
+        let mut classic = raw;
+        classic[start..start + 8].copy_from_slice(b"/Ignored");
+        let mut file = PdfFile::from_raw(classic);
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(
+            pages[0].content_bytes().unwrap(),
+            test_support::HELLO_WORLD_CONTENT
+        );
+        assert_eq!(file.diagnostics(), vec![]);
+
+        // Later versions are kept
+        let builder = DocumentBuilder::new()
+            .with_version("2.0")
+            .with_options(options);
+        assert!(builder.to_bytes().unwrap().starts_with(b"%PDF-2.0\n"));
+    }
+
+    #[test]
+    fn should_write_file_ids() {
+        let builder = || {
+            let mut builder = DocumentBuilder::new();
+            test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+            builder
+        };
+
+        let first = builder().to_bytes().unwrap();
+        let second = builder().to_bytes().unwrap();
+        assert_ne!(first, second);
+
+        let mut file = PdfFile::from_raw(first);
+        file.load_xref_table().unwrap();
+        let trailer = file.trailer().unwrap();
+        let id = trailer.get_array(names::ID).unwrap();
+        assert_eq!(id.len(), 2);
+        assert_eq!(id[0], id[1]);
+        assert_eq!(id[0].as_string().unwrap().len(), 16);
+
+        let fixed = || builder().with_id([7; 16]).to_bytes().unwrap();
+        assert_eq!(fixed(), fixed());
+    }
+
+    #[test]
+    fn should_write_free_entries_for_unused_numbers() {
+        let mut builder = DocumentBuilder::new();
+        builder.reserve();
+        let root = builder.add(Object::Dictionary(Dictionary::new()));
+        builder.set_root(root);
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        assert!(file
+            .indirect_object_offset(IndirectRef {
+                number: 1,
+                generation: 0
+            })
+            .is_err());
+        assert!(file.indirect_object_offset(root).is_ok());
+    }
+
+    #[test]
+    fn should_generate_multi_page_documents() {
+        let mut file = PdfFile::from_raw(test_support::multi_page(3));
+        file.load_xref_table().unwrap();
+        let trailer = file.trailer().unwrap();
+        let root = file.resolve(&trailer[b"Root"]).unwrap();
+        let pages = file.resolve(&root[b"Pages"]).unwrap();
+        assert_eq!(pages.get_int(names::COUNT).unwrap(), 3);
+        for kid in &pages[b"Kids"] {
+            let page = file.resolve(kid).unwrap();
+            file.resolve(&page[b"Contents"])
+                .unwrap()
+                .as_stream()
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! File identifiers, which are written in the `/ID` entry of the trailer
+//! (Adobe, 2008, p. 43 and 715).
+
+use crate::objects::{sorted_entries, Dictionary, Object};
+use crate::writing::objects::{write_object, WriteOptions};
+use std::borrow::Cow;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub type FileId = [u8; 16];
+
+/// Computes a new identifier from the current time, the file's location and
+/// size, and the values in its document information dictionary.
+pub fn generate_id(path: Option<&Path>, size: usize, info: Option<&Dictionary>) -> FileId {
+    let mut context = md5::Context::new();
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    context.consume(time.as_nanos().to_be_bytes());
+    if let Some(path) = path {
+        context.consume(path.to_string_lossy().as_bytes());
+    }
+    context.consume(size.to_be_bytes());
+
+    if let Some(info) = info {
+        for (key, value) in sorted_entries(info) {
+            let mut bytes = key.to_vec();
+            // Values that cannot be written are simply left out
+            if write_object(&mut bytes, value, &WriteOptions::default()).is_ok() {
+                context.consume(&bytes);
+            }
+        }
+    }
+
+    context.compute().0
+}
+
+/// Builds the value of the `/ID` entry. The first identifier stays the same
+/// for the life of the file, while the second changes with each update.
+pub fn id_object(first: &[u8], second: &FileId) -> Object<'static> {
+    Object::Array(vec![
+        Object::String(Cow::Owned(first.to_vec())),
+        Object::String(Cow::Owned(second.to_vec())),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Name;
+
+    #[test]
+    fn should_generate_different_ids() {
+        let mut info = Dictionary::new();
+        info.insert(
+            Name::borrowed(b"Title"),
+            Object::String(Cow::Borrowed(b"Report")),
+        );
+
+        let first = generate_id(Some(Path::new("report.pdf")), 100, Some(&info));
+        let second = generate_id(Some(Path::new("report.pdf")), 100, Some(&info));
+        assert_ne!(first, second);
+        assert_ne!(first, [0; 16]);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::content::edit::ContentStream;
+use crate::content::operators::Operator;
+use crate::error::{Error, Result};
+use crate::geometry::{Matrix, Rect};
+use crate::objects::{names, Dictionary, Name, Object};
+use crate::parsing::pdf_file::PdfFile;
+use crate::writing::document::DocumentBuilder;
+use crate::writing::object_map::ObjectMap;
+use std::borrow::Cow;
+
+/// How pages are arranged on the sheets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PageOrder {
+    /// In reading order, filling each row from left to right.
+    #[default]
+    Sequential,
+    /// For a booklet that is folded down the middle. Each pair of sheets is
+    /// the front and back of one piece of paper, and the pages are padded
+    /// with blanks to a multiple of four. Only works with two pages to a
+    /// sheet.
+    Booklet,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImposeOptions {
+    pub columns: usize,
+    pub rows: usize,
+    /// The size of the output pages. By default this is the size of the first
+    /// page, turned to whichever orientation fits the pages at a larger
+    /// scale.
+    pub sheet: Option<Rect>,
+    pub order: PageOrder,
+}
+
+impl ImposeOptions {
+    pub fn two_up() -> Self {
+        Self {
+            columns: 2,
+            rows: 1,
+            sheet: None,
+            order: PageOrder::Sequential,
+        }
+    }
+
+    pub fn four_up() -> Self {
+        Self {
+            columns: 2,
+            rows: 2,
+            ..Self::two_up()
+        }
+    }
+
+    pub fn booklet() -> Self {
+        Self {
+            order: PageOrder::Booklet,
+            ..Self::two_up()
+        }
+    }
+}
+
+/// Places several pages of a file on each page of a new document. The xref
+/// table of the file must already be loaded.
+///
+/// Each page is copied once as a form XObject (Adobe, 2008, p. 217), which is
+/// scaled to fit its cell and centred in it. Pages are drawn using their crop
+/// box, and `/Rotate` is ignored. Annotations are not copied.
+pub fn impose<'a>(file: &'a PdfFile<'a>, options: &ImposeOptions) -> Result<DocumentBuilder<'a>> {
+    let cells = options.columns * options.rows;
+    if cells == 0 {
+        return Err(Error::Type("Cannot impose onto an empty grid".into()));
+    }
+    if options.order == PageOrder::Booklet && cells != 2 {
+        return Err(Error::Type(format!(
+            "Booklets need two pages to a sheet, not {}",
+            cells
+        )));
+    }
+
+    let pages = file.pages()?;
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    let tree = builder.reserve();
+
+    let mut map = ObjectMap::new();
+    let mut forms = Vec::new();
+    for page in &pages {
+        let bbox = page.crop_box()?;
+        let resources = match page.used_resources() {
+            Ok(resources) => resources,
+            Err(_) => page.resources()?,
+        };
+        let resources = map.copy(file, &mut builder, &resources)?;
+        let mut dict = Dictionary::new();
+        dict.insert(
+            Name::from(names::TYPE),
+            Object::Name(Name::from(names::X_OBJECT)),
+        );
+        dict.insert(
+            Name::from(names::SUBTYPE),
+            Object::Name(Name::from(names::FORM)),
+        );
+        dict.insert(Name::from(names::BBOX), bbox.to_object());
+        dict.insert(Name::from(names::RESOURCES), resources);
+        let form = builder.add(Object::Stream(
+            Box::new(Object::Dictionary(dict)),
+            Cow::Owned(page.content_bytes()?),
+        ));
+        forms.push((form, bbox));
+    }
+
+    let sheet = match (options.sheet, forms.first()) {
+        (Some(sheet), _) => sheet,
+        (None, Some(&(_, bbox))) => default_sheet(bbox, options),
+        (None, None) => Rect::new(0.0, 0.0, 612.0, 792.0),
+    };
+    let cell_width = sheet.width() / options.columns as f64;
+    let cell_height = sheet.height() / options.rows as f64;
+
+    let mut kids = Vec::new();
+    for slots in arrange(forms.len(), cells, options.order) {
+        let mut content = Vec::new();
+        let mut x_objects = Dictionary::new();
+        for (i, slot) in slots.into_iter().enumerate() {
+            let Some(index) = slot else { continue };
+            let (form, bbox) = forms[index];
+            let column = (i % options.columns) as f64;
+            let row = (i / options.columns) as f64;
+            let cell = Rect::new(
+                sheet.x0 + column * cell_width,
+                sheet.y1 - (row + 1.0) * cell_height,
+                sheet.x0 + (column + 1.0) * cell_width,
+                sheet.y1 - row * cell_height,
+            );
+
+            let name = Name(Cow::Owned(format!("P{}", i).into_bytes()));
+            content.extend([
+                Operator::new(b"q", vec![]),
+                Operator::new(b"cm", fit(bbox, cell).to_objects()),
+                Operator::new(b"Do", vec![Object::Name(name.clone())]),
+                Operator::new(b"Q", vec![]),
+            ]);
+            x_objects.insert(name, Object::Indirect(form));
+        }
+
+        let mut resources = Dictionary::new();
+        resources.insert(Name::from(names::X_OBJECT), Object::Dictionary(x_objects));
+        let content = builder.add(Object::Stream(
+            Box::new(Object::Dictionary(Dictionary::new())),
+            Cow::Owned(ContentStream::new(content).to_bytes()?),
+        ));
+
+        let mut page = Dictionary::new();
+        page.insert(
+            Name::from(names::TYPE),
+            Object::Name(Name::from(names::PAGE)),
+        );
+        page.insert(Name::from(names::PARENT), Object::Indirect(tree));
+        page.insert(Name::from(names::MEDIA_BOX), sheet.to_object());
+        page.insert(Name::from(names::RESOURCES), Object::Dictionary(resources));
+        page.insert(Name::from(names::CONTENTS), Object::Indirect(content));
+        kids.push(Object::Indirect(builder.add(Object::Dictionary(page))));
+    }
+
+    let mut pages = Dictionary::new();
+    pages.insert(
+        Name::from(names::TYPE),
+        Object::Name(Name::from(names::PAGES)),
+    );
+    pages.insert(Name::from(names::COUNT), Object::Integer(kids.len() as i64));
+    pages.insert(Name::from(names::KIDS), Object::Array(kids));
+    builder.insert(tree, Object::Dictionary(pages));
+
+    let mut root = Dictionary::new();
+    root.insert(
+        Name::from(names::TYPE),
+        Object::Name(Name::from(names::CATALOG)),
+    );
+    root.insert(Name::from(names::PAGES), Object::Indirect(tree));
+    builder.insert(catalog, Object::Dictionary(root));
+    builder.set_root(catalog);
+
+    Ok(builder)
+}
+
+/// Chooses the orientation of the page that fits a grid of pages at the
+/// larger scale.
+fn default_sheet(page: Rect, options: &ImposeOptions) -> Rect {
+    let scale = |width: f64, height: f64| {
+        let cell_width = width / options.columns as f64;
+        let cell_height = height / options.rows as f64;
+        (cell_width / page.width()).min(cell_height / page.height())
+    };
+    let (width, height) = (page.width(), page.height());
+    if scale(height, width) > scale(width, height) {
+        Rect::new(0.0, 0.0, height, width)
+    } else {
+        Rect::new(0.0, 0.0, width, height)
+    }
+}
+
+/// Returns which page goes in each cell of each sheet, where `None` is left
+/// blank.
+fn arrange(pages: usize, cells: usize, order: PageOrder) -> Vec<Vec<Option<usize>>> {
+    match order {
+        PageOrder::Sequential => (0..pages)
+            .collect::<Vec<_>>()
+            .chunks(cells)
+            .map(|chunk| chunk.iter().copied().map(Some).collect())
+            .collect(),
+        PageOrder::Booklet => {
+            let padded = pages.div_ceil(4) * 4;
+            let page = |i: usize| Some(i).filter(|&i| i < pages);
+            let mut sheets = Vec::new();
+            for k in 0..padded / 4 {
+                sheets.push(vec![page(padded - 1 - 2 * k), page(2 * k)]);
+                sheets.push(vec![page(2 * k + 1), page(padded - 2 - 2 * k)]);
+            }
+            sheets
+        }
+    }
+}
+
+/// The transformation that scales a box to fit in a cell, centred.
+fn fit(bbox: Rect, cell: Rect) -> Matrix {
+    let scale = (cell.width() / bbox.width()).min(cell.height() / bbox.height());
+    let x = cell.x0 + (cell.width() - bbox.width() * scale) / 2.0 - bbox.x0 * scale;
+    let y = cell.y0 + (cell.height() - bbox.height() * scale) / 2.0 - bbox.y0 * scale;
+    Matrix::new(scale, 0.0, 0.0, scale, x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn open(raw: Vec<u8>) -> PdfFile<'static> {
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    fn page_texts(file: &PdfFile) -> Vec<Vec<String>> {
+        file.pages()
+            .unwrap()
+            .iter()
+            .map(|page| {
+                page.text_fragments()
+                    .unwrap()
+                    .iter()
+                    .map(|fragment| fragment.text())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_put_two_pages_on_each_sheet() {
+        let file = open(test_support::multi_page(3));
+        let builder = impose(&file, &ImposeOptions::two_up()).unwrap();
+        let imposed = open(builder.to_bytes().unwrap());
+
+        let pages = imposed.pages().unwrap();
+        assert_eq!(
+            pages[0].media_box().unwrap(),
+            Rect::new(0.0, 0.0, 792.0, 612.0)
+        );
+        assert_eq!(
+            page_texts(&imposed),
+            [vec!["Page 1", "Page 2"], vec!["Page 3"]]
+        );
+
+        // The second page is scaled to fit the right half of the sheet
+        let fragments = pages[0].text_fragments().unwrap();
+        let left = fragments[0].bbox().unwrap();
+        let right = fragments[1].bbox().unwrap();
+        assert!(left.x1 < 396.0 && right.x0 > 396.0);
+        assert!((right.height() - left.height()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_arrange_booklets() {
+        assert_eq!(
+            arrange(5, 2, PageOrder::Booklet),
+            [
+                vec![None, Some(0)],
+                vec![Some(1), None],
+                vec![None, Some(2)],
+                vec![Some(3), Some(4)],
+            ]
+        );
+        assert_eq!(
+            arrange(5, 4, PageOrder::Sequential),
+            [vec![Some(0), Some(1), Some(2), Some(3)], vec![Some(4)]]
+        );
+
+        let file = open(test_support::multi_page(4));
+        let builder = impose(&file, &ImposeOptions::booklet()).unwrap();
+        let imposed = open(builder.to_bytes().unwrap());
+        assert_eq!(
+            page_texts(&imposed),
+            [vec!["Page 4", "Page 1"], vec!["Page 2", "Page 3"]]
+        );
+        assert!(impose(
+            &file,
+            &ImposeOptions {
+                order: PageOrder::Booklet,
+                ..ImposeOptions::four_up()
+            }
+        )
+        .is_err());
+    }
+}
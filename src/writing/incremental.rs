@@ -0,0 +1,319 @@
+use crate::error::{Error, Result};
+use crate::objects::{names, Dictionary, IndirectRef, Name, Object};
+use crate::parsing::pdf_file::{PdfFile, XrefEntry};
+use crate::writing::id::{generate_id, id_object, FileId};
+use crate::writing::objects::{write_object, WriteOptions};
+use crate::writing::xref_stream::{xref_stream, XrefLocation};
+use std::collections::{BTreeMap, HashMap};
+
+/// Changes to a file that are appended after its original contents, leaving
+/// the original bytes untouched (Adobe, 2008, p. 48).
+///
+/// Since the original bytes are kept, this is how signed files should be
+/// changed, as their signatures stay valid.
+pub struct IncrementalUpdate<'a> {
+    file: &'a PdfFile<'a>,
+    options: WriteOptions,
+    objects: HashMap<IndirectRef, Object<'a>>,
+    next_number: u32,
+    id: Option<FileId>,
+}
+
+impl<'a> IncrementalUpdate<'a> {
+    /// Starts an update to a file, whose xref table must already be loaded.
+    pub fn new(file: &'a PdfFile<'a>) -> Result<Self> {
+        let trailer = file.trailer()?;
+        let size = trailer.get_int(names::SIZE)?;
+        let max_number = file
+            .xref_table()?
+            .keys()
+            .map(|reference| reference.number as i64 + 1)
+            .max()
+            .unwrap_or(0);
+
+        Ok(Self {
+            file,
+            options: WriteOptions::default(),
+            objects: HashMap::new(),
+            next_number: u32::try_from(size.max(max_number).max(1))
+                .map_err(|_| Error::Syntax("Invalid trailer size", size.to_string()))?,
+            id: None,
+        })
+    }
+
+    pub fn with_options(mut self, options: WriteOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Uses a fixed identifier for this update rather than generating one.
+    /// The first identifier of the file is always kept.
+    pub fn with_id(mut self, id: FileId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn file(&self) -> &'a PdfFile<'a> {
+        self.file
+    }
+
+    /// Adds a new object, returning a reference to it.
+    pub fn add(&mut self, object: Object<'a>) -> IndirectRef {
+        let reference = IndirectRef {
+            number: self.next_number,
+            generation: 0,
+        };
+        self.next_number += 1;
+        self.objects.insert(reference, object);
+        reference
+    }
+
+    /// Replaces an existing object with a new value.
+    pub fn replace(&mut self, reference: IndirectRef, object: Object<'a>) {
+        self.objects.insert(reference, object);
+    }
+
+    /// Returns the latest value of an object, including changes made in this
+    /// update.
+    pub fn object(&self, reference: IndirectRef) -> Result<Object<'a>> {
+        match self.objects.get(&reference) {
+            Some(object) => Ok(object.clone()),
+            None => self.file.object(reference),
+        }
+    }
+
+    /// Writes the original file followed by the update.
+    ///
+    /// The new xref section lists every object, not just the changed ones,
+    /// so that readers which ignore `/Prev` can still find all of them. The
+    /// xref table cannot list objects in object streams, so if any are left
+    /// from a hybrid-reference file, they are listed as free in the table and
+    /// in a new cross-reference stream as well, which `/XRefStm` points to
+    /// (Adobe, 2008, p. 67).
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let original = self.file.raw();
+        let previous_xref = self.file.last_xref_offset()?;
+        let mut entries = self
+            .file
+            .xref_table()?
+            .iter()
+            .map(|(&reference, &entry)| (reference.number, (reference.generation, entry)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut out = original.to_vec();
+        if !out.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+        let mut objects = self.objects.iter().collect::<Vec<_>>();
+        objects.sort_by_key(|&(reference, _)| reference);
+        for (&reference, object) in objects {
+            entries.insert(
+                reference.number,
+                (reference.generation, XrefEntry::InUse(out.len())),
+            );
+            out.extend_from_slice(
+                format!("{} {} obj\n", reference.number, reference.generation).as_bytes(),
+            );
+            write_object(&mut out, object, &self.options)?;
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let hybrid = entries
+            .values()
+            .any(|(_, entry)| matches!(entry, XrefEntry::Compressed { .. }));
+        let mut size = self.next_number;
+        let xref_stream_offset = if hybrid {
+            let offset = out.len();
+            entries.insert(size, (0, XrefEntry::InUse(offset)));
+            let locations = entries
+                .iter()
+                .filter_map(|(&number, &(generation, entry))| {
+                    let location = match entry {
+                        XrefEntry::Free => return None,
+                        XrefEntry::InUse(offset) => XrefLocation::Offset(generation, offset),
+                        XrefEntry::Compressed { stream, index } => {
+                            XrefLocation::Compressed(stream, index as usize)
+                        }
+                    };
+                    Some((number, location))
+                })
+                .collect();
+            let stream = xref_stream(&locations, size + 1, &Dictionary::new());
+            out.extend_from_slice(format!("{} 0 obj\n", size).as_bytes());
+            write_object(&mut out, &stream, &self.options)?;
+            out.extend_from_slice(b"\nendobj\n");
+            size += 1;
+            Some(offset)
+        } else {
+            None
+        };
+
+        let xref_offset = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n", size).as_bytes());
+        out.extend_from_slice(b"0000000000 65535 f \n");
+        for number in 1..size {
+            match entries.get(&number) {
+                Some(&(generation, XrefEntry::InUse(offset))) => out
+                    .extend_from_slice(format!("{:010} {:05} n \n", offset, generation).as_bytes()),
+                Some(&(generation, _)) => {
+                    out.extend_from_slice(format!("0000000000 {:05} f \n", generation).as_bytes())
+                }
+                None => out.extend_from_slice(b"0000000000 00000 f \n"),
+            }
+        }
+
+        let mut trailer = match self.file.trailer()? {
+            Object::Dictionary(trailer) => trailer,
+            other => return Err(Error::Type(format!("Expected dict got {:?}", other))),
+        };
+        trailer.insert(Name::from(names::SIZE), Object::Integer(size as i64));
+        trailer.insert(
+            Name::from(names::PREV),
+            Object::Integer(previous_xref as i64),
+        );
+        // The previous section's cross-reference stream is found through
+        // `/Prev`, so must not be mistaken for one belonging to this section
+        trailer.remove(names::X_REF_STM.as_bytes());
+        if let Some(offset) = xref_stream_offset {
+            trailer.insert(Name::from(names::X_REF_STM), Object::Integer(offset as i64));
+        }
+
+        let info = match trailer.get(names::INFO.as_bytes()) {
+            Some(&Object::Indirect(info)) => Some(self.object(info)?),
+            _ => None,
+        };
+        let id = self.id.unwrap_or_else(|| {
+            let info = info.as_ref().and_then(|info| info.as_dict().ok());
+            generate_id(None, out.len(), info)
+        });
+        let first_id = match trailer.get(names::ID.as_bytes()) {
+            Some(Object::Array(ids)) => match ids.first() {
+                Some(Object::String(first)) => first.to_vec(),
+                _ => id.to_vec(),
+            },
+            _ => id.to_vec(),
+        };
+        trailer.insert(Name::from(names::ID), id_object(&first_id, &id));
+        out.extend_from_slice(b"trailer\n");
+        write_object(&mut out, &Object::Dictionary(trailer), &self.options)?;
+        out.extend_from_slice(format!("\nstartxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::pdf_file::{find_startxref, parse_xref_section};
+    use crate::test_support;
+    use crate::writing::document::DocumentBuilder;
+
+    #[test]
+    fn should_append_changes() {
+        let raw = test_support::hello_world(WriteOptions::default());
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        let info = update.add(test_support::dict(&[(
+            "Title",
+            Object::String(b"Updated".as_slice().into()),
+        )]));
+        assert_eq!(info.number, 6);
+        let catalog = IndirectRef {
+            number: 1,
+            generation: 0,
+        };
+        let mut root = update.object(catalog).unwrap();
+        if let Object::Dictionary(dict) = &mut root {
+            dict.insert(
+                Name::borrowed(b"Lang"),
+                Object::String(b"en".as_slice().into()),
+            );
+        }
+        update.replace(catalog, root);
+        let updated = update.to_bytes().unwrap();
+        assert!(updated.starts_with(&raw));
+
+        let mut file = PdfFile::from_raw(updated);
+        file.load_xref_table().unwrap();
+        let trailer = file.trailer().unwrap();
+        assert_eq!(trailer.get_int(names::SIZE), Ok(7));
+        assert!(trailer.get_int(names::PREV).is_ok());
+
+        let root = file.object(catalog).unwrap();
+        assert_eq!(root.get_string("Lang").unwrap().as_ref(), b"en");
+        assert_eq!(file.pages().unwrap().len(), 1);
+        assert!(file.object(info).is_ok());
+    }
+
+    #[test]
+    fn should_keep_hybrid_files_consistent() {
+        let raw = test_support::object_streams();
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+
+        // The catalog is written after the original, while the font stays in
+        // the object stream
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        let catalog = IndirectRef::new(1, 0);
+        let mut root = update.object(catalog).unwrap();
+        if let Object::Dictionary(dict) = &mut root {
+            dict.insert(
+                Name::borrowed(b"Lang"),
+                Object::String(b"en".as_slice().into()),
+            );
+        }
+        update.replace(catalog, root);
+        let info = update.add(test_support::dict(&[]));
+        assert_eq!(info.number, 8);
+        let updated = update.to_bytes().unwrap();
+
+        let mut file = PdfFile::from_bytes(&updated);
+        file.load_xref_table().unwrap();
+        let trailer = file.trailer().unwrap();
+        assert_eq!(trailer.get_int(names::SIZE), Ok(10));
+        let xref_stream = trailer.get_int(names::X_REF_STM).unwrap() as usize;
+        assert!(updated[xref_stream..].starts_with(b"9 0 obj"));
+
+        let root = file.object(catalog).unwrap();
+        assert_eq!(root.get_string("Lang").unwrap().as_ref(), b"en");
+        let pages = file.pages().unwrap();
+        assert_eq!(
+            &pages[0].content_bytes().unwrap()[..],
+            test_support::HELLO_WORLD_CONTENT
+        );
+        assert_eq!(file.diagnostics(), vec![]);
+
+        // Older readers only see the xref table, which lists the objects
+        // that are still in the object stream as free
+        let (_, xref_table) = find_startxref(&updated).unwrap();
+        let (section, _) = parse_xref_section(&updated[xref_table..]).unwrap();
+        assert!(section[&catalog].is_in_use());
+        assert!(section[&IndirectRef::new(3, 0)].is_free());
+        assert_eq!(
+            section[&IndirectRef::new(9, 0)],
+            XrefEntry::InUse(xref_stream)
+        );
+    }
+
+    #[test]
+    fn should_keep_first_id() {
+        let mut builder = DocumentBuilder::new().with_id([1; 16]);
+        test_support::build_pages(&mut builder, &[test_support::HELLO_WORLD_CONTENT]);
+        let raw = builder.to_bytes().unwrap();
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+
+        let updated = IncrementalUpdate::new(&file)
+            .unwrap()
+            .with_id([2; 16])
+            .to_bytes()
+            .unwrap();
+        let mut file = PdfFile::from_raw(updated);
+        file.load_xref_table().unwrap();
+        let trailer = file.trailer().unwrap();
+        assert_eq!(trailer.get(names::ID), Some(&id_object(&[1; 16], &[2; 16])));
+    }
+}
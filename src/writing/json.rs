@@ -0,0 +1,253 @@
+//! Builds documents from descriptions of their objects in JSON, such as
+//! templates kept in configuration files. Objects are written as the JSON
+//! export writes them (see `document::json`), and are given labels so that
+//! they can refer to each other:
+//!
+//! ```json
+//! {
+//!     "root": "catalog",
+//!     "objects": {
+//!         "catalog": { "Type": "/Catalog", "Pages": { "ref": "pages" } },
+//!         "pages": { "Type": "/Pages", "Kids": [{ "ref": "page" }], "Count": 1 },
+//!         "page": {
+//!             "Type": "/Page",
+//!             "Parent": { "ref": "pages" },
+//!             "MediaBox": [0, 0, 612, 792],
+//!             "Contents": { "stream": {}, "data": "0 0 1 rg 72 72 144 144 re f" }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Stream data is given as text in `data` or as hexadecimal in `hex`, and is
+//! the decoded data, which is encoded with the filters in the stream
+//! dictionary when it is written. Streams must be indirect objects, so
+//! streams that are written inside of other objects are added as objects of
+//! their own.
+//!
+//! Descriptions in other formats, such as YAML or TOML, can be deserialized
+//! into a `serde_json::Value` with their own serde deserializers and then
+//! added in the same way.
+
+use crate::error::{Error, Result};
+use crate::objects::{Dictionary, IndirectRef, Name, Object};
+use crate::utils::text::encode_text_string;
+use crate::writing::document::DocumentBuilder;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+impl DocumentBuilder<'static> {
+    /// Builds a document from a JSON description of its objects.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let description = serde_json::from_str(json)
+            .map_err(|err| Error::Syntax("Invalid JSON", err.to_string()))?;
+        let mut builder = Self::new();
+        builder.add_json(&description)?;
+        Ok(builder)
+    }
+}
+
+impl<'a> DocumentBuilder<'a> {
+    /// Adds the objects of a JSON description, returning the reference that
+    /// each label was given. The root and information dictionary are set if
+    /// the description names them.
+    pub fn add_json(&mut self, description: &Value) -> Result<HashMap<String, IndirectRef>> {
+        let objects = match description.get("objects") {
+            Some(Value::Object(objects)) => objects,
+            Some(other) => return Err(Error::Type(format!("Expected objects got {}", other))),
+            None => return Err(Error::MissingKey("objects".to_owned())),
+        };
+
+        // Every label is reserved first, so that objects can refer to those
+        // that come after them
+        let labels = objects
+            .keys()
+            .map(|label| (label.clone(), self.reserve()))
+            .collect::<HashMap<_, _>>();
+        for (label, value) in objects {
+            let object = self.json_to_object(value, &labels)?;
+            self.insert(labels[label], object);
+        }
+
+        let label = |key: &str| match description.get(key) {
+            Some(Value::String(label)) => lookup(&labels, label).map(Some),
+            Some(other) => Err(Error::Type(format!("Expected label got {}", other))),
+            None => Ok(None),
+        };
+        if let Some(root) = label("root")? {
+            self.set_root(root);
+        }
+        if let Some(info) = label("info")? {
+            self.set_info(info);
+        }
+        Ok(labels)
+    }
+
+    fn json_to_object(
+        &mut self,
+        value: &Value,
+        labels: &HashMap<String, IndirectRef>,
+    ) -> Result<Object<'a>> {
+        Ok(match value {
+            Value::Null => Object::Null,
+            Value::Bool(boolean) => Object::Boolean(*boolean),
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => Object::Integer(integer),
+                None => Object::Real(number.as_f64().unwrap_or_default()),
+            },
+            Value::String(string) => match string.strip_prefix('/') {
+                Some(name) => Object::Name(Name(Cow::Owned(name.as_bytes().to_vec()))),
+                None => {
+                    return Err(Error::Type(format!(
+                        "Expected a name starting with a slash got {:?}, strings are \
+                         written as {{\"string\": ...}}",
+                        string
+                    )))
+                }
+            },
+            Value::Array(array) => Object::Array(
+                array
+                    .iter()
+                    .map(|item| self.json_to_object(item, labels))
+                    .collect::<Result<_>>()?,
+            ),
+            Value::Object(map) => self.json_map_to_object(map, labels)?,
+        })
+    }
+
+    /// Converts a JSON object, which is a reference, a string or a stream if
+    /// it has the keys that those are written with, and a dictionary
+    /// otherwise.
+    fn json_map_to_object(
+        &mut self,
+        map: &Map<String, Value>,
+        labels: &HashMap<String, IndirectRef>,
+    ) -> Result<Object<'a>> {
+        let only = |key: &str| match map.get(key) {
+            Some(Value::String(value)) if map.len() == 1 => Some(value),
+            _ => None,
+        };
+        if let Some(label) = only("ref") {
+            return Ok(Object::Indirect(lookup(labels, label)?));
+        }
+        if let Some(text) = only("string") {
+            return Ok(Object::String(Cow::Owned(encode_text_string(text))));
+        }
+        if let Some(hex) = only("hex") {
+            return Ok(Object::String(Cow::Owned(decode_hex(hex)?)));
+        }
+
+        if let Some(dict) = map.get("stream") {
+            let dict = match self.json_to_object(dict, labels)? {
+                dict @ Object::Dictionary(_) => dict,
+                other => return Err(Error::Type(format!("Expected dict got {:?}", other))),
+            };
+            let data = match (map.get("data"), map.get("hex")) {
+                (Some(Value::String(text)), None) => text.as_bytes().to_vec(),
+                (None, Some(Value::String(hex))) => decode_hex(hex)?,
+                _ => {
+                    return Err(Error::Syntax(
+                        "Expected stream data as text in data or hexadecimal in hex",
+                        Value::Object(map.clone()).to_string(),
+                    ))
+                }
+            };
+            let stream = Object::Stream(Box::new(dict), Cow::Owned(data));
+            return Ok(Object::Indirect(self.add(stream)));
+        }
+
+        let mut dict = Dictionary::new();
+        for (key, value) in map {
+            let key = Name(Cow::Owned(key.as_bytes().to_vec()));
+            dict.insert(key, self.json_to_object(value, labels)?);
+        }
+        Ok(Object::Dictionary(dict))
+    }
+}
+
+fn lookup(labels: &HashMap<String, IndirectRef>, label: &str) -> Result<IndirectRef> {
+    labels
+        .get(label)
+        .copied()
+        .ok_or_else(|| Error::Syntax("Unknown object label", label.to_owned()))
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    let invalid = || Error::Syntax("Invalid hexadecimal string", hex.to_owned());
+    if !hex.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::pdf_file::PdfFile;
+    use serde_json::json;
+
+    #[test]
+    fn should_build_documents_from_json() {
+        let builder = DocumentBuilder::from_json(
+            r#"{
+                "root": "catalog",
+                "info": "info",
+                "objects": {
+                    "catalog": { "Type": "/Catalog", "Pages": { "ref": "pages" } },
+                    "pages": { "Type": "/Pages", "Kids": [{ "ref": "page" }], "Count": 1 },
+                    "page": {
+                        "Type": "/Page",
+                        "Parent": { "ref": "pages" },
+                        "MediaBox": [0, 0, 612, 792.5],
+                        "Contents": { "stream": {}, "data": "BT /F1 12 Tf (Hi) Tj ET" }
+                    },
+                    "info": { "Title": { "string": "Report" }, "ID": { "hex": "00ff" } }
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(
+            &pages[0].content_bytes().unwrap()[..],
+            b"BT /F1 12 Tf (Hi) Tj ET"
+        );
+        let tree = file.to_json_tree(1).unwrap();
+        assert_eq!(tree["Pages"]["Kids"], json!([{ "ref": "3 0 R" }]));
+        let info = file
+            .resolve_owned(file.trailer().unwrap().get("Info").unwrap())
+            .unwrap();
+        assert_eq!(info.get("Title").unwrap().as_text().unwrap(), "Report");
+        assert_eq!(
+            info.get("ID"),
+            Some(&Object::String(Cow::Borrowed(b"\x00\xff")))
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_descriptions() {
+        let mut builder = DocumentBuilder::new();
+        let invalid = [
+            json!({}),
+            json!({ "objects": { "a": { "ref": "b" } } }),
+            json!({ "objects": { "a": "text" } }),
+            json!({ "objects": { "a": { "hex": "0" } } }),
+            json!({ "objects": { "a": { "stream": {} } } }),
+            json!({ "objects": {}, "root": "a" }),
+        ];
+        for description in invalid {
+            assert!(builder.add_json(&description).is_err(), "{}", description);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::document::page::{Page, INHERITABLE_KEYS};
+use crate::error::Result;
+use crate::objects::{names, Dictionary, IndirectRef, Name, Object};
+use crate::parsing::pdf_file::PdfFile;
+use crate::writing::document::DocumentBuilder;
+use crate::writing::object_map::ObjectMap;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MergeOptions {
+    /// Share a single copy of identical streams and font dictionaries.
+    pub deduplicate: bool,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self { deduplicate: true }
+    }
+}
+
+/// Merges the pages of several files into a new document, in order. The xref
+/// table of each file must already be loaded.
+///
+/// Inherited page attributes are copied onto each page, since the page trees
+/// of the files are not kept. Document-level structures such as outlines and
+/// forms are not merged.
+pub fn merge<'a>(files: &[&'a PdfFile<'a>], options: MergeOptions) -> Result<DocumentBuilder<'a>> {
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    let tree = builder.reserve();
+
+    let mut kids = Vec::new();
+    for file in files {
+        kids.extend(copy_pages(file, &file.pages()?, &mut builder, tree)?);
+    }
+    finish_pages(&mut builder, catalog, tree, kids);
+
+    if options.deduplicate {
+        builder.deduplicate()?;
+    }
+
+    Ok(builder)
+}
+
+/// Copies pages into a builder as kids of the page tree node `tree`,
+/// returning references to the copies.
+pub(crate) fn copy_pages<'a>(
+    file: &'a PdfFile<'a>,
+    pages: &[Page<'a>],
+    builder: &mut DocumentBuilder<'a>,
+    tree: IndirectRef,
+) -> Result<Vec<Object<'a>>> {
+    // Map every page first, so that references to pages from other
+    // objects (such as the /P entry of annotations) point to the copies
+    let mut map = ObjectMap::new();
+    let mut copies = Vec::new();
+    for page in pages {
+        let copy = builder.reserve();
+        if let Some(reference) = page.reference() {
+            map.insert(reference, copy);
+        }
+        copies.push(copy);
+    }
+
+    let mut kids = Vec::new();
+    for (page, copy) in pages.iter().zip(copies) {
+        let Object::Dictionary(dict) = page.dict() else {
+            continue;
+        };
+        let mut dict = dict.clone();
+        for key in INHERITABLE_KEYS {
+            if let Some(value) = page.attribute(key) {
+                dict.insert(Name::from(key), value.clone());
+            }
+        }
+        dict.remove(names::PARENT.as_bytes());
+        // Only the resources that the page uses are copied, as pages often
+        // share one resource dictionary with every font and image in the
+        // file. Pages whose contents cannot be read keep all of theirs.
+        if let Ok(resources) = page.used_resources() {
+            dict.insert(Name::from(names::RESOURCES), resources);
+        }
+
+        let mut object = map.copy(file, builder, &Object::Dictionary(dict))?;
+        if let Object::Dictionary(dict) = &mut object {
+            dict.insert(Name::from(names::PARENT), Object::Indirect(tree));
+        }
+        builder.insert(copy, object);
+        kids.push(Object::Indirect(copy));
+    }
+    Ok(kids)
+}
+
+/// Writes the page tree and catalog of a new document into the objects that
+/// were reserved for them.
+pub(crate) fn finish_pages<'a>(
+    builder: &mut DocumentBuilder<'a>,
+    catalog: IndirectRef,
+    tree: IndirectRef,
+    kids: Vec<Object<'a>>,
+) {
+    let mut pages = Dictionary::new();
+    pages.insert(
+        Name::from(names::TYPE),
+        Object::Name(Name::from(names::PAGES)),
+    );
+    pages.insert(Name::from(names::COUNT), Object::Integer(kids.len() as i64));
+    pages.insert(Name::from(names::KIDS), Object::Array(kids));
+    builder.insert(tree, Object::Dictionary(pages));
+
+    let mut root = Dictionary::new();
+    root.insert(
+        Name::from(names::TYPE),
+        Object::Name(Name::from(names::CATALOG)),
+    );
+    root.insert(Name::from(names::PAGES), Object::Indirect(tree));
+    builder.insert(catalog, Object::Dictionary(root));
+    builder.set_root(catalog);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::writing::objects::WriteOptions;
+
+    fn open(raw: Vec<u8>) -> PdfFile<'static> {
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    #[test]
+    fn should_merge_pages_in_order() {
+        let first = open(test_support::multi_page(2));
+        let second = open(test_support::hello_world(WriteOptions::default()));
+
+        let builder = merge(&[&first, &second], MergeOptions::default()).unwrap();
+        let merged = open(builder.to_bytes().unwrap());
+        let text = merged
+            .pages()
+            .unwrap()
+            .iter()
+            .map(|page| page.extract_text().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(text, vec!["Page 1", "Page 2", "Hello, world!"]);
+        assert_eq!(merged.diagnostics(), vec![]);
+    }
+
+    #[test]
+    fn should_share_identical_resources() {
+        let files = (0..3)
+            .map(|_| open(test_support::hello_world(WriteOptions::default())))
+            .collect::<Vec<_>>();
+        let files = files.iter().collect::<Vec<_>>();
+
+        let options = MergeOptions { deduplicate: false };
+        let full = merge(&files, options).unwrap().to_bytes().unwrap();
+        let shared = merge(&files, MergeOptions::default())
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        assert!(shared.len() < full.len());
+
+        let merged = open(shared);
+        let pages = merged.pages().unwrap();
+        assert_eq!(pages.len(), 3);
+        let fonts = pages
+            .iter()
+            .map(|page| page.resources_of(names::FONT).unwrap()[0].1.clone())
+            .collect::<Vec<_>>();
+        let contents = pages
+            .iter()
+            .map(|page| page.attribute(names::CONTENTS).unwrap().clone())
+            .collect::<Vec<_>>();
+        assert!(fonts.iter().all(|font| *font == fonts[0]));
+        assert!(contents.iter().all(|content| *content == contents[0]));
+        assert_eq!(pages[2].extract_text().unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn should_only_copy_used_resources() {
+        let mut builder = DocumentBuilder::new();
+        test_support::build_pages(
+            &mut builder,
+            &[b"BT /F1 12 Tf (Hi) Tj ET", b"0 0 m 1 1 l S"],
+        );
+        let file = open(builder.to_bytes().unwrap());
+
+        let merged = merge(&[&file], MergeOptions::default()).unwrap();
+        let merged = open(merged.to_bytes().unwrap());
+        let pages = merged.pages().unwrap();
+        assert_eq!(pages[0].resources_of(names::FONT).unwrap().len(), 1);
+        assert!(pages[1].resources_of(names::FONT).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,27 @@
+#[cfg(feature = "write")]
+pub mod app_data;
+#[cfg(feature = "write")]
+pub mod document;
+#[cfg(feature = "write")]
+pub mod id;
+#[cfg(feature = "write")]
+pub mod impose;
+#[cfg(feature = "write")]
+pub mod incremental;
+#[cfg(all(feature = "write", feature = "serde"))]
+pub mod json;
+#[cfg(feature = "write")]
+pub mod merge;
+#[cfg(feature = "write")]
+pub mod object_map;
+pub mod objects;
+#[cfg(feature = "write")]
+pub mod patch;
+#[cfg(feature = "write")]
+pub mod resize;
+#[cfg(feature = "write")]
+pub mod rewrite;
+#[cfg(feature = "write")]
+pub mod split;
+#[cfg(feature = "write")]
+pub mod xref_stream;
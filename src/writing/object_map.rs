@@ -0,0 +1,216 @@
+use crate::error::Result;
+use crate::objects::{IndirectRef, Object};
+use crate::parsing::pdf_file::PdfFile;
+use crate::writing::document::DocumentBuilder;
+use std::collections::HashMap;
+
+/// Maps objects in a source file to their copies in a document that is being
+/// built, so that each object is only copied once and references between the
+/// copies point to the right objects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjectMap {
+    map: HashMap<IndirectRef, IndirectRef>,
+}
+
+impl ObjectMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, old: IndirectRef) -> Option<IndirectRef> {
+        self.map.get(&old).copied()
+    }
+
+    pub fn insert(&mut self, old: IndirectRef, new: IndirectRef) {
+        self.map.insert(old, new);
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterates over the pairs of old and new references.
+    pub fn iter(&self) -> impl Iterator<Item = (IndirectRef, IndirectRef)> + '_ {
+        self.map.iter().map(|(&old, &new)| (old, new))
+    }
+
+    /// Rewrites the references inside an object. References that are not in
+    /// the map are left unchanged.
+    pub fn remap<'a>(&self, object: &Object<'a>) -> Object<'a> {
+        map_references(object, &mut |reference| {
+            self.get(reference).unwrap_or(reference)
+        })
+    }
+
+    /// Copies an object from a file into a document, along with every object
+    /// that it refers to, returning the copy.
+    pub fn copy<'a>(
+        &mut self,
+        file: &'a PdfFile<'a>,
+        builder: &mut DocumentBuilder<'a>,
+        object: &Object<'a>,
+    ) -> Result<Object<'a>> {
+        let mut queue = Vec::new();
+        let copy = self.reserve_references(builder, object, &mut queue);
+
+        // Copy referenced objects with a queue rather than recursion, since
+        // chains of references such as linked lists can be very long
+        while let Some((old, new)) = queue.pop() {
+            let object = file.object(old)?;
+            let copy = self.reserve_references(builder, &object, &mut queue);
+            builder.insert(new, copy);
+        }
+
+        Ok(copy)
+    }
+
+    /// Like `copy`, but for an indirect object, returning its new reference.
+    pub fn copy_reference<'a>(
+        &mut self,
+        file: &'a PdfFile<'a>,
+        builder: &mut DocumentBuilder<'a>,
+        old: IndirectRef,
+    ) -> Result<IndirectRef> {
+        self.copy(file, builder, &Object::Indirect(old))?
+            .as_indirect()
+    }
+
+    /// Rewrites the references inside an object, reserving a new object for
+    /// each reference that has not been seen before and queuing it to be
+    /// copied.
+    fn reserve_references<'a>(
+        &mut self,
+        builder: &mut DocumentBuilder<'a>,
+        object: &Object<'a>,
+        queue: &mut Vec<(IndirectRef, IndirectRef)>,
+    ) -> Object<'a> {
+        map_references(object, &mut |old| {
+            *self.map.entry(old).or_insert_with(|| {
+                let new = builder.reserve();
+                queue.push((old, new));
+                new
+            })
+        })
+    }
+}
+
+fn map_references<'a>(
+    object: &Object<'a>,
+    f: &mut impl FnMut(IndirectRef) -> IndirectRef,
+) -> Object<'a> {
+    match object {
+        Object::Indirect(reference) => Object::Indirect(f(*reference)),
+        Object::Array(array) => {
+            Object::Array(array.iter().map(|item| map_references(item, f)).collect())
+        }
+        Object::Dictionary(dict) => Object::Dictionary(
+            dict.iter()
+                .map(|(key, value)| (key.clone(), map_references(value, f)))
+                .collect(),
+        ),
+        Object::Stream(dict, data) => {
+            Object::Stream(Box::new(map_references(dict, f)), data.clone())
+        }
+        _ => object.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    use crate::objects::{names, Name};
+    use crate::test_support::dict;
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    use crate::test_support::name;
+
+    fn reference(number: u32) -> IndirectRef {
+        IndirectRef {
+            number,
+            generation: 0,
+        }
+    }
+
+    #[test]
+    fn should_remap_nested_references() {
+        let mut map = ObjectMap::new();
+        map.insert(reference(1), reference(10));
+
+        let object = dict(&[(
+            "Kids",
+            Object::Array(vec![
+                Object::Indirect(reference(1)),
+                Object::Indirect(reference(2)),
+            ]),
+        )]);
+        assert_eq!(
+            map.remap(&object),
+            dict(&[(
+                "Kids",
+                Object::Array(vec![
+                    Object::Indirect(reference(10)),
+                    Object::Indirect(reference(2)),
+                ]),
+            )])
+        );
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_copy_pages_between_files() {
+        let mut file = PdfFile::read_file("./examples/hello-world.pdf").unwrap();
+        file.load_xref_table().unwrap();
+        let pages = file.pages().unwrap();
+
+        // Leave out the parent, which would copy the whole page tree
+        let mut page = pages[0].dict().clone();
+        if let Object::Dictionary(dict) = &mut page {
+            dict.remove(names::PARENT.as_bytes());
+            let media_box = pages[0].attribute(names::MEDIA_BOX).unwrap().clone();
+            dict.insert(Name::from(names::MEDIA_BOX), media_box);
+        }
+
+        let mut builder = DocumentBuilder::new().with_id([0; 16]);
+        let mut map = ObjectMap::new();
+        let page = map.copy(&file, &mut builder, &page).unwrap();
+        let page = builder.add(page);
+        let tree = builder.add(dict(&[
+            (names::TYPE, name(names::PAGES)),
+            (names::KIDS, Object::Array(vec![Object::Indirect(page)])),
+            (names::COUNT, Object::Integer(1)),
+        ]));
+        let catalog = builder.add(dict(&[
+            (names::TYPE, name(names::CATALOG)),
+            (names::PAGES, Object::Indirect(tree)),
+        ]));
+        builder.set_root(catalog);
+        assert!(!map.is_empty());
+
+        let mut copy = PdfFile::from_raw(builder.to_bytes().unwrap());
+        copy.load_xref_table().unwrap();
+        let pages = copy.pages().unwrap();
+        assert_eq!(pages[0].extract_text().unwrap(), "Hello, world!");
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_copy_each_object_once() {
+        let mut file = PdfFile::read_file("./examples/hello-world.pdf").unwrap();
+        file.load_xref_table().unwrap();
+
+        let mut builder = DocumentBuilder::new();
+        let mut map = ObjectMap::new();
+        let root = file.trailer().unwrap().get_indirect(names::ROOT).unwrap();
+        let first = map.copy_reference(&file, &mut builder, root).unwrap();
+        let copied = map.len();
+        let second = map.copy_reference(&file, &mut builder, root).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(map.len(), copied);
+        assert_eq!(map.get(root), Some(first));
+    }
+}
@@ -0,0 +1,444 @@
+use crate::error::{Error, Result};
+use crate::filters::{self, flate};
+use crate::objects::{names, sorted_entries, Dictionary, Name, Object};
+use crate::utils::chars::is_name_char;
+use std::borrow::Cow;
+
+/// Options that control how objects are serialized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Compress streams with `FlateDecode`. Otherwise streams are written
+    /// without any filters.
+    pub compress_streams: bool,
+    /// Re-encode streams that have a `/Filter` with the filters that it
+    /// names, so that streams keep their encoding when they are copied or
+    /// edited. Setting `/Filter` on a stream chooses how it is encoded.
+    /// Streams without a `/Filter` are written as set by `compress_streams`.
+    pub keep_filters: bool,
+    /// Make the output 7-bit clean, for systems that mangle binary data. Any
+    /// stream that is still binary once it is encoded has this filter added
+    /// on top of its other filters, strings are written in hexadecimal, and
+    /// the binary comment after the header is left out.
+    pub ascii_armor: Option<AsciiArmor>,
+    /// Pack objects other than streams into object streams, which are listed
+    /// by a cross-reference stream, as most producers now do. This makes
+    /// files smaller, but needs PDF 1.5 or later, so the version of the
+    /// document is raised to 1.5 if it is lower. The file is written as a
+    /// hybrid-reference file, with a classic xref table alongside the stream
+    /// (Adobe, 2008, p. 67). The catalog, page tree and encryption
+    /// dictionary are kept out of object streams, so that older readers can
+    /// still find the pages. This only applies to new documents rather than
+    /// incremental updates.
+    pub object_streams: bool,
+}
+
+/// A filter that encodes binary data as ASCII text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsciiArmor {
+    /// `ASCIIHexDecode`, which is easy to read but doubles the size.
+    Hex,
+    /// `ASCII85Decode`, which adds a quarter to the size.
+    Ascii85,
+}
+
+impl AsciiArmor {
+    fn filter(self) -> &'static str {
+        match self {
+            AsciiArmor::Hex => names::ASCII_HEX_DECODE,
+            AsciiArmor::Ascii85 => names::ASCII_85_DECODE,
+        }
+    }
+}
+
+/// Serializes an object (Adobe, 2008, p. 13-24).
+///
+/// Stream objects hold their decoded data, so their `/Filter`,
+/// `/DecodeParms` and `/Length` entries are replaced to match the data that is
+/// actually written. `/DecodeParms` is always dropped, since the data is
+/// encoded without any parameters.
+pub fn write_object(out: &mut Vec<u8>, object: &Object, options: &WriteOptions) -> Result<()> {
+    match object {
+        Object::Boolean(boolean) => {
+            out.extend_from_slice(if *boolean { b"true" } else { b"false" })
+        }
+        Object::Integer(int) => out.extend_from_slice(int.to_string().as_bytes()),
+        Object::Real(real) => write_real(out, *real)?,
+        Object::String(string) => write_string(out, string, options),
+        Object::Name(name) => write_name(out, name),
+        Object::Array(array) => {
+            out.push(b'[');
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    out.push(b' ');
+                }
+                write_object(out, item, options)?;
+            }
+            out.push(b']');
+        }
+        Object::Dictionary(dict) => write_dictionary(out, dict, options)?,
+        Object::Stream(dict, data) => write_stream(out, dict.as_dict()?, data, options)?,
+        Object::Null => out.extend_from_slice(b"null"),
+        Object::Indirect(reference) => out.extend_from_slice(
+            format!("{} {} R", reference.number, reference.generation).as_bytes(),
+        ),
+    }
+
+    Ok(())
+}
+
+/// Writes a real number without using exponential notation, which PDF does not
+/// allow (Adobe, 2008, p. 14). Without exponents, very large and very small
+/// numbers would need hundreds of digits, so they are limited to the range of
+/// a 32-bit float, which is all that readers are expected to support. Numbers
+/// closer to zero than that are written as zero.
+fn write_real(out: &mut Vec<u8>, real: f64) -> Result<()> {
+    if !real.is_finite() {
+        return Err(Error::Type(format!(
+            "Cannot write non-finite real {}",
+            real
+        )));
+    }
+
+    let real = if real.abs() < f32::MIN_POSITIVE as f64 {
+        0.0
+    } else {
+        real.clamp(f32::MIN as f64, f32::MAX as f64)
+    };
+    let mut string = real.to_string();
+    // Make sure that the number is read back as a real, not an integer
+    if !string.contains('.') {
+        string.push_str(".0");
+    }
+    out.extend_from_slice(string.as_bytes());
+
+    Ok(())
+}
+
+/// Writes a string, as a literal string if it is printable and otherwise as
+/// a hexadecimal string (Adobe, 2008, p. 15-16).
+fn write_string(out: &mut Vec<u8>, string: &[u8], options: &WriteOptions) {
+    let is_printable = |c: &u8| (b' '..=b'~').contains(c) || matches!(c, b'\t' | b'\n');
+    if options.ascii_armor.is_none() && string.iter().all(is_printable) {
+        out.push(b'(');
+        for &c in string {
+            match c {
+                b'(' | b')' | b'\\' => out.extend_from_slice(&[b'\\', c]),
+                _ => out.push(c),
+            }
+        }
+        out.push(b')');
+    } else {
+        out.push(b'<');
+        for c in string {
+            out.extend_from_slice(format!("{:02X}", c).as_bytes());
+        }
+        out.push(b'>');
+    }
+}
+
+/// Writes a name, escaping any characters that are not regular characters
+/// (Adobe, 2008, p. 17).
+fn write_name(out: &mut Vec<u8>, name: &[u8]) {
+    out.push(b'/');
+    for &c in name {
+        if is_name_char(c) && c != b'#' && (b'!'..=b'~').contains(&c) {
+            out.push(c);
+        } else {
+            out.extend_from_slice(format!("#{:02X}", c).as_bytes());
+        }
+    }
+}
+
+/// Writes a dictionary with its keys sorted, so that the output is
+/// deterministic.
+fn write_dictionary(out: &mut Vec<u8>, dict: &Dictionary, options: &WriteOptions) -> Result<()> {
+    out.extend_from_slice(b"<<");
+    for (key, value) in sorted_entries(dict) {
+        out.push(b' ');
+        write_name(out, key);
+        out.push(b' ');
+        write_object(out, value, options)?;
+    }
+    out.extend_from_slice(b" >>");
+
+    Ok(())
+}
+
+fn write_stream(
+    out: &mut Vec<u8>,
+    dict: &Dictionary,
+    data: &[u8],
+    options: &WriteOptions,
+) -> Result<()> {
+    let mut dict = dict.clone();
+    dict.remove(names::DECODE_PARMS.as_bytes());
+    let filters = dict
+        .get(names::FILTER.as_bytes())
+        .filter(|filters| options.keep_filters && filters.into_iter().next().is_some());
+
+    let data = if let Some(filters) = filters {
+        Cow::Owned(filters::encode_chain(filters, data)?)
+    } else if options.compress_streams {
+        dict.insert(
+            Name::from(names::FILTER),
+            Object::Name(Name::from(names::FLATE_DECODE)),
+        );
+        Cow::Owned(flate::encode(data)?)
+    } else {
+        dict.remove(names::FILTER.as_bytes());
+        Cow::Borrowed(data)
+    };
+    let data = match options.ascii_armor {
+        Some(armor) if !is_ascii_text(&data) => {
+            let filter = Object::Name(Name::from(armor.filter()));
+            let filters = match dict.remove(names::FILTER.as_bytes()) {
+                Some(Object::Array(mut filters)) => {
+                    filters.insert(0, filter);
+                    Object::Array(filters)
+                }
+                Some(other) => Object::Array(vec![filter, other]),
+                None => filter,
+            };
+            dict.insert(Name::from(names::FILTER), filters);
+            Cow::Owned(filters::encode(&Name::from(armor.filter()), &data)?)
+        }
+        _ => data,
+    };
+    write_encoded_stream(out, dict, &data, options)
+}
+
+/// Writes a stream whose data is already encoded with the filters in its
+/// dictionary, such as one read with `PdfFile::encoded_object`, only
+/// correcting its `/Length`.
+pub(crate) fn write_encoded_stream(
+    out: &mut Vec<u8>,
+    mut dict: Dictionary,
+    data: &[u8],
+    options: &WriteOptions,
+) -> Result<()> {
+    dict.insert(
+        Name::from(names::LENGTH),
+        Object::Integer(data.len() as i64),
+    );
+
+    write_dictionary(out, &dict, options)?;
+    out.extend_from_slice(b"\nstream\n");
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\nendstream");
+
+    Ok(())
+}
+
+/// Checks whether data is printable ASCII, allowing whitespace but not other
+/// control characters.
+fn is_ascii_text(data: &[u8]) -> bool {
+    data.iter()
+        .all(|&c| (b' '..=b'~').contains(&c) || matches!(c, b'\t' | b'\n' | b'\r' | b'\x0C'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::IndirectRef;
+    use crate::parsing::context::ParseContext;
+    use crate::parsing::objects::parse_object_until_keyword;
+    use crate::parsing::tokens::MAX_NUMERIC_DIGITS;
+
+    fn write(object: &Object, options: &WriteOptions) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_object(&mut out, object, options).unwrap();
+        out
+    }
+
+    fn round_trip<'a>(raw: &'a mut Vec<u8>, object: &Object) -> Object<'a> {
+        *raw = write(object, &WriteOptions::default());
+        raw.extend_from_slice(b" end ");
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(raw, b"end", &ParseContext::default()).unwrap();
+        obj
+    }
+
+    #[test]
+    fn should_write_simple_objects() {
+        let options = WriteOptions::default();
+        assert_eq!(write(&Object::Boolean(true), &options), b"true");
+        assert_eq!(write(&Object::Integer(-42), &options), b"-42");
+        assert_eq!(write(&Object::Real(4.0), &options), b"4.0");
+        assert_eq!(write(&Object::Real(-0.002), &options), b"-0.002");
+        assert_eq!(write(&Object::Real(-1e-300), &options), b"0.0");
+        assert_eq!(write(&Object::Null, &options), b"null");
+        assert_eq!(
+            write(
+                &Object::Indirect(IndirectRef {
+                    number: 12,
+                    generation: 0
+                }),
+                &options
+            ),
+            b"12 0 R"
+        );
+    }
+
+    #[test]
+    fn should_write_strings() {
+        let options = WriteOptions::default();
+        assert_eq!(
+            write(&Object::String(Cow::Borrowed(b"a (b) \\c")), &options),
+            b"(a \\(b\\) \\\\c)"
+        );
+        assert_eq!(
+            write(&Object::String(Cow::Borrowed(b"\x00\xFF")), &options),
+            b"<00FF>"
+        );
+    }
+
+    #[test]
+    fn should_write_names() {
+        let options = WriteOptions::default();
+        assert_eq!(
+            write(&Object::Name(Name::borrowed(b"Lime Green")), &options),
+            b"/Lime#20Green"
+        );
+        assert_eq!(
+            write(&Object::Name(Name::borrowed(b"F#")), &options),
+            b"/F#23"
+        );
+    }
+
+    #[test]
+    fn should_write_dictionaries_in_order() {
+        let mut dict = Dictionary::new();
+        dict.insert(Name::borrowed(b"B"), Object::Integer(2));
+        dict.insert(
+            Name::borrowed(b"A"),
+            Object::Array(vec![Object::Integer(1), Object::Null]),
+        );
+        assert_eq!(
+            write(&Object::Dictionary(dict), &WriteOptions::default()),
+            b"<< /A [1 null] /B 2 >>"
+        );
+    }
+
+    #[test]
+    fn should_bound_the_digits_of_reals() {
+        let options = WriteOptions::default();
+        for real in [1e300, -1e300, f64::MAX, 1e-300, -f64::MIN_POSITIVE, 1.2e-38] {
+            let raw = write(&Object::Real(real), &options);
+            let digits = raw.iter().filter(|c| c.is_ascii_digit()).count();
+            assert!(digits <= MAX_NUMERIC_DIGITS, "{}", real);
+            let mut raw = Vec::new();
+            assert!(matches!(
+                round_trip(&mut raw, &Object::Real(real)),
+                Object::Real(_)
+            ));
+        }
+        let mut raw = Vec::new();
+        assert_eq!(
+            round_trip(&mut raw, &Object::Real(-1e300)),
+            Object::Real(f32::MIN as f64)
+        );
+    }
+
+    #[test]
+    fn should_round_trip_objects() {
+        let mut dict = Dictionary::new();
+        dict.insert(
+            Name::borrowed(b"Name"),
+            Object::Name(Name::borrowed(b"A;B#C")),
+        );
+        dict.insert(
+            Name::borrowed(b"String"),
+            Object::String(Cow::Borrowed(b"(\t\n)")),
+        );
+        dict.insert(
+            Name::borrowed(b"Binary"),
+            Object::String(Cow::Borrowed(b"\x80\x00")),
+        );
+        dict.insert(Name::borrowed(b"Real"), Object::Real(-12.5));
+        let object = Object::Dictionary(dict);
+
+        let mut raw = Vec::new();
+        assert_eq!(round_trip(&mut raw, &object), object);
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_write_streams() {
+        let mut dict = Dictionary::new();
+        dict.insert(
+            Name::from(names::FILTER),
+            Object::Name(Name::borrowed(b"ASCIIHexDecode")),
+        );
+        let object = Object::Stream(
+            Box::new(Object::Dictionary(dict)),
+            Cow::Borrowed(b"Hello, world!"),
+        );
+
+        assert_eq!(
+            write(&object, &WriteOptions::default()),
+            b"<< /Length 13 >>\nstream\nHello, world!\nendstream"
+        );
+
+        let options = WriteOptions {
+            compress_streams: true,
+            ..Default::default()
+        };
+        let mut raw = write(&object, &options);
+        raw.extend_from_slice(b" end ");
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(&raw, b"end", &ParseContext::default()).unwrap();
+        let (dict, data) = obj.as_stream().unwrap();
+        assert_eq!(
+            dict[names::FILTER.as_bytes()],
+            Object::Name(Name::from(names::FLATE_DECODE))
+        );
+        assert_eq!(data, Cow::Borrowed(b"Hello, world!"));
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_keep_stream_filters() {
+        let flate = Object::Name(Name::from(names::FLATE_DECODE));
+        let mut dict = Dictionary::new();
+        dict.insert(
+            Name::from(names::FILTER),
+            Object::Array(vec![flate.clone(), flate.clone()]),
+        );
+        dict.insert(
+            Name::from(names::DECODE_PARMS),
+            Object::Array(vec![Object::Null, Object::Null]),
+        );
+        let object = Object::Stream(
+            Box::new(Object::Dictionary(dict)),
+            Cow::Borrowed(b"Hello, world!"),
+        );
+
+        let options = WriteOptions {
+            keep_filters: true,
+            ..Default::default()
+        };
+        let mut raw = write(&object, &options);
+        raw.extend_from_slice(b" end ");
+        let ((_, obj), _raw) =
+            parse_object_until_keyword(&raw, b"end", &ParseContext::default()).unwrap();
+        let (dict, data) = obj.as_stream().unwrap();
+        assert_eq!(
+            dict[names::FILTER.as_bytes()],
+            Object::Array(vec![flate.clone(), flate])
+        );
+        assert!(!dict.contains_key(names::DECODE_PARMS.as_bytes()));
+        assert_eq!(data, Cow::Borrowed(b"Hello, world!"));
+
+        let mut dict = Dictionary::new();
+        dict.insert(
+            Name::from(names::FILTER),
+            Object::Name(Name::borrowed(b"LZWDecode")),
+        );
+        let object = Object::Stream(Box::new(Object::Dictionary(dict)), Cow::Borrowed(b""));
+        let mut out = Vec::new();
+        assert!(matches!(
+            write_object(&mut out, &object, &options),
+            Err(Error::UnknownFilter(_))
+        ));
+    }
+}
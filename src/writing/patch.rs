@@ -0,0 +1,85 @@
+use crate::error::{Error, Result};
+use crate::objects::IndirectRef;
+use crate::parsing::pdf_file::PdfFile;
+
+impl<'a> PdfFile<'a> {
+    /// Returns a copy of the file with the bytes of one object replaced,
+    /// leaving every other byte where it was so that the xref table stays
+    /// valid. This is meant for repairs and forensic edits, where running
+    /// the writer would change too much.
+    ///
+    /// The replacement should include the `obj` header and `endobj` keyword.
+    /// If it is shorter than the original it is padded with spaces, and if
+    /// it is longer an error is returned. The replacement is not checked, so
+    /// it is up to the caller to write a valid object.
+    ///
+    /// Objects in a signed revision cannot be patched, since that would
+    /// invalidate the signature.
+    pub fn patch_object(&self, reference: IndirectRef, replacement: &[u8]) -> Result<Vec<u8>> {
+        let (original, range) = self.raw_object_bytes(reference)?;
+        self.check_signatures(range.clone())?;
+        if replacement.len() > original.len() {
+            return Err(Error::Syntax(
+                "Replacement is longer than the original object",
+                format!("{} > {} bytes", replacement.len(), original.len()),
+            ));
+        }
+
+        let mut patched = self.raw().to_vec();
+        let (bytes, padding) = patched[range].split_at_mut(replacement.len());
+        bytes.copy_from_slice(replacement);
+        padding.fill(b' ');
+        Ok(patched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::names;
+    use crate::test_support;
+    use crate::writing::objects::WriteOptions;
+
+    #[test]
+    fn should_find_raw_object_bytes() {
+        let raw = test_support::hello_world(WriteOptions::default());
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+
+        let catalog = file.trailer().unwrap().get_indirect(names::ROOT).unwrap();
+        let (bytes, range) = file.raw_object_bytes(catalog).unwrap();
+        assert!(bytes.starts_with(b"1 0 obj"));
+        assert!(bytes.ends_with(b"endobj"));
+        assert_eq!(&raw[range], bytes);
+    }
+
+    #[test]
+    fn should_patch_objects_in_place() {
+        let raw = test_support::hello_world(WriteOptions::default());
+        let mut file = PdfFile::from_bytes(&raw);
+        file.load_xref_table().unwrap();
+        let catalog = file.trailer().unwrap().get_indirect(names::ROOT).unwrap();
+        let (original, range) = file.raw_object_bytes(catalog).unwrap();
+
+        let replacement = b"1 0 obj<</Type/Catalog/Pages 2 0 R>>endobj";
+        assert!(replacement.len() < original.len());
+        let patched = file.patch_object(catalog, replacement).unwrap();
+        assert_eq!(patched.len(), raw.len());
+        assert!(patched[range.clone()].starts_with(replacement));
+        assert!(patched[range.start + replacement.len()..range.end]
+            .iter()
+            .all(|&b| b == b' '));
+
+        let mut file = PdfFile::from_raw(patched);
+        file.load_xref_table().unwrap();
+        assert_eq!(file.pages().unwrap().len(), 1);
+        assert_eq!(file.diagnostics(), vec![]);
+
+        let too_long = [b' '; 1000];
+        assert!(matches!(
+            file.patch_object(catalog, &too_long),
+            Err(Error::Syntax(_, _))
+        ));
+        assert!(file.patch_object(catalog, b"").is_ok());
+    }
+}
@@ -0,0 +1,173 @@
+//! Changes the size of pages and scales their content.
+
+use crate::content::edit::ContentStream;
+use crate::content::operators::Operator;
+use crate::document::page::Page;
+use crate::error::{Error, Result};
+use crate::geometry::{Matrix, Rect};
+use crate::objects::{names, Dictionary, Name, Object};
+use crate::writing::incremental::IncrementalUpdate;
+use std::borrow::Cow;
+
+/// The page boundaries, which are all scaled along with the content.
+const PAGE_BOXES: [&str; 5] = [
+    names::MEDIA_BOX,
+    names::CROP_BOX,
+    names::BLEED_BOX,
+    names::TRIM_BOX,
+    names::ART_BOX,
+];
+
+impl<'a> IncrementalUpdate<'a> {
+    /// Sets the boundaries of a page, which also sets its size (Adobe, 2008,
+    /// p. 77).
+    pub fn set_media_box(&mut self, page: &Page<'a>, rect: Rect) -> Result<()> {
+        self.update_page(page, |dict| {
+            dict.insert(Name::from(names::MEDIA_BOX), rect.to_object());
+            Ok(())
+        })
+    }
+
+    /// Sets the region of a page that is shown or printed, cropping anything
+    /// outside of it.
+    pub fn set_crop_box(&mut self, page: &Page<'a>, rect: Rect) -> Result<()> {
+        self.update_page(page, |dict| {
+            dict.insert(Name::from(names::CROP_BOX), rect.to_object());
+            Ok(())
+        })
+    }
+
+    /// Scales a page and its content by a factor, so that a factor of 0.5
+    /// makes the page half as wide and half as tall.
+    ///
+    /// The existing content streams are left as they are, and are wrapped in
+    /// new streams that apply the transformation with `cm`. Annotations are
+    /// not moved.
+    pub fn scale_content(&mut self, page: &Page<'a>, factor: f64) -> Result<()> {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(Error::Type(format!("Invalid scale factor {}", factor)));
+        }
+        let matrix = Matrix::scale(factor, factor);
+
+        let before = ContentStream::new(vec![
+            Operator::new(b"q", vec![]),
+            Operator::new(b"cm", matrix.to_objects()),
+        ])
+        .to_bytes()?;
+        let before = self.add(stream(before));
+        let after = self.add(stream(b"Q\n".to_vec()));
+
+        self.update_page(page, |dict| {
+            for key in PAGE_BOXES {
+                let rect = match dict.get(key.as_bytes()) {
+                    Some(rect) => Some(Rect::from_object(&page.file().resolve_owned(rect)?)?),
+                    // Inherited boxes are set on the page, so that the
+                    // ancestors are left alone
+                    None if key == names::MEDIA_BOX => Some(page.media_box()?),
+                    None if key == names::CROP_BOX => {
+                        page.attribute(key).map(|_| page.crop_box()).transpose()?
+                    }
+                    None => None,
+                };
+                if let Some(rect) = rect {
+                    dict.insert(Name::from(key), matrix.transform_rect(&rect).to_object());
+                }
+            }
+
+            let mut contents = vec![Object::Indirect(before)];
+            match dict.get(names::CONTENTS.as_bytes()) {
+                Some(Object::Array(streams)) => contents.extend(streams.iter().cloned()),
+                Some(&Object::Indirect(reference)) => match page.file().object(reference)? {
+                    Object::Array(streams) => contents.extend(streams),
+                    _ => contents.push(Object::Indirect(reference)),
+                },
+                Some(other) => contents.push(other.clone()),
+                None => {}
+            }
+            contents.push(Object::Indirect(after));
+            dict.insert(Name::from(names::CONTENTS), Object::Array(contents));
+            Ok(())
+        })
+    }
+
+    /// Replaces a page's dictionary with an edited copy, building on any
+    /// earlier changes to it in this update.
+    fn update_page<F>(&mut self, page: &Page<'a>, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Dictionary<'a>) -> Result<()>,
+    {
+        let reference = page
+            .reference()
+            .ok_or(Error::Type("Cannot change a direct page object".into()))?;
+        let mut page_dict = self.object(reference)?;
+        let Object::Dictionary(dict) = &mut page_dict else {
+            return Err(Error::Type(format!("Expected dict got {:?}", page_dict)));
+        };
+        f(dict)?;
+        self.replace(reference, page_dict);
+        Ok(())
+    }
+}
+
+fn stream<'a>(data: Vec<u8>) -> Object<'a> {
+    Object::Stream(
+        Box::new(Object::Dictionary(Dictionary::new())),
+        Cow::Owned(data),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::pdf_file::PdfFile;
+    use crate::test_support;
+    use crate::writing::objects::WriteOptions;
+
+    fn open(raw: Vec<u8>) -> PdfFile<'static> {
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    #[test]
+    fn should_set_page_boxes() {
+        let file = open(test_support::hello_world(WriteOptions::default()));
+        let page = &file.pages().unwrap()[0];
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        update
+            .set_media_box(page, Rect::new(0.0, 0.0, 300.0, 400.0))
+            .unwrap();
+        update
+            .set_crop_box(page, Rect::new(10.0, 10.0, 290.0, 390.0))
+            .unwrap();
+
+        let updated = open(update.to_bytes().unwrap());
+        let page = &updated.pages().unwrap()[0];
+        assert_eq!(page.media_box().unwrap(), Rect::new(0.0, 0.0, 300.0, 400.0));
+        assert_eq!(
+            page.crop_box().unwrap(),
+            Rect::new(10.0, 10.0, 290.0, 390.0)
+        );
+    }
+
+    #[test]
+    fn should_scale_page_content() {
+        let file = open(test_support::hello_world(WriteOptions::default()));
+        let page = &file.pages().unwrap()[0];
+        let before = page.text_fragments().unwrap()[0].bbox().unwrap();
+        let mut update = IncrementalUpdate::new(&file).unwrap();
+        update.scale_content(page, 0.5).unwrap();
+        assert!(update.scale_content(page, 0.0).is_err());
+
+        let updated = open(update.to_bytes().unwrap());
+        let page = &updated.pages().unwrap()[0];
+        assert_eq!(page.media_box().unwrap(), Rect::new(0.0, 0.0, 306.0, 396.0));
+        let content = page.content_bytes().unwrap();
+        assert!(content.starts_with(b"q\n0.5 0 0 0.5 0 0 cm\n"));
+        assert!(content.ends_with(b"Q\n"));
+
+        let after = page.text_fragments().unwrap()[0].bbox().unwrap();
+        assert_eq!(after, Matrix::scale(0.5, 0.5).transform_rect(&before));
+        assert_eq!(updated.diagnostics(), vec![]);
+    }
+}
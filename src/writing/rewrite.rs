@@ -0,0 +1,398 @@
+use crate::error::{Error, Result};
+use crate::objects::{names, Object};
+use crate::parsing::context::Diagnostic;
+use crate::parsing::keywords::{ENDOBJ_KEYWORD, OBJ_KEYWORD};
+use crate::parsing::pdf_file::{PdfFile, XrefEntry};
+use crate::utils::slices::position_of_sequence;
+use crate::writing::document::DocumentBuilder;
+use crate::writing::objects::{write_encoded_stream, write_object, WriteOptions};
+use std::borrow::Cow;
+
+/// The versions that may be kept from the header of the original file.
+const VERSIONS: [&str; 9] = [
+    "1.0", "1.1", "1.2", "1.3", "1.4", "1.5", "1.6", "1.7", "2.0",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewriteOptions {
+    /// Copy the bytes of each object as they are, rather than parsing the
+    /// object and writing it again. This keeps anything that the parser does
+    /// not understand or would normalise, such as the exact bytes covered by
+    /// a digital signature. Objects that are replaced in the builder are
+    /// still written from scratch.
+    pub raw_copy: bool,
+    /// Rewrite signed files, which invalidates their signatures since the
+    /// signed bytes move. Otherwise signed files are refused, and should be
+    /// changed with an incremental update instead.
+    pub invalidate_signatures: bool,
+}
+
+impl Default for RewriteOptions {
+    fn default() -> Self {
+        Self {
+            raw_copy: true,
+            invalidate_signatures: false,
+        }
+    }
+}
+
+/// Copies every object of a file into a new document with the same object
+/// numbers, so that objects can be replaced in the builder before the file is
+/// written again. The xref table of the file must already be loaded.
+///
+/// The new file has a single xref table, so earlier revisions and free
+/// objects are dropped. Objects in object streams are always parsed, and so
+/// are written from scratch. Without `raw_copy`, objects with a generation
+/// number other than zero are copied as they are, since the builder only
+/// writes new objects with generation zero.
+pub fn rewrite<'a>(file: &'a PdfFile<'a>, options: RewriteOptions) -> Result<DocumentBuilder<'a>> {
+    if !options.invalidate_signatures {
+        file.check_signatures(0..file.raw().len())?;
+    }
+
+    let mut builder = DocumentBuilder::new().with_version(kept_version(file)?);
+
+    let mut entries = file.xref_entries()?;
+    entries.sort_by_key(|&(reference, _)| reference);
+    for (reference, entry) in entries {
+        match entry {
+            XrefEntry::Free => {}
+            XrefEntry::InUse(_) if options.raw_copy || reference.generation != 0 => {
+                let (raw, _) = file.raw_object_bytes(reference)?;
+                builder.insert_raw(reference, Cow::Borrowed(object_body(raw)?));
+            }
+            XrefEntry::InUse(_) | XrefEntry::Compressed { .. } => {
+                builder.insert(reference, file.object(reference)?);
+            }
+        }
+    }
+
+    // The file identifier is kept, since it is used to encrypt the file
+    let trailer = file.trailer()?;
+    for key in [names::ROOT, names::INFO, names::ENCRYPT, names::ID] {
+        if let Some(value) = trailer.get(key) {
+            builder.set_trailer_entry(key, value.clone());
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Rewrites a file so that it is easy to read and edit by hand, like the QDF
+/// mode of qpdf: every stream is written without filters, and the file has a
+/// single classic xref table. The xref table of the file must already be
+/// loaded.
+///
+/// Cross-reference streams and object streams are dropped, since the xref
+/// table replaces them. Objects are written from scratch, so generation
+/// numbers are kept but the exact bytes of each object are not. Encrypted
+/// files are refused, since their streams cannot be decoded. Of the options,
+/// only `invalidate_signatures` is used.
+pub fn decompress<'a>(
+    file: &'a PdfFile<'a>,
+    options: RewriteOptions,
+) -> Result<DocumentBuilder<'a>> {
+    if !options.invalidate_signatures {
+        file.check_signatures(0..file.raw().len())?;
+    }
+    let trailer = file.trailer()?;
+    if trailer.get(names::ENCRYPT).is_some() {
+        return Err(Error::Type("Cannot decompress an encrypted file".into()));
+    }
+
+    let write_options = WriteOptions::default();
+    let mut builder = DocumentBuilder::new()
+        .with_version(kept_version(file)?)
+        .with_options(write_options);
+    for (reference, entry) in file.xref_entries()? {
+        if entry.is_free() {
+            continue;
+        }
+        let object = file.object(reference)?;
+        if is_xref_structure(&object) {
+            continue;
+        }
+        if reference.generation == 0 {
+            builder.insert(reference, object);
+        } else {
+            let mut body = b"\n".to_vec();
+            write_object(&mut body, &object, &write_options)?;
+            body.push(b'\n');
+            builder.insert_raw(reference, Cow::Owned(body));
+        }
+    }
+
+    for key in [names::ROOT, names::INFO, names::ID] {
+        if let Some(value) = trailer.get(key) {
+            builder.set_trailer_entry(key, value.to_static());
+        }
+    }
+    Ok(builder)
+}
+
+/// A file that has been rewritten by `repair`.
+#[derive(Clone, Debug)]
+pub struct Repaired<'a> {
+    pub builder: DocumentBuilder<'a>,
+    /// Every problem that was found while reading the file, along with what
+    /// was done about it.
+    pub repairs: Vec<Diagnostic>,
+}
+
+/// Reads every object of a damaged file and writes them into a new document
+/// with a fresh xref table, so that the result can be read in strict mode.
+/// The file should be opened in lenient mode, and its xref table must already
+/// be loaded, which rebuilds the table if it is broken.
+///
+/// Objects that cannot be read at all are dropped, so references to them
+/// become null. Streams are compressed again, except those with filters that
+/// cannot be decoded, which are copied as they are. Like `decompress`,
+/// encrypted files are refused and signed files are refused unless their
+/// signatures may be invalidated.
+pub fn repair<'a>(file: &'a PdfFile<'a>, options: RewriteOptions) -> Result<Repaired<'a>> {
+    if !options.invalidate_signatures {
+        file.check_signatures(0..file.raw().len())?;
+    }
+    let trailer = file.trailer()?;
+    if trailer.get(names::ENCRYPT).is_some() {
+        return Err(Error::Type("Cannot repair an encrypted file".into()));
+    }
+
+    let write_options = WriteOptions {
+        compress_streams: true,
+        ..Default::default()
+    };
+    let mut builder = DocumentBuilder::new()
+        .with_version(kept_version(file)?)
+        .with_options(write_options);
+    let mut dropped = Vec::new();
+    for (reference, entry) in file.xref_entries()? {
+        if entry.is_free() {
+            continue;
+        }
+        let mut body = b"\n".to_vec();
+        match file.object(reference) {
+            Ok(object) if is_xref_structure(&object) => continue,
+            Ok(object) if reference.generation == 0 => {
+                builder.insert(reference, object);
+                continue;
+            }
+            Ok(object) => write_object(&mut body, &object, &write_options)?,
+            Err(Error::UnknownFilter(_)) => {
+                let object = file.encoded_object(reference)?;
+                let (dict, data) = object.as_stream()?;
+                write_encoded_stream(&mut body, dict.clone(), &data, &write_options)?;
+            }
+            Err(error) => {
+                dropped.push(Diagnostic {
+                    offset: entry.offset(),
+                    error,
+                    repair: format!(
+                        "Dropped object {} {}, which could not be read",
+                        reference.number, reference.generation
+                    ),
+                });
+                continue;
+            }
+        }
+        body.push(b'\n');
+        builder.insert_raw(reference, Cow::Owned(body));
+    }
+    // Reading the page tree repairs or reports any problems with it
+    file.pages()?;
+
+    for key in [names::ROOT, names::INFO, names::ID] {
+        if let Some(value) = trailer.get(key) {
+            builder.set_trailer_entry(key, value.to_static());
+        }
+    }
+    let mut repairs = file.diagnostics();
+    repairs.extend(dropped);
+    Ok(Repaired { builder, repairs })
+}
+
+/// Whether an object is a cross-reference stream or object stream, which a
+/// classic xref table replaces.
+fn is_xref_structure(object: &Object) -> bool {
+    matches!(
+        object.get(names::TYPE),
+        Some(Object::Name(t)) if t.matches(names::OBJ_STM) || t.matches(names::X_REF)
+    )
+}
+
+/// The version in the header of the file, if it is one that can be written.
+fn kept_version(file: &PdfFile) -> Result<&'static str> {
+    let version = file.header_version()?;
+    Ok(VERSIONS
+        .into_iter()
+        .find(|&v| v == version)
+        .unwrap_or("1.7"))
+}
+
+/// The bytes of an object between the `obj` and `endobj` keywords.
+fn object_body(raw: &[u8]) -> Result<&[u8]> {
+    let start = position_of_sequence(raw, OBJ_KEYWORD)
+        .ok_or(Error::Syntax("Could not find obj prefix", "".into()))?
+        + OBJ_KEYWORD.len();
+    let body = raw[start..]
+        .strip_suffix(ENDOBJ_KEYWORD)
+        .ok_or(Error::Syntax("Could not find endobj keyword", "".into()))?;
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{Dictionary, IndirectRef, Name};
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    use crate::parsing::context::ParseOptions;
+    use crate::test_support;
+
+    fn open(raw: Vec<u8>) -> PdfFile<'static> {
+        let mut file = PdfFile::from_raw(raw);
+        file.load_xref_table().unwrap();
+        file
+    }
+
+    #[test]
+    fn should_copy_untouched_objects_byte_for_byte() {
+        let file = open(test_support::hello_world(WriteOptions::default()));
+        let font = IndirectRef {
+            number: 3,
+            generation: 0,
+        };
+        // Written in a way that the writer would not reproduce
+        let odd = b"3 0 obj<</BaseFont/Helvetica %x\n/Type/Font/Subtype/Type1>>endobj";
+        let file = open(file.patch_object(font, odd).unwrap());
+
+        let mut builder = rewrite(&file, RewriteOptions::default()).unwrap();
+        let catalog = file.trailer().unwrap().get_indirect(names::ROOT).unwrap();
+        let mut root = file.object(catalog).unwrap();
+        if let Object::Dictionary(dict) = &mut root {
+            dict.insert(Name::from("Lang"), Object::String(b"en".as_slice().into()));
+        }
+        builder.insert(catalog, root);
+        let rewritten = open(builder.to_bytes().unwrap());
+
+        assert!(rewritten.raw_object_bytes(font).unwrap().0.starts_with(odd));
+        let (raw, _) = rewritten.raw_object_bytes(catalog).unwrap();
+        assert!(raw.windows(5).any(|w| w == b"/Lang"));
+        assert_eq!(
+            rewritten.trailer().unwrap().get(names::ID),
+            file.trailer().unwrap().get(names::ID)
+        );
+        assert_eq!(
+            rewritten.pages().unwrap()[0].extract_text().unwrap(),
+            "Hello, world!"
+        );
+        assert_eq!(rewritten.diagnostics(), vec![]);
+
+        let builder = rewrite(
+            &file,
+            RewriteOptions {
+                raw_copy: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let normalized = open(builder.to_bytes().unwrap());
+        let (raw, _) = normalized.raw_object_bytes(font).unwrap();
+        assert!(!raw.windows(2).any(|w| w == b"%x"));
+        assert_eq!(normalized.object(font), file.object(font));
+    }
+
+    #[test]
+    fn should_keep_generation_numbers() {
+        let mut builder = DocumentBuilder::new();
+        let old = IndirectRef {
+            number: 1,
+            generation: 3,
+        };
+        builder.insert_raw(old, Cow::Borrowed(b"\n<</Type/Catalog>>\n"));
+        builder.set_root(old);
+        let file = open(builder.to_bytes().unwrap());
+        assert_eq!(
+            file.object(old),
+            Ok(Object::Dictionary(Dictionary::from([(
+                Name::from(names::TYPE),
+                Object::Name(Name::from(names::CATALOG))
+            )])))
+        );
+
+        let rewritten = open(
+            rewrite(
+                &file,
+                RewriteOptions {
+                    raw_copy: false,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .to_bytes()
+            .unwrap(),
+        );
+        assert!(rewritten.catalog().is_ok());
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_decompress_streams() {
+        let file = open(test_support::hello_world(WriteOptions {
+            compress_streams: true,
+            ..Default::default()
+        }));
+        let content = file.pages().unwrap()[0]
+            .attribute(names::CONTENTS)
+            .unwrap()
+            .clone();
+        let content = content.as_indirect().unwrap();
+        let (raw, _) = file.raw_object_bytes(content).unwrap();
+        assert!(raw.windows(11).any(|w| w == b"FlateDecode"));
+
+        let decompressed = open(
+            decompress(&file, RewriteOptions::default())
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+        );
+        let (raw, _) = decompressed.raw_object_bytes(content).unwrap();
+        assert!(!raw.windows(11).any(|w| w == b"FlateDecode"));
+        assert!(raw.windows(2).any(|w| w == b"Tj"));
+        assert_eq!(
+            decompressed.pages().unwrap()[0].extract_text().unwrap(),
+            "Hello, world!"
+        );
+        assert_eq!(decompressed.diagnostics(), vec![]);
+    }
+
+    #[cfg(any(feature = "miniz_oxide", feature = "flate2"))]
+    #[test]
+    fn should_repair_damaged_files() {
+        let mut raw = test_support::hello_world(WriteOptions::default());
+        // Point startxref at the wrong place, and add an object that cannot be
+        // parsed
+        let startxref = position_of_sequence(&raw, b"startxref").unwrap();
+        raw.truncate(startxref);
+        raw.extend_from_slice(b"9 0 obj\n<< /Broken ]\nendobj\nstartxref\n1\n%%EOF\n");
+        let mut file = PdfFile::from_raw(raw).with_options(ParseOptions::lenient());
+        file.load_xref_table().unwrap();
+
+        let repaired = repair(&file, RewriteOptions::default()).unwrap();
+        let repairs = repaired
+            .repairs
+            .iter()
+            .map(|diagnostic| diagnostic.repair.as_str())
+            .collect::<Vec<_>>();
+        assert!(repairs.contains(&"Rebuilt the xref table by scanning the file for objects"));
+        assert!(repairs.contains(&"Dropped object 9 0, which could not be read"));
+
+        let mut file = PdfFile::from_raw(repaired.builder.to_bytes().unwrap())
+            .with_options(ParseOptions::strict());
+        file.load_xref_table().unwrap();
+        assert_eq!(
+            file.pages().unwrap()[0].extract_text().unwrap(),
+            "Hello, world!"
+        );
+        assert_eq!(file.diagnostics(), vec![]);
+    }
+}
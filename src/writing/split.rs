@@ -0,0 +1,215 @@
+use crate::error::{Error, Result};
+use crate::parsing::pdf_file::PdfFile;
+use crate::writing::document::DocumentBuilder;
+use crate::writing::merge::{copy_pages, finish_pages};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// One of the documents that a file is split into.
+pub struct SplitPart<'a> {
+    /// A file name for the part, based on its bookmark title.
+    pub name: String,
+    /// The indices of the pages in the original file.
+    pub pages: Range<usize>,
+    pub document: DocumentBuilder<'a>,
+}
+
+/// Copies a range of pages into a new document. The xref table of the file
+/// must already be loaded.
+pub fn extract_pages<'a>(
+    file: &'a PdfFile<'a>,
+    range: Range<usize>,
+) -> Result<DocumentBuilder<'a>> {
+    let pages = file.pages()?;
+    let pages = pages.get(range.clone()).ok_or(Error::Type(format!(
+        "Page range {:?} is outside of {} pages",
+        range,
+        pages.len()
+    )))?;
+
+    let mut builder = DocumentBuilder::new();
+    let catalog = builder.reserve();
+    let tree = builder.reserve();
+    let kids = copy_pages(file, pages, &mut builder, tree)?;
+    finish_pages(&mut builder, catalog, tree, kids);
+    Ok(builder)
+}
+
+/// Splits a document into one part for each top-level bookmark, each starting
+/// at the bookmark's page and ending before the next. The xref table of the
+/// file must already be loaded.
+///
+/// Pages before the first bookmark are included in the first part, and
+/// bookmarks without a page in the document are skipped. If there are no such
+/// bookmarks, the whole document is returned as a single part.
+pub fn split_by_bookmarks<'a>(file: &'a PdfFile<'a>) -> Result<Vec<SplitPart<'a>>> {
+    let page_count = file.pages()?.len();
+    let mut starts = file
+        .outline()?
+        .into_iter()
+        .filter_map(|item| Some((item.page?, item.title)))
+        .collect::<Vec<_>>();
+    // Keeps bookmarks to the same page in order
+    starts.sort_by_key(|&(page, _)| page);
+    match starts.first_mut() {
+        Some((page, _)) => *page = 0,
+        None => starts.push((0, String::new())),
+    }
+
+    let mut used = HashSet::new();
+    let mut parts = Vec::new();
+    for (i, (start, title)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(page_count, |&(next, _)| next);
+        if *start >= end {
+            continue;
+        }
+        parts.push(SplitPart {
+            name: file_name(title, &mut used),
+            pages: *start..end,
+            document: extract_pages(file, *start..end)?,
+        });
+    }
+    Ok(parts)
+}
+
+/// Makes a title safe to use as a file name, adding a number if the name has
+/// already been used.
+fn file_name(title: &str, used: &mut HashSet<String>) -> String {
+    let stem = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+    let stem = stem.trim().trim_start_matches('.');
+    let stem = if stem.is_empty() { "Untitled" } else { stem };
+
+    let mut name = format!("{}.pdf", stem);
+    let mut n = 1;
+    while !used.insert(name.to_lowercase()) {
+        n += 1;
+        name = format!("{} ({}).pdf", stem, n);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{names, IndirectRef, Object};
+    use crate::test_support::{self, dict, name};
+
+    fn string(text: &str) -> Object<'_> {
+        Object::String(text.as_bytes().into())
+    }
+
+    #[test]
+    fn should_split_at_top_level_bookmarks() {
+        let contents = (1..=5)
+            .map(|i| format!("BT /F1 24 Tf 72 720 Td (Page {}) Tj ET", i).into_bytes())
+            .collect::<Vec<_>>();
+        let contents = contents.iter().map(|c| c.as_slice()).collect::<Vec<_>>();
+        let mut builder = DocumentBuilder::new();
+        let catalog = test_support::build_pages(&mut builder, &contents);
+        // After the catalog, page tree and font, each page follows its content
+        let page = |i: u32| IndirectRef {
+            number: catalog.number + 4 + 2 * i,
+            ..catalog
+        };
+        let dest = |i| Object::Array(vec![Object::Indirect(page(i)), name("Fit")]);
+
+        let outlines = builder.reserve();
+        let first = builder.reserve();
+        let second = builder.reserve();
+        let third = builder.reserve();
+        let child = builder.add(dict(&[
+            (names::TITLE, string("Nested")),
+            (names::PARENT, Object::Indirect(second)),
+            (names::DEST, dest(4)),
+        ]));
+        builder.insert(
+            first,
+            dict(&[
+                (names::TITLE, string("Part 1: Intro")),
+                (names::PARENT, Object::Indirect(outlines)),
+                (names::NEXT, Object::Indirect(second)),
+                (names::DEST, dest(1)),
+            ]),
+        );
+        builder.insert(
+            second,
+            dict(&[
+                (names::TITLE, string("Part 1: Intro")),
+                (names::PARENT, Object::Indirect(outlines)),
+                (names::NEXT, Object::Indirect(third)),
+                (names::FIRST, Object::Indirect(child)),
+                (
+                    names::A,
+                    dict(&[(names::S, name(names::GO_TO)), (names::D, dest(2))]),
+                ),
+            ]),
+        );
+        builder.insert(
+            third,
+            dict(&[
+                (names::TITLE, string("Website")),
+                (names::PARENT, Object::Indirect(outlines)),
+            ]),
+        );
+        builder.insert(
+            outlines,
+            dict(&[
+                (names::FIRST, Object::Indirect(first)),
+                (names::COUNT, Object::Integer(3)),
+            ]),
+        );
+        let pages = IndirectRef {
+            number: catalog.number + 1,
+            ..catalog
+        };
+        builder.insert(
+            catalog,
+            dict(&[
+                (names::TYPE, name(names::CATALOG)),
+                (names::PAGES, Object::Indirect(pages)),
+                (names::OUTLINES, Object::Indirect(outlines)),
+            ]),
+        );
+
+        let mut file = PdfFile::from_raw(builder.to_bytes().unwrap());
+        file.load_xref_table().unwrap();
+        let parts = split_by_bookmarks(&file).unwrap();
+        let summary = parts
+            .iter()
+            .map(|part| (part.name.as_str(), part.pages.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [("Part 1_ Intro.pdf", 0..2), ("Part 1_ Intro (2).pdf", 2..5)]
+        );
+
+        let mut second = PdfFile::from_raw(parts[1].document.to_bytes().unwrap());
+        second.load_xref_table().unwrap();
+        let text = second
+            .pages()
+            .unwrap()
+            .iter()
+            .map(|page| page.extract_text().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(text, ["Page 3", "Page 4", "Page 5"]);
+        assert_eq!(second.diagnostics(), vec![]);
+    }
+
+    #[test]
+    fn should_return_whole_document_without_bookmarks() {
+        let mut file = PdfFile::from_raw(test_support::multi_page(3));
+        file.load_xref_table().unwrap();
+        let parts = split_by_bookmarks(&file).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].name, "Untitled.pdf");
+        assert_eq!(parts[0].pages, 0..3);
+        assert!(extract_pages(&file, 2..4).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Writes object streams, which hold objects other than streams in a single
+//! compressed stream (Adobe, 2008, p. 45-48), and the cross-reference streams
+//! that are needed to locate the objects inside of them (Adobe, 2008,
+//! p. 48-51).
+
+use crate::error::Result;
+use crate::objects::{names, Dictionary, Name, Object};
+use crate::writing::objects::{write_object, WriteOptions};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// The most objects that are put in one object stream, so that reading one
+/// object does not mean decoding all of the others.
+pub const OBJECTS_PER_STREAM: usize = 100;
+
+/// Where an object is written, as listed in a cross-reference stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XrefLocation {
+    /// At a byte offset in the file, with a generation number.
+    Offset(u16, usize),
+    /// At an index in an object stream, by the object number of the stream.
+    Compressed(u32, usize),
+}
+
+/// Whether an object may be put in an object stream, which streams cannot
+/// be.
+pub fn is_compressible(object: &Object) -> bool {
+    !matches!(object, Object::Stream(_, _))
+}
+
+/// Builds an object stream holding objects by their object numbers.
+pub fn object_stream(
+    objects: &[(u32, &Object)],
+    options: &WriteOptions,
+) -> Result<Object<'static>> {
+    let mut header = Vec::new();
+    let mut body = Vec::new();
+    for (number, object) in objects {
+        header.extend_from_slice(format!("{} {} ", number, body.len()).as_bytes());
+        write_object(&mut body, object, options)?;
+        body.push(b'\n');
+    }
+    header.push(b'\n');
+
+    let dict = Dictionary::from([
+        (
+            Name::from(names::TYPE),
+            Object::Name(Name::from(names::OBJ_STM)),
+        ),
+        (Name::from(names::N), Object::Integer(objects.len() as i64)),
+        (
+            Name::from(names::FIRST),
+            Object::Integer(header.len() as i64),
+        ),
+    ]);
+    header.extend_from_slice(&body);
+    Ok(Object::Stream(
+        Box::new(Object::Dictionary(dict)),
+        Cow::Owned(header),
+    ))
+}
+
+/// Builds a cross-reference stream listing the objects from 0 up to `size`,
+/// which also takes the place of the trailer, so has its entries. Numbers
+/// without a location are listed as free.
+pub fn xref_stream<'a>(
+    locations: &BTreeMap<u32, XrefLocation>,
+    size: u32,
+    trailer: &Dictionary<'a>,
+) -> Object<'a> {
+    let fields = |number: u32| match locations.get(&number) {
+        Some(&XrefLocation::Offset(generation, offset)) => (1, offset, generation as usize),
+        Some(&XrefLocation::Compressed(stream, index)) => (2, stream as usize, index),
+        None if number == 0 => (0, 0, 65535),
+        None => (0, 0, 0),
+    };
+
+    // Offsets are written with as few bytes as they need
+    let largest = (0..size).map(|number| fields(number).1).max().unwrap_or(0);
+    let width = (usize::BITS - largest.leading_zeros()).div_ceil(8).max(1) as usize;
+    let mut data = Vec::with_capacity(size as usize * (width + 3));
+    for number in 0..size {
+        let (kind, second, third) = fields(number);
+        data.push(kind);
+        data.extend_from_slice(&second.to_be_bytes()[usize::BITS as usize / 8 - width..]);
+        data.extend_from_slice(&(third as u16).to_be_bytes());
+    }
+
+    let mut dict = trailer.clone();
+    dict.insert(
+        Name::from(names::TYPE),
+        Object::Name(Name::from(names::X_REF)),
+    );
+    dict.insert(Name::from(names::SIZE), Object::Integer(size as i64));
+    dict.insert(
+        Name::from(names::W),
+        Object::Array(vec![
+            Object::Integer(1),
+            Object::Integer(width as i64),
+            Object::Integer(2),
+        ]),
+    );
+    Object::Stream(Box::new(Object::Dictionary(dict)), Cow::Owned(data))
+}
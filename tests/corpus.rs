@@ -0,0 +1,107 @@
+//! Loads a corpus of small, intentionally broken files, to check that lenient
+//! mode can repair each of them and that strict mode reports the problem, as
+//! an error or, for problems that the spec allows, a diagnostic.
+
+use bdf::error::{Error, Result};
+use bdf::parsing::context::ParseOptions;
+use bdf::parsing::pdf_file::PdfFile;
+
+/// Each file in `tests/corpus`, along with the error that strict mode should
+/// report for it and the start of the repair that lenient mode should make.
+const CORPUS: &[(&str, &str, &str)] = &[
+    (
+        "truncated-xref.pdf",
+        "Invalid xref entry",
+        "Rebuilt the xref table",
+    ),
+    (
+        "wrong-xref-offsets.pdf",
+        "Could not find object header at offset",
+        "Found the object at",
+    ),
+    (
+        "mixed-eols.pdf",
+        "'stream' keyword must not be followed by just a CR",
+        "Treated the CR as the end of line",
+    ),
+    (
+        "bad-stream-length.pdf",
+        "Stream length does not match /Length",
+        "Ended the stream at the first endstream keyword",
+    ),
+    (
+        "broken-escapes.pdf",
+        "Invalid escape sequence",
+        "Kept the character without the backslash",
+    ),
+    (
+        "missing-eof.pdf",
+        "Could not find eof marker",
+        "Ignored the missing eof marker",
+    ),
+];
+
+fn open(name: &str, options: ParseOptions) -> PdfFile<'static> {
+    PdfFile::read_file(format!("./tests/corpus/{}", name))
+        .unwrap()
+        .with_options(options)
+}
+
+/// Walks the parts of the document that every reader needs.
+fn walk(file: &mut PdfFile<'_>) -> Result<()> {
+    file.load_xref_table()?;
+
+    let trailer = file.trailer()?;
+    let root = file.resolve(&trailer[b"Root"])?;
+    let pages = file.resolve(&root[b"Pages"])?;
+    for kid in &pages[b"Kids"] {
+        let page = file.resolve(kid)?;
+        file.resolve(&page[b"Contents"])?.as_stream()?;
+    }
+    if let Some(info) = trailer.get(b"Info") {
+        file.resolve(info)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn should_load_valid_file_in_strict_mode() {
+    let mut file = open("valid.pdf", ParseOptions::strict());
+    walk(&mut file).unwrap();
+    assert_eq!(file.diagnostics(), vec![]);
+}
+
+#[test]
+fn should_report_errors_in_strict_mode() {
+    for (name, message, _) in CORPUS {
+        let mut file = open(name, ParseOptions::strict());
+        match walk(&mut file) {
+            Err(Error::Syntax(m, _)) if m == *message => {}
+            Ok(())
+                if file
+                    .diagnostics()
+                    .iter()
+                    .any(|d| matches!(d.error, Error::Syntax(m, _) if m == *message)) => {}
+            result => panic!("{}: expected {:?}, got {:?}", name, message, result),
+        }
+    }
+}
+
+#[test]
+fn should_repair_files_in_lenient_mode() {
+    for (name, message, repair) in CORPUS {
+        let mut file = open(name, ParseOptions::lenient());
+        if let Err(err) = walk(&mut file) {
+            panic!("{}: {:?}", name, err);
+        }
+
+        let diagnostics = file.diagnostics();
+        match diagnostics.first() {
+            Some(d)
+                if matches!(d.error, Error::Syntax(m, _) if m == *message)
+                    && d.repair.starts_with(repair) => {}
+            _ => panic!("{}: expected {:?}, got {:?}", name, message, diagnostics),
+        }
+    }
+}